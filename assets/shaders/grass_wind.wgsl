@@ -0,0 +1,64 @@
+// Vertex shader for GrassMaterial: the standard mesh vertex plus wind sway.
+// UV_1.x (uv_b) is the sway weight, 0 at the base of a blade.
+#import bevy_pbr::{
+    mesh_functions,
+    forward_io::{Vertex, VertexOutput},
+    view_transformations::position_world_to_clip,
+}
+
+struct WindParams {
+  direction: vec2<f32>,
+  strength: f32,
+  // radians
+  phase: f32,
+  amplitude: f32,
+  phase_seed: f32,
+};
+
+@group(2) @binding(100) var<uniform> wind: WindParams;
+
+// Horizontal offset for a vertex at `pos` (world) with sway weight `w`. The
+// phase travels along the wind and wobbles across it, so gusts roll over the
+// field instead of every blade moving at once.
+fn wind_offset(pos: vec3<f32>, w: f32) -> vec3<f32> {
+  let along = dot(pos.xz, wind.direction) * 0.15;
+  let across = 1.7 * sin(dot(pos.xz, vec2<f32>(0.071, 0.053)) + wind.phase_seed);
+  let sway = wind.strength * wind.amplitude * (0.6 + 0.4 * sin(wind.phase - along + across));
+  // quadratic so the lower part bends less
+  return vec3<f32>(wind.direction.x, 0.0, wind.direction.y) * sway * w * w;
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+  var out: VertexOutput;
+  let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+
+#ifdef VERTEX_NORMALS
+  out.world_normal = mesh_functions::mesh_normal_local_to_world(vertex.normal, vertex.instance_index);
+#endif
+
+  var world_pos = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(vertex.position, 1.0));
+#ifdef VERTEX_UVS_B
+  world_pos = vec4<f32>(world_pos.xyz + wind_offset(world_pos.xyz, vertex.uv_b.x), world_pos.w);
+  out.uv_b = vertex.uv_b;
+#endif
+  out.world_position = world_pos;
+  out.position = position_world_to_clip(world_pos.xyz);
+
+#ifdef VERTEX_UVS_A
+  out.uv = vertex.uv;
+#endif
+#ifdef VERTEX_TANGENTS
+  out.world_tangent = mesh_functions::mesh_tangent_local_to_world(world_from_local, vertex.tangent, vertex.instance_index);
+#endif
+#ifdef VERTEX_COLORS
+  out.color = vertex.color;
+#endif
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+  out.instance_index = vertex.instance_index;
+#endif
+#ifdef VISIBILITY_RANGE_DITHER
+  out.visibility_range_dither = mesh_functions::get_visibility_range_dither_level(vertex.instance_index, world_from_local[3]);
+#endif
+  return out;
+}
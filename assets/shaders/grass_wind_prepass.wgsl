@@ -0,0 +1,62 @@
+// Depth/shadow prepass for GrassMaterial: same sway as grass_wind.wgsl so
+// shadows and the depth prepass follow the blades.
+#import bevy_pbr::{
+    mesh_functions,
+    view_transformations::position_world_to_clip,
+    prepass_io::{Vertex, VertexOutput},
+}
+
+struct WindParams {
+  direction: vec2<f32>,
+  strength: f32,
+  phase: f32,
+  amplitude: f32,
+  phase_seed: f32,
+};
+
+@group(2) @binding(100) var<uniform> wind: WindParams;
+
+fn wind_offset(pos: vec3<f32>, w: f32) -> vec3<f32> {
+  let along = dot(pos.xz, wind.direction) * 0.15;
+  let across = 1.7 * sin(dot(pos.xz, vec2<f32>(0.071, 0.053)) + wind.phase_seed);
+  let sway = wind.strength * wind.amplitude * (0.6 + 0.4 * sin(wind.phase - along + across));
+  return vec3<f32>(wind.direction.x, 0.0, wind.direction.y) * sway * w * w;
+}
+
+@vertex
+fn vertex(in: Vertex) -> VertexOutput {
+  var out: VertexOutput;
+
+  let world_from_local = mesh_functions::get_world_from_local(in.instance_index);
+  var world_pos = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(in.position, 1.0));
+#ifdef VERTEX_UVS_B
+  world_pos = vec4<f32>(world_pos.xyz + wind_offset(world_pos.xyz, in.uv_b.x), world_pos.w);
+  out.uv_b = in.uv_b;
+#endif
+#ifdef VERTEX_UVS_A
+  out.uv = in.uv;
+#endif
+
+  out.position = position_world_to_clip(world_pos.xyz);
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+  out.unclipped_depth = out.position.z;
+  out.position.z = min(out.position.z, 1.0);
+#endif
+
+#ifdef NORMAL_PREPASS_OR_DEFERRED_PREPASS
+#ifdef VERTEX_NORMALS
+  out.world_normal = mesh_functions::mesh_normal_local_to_world(in.normal, in.instance_index);
+#endif
+#endif
+
+  out.world_position = world_pos;
+#ifdef MOTION_VECTOR_PREPASS
+  // sway is slow; no motion vectors for it
+  out.previous_world_position = world_pos;
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+  out.instance_index = in.instance_index;
+#endif
+  return out;
+}
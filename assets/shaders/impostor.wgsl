@@ -0,0 +1,91 @@
+#import bevy_pbr::{
+    mesh_functions,
+    mesh_view_bindings::view,
+    view_transformations::position_world_to_clip,
+}
+
+struct ImpostorParams {
+  size: vec2<f32>,
+  fade_start: f32,
+  fade_end: f32,
+  cull_distance: f32,
+  alpha_cutoff: f32,
+};
+
+@group(2) @binding(0) var<uniform> params: ImpostorParams;
+@group(2) @binding(1) var impostor_tex: texture_2d<f32>;
+@group(2) @binding(2) var impostor_sampler: sampler;
+
+// Same layout and sway as grass_wind.wgsl.
+struct WindParams {
+  direction: vec2<f32>,
+  strength: f32,
+  phase: f32,
+  amplitude: f32,
+  phase_seed: f32,
+};
+
+@group(2) @binding(3) var<uniform> wind: WindParams;
+
+fn wind_offset(pos: vec3<f32>, w: f32) -> vec3<f32> {
+  let along = dot(pos.xz, wind.direction) * 0.15;
+  let across = 1.7 * sin(dot(pos.xz, vec2<f32>(0.071, 0.053)) + wind.phase_seed);
+  let sway = wind.strength * wind.amplitude * (0.6 + 0.4 * sin(wind.phase - along + across));
+  return vec3<f32>(wind.direction.x, 0.0, wind.direction.y) * sway * w * w;
+}
+
+// All four corners of a quad share the instance position; uv picks the corner.
+struct Vertex {
+  @builtin(instance_index) instance_index: u32,
+  @location(0) position: vec3<f32>,
+  @location(2) uv: vec2<f32>,
+  @location(3) instance: vec2<f32>, // x = scale
+};
+
+struct VertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) uv: vec2<f32>,
+  @location(1) fade: f32,
+};
+
+@vertex
+fn vertex(in: Vertex) -> VertexOutput {
+  var out: VertexOutput;
+
+  let world_from_local = mesh_functions::get_world_from_local(in.instance_index);
+  let center = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(in.position, 1.0)).xyz;
+
+  // Cylindrical billboard: rotate about Y to face the camera.
+  let to_cam = view.world_position - center;
+  let flat = normalize(select(vec2<f32>(0.0, 1.0), to_cam.xz, dot(to_cam.xz, to_cam.xz) > 1e-6));
+  let right = vec3<f32>(flat.y, 0.0, -flat.x);
+  let size = params.size * in.instance.x;
+  // top corners (uv.y = 0) sway, the base stays planted
+  let offset = right * (in.uv.x - 0.5) * size.x + vec3<f32>(0.0, (1.0 - in.uv.y) * size.y, 0.0)
+    + wind_offset(center, 1.0 - in.uv.y) * in.instance.x;
+
+  let dist = length(to_cam);
+  let band = max(params.fade_end - params.fade_start, 1e-4);
+  out.fade = select(clamp((dist - params.fade_start) / band, 0.0, 1.0), 0.0, dist > params.cull_distance);
+  out.position = position_world_to_clip(center + offset);
+  out.uv = in.uv;
+  return out;
+}
+
+// 4x4 ordered dither threshold in (0, 1).
+fn bayer4(p: vec2<u32>) -> f32 {
+  var m = array<f32, 16>(0.0, 8.0, 2.0, 10.0, 12.0, 4.0, 14.0, 6.0, 3.0, 11.0, 1.0, 9.0, 15.0, 7.0, 13.0, 5.0);
+  return (m[(p.y % 4u) * 4u + (p.x % 4u)] + 0.5) / 16.0;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+  if (in.fade <= bayer4(vec2<u32>(in.position.xy))) {
+    discard;
+  }
+  let color = textureSample(impostor_tex, impostor_sampler, in.uv);
+  if (color.a < params.alpha_cutoff) {
+    discard;
+  }
+  return vec4<f32>(color.rgb, 1.0);
+}
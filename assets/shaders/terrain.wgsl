@@ -1,48 +1,498 @@
-#import bevy_pbr::mesh_view_bindings
-#import bevy_pbr::mesh_bindings
-#import bevy_pbr::mesh_functions
-#import bevy_pbr::forward_io::{Vertex, VertexOutput, FragmentOutput}
+#import bevy_pbr::{
+    mesh_functions,
+    mesh_view_bindings::{view, lights},
+    view_transformations::position_world_to_clip,
+    forward_io::{Vertex, VertexOutput, FragmentOutput},
+    pbr_types,
+    pbr_functions,
+}
 
 struct TileParams {
-  tile_size: f32;
-  height_scale: f32;
-  texels_per_side: u32;
-  _pad0: u32;
-  tile_color: vec4<f32>;
+  tile_size: f32,
+  height_scale: f32,
+  texels_per_side: u32,
+  debug_mode: u32,
+  tile_color: vec4<f32>,
+  // texel v decodes to height_offset + v * height_range (R16Unorm heights)
+  height_offset: f32,
+  height_range: f32,
+  detail_offset: vec2<f32>,
+  detail_rotation: u32,
+};
+
+struct SplatParams {
+  // 0 = grass, 1 = rock, 2 = sand, 3 = snow
+  layer_colors: array<vec4<f32>, 4>,
+  sand_height: f32,
+  snow_height: f32,
+  rock_slope: f32,
+  blend: f32,
+  snow_blend: f32,
+  // 0..1, darker and glossier ground
+  wetness: f32,
+  sea_level: f32,
+  // rgb multiplied in below sea level, a = strength
+  underwater_tint: vec4<f32>,
+};
+
+struct OverlayParams {
+  contour_enabled: u32,
+  contour_major_every: u32,
+  contour_interval: f32,
+  contour_width_px: f32,
+  // a = strength
+  contour_color: vec4<f32>,
+};
+
+struct GridParams {
+  enabled: u32,
+  major_every: u32,
+  spacing: f32,
+  width_px: f32,
+  color: vec4<f32>,
+  major_color: vec4<f32>,
+  // world offset modulo the major period
+  phase: vec2<f32>,
+  fade_start: f32,
+  fade_end: f32,
+};
+
+struct DetailParams {
+  enabled: u32,
+  // world units per repeat
+  scale: f32,
+  strength: f32,
+  // hex-tile stochastic sampling on when non-zero, up to stochastic_distance
+  stochastic: u32,
+  stochastic_sharpness: f32,
+  stochastic_distance: f32,
 };
 
+struct PomParams {
+  enabled: u32,
+  max_steps: u32,
+  // relief depth, world units
+  depth_scale: f32,
+  fade_distance: f32,
+  self_shadow: u32,
+};
+
+struct TileDecal {
+  // relative to the tile's min corner
+  center: vec2<f32>,
+  // cos, sin
+  rotation: vec2<f32>,
+  radius: f32,
+  alpha: f32,
+};
+
+// MAX_TILE_DECALS in material.rs
+struct DecalParams {
+  count: u32,
+  decals: array<TileDecal, 4>,
+};
+
+const DEBUG_NONE: u32 = 0u;
+const DEBUG_CURVATURE: u32 = 1u;
+const DEBUG_HEX_TILES: u32 = 2u;
+const DEBUG_SPLAT_COVERAGE: u32 = 3u;
+
 @group(2) @binding(0) var<uniform> params: TileParams;
 @group(2) @binding(1) var height_tex: texture_2d<f32>;
-@group(2) @binding(2) var normal_tex: texture_2d<f32>; // unused for now
+@group(2) @binding(2) var normal_tex: texture_2d<f32>;
+@group(2) @binding(3) var curvature_tex: texture_2d<f32>;
+@group(2) @binding(4) var splat_override_tex: texture_2d<f32>;
+@group(2) @binding(5) var<uniform> splat: SplatParams;
+@group(2) @binding(6) var<uniform> overlay: OverlayParams;
+@group(2) @binding(7) var<uniform> grid: GridParams;
+@group(2) @binding(8) var normal_sampler: sampler;
+@group(2) @binding(9) var<uniform> detail: DetailParams;
+@group(2) @binding(10) var detail_tex: texture_2d<f32>;
+@group(2) @binding(11) var detail_sampler: sampler;
+@group(2) @binding(12) var detail_height_tex: texture_2d<f32>;
+@group(2) @binding(13) var detail_height_sampler: sampler;
+@group(2) @binding(14) var<uniform> pom: PomParams;
+@group(2) @binding(15) var<uniform> decals: DecalParams;
+@group(2) @binding(16) var decal_tex_0: texture_2d<f32>;
+@group(2) @binding(17) var decal_tex_1: texture_2d<f32>;
+@group(2) @binding(18) var decal_tex_2: texture_2d<f32>;
+@group(2) @binding(19) var decal_tex_3: texture_2d<f32>;
+@group(2) @binding(20) var decal_sampler: sampler;
 
-fn height_at_uv(uv: vec2<f32>) -> f32 {
+#ifdef TERRAIN_HOLES
+@group(2) @binding(21) var hole_tex: texture_2d<f32>;
+
+// Bilinear hole mask (0 = hole, 1 = solid); below one half is cut, the same
+// outline as `HeightTile::is_hole`.
+fn solid_at_uv(uv: vec2<f32>) -> f32 {
   let N = f32(params.texels_per_side);
-  let x = i32(clamp(floor(uv.x * (N - 1.0)), 0.0, N - 1.0));
-  let y = i32(clamp(floor(uv.y * (N - 1.0)), 0.0, N - 1.0));
-  return textureLoad(height_tex, vec2<i32>(x, y), 0).r;
+  let p = clamp(uv * (N - 1.0), vec2<f32>(0.0), vec2<f32>(N - 1.0));
+  let i = vec2<i32>(min(floor(p), vec2<f32>(N - 2.0)));
+  let f = p - vec2<f32>(i);
+  let a = textureLoad(hole_tex, i, 0).r;
+  let b = textureLoad(hole_tex, i + vec2<i32>(1, 0), 0).r;
+  let c = textureLoad(hole_tex, i + vec2<i32>(0, 1), 0).r;
+  let d = textureLoad(hole_tex, i + vec2<i32>(1, 1), 0).r;
+  return mix(mix(a, b, f.x), mix(c, d, f.x), f.y);
+}
+#endif
+
+fn texel_at_uv(uv: vec2<f32>) -> vec2<i32> {
+  let N = f32(params.texels_per_side);
+  let x = i32(clamp(round(uv.x * (N - 1.0)), 0.0, N - 1.0));
+  let y = i32(clamp(round(uv.y * (N - 1.0)), 0.0, N - 1.0));
+  return vec2<i32>(x, y);
+}
+
+// Unscaled normal under a tile uv. NORMAL_MIPS: filtered through the mip
+// chain (uv mapped onto texel centers); otherwise the nearest texel. A
+// compressed map is padded past texels_per_side, hence textureDimensions.
+fn normal_at_uv(uv: vec2<f32>) -> vec3<f32> {
+#ifdef NORMAL_MIPS
+  let N = f32(params.texels_per_side);
+  let size = vec2<f32>(textureDimensions(normal_tex));
+  let st = (clamp(uv, vec2<f32>(0.0), vec2<f32>(1.0)) * (N - 1.0) + 0.5) / size;
+  return textureSample(normal_tex, normal_sampler, st).xyz * 2.0 - 1.0;
+#else
+  return textureLoad(normal_tex, texel_at_uv(uv), 0).xyz * 2.0 - 1.0;
+#endif
+}
+
+fn height_at_texel(texel: vec2<i32>) -> f32 {
+  return params.height_offset + textureLoad(height_tex, texel, 0).r * params.height_range;
+}
+
+fn height_at_uv(uv: vec2<f32>) -> f32 {
+  return height_at_texel(texel_at_uv(uv));
 }
 
 @vertex
 fn vertex(in: Vertex) -> VertexOutput {
   var out: VertexOutput;
 
+#ifdef CPU_DISPLACED
+  // the mesh already has the heights baked in
+  let h = 0.0;
+#else
   let h = height_at_uv(in.uv) * params.height_scale;
+#endif
+
+  let world_from_local = mesh_functions::get_world_from_local(in.instance_index);
+  let local_pos = vec4<f32>(in.position.x, in.position.y + h, in.position.z, 1.0);
+  let world_pos = mesh_functions::mesh_position_local_to_world(world_from_local, local_pos);
+
+  out.position       = position_world_to_clip(world_pos.xyz);
+  out.world_position = world_pos;
+  out.world_normal   = mesh_functions::mesh_normal_local_to_world(in.normal, in.instance_index);
+  out.uv             = in.uv;
+  return out;
+}
+
+// Blue = concave, red = convex, white = flat.
+fn curvature_color(uv: vec2<f32>) -> vec4<f32> {
+  let c = textureLoad(curvature_tex, texel_at_uv(uv), 0).r * params.height_scale;
+  let t = tanh(c * 4.0);
+  let concave = vec3<f32>(0.1, 0.3, 1.0);
+  let convex  = vec3<f32>(1.0, 0.2, 0.1);
+  let rgb = select(mix(vec3<f32>(1.0), convex, -t), mix(vec3<f32>(1.0), concave, t), t >= 0.0);
+  return vec4<f32>(rgb, 1.0);
+}
+
+// Procedural layer weights from height and slope.
+fn procedural_weights(uv: vec2<f32>) -> vec4<f32> {
+  let h = height_at_uv(uv) * params.height_scale;
+  // stored normals are unscaled; rescale the gradient before taking the slope
+  let n = normal_at_uv(uv);
+  let scaled = normalize(vec3<f32>(n.x * params.height_scale, n.y, n.z * params.height_scale));
+  let slope = degrees(acos(clamp(scaled.y, -1.0, 1.0)));
 
-  let world_from_local = mesh::get_world_from_local();
-  let local_pos  = vec4<f32>(in.position.x, in.position.y + h, in.position.z, 1.0);
-  let world_pos  = (world_from_local * local_pos).xyz;
+  let b = max(splat.blend, 1e-3);
+  let rock = smoothstep(splat.rock_slope - b, splat.rock_slope + b, slope);
+  let sand = (1.0 - smoothstep(splat.sand_height - b, splat.sand_height + b, h)) * (1.0 - rock);
+  let sb = max(splat.snow_blend, 1e-3);
+  let snow = smoothstep(splat.snow_height - sb, splat.snow_height + sb, h) * (1.0 - rock);
+  let grass = max(1.0 - rock - sand - snow, 0.0);
+  return vec4<f32>(grass, rock, sand, snow);
+}
+
+// Painted overrides replace the procedural weights by their channel sum.
+fn splat_weights(uv: vec2<f32>) -> vec4<f32> {
+  let procedural = procedural_weights(uv);
+  let painted = textureLoad(splat_override_tex, texel_at_uv(uv), 0);
+  let amount = dot(painted, vec4<f32>(1.0));
+  if (amount <= 0.0) {
+    return procedural;
+  }
+  return mix(procedural, painted / amount, min(amount, 1.0));
+}
+
+// Detail texture coordinates: the tile uv turned by detail_rotation quarter
+// turns about the tile center, in repeats, shifted by detail_offset. With no
+// turn and the tile's phase as the offset it runs on across tiles.
+fn detail_uv(uv: vec2<f32>) -> vec2<f32> {
+  return (rotate_detail(uv - 0.5) + 0.5) * params.tile_size / detail.scale + params.detail_offset;
+}
+
+// A tile-uv (world xz) direction in detail texture axes.
+fn rotate_detail(c: vec2<f32>) -> vec2<f32> {
+  switch params.detail_rotation & 3u {
+    case 1u: { return vec2<f32>(-c.y, c.x); }
+    case 2u: { return -c; }
+    case 3u: { return vec2<f32>(c.y, -c.x); }
+    default: { return c; }
+  }
+}
+
+// Parallax occlusion mapping: march the view ray down through the detail
+// height layers (1 = top, depth_scale world units to 0) and return the
+// detail uv where it hits, interpolated between the last two layers. The
+// surface is taken as level; on slopes the relief skews a little.
+struct PomHit {
+  uv: vec2<f32>,
+  // 0..1 below the top
+  depth: f32,
+};
+
+fn detail_height(uv: vec2<f32>, ddx: vec2<f32>, ddy: vec2<f32>) -> f32 {
+  return textureSampleGrad(detail_height_tex, detail_height_sampler, uv, ddx, ddy).r;
+}
+
+fn parallax(uv: vec2<f32>, to_eye: vec3<f32>, steps: u32, ddx: vec2<f32>, ddy: vec2<f32>) -> PomHit {
+  let layer = 1.0 / f32(steps);
+  // detail uv shift per layer along the ray
+  let delta = rotate_detail(to_eye.xz) / max(to_eye.y, 0.1) * (pom.depth_scale / detail.scale) * layer;
+  var cur = uv;
+  var depth = 0.0;
+  var surface = 1.0 - detail_height(cur, ddx, ddy);
+  for (var i = 0u; i < steps && depth < surface; i++) {
+    cur -= delta;
+    depth += layer;
+    surface = 1.0 - detail_height(cur, ddx, ddy);
+  }
+  let prev = cur + delta;
+  let after = surface - depth;
+  let before = (1.0 - detail_height(prev, ddx, ddy)) - depth + layer;
+  let t = after / min(after - before, -1e-6);
+  return PomHit(mix(cur, prev, t), depth - layer * t);
+}
+
+// 1 lit .. 0 occluded: march from the hit toward the light and keep the
+// deepest the relief rises above the ray.
+fn parallax_shadow(hit: PomHit, to_light: vec3<f32>, steps: u32, ddx: vec2<f32>, ddy: vec2<f32>) -> f32 {
+  if (to_light.y <= 0.0) {
+    return 0.0;
+  }
+  let layer = hit.depth / f32(steps);
+  let delta = rotate_detail(to_light.xz) / max(to_light.y, 0.1) * (pom.depth_scale / detail.scale) * layer;
+  var occlusion = 0.0;
+  var cur = hit.uv;
+  var depth = hit.depth;
+  for (var i = 0u; i < steps && depth > 0.0; i++) {
+    cur += delta;
+    depth -= layer;
+    occlusion = max(occlusion, (depth - (1.0 - detail_height(cur, ddx, ddy))) * f32(steps));
+  }
+  return 1.0 - clamp(occlusion, 0.0, 1.0);
+}
+
+// Hex-tile stochastic sampling (Heitz & Neyret, "High-Performance By-Example
+// Noise using a Histogram-Preserving Blending Operator"; the simpler variant
+// without the histogram transform): the uv plane is cut into a triangle grid
+// whose vertices each shift the texture by a hashed offset, and every point
+// blends the three samples of its triangle by barycentric weight.
+struct HexSamples {
+  weights: vec3<f32>,
+  v1: vec2<f32>,
+  v2: vec2<f32>,
+  v3: vec2<f32>,
+};
+
+fn hex_samples(uv: vec2<f32>) -> HexSamples {
+  // 2 * sqrt(3): about one hex per repeat
+  let p = uv * 3.4641016;
+  let skewed = vec2<f32>(p.x, -0.57735027 * p.x + 1.15470054 * p.y);
+  let base = floor(skewed);
+  let f = fract(skewed);
+  let z = 1.0 - f.x - f.y;
+  var out: HexSamples;
+  if (z > 0.0) {
+    out.weights = vec3<f32>(z, f.y, f.x);
+    out.v1 = base;
+    out.v2 = base + vec2<f32>(0.0, 1.0);
+    out.v3 = base + vec2<f32>(1.0, 0.0);
+  } else {
+    out.weights = vec3<f32>(-z, 1.0 - f.y, 1.0 - f.x);
+    out.v1 = base + vec2<f32>(1.0, 1.0);
+    out.v2 = base + vec2<f32>(1.0, 0.0);
+    out.v3 = base + vec2<f32>(0.0, 1.0);
+  }
+  let w = pow(out.weights, vec3<f32>(detail.stochastic_sharpness));
+  out.weights = w / max(w.x + w.y + w.z, 1e-6);
+  return out;
+}
+
+fn hex_offset(vertex: vec2<f32>) -> vec2<f32> {
+  return fract(sin(vec2<f32>(dot(vertex, vec2<f32>(127.1, 311.7)), dot(vertex, vec2<f32>(269.5, 183.3)))) * 43758.5453);
+}
+
+// Detail color at uv; stochastic within stochastic_distance of the camera.
+// Explicit gradients from the unshifted uv keep the shifted samples seamless
+// and allow the distance branch.
+fn sample_detail(uv: vec2<f32>, dist: f32) -> vec3<f32> {
+  let ddx = dpdx(uv);
+  let ddy = dpdy(uv);
+  if (detail.stochastic == 0u || dist > detail.stochastic_distance) {
+    return textureSampleGrad(detail_tex, detail_sampler, uv, ddx, ddy).rgb;
+  }
+  let hex = hex_samples(uv);
+  let c1 = textureSampleGrad(detail_tex, detail_sampler, uv + hex_offset(hex.v1), ddx, ddy).rgb;
+  let c2 = textureSampleGrad(detail_tex, detail_sampler, uv + hex_offset(hex.v2), ddx, ddy).rgb;
+  let c3 = textureSampleGrad(detail_tex, detail_sampler, uv + hex_offset(hex.v3), ddx, ddy).rgb;
+  return c1 * hex.weights.x + c2 * hex.weights.y + c3 * hex.weights.z;
+}
+
+// Smooth: the baked normal map. FLAT_SHADING: one normal per triangle from
+// screen-space derivatives of the displaced position (low-poly look).
+fn surface_normal(in: VertexOutput) -> vec3<f32> {
+#ifdef FLAT_SHADING
+  return normalize(cross(dpdy(in.world_position.xyz), dpdx(in.world_position.xyz)));
+#else
+  let n = normal_at_uv(in.uv);
+  return normalize(vec3<f32>(n.x * params.height_scale, n.y, n.z * params.height_scale));
+#endif
+}
+
+// Coverage of the contour line nearest to world height `h`. The distance to
+// it is measured in pixels through fwidth, so lines keep their screen width
+// on steep and flat ground alike.
+fn contour_coverage(h: f32) -> f32 {
+  let f = h / overlay.contour_interval;
+  let pixels = abs(fract(f + 0.5) - 0.5) / max(fwidth(f), 1e-6);
+  let index = i32(round(f));
+  let major = overlay.contour_major_every > 0u && (abs(index) % i32(overlay.contour_major_every)) == 0;
+  let half_width = overlay.contour_width_px * select(0.5, 1.0, major);
+  return 1.0 - smoothstep(half_width - 0.5, half_width + 0.5, pixels);
+}
+
+// Coverage of the nearest grid line of period `cell` through world `xz`.
+fn grid_line_coverage(xz: vec2<f32>, cell: f32, width_px: f32) -> f32 {
+  let f = xz / cell;
+  let w = max(fwidth(f), vec2<f32>(1e-6));
+  let pixels = abs(fract(f + 0.5) - 0.5) / w;
+  let d = min(pixels.x, pixels.y);
+  // lines closer than a few pixels alias; fade them out instead
+  let density = 1.0 - smoothstep(0.25, 0.5, max(w.x, w.y));
+  return (1.0 - smoothstep(width_px * 0.5 - 0.5, width_px * 0.5 + 0.5, d)) * density;
+}
 
-  out.position           = mesh::get_clip_from_world() * vec4<f32>(world_pos, 1.0);
-  out.prev_clip_position = mesh::get_prev_clip_from_world() * vec4<f32>(world_pos, 1.0);
-  out.world_position     = world_pos;
-  out.world_normal       = normalize((mesh::get_inverse_transpose_model() * vec4<f32>(in.normal, 0.0)).xyz);
-  out.uv                 = in.uv;
+fn apply_grid(color: vec3<f32>, world_position: vec3<f32>) -> vec3<f32> {
+  let xz = world_position.xz + grid.phase;
+  let dist = distance(world_position, view.world_position);
+  let fade = 1.0 - smoothstep(grid.fade_start, grid.fade_end, dist);
+  var out = mix(color, grid.color.rgb, grid_line_coverage(xz, grid.spacing, grid.width_px) * grid.color.a * fade);
+  if (grid.major_every > 0u) {
+    let major = grid_line_coverage(xz, grid.spacing * f32(grid.major_every), grid.width_px * 2.0);
+    out = mix(out, grid.major_color.rgb, major * grid.major_color.a * fade);
+  }
   return out;
 }
 
+// Decal i's color at a point relative to the tile corner; alpha is 0
+// outside its square.
+fn decal_color(i: u32, p: vec2<f32>) -> vec4<f32> {
+  let d = decals.decals[i];
+  let r = p - d.center;
+  // into the decal's frame: rotate back by its angle
+  let q = vec2<f32>(r.x * d.rotation.x + r.y * d.rotation.y, r.y * d.rotation.x - r.x * d.rotation.y) / d.radius;
+  let uv = q * 0.5 + 0.5;
+  // the count and index are uniform, so plain textureSample is fine here
+  var c: vec4<f32>;
+  switch i {
+    case 0u: { c = textureSample(decal_tex_0, decal_sampler, uv); }
+    case 1u: { c = textureSample(decal_tex_1, decal_sampler, uv); }
+    case 2u: { c = textureSample(decal_tex_2, decal_sampler, uv); }
+    default: { c = textureSample(decal_tex_3, decal_sampler, uv); }
+  }
+  let inside = f32(max(abs(q.x), abs(q.y)) <= 1.0);
+  return vec4<f32>(c.rgb, c.a * d.alpha * inside);
+}
+
 @fragment
-fn fragment(_in: VertexOutput) -> FragmentOutput {
+fn fragment(in: VertexOutput) -> FragmentOutput {
+#ifdef TERRAIN_HOLES
+  if (solid_at_uv(in.uv) < 0.5) {
+    discard;
+  }
+#endif
   var out: FragmentOutput;
-  out.color = params.tile_color; // obvious solid tiles while validating
+  if (params.debug_mode == DEBUG_CURVATURE) {
+    out.color = curvature_color(in.uv);
+    return out;
+  }
+  if (params.debug_mode == DEBUG_HEX_TILES) {
+    out.color = vec4<f32>(hex_samples(detail_uv(in.uv)).weights, 1.0);
+    return out;
+  }
+  let w = splat_weights(in.uv);
+  if (params.debug_mode == DEBUG_SPLAT_COVERAGE && abs(dot(w, vec4<f32>(1.0)) - 1.0) > 1e-3) {
+    out.color = vec4<f32>(1.0, 0.0, 1.0, 1.0);
+    return out;
+  }
+  var color = vec4<f32>(0.0);
+  for (var i = 0; i < 4; i++) {
+    color += splat.layer_colors[i] * w[i];
+  }
+  if (detail.enabled != 0u) {
+    // mid-gray is neutral
+    let dist = distance(in.world_position.xyz, view.world_position);
+    var uv = detail_uv(in.uv);
+    var shade = 1.0;
+    // gradients of the unshifted uv: the march is in non-uniform control flow
+    let ddx = dpdx(uv);
+    let ddy = dpdy(uv);
+    let steps = u32(round(f32(pom.max_steps) * (1.0 - smoothstep(pom.fade_distance * 0.5, pom.fade_distance, dist))));
+    if (pom.enabled != 0u && steps > 0u) {
+      let to_eye = normalize(view.world_position - in.world_position.xyz);
+      let hit = parallax(uv, to_eye, steps, ddx, ddy);
+      uv = hit.uv;
+      if (pom.self_shadow != 0u && lights.n_directional_lights > 0u) {
+        shade = parallax_shadow(hit, lights.directional_lights[0].direction_to_light, steps * 2u, ddx, ddy);
+      }
+    }
+    let d = sample_detail(uv, dist) * 2.0 * mix(1.0, shade, 0.6);
+    color = vec4<f32>(color.rgb * mix(vec3<f32>(1.0), d, detail.strength), color.a);
+  }
+  for (var i = 0u; i < decals.count; i++) {
+    let c = decal_color(i, in.uv * params.tile_size);
+    color = vec4<f32>(mix(color.rgb, c.rgb, c.a), color.a);
+  }
+  let h = height_at_uv(in.uv) * params.height_scale;
+  let under = 1.0 - smoothstep(splat.sea_level - max(splat.blend, 1e-3), splat.sea_level, h);
+  let tinted = mix(color.rgb, color.rgb * splat.underwater_tint.rgb, under * splat.underwater_tint.a);
+
+  var pbr = pbr_types::pbr_input_new();
+  // wet ground: darker albedo, snow stays bright
+  let wet = splat.wetness * (1.0 - w[3]);
+  pbr.material.base_color = vec4<f32>(tinted * mix(1.0, 0.55, wet), 1.0) * params.tile_color;
+  pbr.material.perceptual_roughness = mix(0.9, 0.35, wet);
+  pbr.frag_coord = in.position;
+  pbr.world_position = in.world_position;
+  let N = surface_normal(in);
+  pbr.world_normal = N;
+  pbr.N = N;
+  pbr.is_orthographic = view.clip_from_view[3].w == 1.0;
+  pbr.V = pbr_functions::calculate_view(in.world_position, pbr.is_orthographic);
+
+  let lit = pbr_functions::apply_pbr_lighting(pbr);
+  out.color = pbr_functions::main_pass_post_lighting_processing(pbr, lit);
+
+  if (overlay.contour_enabled != 0u) {
+    // the displaced world height, continuous across tiles
+    let line = contour_coverage(in.world_position.y) * overlay.contour_color.a;
+    out.color = vec4<f32>(mix(out.color.rgb, overlay.contour_color.rgb, line), out.color.a);
+  }
+  if (grid.enabled != 0u) {
+    out.color = vec4<f32>(apply_grid(out.color.rgb, in.world_position.xyz), out.color.a);
+  }
   return out;
 }
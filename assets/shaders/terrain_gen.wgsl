@@ -0,0 +1,126 @@
+// Tile generation on the GPU, see `gpu_generation.rs`.
+//
+// `heights` fills the (n+2)² apron grid with the same hashed Perlin fBm as
+// `HashedFbm` in meshgen.rs (same operations, same order). `derive` then
+// writes the interior heights, normals (`normalmap_from_height`) and
+// curvature (`curvature_from_height`) into the tile's textures.
+
+struct GenParams {
+    origin: vec2<f32>,
+    step: f32,
+    n: u32,
+    seed: u32,
+    octaves: u32,
+    lacunarity: f32,
+    persistence: f32,
+    frequency: f32,
+    amplitude: f32,
+    falloff_enabled: u32,
+    falloff_radius: f32,
+    falloff_center: vec2<f32>,
+    falloff_width: f32,
+    falloff_edge: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: GenParams;
+@group(0) @binding(1) var<storage, read_write> padded: array<f32>;
+@group(0) @binding(2) var height_out: texture_storage_2d<r32float, write>;
+@group(0) @binding(3) var normal_out: texture_storage_2d<rgba8unorm, write>;
+@group(0) @binding(4) var curvature_out: texture_storage_2d<r32float, write>;
+
+const FRAC_1_SQRT_2: f32 = 0.70710678;
+const SQRT_2: f32 = 1.41421356;
+
+fn pcg_hash(x: u32) -> u32 {
+    let state = x * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn gradient(h: u32) -> vec2<f32> {
+    switch h & 7u {
+        case 0u: { return vec2(1.0, 0.0); }
+        case 1u: { return vec2(-1.0, 0.0); }
+        case 2u: { return vec2(0.0, 1.0); }
+        case 3u: { return vec2(0.0, -1.0); }
+        case 4u: { return vec2(FRAC_1_SQRT_2, FRAC_1_SQRT_2); }
+        case 5u: { return vec2(-FRAC_1_SQRT_2, FRAC_1_SQRT_2); }
+        case 6u: { return vec2(FRAC_1_SQRT_2, -FRAC_1_SQRT_2); }
+        default: { return vec2(-FRAC_1_SQRT_2, -FRAC_1_SQRT_2); }
+    }
+}
+
+fn corner(cell: vec2<i32>, f: vec2<f32>, d: vec2<i32>, seed_hash: u32) -> f32 {
+    let c = cell + d;
+    let h = pcg_hash(bitcast<u32>(c.x) ^ pcg_hash(bitcast<u32>(c.y) ^ seed_hash));
+    return dot(gradient(h), f - vec2<f32>(d));
+}
+
+fn hashed_perlin(p: vec2<f32>, seed: u32) -> f32 {
+    let cell_f = floor(p);
+    let f = p - cell_f;
+    let cell = vec2<i32>(cell_f);
+    let seed_hash = pcg_hash(seed);
+    let c00 = corner(cell, f, vec2(0, 0), seed_hash);
+    let c10 = corner(cell, f, vec2(1, 0), seed_hash);
+    let c01 = corner(cell, f, vec2(0, 1), seed_hash);
+    let c11 = corner(cell, f, vec2(1, 1), seed_hash);
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    let bottom = c00 + (c10 - c00) * u.x;
+    let top = c01 + (c11 - c01) * u.x;
+    return (bottom + (top - bottom) * u.y) * SQRT_2;
+}
+
+fn fbm(p: vec2<f32>) -> f32 {
+    var sum = 0.0;
+    var total = 0.0;
+    var amplitude = 1.0;
+    var frequency = params.frequency;
+    for (var octave = 0u; octave < params.octaves; octave++) {
+        sum += amplitude * hashed_perlin(p * frequency, params.seed + octave);
+        total += amplitude;
+        amplitude *= params.persistence;
+        frequency *= params.lacunarity;
+    }
+    return sum / max(total, 1e-6);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn heights(@builtin(global_invocation_id) id: vec3<u32>) {
+    let m = params.n + 2u;
+    if id.x >= m || id.y >= m { return; }
+    let p = params.origin + vec2<f32>(id.xy) * params.step;
+    var h = fbm(p) * params.amplitude;
+    if params.falloff_enabled != 0u {
+        let t = clamp((distance(p, params.falloff_center) - params.falloff_radius) / params.falloff_width, 0.0, 1.0);
+        let mask = 1.0 - t * t * (3.0 - 2.0 * t);
+        h = params.falloff_edge + (h - params.falloff_edge) * mask;
+    }
+    padded[id.y * m + id.x] = h;
+}
+
+fn padded_at(x: u32, z: u32) -> f32 {
+    return padded[z * (params.n + 2u) + x];
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn derive(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.n || id.y >= params.n { return; }
+    // interior sample (x, z) sits at (x + 1, z + 1) in the apron grid
+    let x = id.x + 1u;
+    let z = id.y + 1u;
+    let h = padded_at(x, z);
+    let h_l = padded_at(x - 1u, z);
+    let h_r = padded_at(x + 1u, z);
+    let h_d = padded_at(x, z - 1u);
+    let h_u = padded_at(x, z + 1u);
+    let texel = vec2<i32>(id.xy);
+
+    textureStore(height_out, texel, vec4(h, 0.0, 0.0, 0.0));
+    let dx = (h_r - h_l) / (2.0 * params.step);
+    let dz = (h_u - h_d) / (2.0 * params.step);
+    let normal = normalize(vec3(-dx, 1.0, -dz));
+    textureStore(normal_out, texel, vec4(normal * 0.5 + 0.5, 1.0));
+    let curvature = (h_l + h_r + h_d + h_u - 4.0 * h) / (params.step * params.step);
+    textureStore(curvature_out, texel, vec4(curvature, 0.0, 0.0, 0.0));
+}
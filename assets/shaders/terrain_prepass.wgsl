@@ -0,0 +1,130 @@
+// Depth/shadow prepass for terrain tiles: same displacement as terrain.wgsl so
+// the depth prepass and shadow maps see the real surface, not the flat mesh.
+// The fragment stage cuts the tile's holes and otherwise matches Bevy's
+// default prepass fragment.
+#import bevy_pbr::{
+    mesh_functions,
+    mesh_view_bindings::view,
+    view_transformations::position_world_to_clip,
+    prepass_io::{Vertex, VertexOutput, FragmentOutput},
+    prepass_bindings,
+}
+
+struct TileParams {
+  tile_size: f32,
+  height_scale: f32,
+  texels_per_side: u32,
+  debug_mode: u32,
+  tile_color: vec4<f32>,
+  // texel v decodes to height_offset + v * height_range (R16Unorm heights)
+  height_offset: f32,
+  height_range: f32,
+  detail_offset: vec2<f32>,
+  detail_rotation: u32,
+};
+
+@group(2) @binding(0) var<uniform> params: TileParams;
+@group(2) @binding(1) var height_tex: texture_2d<f32>;
+
+#ifdef TERRAIN_HOLES
+@group(2) @binding(21) var hole_tex: texture_2d<f32>;
+
+// Bilinear hole mask (0 = hole, 1 = solid); below one half is cut, the same
+// outline as `HeightTile::is_hole`.
+fn solid_at_uv(uv: vec2<f32>) -> f32 {
+  let N = f32(params.texels_per_side);
+  let p = clamp(uv * (N - 1.0), vec2<f32>(0.0), vec2<f32>(N - 1.0));
+  let i = vec2<i32>(min(floor(p), vec2<f32>(N - 2.0)));
+  let f = p - vec2<f32>(i);
+  let a = textureLoad(hole_tex, i, 0).r;
+  let b = textureLoad(hole_tex, i + vec2<i32>(1, 0), 0).r;
+  let c = textureLoad(hole_tex, i + vec2<i32>(0, 1), 0).r;
+  let d = textureLoad(hole_tex, i + vec2<i32>(1, 1), 0).r;
+  return mix(mix(a, b, f.x), mix(c, d, f.x), f.y);
+}
+#endif
+
+fn height_at_uv(uv: vec2<f32>) -> f32 {
+  let N = f32(params.texels_per_side);
+  let texel = vec2<i32>(clamp(round(uv * (N - 1.0)), vec2<f32>(0.0), vec2<f32>(N - 1.0)));
+  return params.height_offset + textureLoad(height_tex, texel, 0).r * params.height_range;
+}
+
+@vertex
+fn vertex(in: Vertex) -> VertexOutput {
+  var out: VertexOutput;
+
+  var h = 0.0;
+#ifdef VERTEX_UVS_A
+#ifndef CPU_DISPLACED
+  h = height_at_uv(in.uv) * params.height_scale;
+#endif
+  out.uv = in.uv;
+#endif
+
+  let world_from_local = mesh_functions::get_world_from_local(in.instance_index);
+  let local_pos = vec4<f32>(in.position.x, in.position.y + h, in.position.z, 1.0);
+  let world_pos = mesh_functions::mesh_position_local_to_world(world_from_local, local_pos);
+
+  out.position = position_world_to_clip(world_pos.xyz);
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+  out.unclipped_depth = out.position.z;
+  out.position.z = min(out.position.z, 1.0);
+#endif
+
+#ifdef NORMAL_PREPASS_OR_DEFERRED_PREPASS
+#ifdef VERTEX_NORMALS
+  out.world_normal = mesh_functions::mesh_normal_local_to_world(in.normal, in.instance_index);
+#endif
+#endif
+
+  out.world_position = world_pos;
+#ifdef MOTION_VECTOR_PREPASS
+  // tiles never move; treat the surface as static
+  out.previous_world_position = world_pos;
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+  out.instance_index = in.instance_index;
+#endif
+  return out;
+}
+
+fn cut_holes(in: VertexOutput) {
+#ifdef TERRAIN_HOLES
+#ifdef VERTEX_UVS_A
+  if (solid_at_uv(in.uv) < 0.5) {
+    discard;
+  }
+#endif
+#endif
+}
+
+#ifdef PREPASS_FRAGMENT
+@fragment
+fn fragment(in: VertexOutput) -> FragmentOutput {
+  cut_holes(in);
+  var out: FragmentOutput;
+#ifdef NORMAL_PREPASS
+  out.normal = vec4<f32>(in.world_normal * 0.5 + vec3<f32>(0.5), 1.0);
+#endif
+#ifdef UNCLIPPED_DEPTH_ORTHO_EMULATION
+  out.frag_depth = in.unclipped_depth;
+#endif
+#ifdef MOTION_VECTOR_PREPASS
+  // camera motion only, as in Bevy's prepass.wgsl
+  let clip_position_t = view.unjittered_clip_from_world * in.world_position;
+  let clip_position = clip_position_t.xy / clip_position_t.w;
+  let previous_clip_position_t = prepass_bindings::previous_view_uniforms.clip_from_world * in.previous_world_position;
+  let previous_clip_position = previous_clip_position_t.xy / previous_clip_position_t.w;
+  out.motion_vector = (clip_position - previous_clip_position) * vec2<f32>(0.5, -0.5);
+#endif
+  return out;
+}
+#else
+// shadow and depth-only passes of alpha-masked (holed) tiles
+@fragment
+fn fragment(in: VertexOutput) {
+  cut_holes(in);
+}
+#endif
@@ -0,0 +1,157 @@
+#import bevy_pbr::{
+    mesh_functions,
+    mesh_view_bindings::{view, globals},
+    view_transformations::position_world_to_clip,
+    pbr_types,
+    pbr_functions,
+}
+
+const WAVE_COUNT: u32 = 4u;
+const TAU: f32 = 6.28318530718;
+
+struct WaterParams {
+  color: vec4<f32>,
+  deep_color: vec4<f32>,
+  // xy = direction, z = amplitude, w = wavelength
+  waves: array<vec4<f32>, 4>,
+  speeds: vec4<f32>,
+  foam_color: vec4<f32>,
+  steepness: f32,
+  roughness: f32,
+  // 1/m; how quickly the deep color takes over with water depth
+  absorption: f32,
+  // water depth (m) below which foam appears
+  foam_width: f32,
+  foam_noise_scale: f32,
+};
+
+// Same layout as the terrain's TileParams; only height lookup is used here.
+struct TileParams {
+  tile_size: f32,
+  height_scale: f32,
+  texels_per_side: u32,
+  debug_mode: u32,
+  tile_color: vec4<f32>,
+  // texel v decodes to height_offset + v * height_range (R16Unorm heights)
+  height_offset: f32,
+  height_range: f32,
+  detail_offset: vec2<f32>,
+  detail_rotation: u32,
+};
+
+@group(2) @binding(0) var<uniform> params: WaterParams;
+@group(2) @binding(1) var<uniform> tile: TileParams;
+@group(2) @binding(2) var height_tex: texture_2d<f32>;
+
+struct Vertex {
+  @builtin(instance_index) instance_index: u32,
+  @location(0) position: vec3<f32>,
+};
+
+struct WaterVertexOutput {
+  @builtin(position) position: vec4<f32>,
+  @location(0) world_position: vec4<f32>,
+  @location(1) world_normal: vec3<f32>,
+  // rest position in the tile, 0..1 (the water quad spans the tile)
+  @location(2) tile_uv: vec2<f32>,
+};
+
+struct Gerstner {
+  offset: vec3<f32>,
+  normal: vec3<f32>,
+};
+
+// Sum of Gerstner waves at rest position `p` (world xz). Must match
+// `WaterSettings::displacement_at` on the CPU.
+fn gerstner(p: vec2<f32>, t: f32) -> Gerstner {
+  var offset = vec3<f32>(0.0);
+  var n = vec3<f32>(0.0, 1.0, 0.0);
+  for (var i = 0u; i < WAVE_COUNT; i++) {
+    let w = params.waves[i];
+    if (w.z <= 0.0 || w.w <= 0.0) { continue; }
+    let dir = normalize(w.xy);
+    let k = TAU / w.w;
+    let f = k * (dot(dir, p) - params.speeds[i] * t);
+    let q = params.steepness / (k * w.z * f32(WAVE_COUNT));
+    let s = sin(f);
+    let c = cos(f);
+    offset += vec3<f32>(dir.x * q * w.z * c, w.z * s, dir.y * q * w.z * c);
+    let wa = k * w.z;
+    n += vec3<f32>(-dir.x * wa * c, -q * wa * s, -dir.y * wa * c);
+  }
+  return Gerstner(offset, normalize(n));
+}
+
+@vertex
+fn vertex(in: Vertex) -> WaterVertexOutput {
+  var out: WaterVertexOutput;
+
+  let world_from_local = mesh_functions::get_world_from_local(in.instance_index);
+  let rest = mesh_functions::mesh_position_local_to_world(world_from_local, vec4<f32>(in.position, 1.0));
+  let wave = gerstner(rest.xz, globals.time);
+  let world_pos = vec4<f32>(rest.xyz + wave.offset, 1.0);
+
+  out.position = position_world_to_clip(world_pos.xyz);
+  out.world_position = world_pos;
+  out.world_normal = wave.normal;
+  out.tile_uv = in.position.xz;
+  return out;
+}
+
+// Bilinear terrain height (tile-local y) under a tile uv.
+fn terrain_height(uv: vec2<f32>) -> f32 {
+  let last = f32(tile.texels_per_side - 1u);
+  let p = clamp(uv, vec2<f32>(0.0), vec2<f32>(1.0)) * last;
+  let i = vec2<i32>(min(floor(p), vec2<f32>(last - 1.0)));
+  let f = p - vec2<f32>(i);
+  let h00 = textureLoad(height_tex, i, 0).r;
+  let h10 = textureLoad(height_tex, i + vec2<i32>(1, 0), 0).r;
+  let h01 = textureLoad(height_tex, i + vec2<i32>(0, 1), 0).r;
+  let h11 = textureLoad(height_tex, i + vec2<i32>(1, 1), 0).r;
+  let v = mix(mix(h00, h10, f.x), mix(h01, h11, f.x), f.y);
+  return (tile.height_offset + v * tile.height_range) * tile.height_scale;
+}
+
+fn hash2(p: vec2<f32>) -> f32 {
+  return fract(sin(dot(p, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+fn value_noise(p: vec2<f32>) -> f32 {
+  let i = floor(p);
+  let f = fract(p);
+  let u = f * f * (3.0 - 2.0 * f);
+  let a = hash2(i);
+  let b = hash2(i + vec2<f32>(1.0, 0.0));
+  let c = hash2(i + vec2<f32>(0.0, 1.0));
+  let d = hash2(i + vec2<f32>(1.0, 1.0));
+  return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+@fragment
+fn fragment(in: WaterVertexOutput) -> @location(0) vec4<f32> {
+  // Vertical water depth over the terrain under this pixel.
+  let depth = max(in.world_position.y - terrain_height(in.tile_uv), 0.0);
+  let absorbed = 1.0 - exp(-depth * params.absorption);
+  var base = mix(params.color, params.deep_color, absorbed);
+
+  // Foam band along the shore, broken up by drifting noise.
+  let shore = 1.0 - smoothstep(0.0, max(params.foam_width, 1e-4), depth);
+  let drift = vec2<f32>(globals.time * 0.3, globals.time * 0.17);
+  let noise = value_noise(in.world_position.xz * params.foam_noise_scale + drift);
+  let foam = smoothstep(noise * 0.7, noise * 0.7 + 0.25, shore);
+  base = mix(base, params.foam_color, foam * params.foam_color.a);
+
+  var pbr = pbr_types::pbr_input_new();
+  pbr.material.base_color = vec4<f32>(base.rgb, max(base.a, foam));
+  pbr.material.perceptual_roughness = mix(params.roughness, 0.9, foam);
+  pbr.material.reflectance = vec3<f32>(0.3);
+  pbr.frag_coord = in.position;
+  pbr.world_position = in.world_position;
+  pbr.world_normal = in.world_normal;
+  pbr.N = in.world_normal;
+  pbr.is_orthographic = view.clip_from_view[3].w == 1.0;
+  pbr.V = pbr_functions::calculate_view(in.world_position, pbr.is_orthographic);
+
+  var color = pbr_functions::apply_pbr_lighting(pbr);
+  return pbr_functions::main_pass_post_lighting_processing(pbr, color);
+}
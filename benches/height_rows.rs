@@ -0,0 +1,43 @@
+//! Tile height generation: `HashedFbm` one sample at a time against the
+//! batched row sampler `generate_height_field` uses.
+//!
+//! `cargo bench --bench height_rows`
+
+use bevy::math::DVec2;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use thrive::prelude::*;
+use thrive::terrain::meshgen::{generate_height_field, FbmHeightSource};
+
+const FBM: HashedFbm = HashedFbm { seed: 12345, octaves: 6, lacunarity: 2.0, persistence: 0.5, frequency: 0.08 };
+
+/// The default `HeightSource::sample_row`: one `height_at` per sample.
+struct Scalar(FbmHeightSource);
+impl HeightSource for Scalar {
+    fn height_at(&self, world_xz: DVec2) -> f32 {
+        self.0.height_at(world_xz)
+    }
+}
+
+fn height_rows(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tile_heights");
+    let origin = DVec2::new(1024.0, -512.0);
+    for n in [65, 129, 257] {
+        for (name, f64_noise) in [("hashed", false), ("hashed_f64", true)] {
+            let source = || match f64_noise {
+                true => FbmHeightSource::hashed_f64(FBM, 10.0),
+                false => FbmHeightSource::hashed(FBM, 10.0),
+            };
+            let (scalar, batched) = (Scalar(source()), source());
+            group.bench_with_input(BenchmarkId::new(format!("{name}/scalar"), n), &n, |b, &n| {
+                b.iter(|| generate_height_field(n, 64.0, black_box(origin), &scalar))
+            });
+            group.bench_with_input(BenchmarkId::new(format!("{name}/rows"), n), &n, |b, &n| {
+                b.iter(|| generate_height_field(n, 64.0, black_box(origin), &batched))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, height_rows);
+criterion_main!(benches);
@@ -0,0 +1,83 @@
+//! CPU terrain queries over a 5×5 block of 129² tiles: raycasts from a
+//! camera height and line-of-sight checks between ground points.
+//!
+//! `cargo bench --bench terrain_queries`
+
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use thrive::prelude::*;
+use thrive::terrain::heightfield::HeightTile;
+use thrive::terrain::meshgen::{generate_height_field, FbmHeightSource};
+
+const N: usize = 129;
+const TILE: f32 = 64.0;
+const FBM: HashedFbm = HashedFbm { seed: 7, octaves: 6, lacunarity: 2.0, persistence: 0.5, frequency: 0.01 };
+
+/// Tiles -2..=2 on both axes, heights up to ±40.
+fn heightfield() -> TerrainHeightfield {
+    let source = FbmHeightSource::hashed(FBM, 40.0);
+    let mut field = TerrainHeightfield::default();
+    field.tile_size = TILE;
+    field.resolution = N;
+    for z in -2..=2 {
+        for x in -2..=2 {
+            let coord = IVec2::new(x, z);
+            let heights = generate_height_field(N, TILE, coord.as_dvec2() * TILE as f64, &source);
+            let min_height = heights.iter().copied().fold(f32::INFINITY, f32::min);
+            let max_height = heights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            field.insert(coord, HeightTile {
+                heights: heights.into(),
+                curvature: Arc::from(vec![0.0; N * N]),
+                flow: None,
+                holes: None,
+                min_height,
+                max_height,
+            });
+        }
+    }
+    field
+}
+
+/// Points on a ring of `radius` around the origin, `height` above the ground.
+fn ring(field: &TerrainHeightfield, radius: f32, height: f32) -> Vec<Vec3> {
+    (0..32)
+        .map(|i| {
+            let xz = Vec2::from_angle(i as f32 / 32.0 * std::f32::consts::TAU) * radius;
+            Vec3::new(xz.x, field.height_at(xz).unwrap() + height, xz.y)
+        })
+        .collect()
+}
+
+fn raycasts(c: &mut Criterion) {
+    let field = heightfield();
+    let eye = Vec3::new(0.0, field.height_at(Vec2::ZERO).unwrap() + 30.0, 0.0);
+    let mut group = c.benchmark_group("raycast");
+    for distance in [32.0, 64.0, 120.0] {
+        let rays: Vec<Ray3d> = ring(&field, distance, 0.0)
+            .into_iter()
+            .map(|p| Ray3d::new(eye, Dir3::new(p - eye).unwrap()))
+            .collect();
+        group.bench_with_input(BenchmarkId::from_parameter(distance), &rays, |b, rays| {
+            b.iter(|| rays.iter().filter_map(|ray| field.raycast(black_box(*ray), 400.0)).count())
+        });
+    }
+    group.finish();
+}
+
+fn line_of_sight(c: &mut Criterion) {
+    let field = heightfield();
+    let mut group = c.benchmark_group("line_of_sight");
+    let from = Vec3::new(0.0, field.height_at(Vec2::ZERO).unwrap() + 2.0, 0.0);
+    for distance in [32.0, 64.0, 120.0] {
+        let targets = ring(&field, distance, 2.0);
+        group.bench_with_input(BenchmarkId::from_parameter(distance), &targets, |b, targets| {
+            b.iter(|| targets.iter().filter(|to| field.line_of_sight(black_box(from), **to) == LosResult::Clear).count())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, raycasts, line_of_sight);
+criterion_main!(benches);
@@ -1,5 +1,5 @@
 
-use thrive::camera::{FreeFlightCamera, FreeFlightCameraPlugin};
+use thrive::prelude::*;
 
 use bevy::{
     prelude::*,
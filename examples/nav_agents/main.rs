@@ -0,0 +1,118 @@
+//! A handful of agents wandering over streamed terrain, walking only on
+//! tiles that have nav geometry and turning away from slopes too steep to
+//! climb. The wireframe of the nav tile under the camera is drawn, and
+//! `NavTileUpdated` / `NavTileRemoved` are counted in the log; a real game
+//! would feed them to its nav mesh baker instead.
+
+use thrive::prelude::*;
+
+use bevy::prelude::*;
+
+const AGENTS: usize = 6;
+const SPEED: f32 = 6.0;
+/// Steepest climb an agent takes, rise over run.
+const MAX_GRADE: f32 = 0.7;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins((TerrainPlugin, TerrainNavPlugin, FreeFlightCameraPlugin, TerrainCameraPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, (log_nav_events, walk_agents, draw_nav))
+        .run();
+}
+
+#[derive(Component)]
+struct Agent {
+    goal: Vec2,
+    /// Seeds the next goal pick.
+    picks: u32,
+}
+
+fn setup(mut commands: Commands) {
+    spawn_terrain_camera(&mut commands, TerrainCameraSettings { load_radius: 3, ..default() });
+
+    // Light
+    commands.spawn((
+        Name::new("Sun"),
+        DirectionalLight::default(),
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+
+    for i in 0..AGENTS {
+        let a = i as f32 / AGENTS as f32 * std::f32::consts::TAU;
+        let start = Vec2::new(a.cos(), a.sin()) * 20.0;
+        commands.spawn((
+            Name::new(format!("Agent {i}")),
+            Agent { goal: start, picks: i as u32 * 7919 },
+            Transform::from_xyz(start.x, 0.0, start.y),
+        ));
+    }
+}
+
+fn log_nav_events(mut updated: EventReader<NavTileUpdated>, mut removed: EventReader<NavTileRemoved>, nav: Res<NavTiles>) {
+    let (u, r) = (updated.read().count(), removed.read().count());
+    if u + r > 0 {
+        info!("nav tiles: {u} updated, {r} removed, {} loaded", nav.len());
+    }
+}
+
+/// Cheap hash, so goals differ per agent without an RNG dependency.
+fn next_goal(agent: &mut Agent, from: Vec2) -> Vec2 {
+    agent.picks = agent.picks.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+    let a = (agent.picks >> 8) as f32 / (1 << 24) as f32 * std::f32::consts::TAU;
+    let d = 15.0 + (agent.picks & 0xff) as f32 / 255.0 * 35.0;
+    from + Vec2::new(a.cos(), a.sin()) * d
+}
+
+fn walk_agents(
+    time: Res<Time>,
+    heightfield: Res<TerrainHeightfield>,
+    nav: Res<NavTiles>,
+    mut q_agents: Query<(&mut Agent, &mut Transform)>,
+) {
+    let dt = time.delta_secs();
+    for (mut agent, mut transform) in q_agents.iter_mut() {
+        let pos = transform.translation.xz();
+        let Some(y) = heightfield.height_at(pos) else { continue };
+        transform.translation.y = y;
+
+        let to_goal = agent.goal - pos;
+        if to_goal.length() < 1.0 {
+            agent.goal = next_goal(&mut agent, pos);
+            continue;
+        }
+        let next = pos + to_goal.normalize() * (SPEED * dt).min(to_goal.length());
+        let walkable = nav.get(heightfield.world_to_coord(next)).is_some()
+            && heightfield.height_at(next).is_some_and(|ny| (ny - y).abs() <= MAX_GRADE * next.distance(pos));
+        if walkable {
+            transform.translation = Vec3::new(next.x, y, next.y);
+        } else {
+            agent.goal = next_goal(&mut agent, pos);
+        }
+    }
+}
+
+fn draw_nav(
+    heightfield: Res<TerrainHeightfield>,
+    nav: Res<NavTiles>,
+    q_camera: Query<&Transform, With<Camera3d>>,
+    q_agents: Query<(&Agent, &Transform)>,
+    mut gizmos: Gizmos,
+) {
+    if let Ok(camera) = q_camera.single() {
+        if let Some(geometry) = nav.get(heightfield.world_to_coord(camera.translation.xz())) {
+            let lift = Vec3::Y * 0.05;
+            for tri in geometry.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| geometry.positions[tri[i] as usize] + lift);
+                gizmos.linestrip([a, b, c, a], Color::srgba(0.3, 0.8, 1.0, 0.25));
+            }
+        }
+    }
+    for (agent, transform) in &q_agents {
+        let p = transform.translation + Vec3::Y * 0.6;
+        gizmos.sphere(Isometry3d::from_translation(p), 0.6, Color::srgb(1.0, 0.4, 0.2));
+        let goal = Vec3::new(agent.goal.x, p.y, agent.goal.y);
+        gizmos.line(p, goal, Color::srgba(1.0, 0.85, 0.2, 0.4));
+    }
+}
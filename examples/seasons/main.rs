@@ -0,0 +1,48 @@
+use thrive::prelude::*;
+
+use bevy::prelude::*;
+
+/// Seconds to blend from one season into the next.
+const TRANSITION_SECONDS: f32 = 60.0;
+/// Seconds to stay in a season before moving on.
+const HOLD_SECONDS: f32 = 10.0;
+const ORDER: [&str; 4] = ["spring", "summer", "autumn", "winter"];
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins((TerrainPlugin, FreeFlightCameraPlugin, TerrainCameraPlugin))
+        .insert_resource(Season::new("autumn"))
+        .add_systems(Startup, setup)
+        .add_systems(Update, advance_season)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    spawn_terrain_camera(&mut commands, TerrainCameraSettings { load_radius: 4, ..default() });
+
+    // Light
+    commands.spawn((
+        Name::new("Sun"),
+        DirectionalLight::default(),
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+/// Hold each season, then blend into the next one. Only materials change;
+/// no tile is regenerated.
+fn advance_season(time: Res<Time>, mut season: ResMut<Season>, mut clock: Local<f32>) {
+    *clock += time.delta_secs();
+    if *clock < HOLD_SECONDS { return; }
+
+    if season.from == season.to {
+        let i = ORDER.iter().position(|s| *s == season.from).unwrap_or(0);
+        season.to = ORDER[(i + 1) % ORDER.len()].to_string();
+    }
+    season.blend = ((*clock - HOLD_SECONDS) / TRANSITION_SECONDS).min(1.0);
+    if season.blend >= 1.0 {
+        *season = Season::new(season.to.clone());
+        *clock = 0.0;
+        info!("Season: {}", season.from);
+    }
+}
@@ -0,0 +1,98 @@
+//! Teleports a tile loader across the world every few seconds and logs how
+//! long the frames after each jump take, to compare the spawn path with and
+//! without the tile entity pool. Press P to toggle the pool. Tasks for the
+//! tiles left behind are cancelled, so the pending count logged with each
+//! jump never includes the old spot.
+//!
+//! Also logged is the most tiles spawned in one frame. Finished tasks used
+//! to all be finalized in the frame they landed, up to
+//! `max_in_flight_tasks` (16) texture uploads and spawns at once right
+//! after a jump; now it never exceeds `max_spawns_per_frame` (8), and the
+//! rest spawn over the next frames.
+
+use thrive::prelude::*;
+
+use bevy::prelude::*;
+
+const JUMP_SECONDS: f32 = 3.0;
+const JUMP_DISTANCE: f32 = 4096.0;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(TerrainPlugin)
+        .init_resource::<Stress>()
+        .add_systems(Startup, setup)
+        .add_systems(Update, (toggle_pool, count_spawns, teleport_and_measure).chain())
+        .run();
+}
+
+#[derive(Resource, Default)]
+struct Stress {
+    next_jump: f32,
+    jumps: u32,
+    /// Frame times (seconds) since the last jump.
+    frames: Vec<f32>,
+    /// Most tiles spawned in one frame since the last jump.
+    most_spawned: usize,
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Stress camera"),
+        Camera3d::default(),
+        Transform::from_xyz(0.0, 60.0, 0.0).looking_at(Vec3::new(64.0, 0.0, 64.0), Vec3::Y),
+        TileLoader { radius_tiles: 8, ..default() },
+    ));
+    commands.spawn((
+        DirectionalLight::default(),
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+fn toggle_pool(keys: Res<ButtonInput<KeyCode>>, mut cfg: ResMut<TerrainConfig>) {
+    if keys.just_pressed(KeyCode::KeyP) {
+        cfg.max_pooled_tiles = if cfg.max_pooled_tiles == 0 { TerrainConfig::default().max_pooled_tiles } else { 0 };
+        info!("tile pool: {}", cfg.max_pooled_tiles);
+    }
+}
+
+fn count_spawns(mut spawned: EventReader<TileSpawned>, mut stress: ResMut<Stress>) {
+    stress.most_spawned = stress.most_spawned.max(spawned.read().count());
+}
+
+fn teleport_and_measure(
+    time: Res<Time>,
+    cfg: Res<TerrainConfig>,
+    state: Res<TerrainState>,
+    mut stress: ResMut<Stress>,
+    mut q_loader: Query<&mut Transform, With<TileLoader>>,
+) {
+    stress.frames.push(time.delta_secs());
+    if time.elapsed_secs() < stress.next_jump { return; }
+    stress.next_jump = time.elapsed_secs() + JUMP_SECONDS;
+
+    if stress.jumps > 0 {
+        let frames = &stress.frames;
+        let worst = frames.iter().copied().fold(0.0, f32::max);
+        let mean = frames.iter().sum::<f32>() / frames.len().max(1) as f32;
+        info!(
+            "pool {:>3} ({} pooled, {} pending): worst frame {:.2} ms, mean {:.2} ms over {} frames, at most {} spawns a frame",
+            cfg.max_pooled_tiles,
+            state.pooled(),
+            state.pending.len(),
+            worst * 1000.0,
+            mean * 1000.0,
+            frames.len(),
+            stress.most_spawned,
+        );
+    }
+    stress.frames.clear();
+    stress.most_spawned = 0;
+    stress.jumps += 1;
+    // alternate between two far apart spots so every jump replaces the whole set
+    let x = if stress.jumps.is_multiple_of(2) { 0.0 } else { JUMP_DISTANCE };
+    for mut xf in &mut q_loader {
+        xf.translation.x = x;
+    }
+}
@@ -0,0 +1,34 @@
+use thrive::prelude::*;
+
+use bevy::{
+    prelude::*,
+    picking::pointer::PointerInteraction
+};
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, ThrivePlugins::default()))
+        .add_systems(Startup, setup)
+        .add_systems(PostUpdate, draw_terrain_intersections)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    spawn_default_world(&mut commands, WorldPreset::Hills);
+}
+
+fn draw_terrain_intersections(
+    pointers: Query<&PointerInteraction>,
+    q_tiles: Query<&Tile>,
+    mut gizmos: Gizmos,
+) {
+    for (point, normal) in pointers
+        .iter()
+        .filter_map(|interaction| interaction.get_nearest_hit())
+        .filter(|(entity, _hit)| q_tiles.contains(*entity))
+        .filter_map(|(_entity, hit)| hit.position.zip(hit.normal))
+    {
+        gizmos.sphere(point, 0.25, Color::srgb(1.0, 0.2, 0.2));
+        gizmos.arrow(point, point + normal.normalize() * 2.0, Color::srgb(1.0, 1.0, 0.0));
+    }
+}
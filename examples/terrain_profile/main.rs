@@ -0,0 +1,42 @@
+use thrive::prelude::*;
+
+use bevy::prelude::*;
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins((TerrainPlugin, FreeFlightCameraPlugin, TerrainCameraPlugin))
+        .add_systems(Startup, setup)
+        .add_systems(Update, draw_profile)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    spawn_terrain_camera(&mut commands, TerrainCameraSettings { load_radius: 4, ..default() });
+
+    // Light
+    commands.spawn((
+        Name::new("Sun"),
+        DirectionalLight::default(),
+        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+    ));
+}
+
+/// A winding path draped over the terrain, with a tick at every sample.
+fn draw_profile(heightfield: Res<TerrainHeightfield>, mut gizmos: Gizmos) {
+    let path: Vec<Vec2> = (0..=24)
+        .map(|i| {
+            let a = i as f32 / 24.0 * std::f32::consts::TAU;
+            Vec2::new(16.0, 16.0) + Vec2::new(a.cos() * 40.0, a.sin() * 25.0 + (a * 3.0).sin() * 6.0)
+        })
+        .collect();
+    let samples = heightfield.sample_polyline(&path, 1.0);
+
+    // break the line where samples were skipped over unloaded tiles
+    for run in samples.chunk_by(|a, b| b.t - a.t <= 1.0 + 1e-3) {
+        gizmos.linestrip(run.iter().map(|s| s.pos + Vec3::Y * 0.2), Color::srgb(1.0, 0.85, 0.2));
+    }
+    for s in &samples {
+        gizmos.line(s.pos, s.pos + s.normal * 0.8, Color::srgb(0.3, 0.8, 1.0));
+    }
+}
@@ -1,4 +1,4 @@
-use thrive::camera::{FreeFlightCamera, FreeFlightCameraPlugin};
+use thrive::prelude::*;
 
 use bevy::{
     prelude::*,
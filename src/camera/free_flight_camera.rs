@@ -3,6 +3,7 @@
 //! Controls: RMB look | WASD move | Space up | Ctrl down | Shift boost | Esc release
 
 use bevy::input::mouse::MouseMotion;
+use bevy::input::InputPlugin;
 use bevy::prelude::*;
 use bevy::transform::TransformSystem;
 use bevy::window::{CursorGrabMode, PrimaryWindow};
@@ -10,17 +11,34 @@ use bevy::window::{CursorGrabMode, PrimaryWindow};
 pub struct FreeFlightCameraPlugin;
 impl Plugin for FreeFlightCameraPlugin {
     fn build(&self, app: &mut App) {
+        #[cfg(feature = "inspector")]
+        app.register_type::<FreeFlightCamera>();
+        app
+            .init_resource::<CameraInputCaptured>()
+            .add_event::<TeleportCamera>()
+            .add_systems(Update, teleport_camera.before(TransformSystem::TransformPropagate));
+        // headless apps have no input to fly with
+        if !app.is_plugin_added::<InputPlugin>() {
+            return;
+        }
         app.add_systems(
             Update,
-            (cursor_grab, flight_camera_move).chain().before(TransformSystem::TransformPropagate),
+            (cursor_grab, flight_camera_move)
+                .chain()
+                .run_if(|c: Res<CameraInputCaptured>| !c.0)
+                .after(teleport_camera)
+                .before(TransformSystem::TransformPropagate),
         );
     }
 }
 
 /// Tunables / state for a free-flight camera
-#[derive(Component)]
+#[derive(Component, Clone)]
+#[cfg_attr(feature = "inspector", derive(Reflect), reflect(Component))]
 pub struct FreeFlightCamera {
+    #[cfg_attr(feature = "inspector", reflect(@0.0..=500.0_f32))]
     pub speed:       f32, // units/s
+    #[cfg_attr(feature = "inspector", reflect(@0.0..=2000.0_f32))]
     pub boost_speed: f32, // when Shift is held
     pub mouse_sens:  f32, // radians per pixel
     pub yaw:   f32,       // internal state
@@ -38,6 +56,32 @@ impl Default for FreeFlightCamera {
     }
 }
 
+/// Set by UI that wants the mouse and keys (e.g. a map screen); the camera
+/// ignores input while it is `true`.
+#[derive(Resource, Default)]
+pub struct CameraInputCaptured(pub bool);
+
+/// Move the free-flight camera to `translation` (local space). `yaw` and
+/// `pitch` keep their current values when `None`.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TeleportCamera {
+    pub translation: Vec3,
+    pub yaw: Option<f32>,
+    pub pitch: Option<f32>,
+}
+
+fn teleport_camera(
+    mut events: EventReader<TeleportCamera>,
+    mut q_cam: Query<(&mut Transform, &mut FreeFlightCamera)>,
+) {
+    let Some(ev) = events.read().last() else { return };
+    let Some((mut transform, mut cam)) = q_cam.iter_mut().next() else { return };
+    cam.yaw = ev.yaw.unwrap_or(cam.yaw);
+    cam.pitch = ev.pitch.unwrap_or(cam.pitch);
+    transform.translation = ev.translation;
+    transform.rotation = Quat::from_euler(EulerRot::YXZ, cam.yaw, cam.pitch, 0.0);
+}
+
 fn cursor_grab(
     mut windows: Query<&mut Window, With<PrimaryWindow>>,
     mouse:       Res<ButtonInput<MouseButton>>,
@@ -1,3 +1,15 @@
 pub mod free_flight_camera;
+pub mod navigation_hud;
+#[cfg(feature = "terrain")]
+pub mod overview_map;
+#[cfg(feature = "terrain")]
+pub mod terrain_camera;
 
-pub use free_flight_camera::{FreeFlightCamera, FreeFlightCameraPlugin};
\ No newline at end of file
+pub use free_flight_camera::{CameraInputCaptured, FreeFlightCamera, FreeFlightCameraPlugin, TeleportCamera};
+pub use navigation_hud::{HudCorner, NavigationHudPlugin, NavigationHudSettings};
+#[cfg(feature = "terrain")]
+pub use overview_map::{OverviewMap, OverviewMapPlugin, OverviewMapSettings};
+#[cfg(feature = "terrain")]
+pub use terrain_camera::{
+    spawn_terrain_camera, LoadRadiusFog, TerrainCameraPlugin, TerrainCameraSettings, TerrainFogSync,
+};
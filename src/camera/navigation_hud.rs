@@ -0,0 +1,126 @@
+//! navigation_hud.rs – compass strip and coordinates for the free-flight camera
+//! Heading comes from `FreeFlightCamera::yaw` (north = -Z). With terrain the
+//! readout also shows the true world position and tile coord.
+
+use bevy::prelude::*;
+
+#[cfg(feature = "terrain")]
+use crate::terrain::{origin::WorldOffset, systems::TerrainConfig};
+
+use super::FreeFlightCamera;
+
+pub struct NavigationHudPlugin;
+impl Plugin for NavigationHudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<NavigationHudSettings>()
+            .add_systems(Startup, spawn_navigation_hud)
+            .add_systems(Update, update_navigation_hud_system);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum HudCorner {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Read once at startup.
+#[derive(Resource, Clone)]
+pub struct NavigationHudSettings {
+    pub corner: HudCorner,
+    /// Distance from the screen edges, in logical pixels.
+    pub margin: f32,
+    pub font_size: f32,
+}
+impl Default for NavigationHudSettings {
+    fn default() -> Self {
+        Self { corner: HudCorner::TopLeft, margin: 12.0, font_size: 16.0 }
+    }
+}
+
+#[derive(Component)]
+struct NavigationHudText;
+
+fn spawn_navigation_hud(mut commands: Commands, settings: Res<NavigationHudSettings>) {
+    let m = Val::Px(settings.margin);
+    let mut node = Node { position_type: PositionType::Absolute, ..default() };
+    match settings.corner {
+        HudCorner::TopLeft => (node.top, node.left) = (m, m),
+        HudCorner::TopRight => (node.top, node.right) = (m, m),
+        HudCorner::BottomLeft => (node.bottom, node.left) = (m, m),
+        HudCorner::BottomRight => (node.bottom, node.right) = (m, m),
+    }
+    commands.spawn((
+        Name::new("Navigation HUD"),
+        NavigationHudText,
+        node,
+        Text::default(),
+        TextFont { font_size: settings.font_size, ..default() },
+        TextColor(Color::WHITE),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+    ));
+}
+
+/// Compass heading in degrees, clockwise from north (-Z).
+pub fn heading_degrees(yaw: f32) -> f32 {
+    (-yaw.to_degrees()).rem_euclid(360.0)
+}
+
+/// `·  NW  ·  [N]  ·  NE  ·` style strip, 22.5° per slot around `heading`.
+fn compass_strip(heading: f32) -> String {
+    const LABELS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+    let center = (heading / 22.5).round() as i32;
+    (center - 4..=center + 4)
+        .map(|slot| {
+            let slot = slot.rem_euclid(16);
+            let label = if slot % 2 == 0 { LABELS[slot as usize / 2] } else { "·" };
+            if slot == center.rem_euclid(16) { format!("[{label}]") } else { label.to_string() }
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+fn update_navigation_hud_system(
+    #[cfg(feature = "terrain")] cfg: Option<Res<TerrainConfig>>,
+    #[cfg(feature = "terrain")] offset: Option<Res<WorldOffset>>,
+    q_camera: Query<(&Transform, &FreeFlightCamera)>,
+    mut q_text: Query<&mut Text, With<NavigationHudText>>,
+    mut shown: Local<Option<(i32, IVec3)>>,
+) {
+    let Some((xf, cam)) = q_camera.iter().next() else { return };
+    let heading = heading_degrees(cam.yaw);
+    #[allow(unused_mut)]
+    let mut position = xf.translation.as_dvec3();
+    #[cfg(feature = "terrain")]
+    if let Some(offset) = offset {
+        let world = offset.to_world(xf.translation.xz());
+        (position.x, position.z) = (world.x, world.y);
+    }
+
+    // only rebuild the text when the rounded readout changes
+    let key = (heading.round() as i32 % 360, position.round().as_ivec3());
+    if *shown == Some(key) { return; }
+    *shown = Some(key);
+
+    #[allow(unused_mut)]
+    let mut readout = format!(
+        "{}\n{:03}°   x {} y {} z {}",
+        compass_strip(heading),
+        key.0,
+        key.1.x,
+        key.1.y,
+        key.1.z,
+    );
+    #[cfg(feature = "terrain")]
+    if let Some(cfg) = cfg {
+        let tile = (position.xz() / cfg.tile_size as f64).floor().as_ivec2();
+        readout += &format!("   tile ({}, {})", tile.x, tile.y);
+    }
+    for mut text in q_text.iter_mut() {
+        text.0.clone_from(&readout);
+    }
+}
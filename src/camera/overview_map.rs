@@ -0,0 +1,261 @@
+//! overview_map.rs – fullscreen map over the minimap composite
+//! Toggle with M | Scroll zoom | LMB drag pan | LMB click teleport
+//! Camera input is captured while the map is open.
+
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+use super::free_flight_camera::{CameraInputCaptured, TeleportCamera};
+use crate::terrain::heightfield::TerrainHeightfield;
+use crate::terrain::minimap::{init_minimap, Minimap, MinimapPlugin};
+use crate::terrain::origin::WorldOffset;
+use crate::terrain::systems::{TerrainConfig, TerrainState};
+
+/// Adds `MinimapPlugin` if needed; the map shows its image.
+pub struct OverviewMapPlugin;
+impl Plugin for OverviewMapPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<MinimapPlugin>() {
+            app.add_plugins(MinimapPlugin);
+        }
+        app
+            .init_resource::<OverviewMapSettings>()
+            .init_resource::<OverviewMap>()
+            .add_systems(Startup, spawn_overview_map.after(init_minimap))
+            .add_systems(
+                Update,
+                (
+                    toggle_overview_map_system,
+                    overview_map_input_system.run_if(|map: Res<OverviewMap>| map.open),
+                    update_overview_map_view_system.run_if(|map: Res<OverviewMap>| map.open),
+                ).chain(),
+            );
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct OverviewMapSettings {
+    pub toggle_key: KeyCode,
+    /// Teleport height above the terrain under the click.
+    pub clearance: f32,
+    /// Teleport height when the clicked tile isn't loaded.
+    pub safe_altitude: f32,
+    pub max_zoom: f32,
+}
+impl Default for OverviewMapSettings {
+    fn default() -> Self {
+        Self { toggle_key: KeyCode::KeyM, clearance: 20.0, safe_altitude: 150.0, max_zoom: 8.0 }
+    }
+}
+
+/// Map screen state. The view is `1 / zoom` of the minimap around `center` (UV).
+#[derive(Resource)]
+pub struct OverviewMap {
+    pub open: bool,
+    pub zoom: f32,
+    pub center: Vec2,
+    /// Cursor UV when the left button went down, and the last dragged-to UV.
+    drag: Option<(Vec2, Vec2)>,
+}
+impl Default for OverviewMap {
+    fn default() -> Self {
+        Self { open: false, zoom: 1.0, center: Vec2::splat(0.5), drag: None }
+    }
+}
+
+impl OverviewMap {
+    fn half_extent(&self) -> f32 {
+        0.5 / self.zoom
+    }
+
+    /// Minimap UV under a position normalized to the map node (0..1).
+    fn node_to_uv(&self, p: Vec2) -> Vec2 {
+        self.center + (p - 0.5) * 2.0 * self.half_extent()
+    }
+
+    fn uv_to_node(&self, uv: Vec2) -> Vec2 {
+        (uv - self.center) / (2.0 * self.half_extent()) + 0.5
+    }
+
+    fn clamp_center(&mut self) {
+        let h = self.half_extent();
+        self.center = self.center.clamp(Vec2::splat(h), Vec2::splat(1.0 - h));
+    }
+}
+
+#[derive(Component)]
+struct OverviewMapRoot;
+
+#[derive(Component)]
+struct OverviewMapImage;
+
+#[derive(Component)]
+struct OverviewCameraMarker;
+
+#[derive(Component)]
+struct OverviewPinMarker;
+
+fn spawn_overview_map(mut commands: Commands, minimap: Res<Minimap>) {
+    commands
+        .spawn((
+            Name::new("Overview map"),
+            OverviewMapRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            GlobalZIndex(10),
+            Visibility::Hidden,
+        ))
+        .with_child((
+            OverviewMapImage,
+            ImageNode::new(minimap.image.clone()),
+            RelativeCursorPosition::default(),
+            Node {
+                height: Val::Vh(90.0),
+                aspect_ratio: Some(1.0),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            children![(
+                OverviewCameraMarker,
+                Node {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(10.0),
+                    height: Val::Px(10.0),
+                    margin: UiRect::all(Val::Px(-5.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(1.0, 0.2, 0.2)),
+            )],
+        ));
+}
+
+fn toggle_overview_map_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<OverviewMapSettings>,
+    mut map: ResMut<OverviewMap>,
+    mut captured: ResMut<CameraInputCaptured>,
+    mut q_root: Query<&mut Visibility, With<OverviewMapRoot>>,
+) {
+    if !keys.just_pressed(settings.toggle_key) { return; }
+    map.open = !map.open;
+    map.drag = None;
+    captured.0 = map.open;
+    for mut visibility in q_root.iter_mut() {
+        *visibility = if map.open { Visibility::Visible } else { Visibility::Hidden };
+    }
+}
+
+fn overview_map_input_system(
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut wheel: EventReader<MouseWheel>,
+    settings: Res<OverviewMapSettings>,
+    mut map: ResMut<OverviewMap>,
+    minimap: Res<Minimap>,
+    cfg: Res<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    heightfield: Res<TerrainHeightfield>,
+    mut teleport: EventWriter<TeleportCamera>,
+    q_image: Query<&RelativeCursorPosition, With<OverviewMapImage>>,
+) {
+    let Some(cursor) = q_image.iter().next().filter(|c| c.mouse_over()).and_then(|c| c.normalized) else {
+        wheel.clear();
+        map.drag = None;
+        return;
+    };
+
+    // zoom around the point under the cursor
+    let scroll: f32 = wheel.read().map(|ev| ev.y.signum()).sum();
+    if scroll != 0.0 {
+        let anchor = map.node_to_uv(cursor);
+        map.zoom = (map.zoom * 1.25_f32.powf(scroll)).clamp(1.0, settings.max_zoom.max(1.0));
+        map.center = anchor - (cursor - 0.5) * 2.0 * map.half_extent();
+        map.clamp_center();
+    }
+
+    if mouse.just_pressed(MouseButton::Left) {
+        map.drag = Some((cursor, cursor));
+    }
+    let Some((start, last)) = map.drag else { return };
+    if mouse.pressed(MouseButton::Left) {
+        // pan so the grabbed point stays under the cursor
+        let half = map.half_extent();
+        map.center += (last - cursor) * 2.0 * half;
+        map.clamp_center();
+        map.drag = Some((start, cursor));
+    } else if mouse.just_released(MouseButton::Left) {
+        map.drag = None;
+        // a click, not a drag
+        if start.distance(cursor) > 0.01 { return; }
+        let world = minimap.uv_to_world(map.node_to_uv(cursor), cfg.tile_size);
+        let local = offset.to_local(world);
+        let y = heightfield
+            .height_at(local)
+            .map(|h| h + settings.clearance)
+            .unwrap_or(settings.safe_altitude);
+        teleport.write(TeleportCamera { translation: Vec3::new(local.x, y, local.y), yaw: None, pitch: None });
+    }
+}
+
+fn update_overview_map_view_system(
+    mut commands: Commands,
+    map: Res<OverviewMap>,
+    minimap: Res<Minimap>,
+    cfg: Res<TerrainConfig>,
+    state: Res<TerrainState>,
+    offset: Res<WorldOffset>,
+    q_camera: Query<&Transform, With<Camera3d>>,
+    mut q_image: Query<(Entity, &mut ImageNode), With<OverviewMapImage>>,
+    mut q_marker: Query<(&mut Node, &mut Visibility), With<OverviewCameraMarker>>,
+    q_pins: Query<Entity, With<OverviewPinMarker>>,
+) {
+    let Ok((image_entity, mut image)) = q_image.single_mut() else { return };
+    let res = minimap.resolution() as f32;
+    let h = map.half_extent();
+    let rect = Rect::from_center_half_size(map.center * res, Vec2::splat(h * res));
+    if image.rect != Some(rect) {
+        image.rect = Some(rect);
+    }
+
+    let place = |node: &mut Node, uv: Vec2| -> bool {
+        let p = map.uv_to_node(uv);
+        node.left = Val::Percent(p.x * 100.0);
+        node.top = Val::Percent(p.y * 100.0);
+        p.cmpge(Vec2::ZERO).all() && p.cmple(Vec2::ONE).all()
+    };
+
+    if let (Some(xf), Ok((mut node, mut visibility))) = (q_camera.iter().next(), q_marker.single_mut()) {
+        let uv = minimap.world_to_uv(offset.to_world(xf.translation.xz()), cfg.tile_size);
+        let inside = place(&mut node, uv);
+        visibility.set_if_neq(if inside { Visibility::Inherited } else { Visibility::Hidden });
+    }
+
+    // pins are few; rebuild their markers every frame the map is open
+    for e in &q_pins {
+        commands.entity(e).despawn();
+    }
+    for coord in state.pinned() {
+        let center = (coord.as_dvec2() + 0.5) * cfg.tile_size as f64;
+        let mut node = Node {
+            position_type: PositionType::Absolute,
+            width: Val::Px(8.0),
+            height: Val::Px(8.0),
+            margin: UiRect::all(Val::Px(-4.0)),
+            ..default()
+        };
+        if !place(&mut node, minimap.world_to_uv(center, cfg.tile_size)) { continue; }
+        commands.spawn((
+            OverviewPinMarker,
+            node,
+            BackgroundColor(Color::srgb(1.0, 0.85, 0.2)),
+            ChildOf(image_entity),
+        ));
+    }
+}
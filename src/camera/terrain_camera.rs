@@ -0,0 +1,155 @@
+//! terrain_camera.rs – one-call camera for terrain scenes
+//! `spawn_terrain_camera(&mut commands, TerrainCameraSettings::default())` gives an
+//! HDR camera with atmosphere, free-flight controls, a `TileLoader`, and distance
+//! fog that ends where the loaded terrain does.
+//!
+//! Two ways to keep `DistanceFog` in step with the streaming radius:
+//! `LoadRadiusFog` rebuilds the falloff from its own colors, while
+//! `TerrainFogSync` keeps whatever falloff and colors the camera already has
+//! and only rescales it to the load distance.
+//!
+//! With `Atmosphere` (as `spawn_terrain_camera` sets up) the sky already
+//! has aerial perspective; the distance fog is what hides the terrain edge.
+//! It is applied on top of the atmosphere, so keep its colors close to the
+//! horizon color or terrain and sky won't meet cleanly at the far edge.
+
+use bevy::pbr::Atmosphere;
+use bevy::prelude::*;
+
+use super::FreeFlightCamera;
+use crate::terrain::systems::{TerrainConfig, TileLoader};
+
+/// Keeps `LoadRadiusFog` cameras fitted to their loader; part of `ThrivePlugins`.
+pub struct TerrainCameraPlugin;
+impl Plugin for TerrainCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (fit_fog_to_load_radius_system, sync_terrain_fog_system));
+    }
+}
+
+/// Sizes the camera's `DistanceFog` from its `TileLoader`, so the far edge of
+/// the streamed terrain fades out instead of ending in a hard line.
+#[derive(Component, Clone)]
+pub struct LoadRadiusFog {
+    /// Visible distance as a fraction of the load distance (`radius_tiles * tile_size`).
+    pub visibility: f32,
+    pub extinction_color: Color,
+    pub inscattering_color: Color,
+    /// Refit when the loader radius or tile size changes, not just on spawn.
+    pub follow_changes: bool,
+}
+impl Default for LoadRadiusFog {
+    fn default() -> Self {
+        Self {
+            visibility: 1.0,
+            extinction_color: Color::WHITE,
+            inscattering_color: Color::WHITE,
+            follow_changes: true,
+        }
+    }
+}
+
+/// Opt-in: rescale the camera's existing `DistanceFog` falloff so the fog
+/// reaches its visibility limit at `margin * radius_tiles * tile_size`.
+/// Colors, fog type and the linear start/end ratio are kept.
+#[derive(Component, Clone)]
+pub struct TerrainFogSync {
+    /// Fraction of the load distance where the terrain is fully fogged.
+    pub margin: f32,
+}
+impl Default for TerrainFogSync {
+    fn default() -> Self {
+        Self { margin: 0.95 }
+    }
+}
+
+/// What `spawn_terrain_camera` adds; set any optional piece to `None` to leave it out.
+#[derive(Clone)]
+pub struct TerrainCameraSettings {
+    pub transform: Transform,
+    /// `TileLoader::radius_tiles`.
+    pub load_radius: i32,
+    pub atmosphere: Option<Atmosphere>,
+    pub fog: Option<LoadRadiusFog>,
+    pub free_flight: Option<FreeFlightCamera>,
+}
+impl Default for TerrainCameraSettings {
+    fn default() -> Self {
+        Self {
+            transform: Transform::from_xyz(40.0, 45.0, 80.0).looking_at(Vec3::new(16.0, 0.0, 16.0), Vec3::Y),
+            load_radius: 6,
+            atmosphere: Some(Atmosphere::EARTH),
+            fog: Some(LoadRadiusFog::default()),
+            free_flight: Some(FreeFlightCamera::default()),
+        }
+    }
+}
+
+/// Spawns the camera and returns its entity for further customization.
+pub fn spawn_terrain_camera(commands: &mut Commands, settings: TerrainCameraSettings) -> Entity {
+    let mut camera = commands.spawn((
+        Name::new("Camera"),
+        Camera3d::default(),
+        Camera { hdr: true, ..default() }, // atmosphere needs HDR
+        settings.transform,
+        TileLoader { radius_tiles: settings.load_radius, ..default() },
+    ));
+    if let Some(atmosphere) = settings.atmosphere {
+        camera.insert(atmosphere);
+    }
+    if let Some(fog) = settings.fog {
+        camera.insert((DistanceFog::default(), fog));
+    }
+    if let Some(mut free_flight) = settings.free_flight {
+        // start from the spawn orientation so the first mouse look doesn't snap
+        let (yaw, pitch, _) = settings.transform.rotation.to_euler(EulerRot::YXZ);
+        free_flight.yaw = yaw;
+        free_flight.pitch = pitch;
+        camera.insert(free_flight);
+    }
+    camera.id()
+}
+
+pub fn fit_fog_to_load_radius_system(
+    cfg: Res<TerrainConfig>,
+    mut q_cameras: Query<(Ref<LoadRadiusFog>, Ref<TileLoader>, &mut DistanceFog)>,
+) {
+    for (fit, loader, mut fog) in q_cameras.iter_mut() {
+        let source_changed = loader.is_changed() || cfg.is_changed();
+        if !(fit.is_changed() || (fit.follow_changes && source_changed)) { continue; }
+
+        let distance = loader.radius_tiles.max(1) as f32 * cfg.tile_size * fit.visibility;
+        fog.falloff = FogFalloff::from_visibility_colors(distance, fit.extinction_color, fit.inscattering_color);
+    }
+}
+
+pub fn sync_terrain_fog_system(
+    cfg: Res<TerrainConfig>,
+    mut q_cameras: Query<(Ref<TerrainFogSync>, Ref<TileLoader>, &mut DistanceFog)>,
+) {
+    for (sync, loader, mut fog) in q_cameras.iter_mut() {
+        if !sync.is_changed() && !loader.is_changed() && !cfg.is_changed() { continue; }
+        let distance = (loader.radius_tiles.max(1) as f32 * cfg.tile_size * sync.margin).max(1e-3);
+        fog.falloff = rescale_falloff(&fog.falloff, distance);
+    }
+}
+
+/// Same kind of falloff, reaching the 5% contrast threshold at `visibility`.
+fn rescale_falloff(falloff: &FogFalloff, visibility: f32) -> FogFalloff {
+    match *falloff {
+        FogFalloff::Linear { start, end } => {
+            let ratio = if end > 0.0 { (start / end).clamp(0.0, 1.0) } else { 0.0 };
+            FogFalloff::Linear { start: visibility * ratio, end: visibility }
+        }
+        FogFalloff::Exponential { .. } => FogFalloff::from_visibility(visibility),
+        FogFalloff::ExponentialSquared { .. } => FogFalloff::from_visibility_squared(visibility),
+        FogFalloff::Atmospheric { extinction, inscattering } => {
+            // scale both so the densest channel hits the threshold, keeping the color ratios
+            let densest = extinction.max_element();
+            if densest <= 0.0 { return falloff.clone(); }
+            let k = FogFalloff::koschmieder(visibility, FogFalloff::REVISED_KOSCHMIEDER_CONTRAST_THRESHOLD);
+            let s = k / densest;
+            FogFalloff::Atmospheric { extinction: extinction * s, inscattering: inscattering * s }
+        }
+    }
+}
@@ -1,2 +1,12 @@
+#[cfg(feature = "camera")]
 pub mod camera;
+pub mod plugins;
+pub mod prelude;
+#[cfg(all(feature = "camera", feature = "terrain"))]
+pub mod quickstart;
+#[cfg(all(feature = "camera", feature = "terrain"))]
+pub mod session;
+#[cfg(feature = "terrain")]
 pub mod terrain;
+
+pub use plugins::{AppPlugin, ThrivePlugins};
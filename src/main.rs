@@ -1,26 +1,11 @@
 // src/main.rs
-mod camera;
-use camera::{FreeFlightCamera, FreeFlightCameraPlugin};
+use thrive::prelude::*;
 
-mod terrain;
-use terrain::TerrainPlugin;
-use crate::terrain::systems::TileLoader;
-
-use bevy::{
-    pbr::Atmosphere, prelude::*, window::PresentMode
-};
+use bevy::{prelude::*, window::PresentMode};
 fn main() {
     App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Bevy Terrain Part 1".into(),
-                present_mode: PresentMode::AutoVsync,
-                ..default()
-            }),
-            ..default()
-        }))
-        .add_plugins(TerrainPlugin)
-        .add_plugins(FreeFlightCameraPlugin)
+        .add_plugins(AppPlugin::default().title("Bevy Terrain Part 1").present_mode(PresentMode::AutoVsync))
+        .add_plugins(ThrivePlugins::default())
         .add_systems(Startup, setup)
         .run();
 }
@@ -31,27 +16,12 @@ fn setup(
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
     // Camera
-    commands.spawn((
-        Name::new("Camera"),
-        Camera3d::default(),
-        Camera{hdr: true, ..default()},
-        Transform::from_xyz(40.0, 45.0, 80.0).looking_at(Vec3::new(16.0, 0.0, 16.0), Vec3::Y),
-        Atmosphere::EARTH,
-        DistanceFog {
-            falloff: FogFalloff::from_visibility_colors(
-                4000.0, // distance in world units up to which objects retain visibility (>= 5% contrast)
-                Color::srgb(1., 1., 1.), // atmospheric extinction color (after light is lost due to absorption by atmospheric particles)
-                Color::srgb(1., 1., 1.), // atmospheric inscattering color (light gained due to scattering from the sun)
-            ),
-            ..default()
-        },
-        FreeFlightCamera::default(),
-        TileLoader{radius_tiles: 6}
-    ));
+    spawn_terrain_camera(&mut commands, TerrainCameraSettings::default());
 
     // Directional Light
     commands.spawn((
         Name::new("Sun"),
+        Sun, // cascades fitted to the streamed terrain
         DirectionalLight {
             shadows_enabled: true,
             ..default()
@@ -0,0 +1,167 @@
+//! `ThrivePlugins` (terrain, water and camera plugins as one group) and
+//! `AppPlugin` (window and camera defaults).
+
+use bevy::app::PluginGroupBuilder;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow};
+
+#[cfg(feature = "camera")]
+use crate::camera::FreeFlightCameraPlugin;
+#[cfg(feature = "terrain")]
+use crate::terrain::{systems::TerrainConfig, water::WaterPlugin, TerrainPlugin};
+
+/// Terrain streaming, water and the free-flight camera. Add it after
+/// `DefaultPlugins` and customize like any plugin group:
+/// `ThrivePlugins::default().build().disable::<WaterPlugin>()`.
+///
+/// Scatter, vegetation, editing and the floating origin stay opt-in. Only
+/// the plugins of enabled cargo features (`terrain`, `camera`) are included.
+#[derive(Default)]
+pub struct ThrivePlugins {
+    #[cfg(feature = "terrain")]
+    terrain_config: Option<TerrainConfig>,
+}
+
+#[cfg(feature = "terrain")]
+impl ThrivePlugins {
+    /// Start from `cfg` instead of `TerrainConfig::default()`.
+    pub fn with_terrain_config(cfg: TerrainConfig) -> Self {
+        Self { terrain_config: Some(cfg) }
+    }
+}
+
+impl PluginGroup for ThrivePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        #[allow(unused_mut)]
+        let mut group = PluginGroupBuilder::start::<Self>();
+        #[cfg(feature = "terrain")]
+        {
+            if let Some(cfg) = self.terrain_config {
+                // before TerrainPlugin, whose init_resource keeps an existing config
+                group = group.add(TerrainConfigPlugin(cfg));
+            }
+            group = group.add(TerrainPlugin).add(WaterPlugin);
+        }
+        #[cfg(feature = "camera")]
+        {
+            group = group.add(FreeFlightCameraPlugin);
+        }
+        #[cfg(all(feature = "camera", feature = "terrain"))]
+        {
+            group = group.add(crate::camera::TerrainCameraPlugin);
+        }
+        group
+    }
+}
+
+#[cfg(feature = "terrain")]
+struct TerrainConfigPlugin(TerrainConfig);
+#[cfg(feature = "terrain")]
+impl Plugin for TerrainConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone());
+    }
+}
+
+/// Window, presentation and camera defaults for a Thrive app.
+///
+/// Adds `DefaultPlugins` configured from these settings unless they were
+/// already added, in which case the primary window is updated at startup
+/// instead. Every 3D camera gets `msaa` and `hdr` when spawned.
+///
+/// ```ignore
+/// App::new()
+///     .add_plugins(AppPlugin::default().title("Islands").present_mode(PresentMode::Mailbox))
+///     .add_plugins(ThrivePlugins::default())
+/// ```
+#[derive(Clone)]
+pub struct AppPlugin {
+    pub title: String,
+    pub present_mode: PresentMode,
+    /// Logical window size; `None` keeps Bevy's default.
+    pub resolution: Option<Vec2>,
+    pub msaa: Msaa,
+    pub hdr: bool,
+}
+impl Default for AppPlugin {
+    fn default() -> Self {
+        Self {
+            title: "Thrive".into(),
+            present_mode: PresentMode::AutoVsync,
+            resolution: None,
+            msaa: Msaa::Sample4,
+            hdr: true,
+        }
+    }
+}
+
+impl AppPlugin {
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    pub fn resolution(mut self, width: f32, height: f32) -> Self {
+        self.resolution = Some(Vec2::new(width, height));
+        self
+    }
+
+    pub fn msaa(mut self, msaa: Msaa) -> Self {
+        self.msaa = msaa;
+        self
+    }
+
+    pub fn hdr(mut self, hdr: bool) -> Self {
+        self.hdr = hdr;
+        self
+    }
+
+    fn configure(&self, window: &mut Window) {
+        window.title = self.title.clone();
+        window.present_mode = self.present_mode;
+        if let Some(size) = self.resolution {
+            window.resolution.set(size.x, size.y);
+        }
+    }
+}
+
+/// The `AppPlugin` settings, applied to cameras as they spawn.
+#[derive(Resource, Clone)]
+pub struct AppSettings(pub AppPlugin);
+
+impl Plugin for AppPlugin {
+    fn build(&self, app: &mut App) {
+        if app.is_plugin_added::<WindowPlugin>() {
+            app.add_systems(Startup, configure_primary_window_system);
+        } else {
+            let mut window = Window::default();
+            self.configure(&mut window);
+            app.add_plugins(DefaultPlugins.set(WindowPlugin { primary_window: Some(window), ..default() }));
+        }
+        app
+            .insert_resource(AppSettings(self.clone()))
+            .add_systems(PostUpdate, configure_new_cameras_system);
+    }
+}
+
+fn configure_primary_window_system(settings: Res<AppSettings>, mut q_window: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in q_window.iter_mut() {
+        settings.0.configure(&mut window);
+    }
+}
+
+fn configure_new_cameras_system(
+    mut commands: Commands,
+    settings: Res<AppSettings>,
+    mut q_cameras: Query<(Entity, &mut Camera), Added<Camera3d>>,
+) {
+    for (e, mut camera) in q_cameras.iter_mut() {
+        camera.hdr = settings.0.hdr;
+        commands.entity(e).insert(settings.0.msaa);
+    }
+}
@@ -0,0 +1,63 @@
+//! The common surface for apps: `use thrive::prelude::*;`
+
+#[cfg(feature = "camera")]
+pub use crate::camera::{
+    CameraInputCaptured, FreeFlightCamera, FreeFlightCameraPlugin, HudCorner, NavigationHudPlugin, NavigationHudSettings,
+    TeleportCamera,
+};
+#[cfg(all(feature = "camera", feature = "terrain"))]
+pub use crate::camera::{
+    spawn_terrain_camera, LoadRadiusFog, OverviewMap, OverviewMapPlugin, OverviewMapSettings, TerrainCameraPlugin,
+    TerrainCameraSettings, TerrainFogSync,
+};
+#[cfg(all(feature = "camera", feature = "terrain"))]
+pub use crate::session::{CameraPose, SessionPlugin, SessionSettings, SessionSnapshot};
+pub use crate::plugins::{AppPlugin, ThrivePlugins};
+#[cfg(all(feature = "camera", feature = "terrain"))]
+pub use crate::quickstart::{spawn_default_world, DefaultWorld, WorldPreset};
+#[cfg(feature = "terrain")]
+pub use terrain_prelude::*;
+#[cfg(feature = "egui")]
+pub use crate::terrain::debug_ui::{TerrainDebugUi, TerrainDebugUiPlugin};
+#[cfg(feature = "nav")]
+pub use crate::terrain::nav::{
+    NavSettings, NavTileGeometry, NavTileRemoved, NavTileSource, NavTileUpdated, NavTiles, TerrainNavPlugin,
+};
+#[cfg(feature = "picking")]
+pub use crate::terrain::picking::{TerrainPickingPlugin, TerrainPickingSettings};
+
+#[cfg(feature = "terrain")]
+mod terrain_prelude {
+    pub use crate::terrain::biome::{BiomeDef, BiomeSettings};
+    pub use crate::terrain::cliffs::{CliffPiece, CliffPlacement, CliffPlugin, CliffSettings, CliffsReady};
+    pub use crate::terrain::climate::{AppliedClimate, ClimateSettings, ClimateState, Season};
+    pub use crate::terrain::decal::{DecalId, TerrainDecal, TerrainDecals};
+    pub use crate::terrain::diagnostics::TerrainStatsOverlay;
+    pub use crate::terrain::edit::{
+        BrushMode, PaintBrush, RedoTerrainEdit, TerrainBrush, TerrainBrushStroke, TerrainEditHistory, TerrainEditPlugin,
+        TerrainEdits, TerrainEditsAutosave, TerrainPaintStroke, TileHeightsEdited, UndoTerrainEdit,
+    };
+    pub use crate::terrain::heightfield::{LosResult, PolylineSample, TerrainHeightfield, TerrainRayHit};
+    pub use crate::terrain::holes::TerrainHoles;
+    pub use crate::terrain::material::{SplatParams, TerrainMaterial, TileParams};
+    pub use crate::terrain::gpu_generation::{AwaitingGpuGeneration, HeightReadbackSettings, TileHeightsReady};
+    pub use crate::terrain::meshgen::{HashedFbm, HeightSource, WorldFalloff};
+    pub use crate::terrain::metadata::TileMetadata;
+    pub use crate::terrain::minimap::{Minimap, MinimapPlugin, MinimapSettings};
+    pub use crate::terrain::origin::{FloatingOrigin, FloatingOriginPlugin, WorldOffset, WorldRebased};
+    pub use crate::terrain::pathfinding::{PathError, PathOptions};
+    pub use crate::terrain::requests::{ReleaseTiles, RequestTiles, TilePriority, TilesReady};
+    pub use crate::terrain::scatter::{PropPlacementReady, ScatterLayer, ScatterPlugin, ScatterSettings};
+    pub use crate::terrain::shading::{ContourSettings, DetailSettings, GridOverlaySettings, HeightRef, PomSettings, SeasonPalette, TerrainDebugView, TerrainShading, TerrainShadingSettings};
+    pub use crate::terrain::shadows::{Sun, TerrainShadowConfig};
+    pub use crate::terrain::snapshot::{SnapshotConfig, TerrainSnapshot};
+    pub use crate::terrain::spawn_point::{FlatArea, FlatAreaCandidate, FlatAreaDebug, FlatAreaSearch};
+    pub use crate::terrain::systems::{
+        HeightFormat, LoadMode, PooledTile, RearCull, RegenerateTerrain, TerrainConfig, TerrainRenderMode, TerrainState, TerrainStreaming, Tile, TileAttachment, TileDespawned, TileLoader, TileSpawned,
+    };
+    pub use crate::terrain::texture_pool::TileTexturePool;
+    pub use crate::terrain::vegetation::{GrassMaterial, GrassSettings, VegetationPlugin};
+    pub use crate::terrain::water::{WaterPlugin, WaterSettings};
+    pub use crate::terrain::wind::Wind;
+    pub use crate::terrain::TerrainPlugin;
+}
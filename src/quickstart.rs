@@ -0,0 +1,104 @@
+//! Terrain on screen in a couple of lines, for prototypes:
+//!
+//! ```ignore
+//! App::new().add_plugins((DefaultPlugins, ThrivePlugins::default()))
+//!     .add_systems(Startup, |mut commands: Commands| { spawn_default_world(&mut commands, WorldPreset::Hills); })
+//!     .run();
+//! ```
+//!
+//! `spawn_default_world` sets `TerrainConfig` and `WaterSettings` from a
+//! `WorldPreset` and spawns a sun and a terrain camera (with its
+//! `TileLoader`). Water shows where the terrain dips below the preset's sea
+//! level, if `WaterPlugin` is added (it is part of `ThrivePlugins`).
+
+use bevy::prelude::*;
+
+use crate::camera::{spawn_terrain_camera, TerrainCameraSettings};
+use crate::terrain::meshgen::WorldFalloff;
+use crate::terrain::systems::TerrainConfig;
+use crate::terrain::water::WaterSettings;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum WorldPreset {
+    /// Gentle rolling ground, no water.
+    #[default]
+    Hills,
+    /// Tall, rough ranges with lakes in the deepest valleys.
+    Mountains,
+    /// One island around the origin, the sea past its coast.
+    Islands,
+}
+
+impl WorldPreset {
+    /// The preset's generation settings on top of `TerrainConfig::default()`.
+    pub fn terrain_config(self) -> TerrainConfig {
+        let cfg = TerrainConfig::default();
+        match self {
+            Self::Hills => TerrainConfig { noise_amplitude: 8.0, noise_frequency: 0.04, noise_octaves: 4, ..cfg },
+            Self::Mountains => TerrainConfig {
+                noise_amplitude: 45.0,
+                noise_frequency: 0.015,
+                noise_octaves: 7,
+                noise_persistence: 0.5,
+                ..cfg
+            },
+            Self::Islands => TerrainConfig {
+                noise_amplitude: 14.0,
+                noise_frequency: 0.03,
+                world_extent: Some(WorldFalloff {
+                    center: Vec2::ZERO,
+                    radius: 160.0,
+                    falloff_width: 120.0,
+                    edge_height: -12.0,
+                }),
+                ..cfg
+            },
+        }
+    }
+
+    /// Sea level for `WaterSettings`; below the lowest possible height for
+    /// presets without water.
+    pub fn sea_level(self) -> f32 {
+        match self {
+            Self::Hills => -100.0,
+            Self::Mountains => -30.0,
+            Self::Islands => 0.0,
+        }
+    }
+}
+
+/// What `spawn_default_world` spawned, to customize afterwards.
+#[derive(Clone, Copy, Debug)]
+pub struct DefaultWorld {
+    pub camera: Entity,
+    pub sun: Entity,
+}
+
+pub fn spawn_default_world(commands: &mut Commands, preset: WorldPreset) -> DefaultWorld {
+    let cfg = preset.terrain_config();
+    // look down at the origin from above the highest possible ground
+    let height = cfg.noise_amplitude.abs() + 30.0;
+    commands.insert_resource(cfg);
+    commands.insert_resource(WaterSettings { sea_level: preset.sea_level(), ..default() });
+
+    let camera = spawn_terrain_camera(
+        commands,
+        TerrainCameraSettings {
+            transform: Transform::from_xyz(60.0, height, 90.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+    );
+    let sun = commands
+        .spawn((
+            Name::new("Sun"),
+            DirectionalLight { shadows_enabled: true, ..default() },
+            Transform::from_rotation(Quat::from_euler(
+                EulerRot::ZYX,
+                0.0,
+                std::f32::consts::FRAC_PI_4,
+                -std::f32::consts::FRAC_PI_4,
+            )),
+        ))
+        .id();
+    DefaultWorld { camera, sun }
+}
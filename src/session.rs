@@ -0,0 +1,228 @@
+//! Save and restore "where I was and what the world looked like".
+//!
+//! `SessionSnapshot` holds the camera pose, the generation fields of the
+//! `TerrainConfig` (`SnapshotConfig`, seed included) and the path of the
+//! terrain edits file. Loading overwrites only those fields, so streaming and
+//! render settings stay as the app set them. `SessionPlugin` binds
+//! saving and loading to keys (F5 / F9 by default). A snapshot that fails to
+//! load is logged and the current session is kept.
+
+use bevy::math::DVec3;
+use bevy::prelude::*;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::camera::FreeFlightCamera;
+use crate::terrain::edit::{ByteReader, TerrainEdits, TerrainEditsAutosave};
+use crate::terrain::origin::WorldOffset;
+use crate::terrain::snapshot::SnapshotConfig;
+use crate::terrain::systems::{regenerate_on_config_change_system, RegenerateTerrain, TerrainConfig};
+
+const SESSION_MAGIC: &[u8; 4] = b"TSES";
+const SESSION_VERSION: u32 = 3;
+
+pub struct SessionPlugin;
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SessionSettings>()
+            .add_systems(
+                Update,
+                (
+                    save_session_system,
+                    // loaded edits must be in place before tiles are rebuilt
+                    load_session_system.before(regenerate_on_config_change_system),
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct SessionSettings {
+    pub path: PathBuf,
+    pub save_key: KeyCode,
+    pub load_key: KeyCode,
+}
+impl Default for SessionSettings {
+    fn default() -> Self {
+        Self { path: PathBuf::from("session.bin"), save_key: KeyCode::F5, load_key: KeyCode::F9 }
+    }
+}
+
+/// Free-flight camera pose. `translation` is the true world position, so a
+/// snapshot stays valid across floating-origin rebases.
+#[derive(Clone, Copy, Debug)]
+pub struct CameraPose {
+    pub translation: DVec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+#[derive(Clone)]
+pub struct SessionSnapshot {
+    pub camera: CameraPose,
+    pub terrain_config: SnapshotConfig,
+    /// Terrain edits to reload with the session, if any.
+    pub edits_path: Option<PathBuf>,
+}
+
+impl SessionSnapshot {
+    /// Write as little-endian binary: magic, version, camera pose, the terrain
+    /// config as length-prefixed JSON, then the edits path.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut out = Vec::new();
+        out.extend_from_slice(SESSION_MAGIC);
+        out.extend_from_slice(&SESSION_VERSION.to_le_bytes());
+
+        let c = &self.camera;
+        for v in c.translation.to_array() {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        out.extend_from_slice(&c.yaw.to_le_bytes());
+        out.extend_from_slice(&c.pitch.to_le_bytes());
+
+        let config = serde_json::to_vec(&self.terrain_config).map_err(io::Error::other)?;
+        out.extend_from_slice(&(config.len() as u32).to_le_bytes());
+        out.extend_from_slice(&config);
+
+        let edits = self.edits_path.as_ref().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+        out.extend_from_slice(&(edits.len() as u32).to_le_bytes());
+        out.extend_from_slice(edits.as_bytes());
+
+        // write-then-rename so a crash mid-save keeps the previous file
+        let tmp = path.as_ref().with_extension("tmp");
+        std::fs::File::create(&tmp)?.write_all(&out)?;
+        std::fs::rename(tmp, path)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        std::fs::File::open(path)?.read_to_end(&mut bytes)?;
+        let mut r = ByteReader(&bytes);
+
+        if r.take(4)? != SESSION_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a session file"));
+        }
+        let version = r.u32()?;
+        if version != SESSION_VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unsupported session version {version}")));
+        }
+
+        let camera = CameraPose {
+            translation: DVec3::new(r.f64()?, r.f64()?, r.f64()?),
+            yaw: r.f32()?,
+            pitch: r.f32()?,
+        };
+        let len = r.u32()? as usize;
+        let terrain_config = serde_json::from_slice(r.take(len)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let len = r.u32()? as usize;
+        let edits = String::from_utf8_lossy(r.take(len)?).into_owned();
+        let edits_path = (!edits.is_empty()).then(|| PathBuf::from(edits));
+
+        Ok(Self { camera, terrain_config, edits_path })
+    }
+}
+
+pub fn save_session_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<SessionSettings>,
+    cfg: Res<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    edits: Res<TerrainEdits>,
+    autosave: Option<Res<TerrainEditsAutosave>>,
+    q_camera: Query<(&Transform, &FreeFlightCamera)>,
+) {
+    if !keys.just_pressed(settings.save_key) { return; }
+    let Some((xf, cam)) = q_camera.iter().next() else { return };
+
+    // keep the referenced edits file in step with the snapshot
+    let edits_path = autosave.map(|a| a.path.clone());
+    if let Some(path) = &edits_path {
+        if let Err(e) = edits.save(path) {
+            warn!("Failed to save terrain edits to {}: {e}", path.display());
+        }
+    }
+    let world_xz = offset.to_world(xf.translation.xz());
+    let snapshot = SessionSnapshot {
+        camera: CameraPose {
+            translation: DVec3::new(world_xz.x, xf.translation.y as f64, world_xz.y),
+            yaw: cam.yaw,
+            pitch: cam.pitch,
+        },
+        terrain_config: SnapshotConfig::from(&*cfg),
+        edits_path,
+    };
+    match snapshot.save(&settings.path) {
+        Ok(()) => info!("Saved session to {}", settings.path.display()),
+        Err(e) => warn!("Failed to save session to {}: {e}", settings.path.display()),
+    }
+}
+
+pub fn load_session_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<SessionSettings>,
+    mut cfg: ResMut<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    mut regenerate: EventWriter<RegenerateTerrain>,
+    mut q_camera: Query<(&mut Transform, &mut FreeFlightCamera)>,
+) {
+    if !keys.just_pressed(settings.load_key) { return; }
+    let snapshot = match SessionSnapshot::load(&settings.path) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("Failed to load session from {}, keeping the current one: {e}", settings.path.display());
+            return;
+        }
+    };
+
+    if let Some(path) = &snapshot.edits_path {
+        match TerrainEdits::load(path) {
+            Ok(edits) => commands.insert_resource(edits),
+            Err(e) => warn!("Session edits {} not loaded: {e}", path.display()),
+        }
+    }
+    snapshot.terrain_config.restore(&mut cfg);
+    // same config still needs a rebuild for the restored edits to show
+    regenerate.write(RegenerateTerrain);
+
+    let pose = snapshot.camera;
+    if let Some((mut xf, mut cam)) = q_camera.iter_mut().next() {
+        let local = offset.to_local(pose.translation.xz());
+        xf.translation = Vec3::new(local.x, pose.translation.y as f32, local.y);
+        xf.rotation = Quat::from_euler(EulerRot::YXZ, pose.yaw, pose.pitch, 0.0);
+        cam.yaw = pose.yaw;
+        cam.pitch = pose.pitch;
+    }
+    info!("Loaded session from {}", settings.path.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::systems::TerrainRenderMode;
+
+    #[test]
+    fn load_restores_generation_fields_only() {
+        let saved = TerrainConfig { seed: 77, f64_noise: true, gpu_generation: false, ..default() };
+        let path = std::env::temp_dir().join(format!("thrive_session_{}.bin", std::process::id()));
+        SessionSnapshot {
+            camera: CameraPose { translation: DVec3::new(1.0, 2.0, 3.0), yaw: 0.5, pitch: -0.25 },
+            terrain_config: SnapshotConfig::from(&saved),
+            edits_path: None,
+        }
+        .save(&path)
+        .unwrap();
+        let loaded = SessionSnapshot::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut cfg = TerrainConfig { render_mode: TerrainRenderMode::CpuMesh, max_pooled_tiles: 3, ..default() };
+        loaded.terrain_config.restore(&mut cfg);
+        assert_eq!(cfg.generation_hash(), saved.generation_hash());
+        assert!(cfg.f64_noise);
+        assert_eq!(cfg.render_mode, TerrainRenderMode::CpuMesh);
+        assert_eq!(cfg.max_pooled_tiles, 3);
+        assert_eq!(loaded.camera.translation, DVec3::new(1.0, 2.0, 3.0));
+    }
+}
@@ -0,0 +1,121 @@
+//! Climate-driven biome weights (temperature × rainfall, see `_notes/biomes.md`).
+//!
+//! Two low-frequency noise fields give every world position a normalized
+//! temperature and rainfall. Each `BiomeDef` claims a rectangle of that
+//! climate space; weights fade out over `blend_width` past the rectangle so
+//! anything derived from them (scatter densities) interpolates at borders.
+
+use bevy::prelude::*;
+use noiz::prelude::*;
+use smallvec::SmallVec;
+use std::collections::HashMap;
+
+use super::meshgen::{perlin_fbm, PerlinFbm};
+
+#[derive(Clone)]
+pub struct BiomeDef {
+    pub name: String,
+    /// Normalized climate band (0..1) this biome covers.
+    pub temperature: (f32, f32),
+    pub rainfall: (f32, f32),
+    /// Density multipliers keyed by scatter layer name (`"grass"` for the
+    /// vegetation plugin). Missing layers use 1.0.
+    pub densities: HashMap<String, f32>,
+}
+
+impl BiomeDef {
+    pub fn new(name: impl Into<String>, temperature: (f32, f32), rainfall: (f32, f32)) -> Self {
+        Self { name: name.into(), temperature, rainfall, densities: HashMap::new() }
+    }
+
+    pub fn density(mut self, layer: impl Into<String>, multiplier: f32) -> Self {
+        self.densities.insert(layer.into(), multiplier);
+        self
+    }
+
+    /// Distance (in climate units) from `(t, r)` to this biome's rectangle.
+    fn climate_distance(&self, t: f32, r: f32) -> f32 {
+        let dt = (self.temperature.0 - t).max(t - self.temperature.1).max(0.0);
+        let dr = (self.rainfall.0 - r).max(r - self.rainfall.1).max(0.0);
+        Vec2::new(dt, dr).length()
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct BiomeSettings {
+    pub climate_frequency: f32,
+    /// Climate distance over which a biome's weight fades to zero.
+    pub blend_width: f32,
+    pub biomes: Vec<BiomeDef>,
+}
+impl Default for BiomeSettings {
+    fn default() -> Self {
+        Self {
+            climate_frequency: 0.002,
+            blend_width: 0.08,
+            biomes: vec![
+                BiomeDef::new("desert", (0.3, 1.0), (0.0, 0.2))
+                    .density("grass", 0.0).density("trees", 0.0).density("bushes", 0.5).density("rocks", 1.5),
+                BiomeDef::new("tundra", (0.0, 0.2), (0.2, 1.0))
+                    .density("grass", 0.5).density("trees", 0.1),
+                BiomeDef::new("grassland", (0.2, 0.7), (0.2, 0.5))
+                    .density("grass", 1.5).density("trees", 0.2),
+                BiomeDef::new("forest", (0.2, 1.0), (0.5, 1.0))
+                    .density("grass", 0.6).density("trees", 3.0).density("bushes", 1.5),
+                BiomeDef::new("scrub", (0.7, 1.0), (0.2, 0.5))
+                    .density("grass", 0.8).density("trees", 0.4).density("bushes", 2.0),
+            ],
+        }
+    }
+}
+
+/// Samples climate and biome weights; cheap to build per task.
+pub struct BiomeSampler {
+    settings: BiomeSettings,
+    temperature: PerlinFbm,
+    rainfall: PerlinFbm,
+}
+
+impl BiomeSampler {
+    pub fn new(settings: &BiomeSettings, seed: u32) -> Self {
+        let freq = settings.climate_frequency;
+        Self {
+            settings: settings.clone(),
+            temperature: perlin_fbm(seed ^ 0x7E3A_11C5, 3, 2.0, 0.5, freq),
+            rainfall: perlin_fbm(seed ^ 0x4A11_F0A1, 3, 2.0, 0.5, freq),
+        }
+    }
+
+    /// Normalized `(temperature, rainfall)` at a world position.
+    pub fn climate_at(&self, world_xz: Vec2) -> (f32, f32) {
+        let t: f32 = self.temperature.sample(world_xz);
+        let r: f32 = self.rainfall.sample(world_xz);
+        ((t * 0.5 + 0.5).clamp(0.0, 1.0), (r * 0.5 + 0.5).clamp(0.0, 1.0))
+    }
+
+    /// Normalized `(biome index, weight)` pairs; empty if no biome covers the point.
+    pub fn weights_at(&self, world_xz: Vec2) -> SmallVec<[(usize, f32); 4]> {
+        let (t, r) = self.climate_at(world_xz);
+        let blend = self.settings.blend_width.max(1e-4);
+        let mut out: SmallVec<[(usize, f32); 4]> = SmallVec::new();
+        for (i, biome) in self.settings.biomes.iter().enumerate() {
+            let d = biome.climate_distance(t, r);
+            let w = 1.0 - (d / blend).clamp(0.0, 1.0);
+            let w = w * w * (3.0 - 2.0 * w);
+            if w > 0.0 { out.push((i, w)); }
+        }
+        let sum: f32 = out.iter().map(|(_, w)| *w).sum();
+        for (_, w) in out.iter_mut() { *w /= sum; }
+        out
+    }
+
+    /// Blended density multiplier for a scatter layer at a world position.
+    pub fn density_at(&self, world_xz: Vec2, layer: &str) -> f32 {
+        let weights = self.weights_at(world_xz);
+        if weights.is_empty() { return 1.0; }
+        weights
+            .iter()
+            .map(|(i, w)| w * self.settings.biomes[*i].densities.get(layer).copied().unwrap_or(1.0))
+            .sum()
+    }
+}
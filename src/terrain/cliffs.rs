@@ -0,0 +1,307 @@
+//! Cliff and rock meshes stamped onto steep slopes.
+//!
+//! A heightfield can't overhang, and steep noise slopes stretch the splat
+//! texturing. `CliffPlugin` finds the connected patches of each tile's
+//! slope field above `CliffSettings::slope_threshold` and places the
+//! user-supplied `CliffPiece` meshes over them: aligned to the surface,
+//! facing down-slope, at least `spacing` apart and at most `max_per_tile`
+//! per tile. The pieces are children of the tile and share meshes and
+//! materials, so Bevy batches them. Placement only depends on the seed, the
+//! coord and the tile's heights at spawn time.
+//!
+//! With `paint_rock`, the texels of those patches are set to the rock layer
+//! in the tile's splat override texture (on top of painted edits, not stored
+//! in them), so the ground under the pieces matches them.
+//!
+//! This is visual only: the crate builds no colliders for the pieces; use
+//! `CliffsReady` to add them.
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use std::sync::Arc;
+
+use super::edit::{decompress_tile_texture, TerrainEdits};
+use super::heightfield::{sample_bilinear, TerrainHeightfield};
+use super::material::TerrainMaterial;
+use super::rng::TileRng;
+use super::systems::{collect_finished_tasks_system, TerrainConfig, TerrainState, TileSpawned};
+
+pub struct CliffPlugin;
+impl Plugin for CliffPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<CliffSettings>()
+            .add_event::<CliffsReady>()
+            .add_systems(
+                Update,
+                (spawn_cliff_tasks_system, collect_cliff_tasks_system)
+                    .chain()
+                    .after(collect_finished_tasks_system),
+            );
+    }
+}
+
+/// A mesh to stamp; its local +Y is laid along the surface normal and -Z
+/// faces down-slope.
+#[derive(Clone)]
+pub struct CliffPiece {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+}
+
+#[derive(Resource, Clone)]
+pub struct CliffSettings {
+    /// Picked from at random per placement; nothing is placed while empty.
+    pub pieces: Vec<CliffPiece>,
+    /// Slope in degrees above which a texel counts as cliff.
+    pub slope_threshold: f32,
+    /// Smaller connected patches are ignored (texels).
+    pub min_region_texels: usize,
+    /// Minimum distance between two pieces, world units.
+    pub spacing: f32,
+    pub max_per_tile: usize,
+    pub scale_range: (f32, f32),
+    /// How far pieces sit inside the slope along its normal, at scale 1.
+    pub sink: f32,
+    /// Set the patches' texels to the rock splat layer.
+    pub paint_rock: bool,
+}
+impl Default for CliffSettings {
+    fn default() -> Self {
+        Self {
+            pieces: Vec::new(),
+            slope_threshold: 50.0,
+            min_region_texels: 24,
+            spacing: 6.0,
+            max_per_tile: 48,
+            scale_range: (0.8, 1.4),
+            sink: 0.5,
+            paint_rock: true,
+        }
+    }
+}
+
+/// One stamped piece, in the tile's local space.
+#[derive(Clone, Copy, Debug)]
+pub struct CliffPlacement {
+    pub transform: Transform,
+    /// Index into `CliffSettings::pieces`.
+    pub piece: usize,
+}
+
+/// A tile's cliff pieces were placed (and spawned under `tile`).
+#[derive(Event, Clone)]
+pub struct CliffsReady {
+    pub coord: IVec2,
+    pub tile: Entity,
+    pub placements: Arc<[CliffPlacement]>,
+}
+
+/// Parent of one tile's pieces.
+#[derive(Component)]
+pub struct CliffGroup {
+    pub coord: IVec2,
+}
+
+struct CliffLayout {
+    placements: Vec<CliffPlacement>,
+    /// Texels of the kept patches, row-major indices.
+    texels: Vec<u32>,
+}
+
+#[derive(Component)]
+pub struct CliffTask {
+    pub coord: IVec2,
+    task: Task<CliffLayout>,
+    /// Rewrite the splat texture even without rock texels (settings changed).
+    repaint: bool,
+}
+
+pub fn spawn_cliff_tasks_system(
+    mut commands: Commands,
+    mut spawned: EventReader<TileSpawned>,
+    settings: Res<CliffSettings>,
+    cfg: Res<TerrainConfig>,
+    state: Res<TerrainState>,
+    heightfield: Res<TerrainHeightfield>,
+    q_groups: Query<Entity, With<CliffGroup>>,
+) {
+    let mut tiles: Vec<(IVec2, Entity)> = spawned.read().map(|ev| (ev.coord, ev.entity)).collect();
+    let repaint = settings.is_changed() && !settings.is_added();
+    if repaint {
+        for e in &q_groups {
+            commands.entity(e).despawn();
+        }
+        tiles = state.tiles.iter().map(|(c, t)| (*c, t.entity)).collect();
+    }
+
+    let pool = AsyncComputeTaskPool::get();
+    for (coord, entity) in tiles {
+        let Some(tile) = heightfield.tile(coord) else { continue };
+        let heights = tile.heights.clone();
+        let settings = settings.clone();
+        let (n, size, scale) = (heightfield.resolution, heightfield.tile_size, heightfield.height_scale);
+        let rng = TileRng::new(cfg.seed, coord, 0x434C_4946); // "CLIF"
+        let task = pool.spawn(async move { layout_cliffs(&settings, &heights, n, size, scale, rng) });
+        commands.entity(entity).insert(CliffTask { coord, task, repaint });
+    }
+}
+
+/// Patches of texels steeper than the threshold (4-connected), then pieces
+/// over them in random order, spaced out.
+fn layout_cliffs(
+    settings: &CliffSettings,
+    heights: &[f32],
+    n: usize,
+    tile_size: f32,
+    height_scale: f32,
+    mut rng: TileRng,
+) -> CliffLayout {
+    let step = tile_size / (n as f32 - 1.0);
+    let h = |x: usize, z: usize| heights[z * n + x] * height_scale;
+    // central differences, one-sided at the tile edges
+    let normal = |x: usize, z: usize| {
+        let (xl, xr, zd, zu) = (x.saturating_sub(1), (x + 1).min(n - 1), z.saturating_sub(1), (z + 1).min(n - 1));
+        let dx = (h(xr, z) - h(xl, z)) / ((xr - xl) as f32 * step);
+        let dz = (h(x, zu) - h(x, zd)) / ((zu - zd) as f32 * step);
+        Vec3::new(-dx, 1.0, -dz).normalize()
+    };
+    let min_y = settings.slope_threshold.to_radians().cos();
+    let steep: Vec<bool> = (0..n * n).map(|i| normal(i % n, i / n).y < min_y).collect();
+
+    let mut seen = vec![false; n * n];
+    let mut texels = Vec::new();
+    let mut stack = Vec::new();
+    for start in 0..n * n {
+        if !steep[start] || seen[start] { continue; }
+        seen[start] = true;
+        stack.push(start);
+        let first = texels.len();
+        while let Some(i) = stack.pop() {
+            texels.push(i as u32);
+            let (x, z) = (i % n, i / n);
+            let neighbours = [
+                (x > 0).then(|| i - 1),
+                (x + 1 < n).then(|| i + 1),
+                (z > 0).then(|| i - n),
+                (z + 1 < n).then(|| i + n),
+            ];
+            for j in neighbours.into_iter().flatten() {
+                if steep[j] && !seen[j] {
+                    seen[j] = true;
+                    stack.push(j);
+                }
+            }
+        }
+        if texels.len() - first < settings.min_region_texels {
+            texels.truncate(first);
+        }
+    }
+
+    let mut placements: Vec<CliffPlacement> = Vec::new();
+    if !settings.pieces.is_empty() {
+        let mut candidates = texels.clone();
+        // Fisher-Yates, so pieces spread over all patches before the cap hits
+        for i in (1..candidates.len()).rev() {
+            let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+            candidates.swap(i, j);
+        }
+        let spacing2 = settings.spacing * settings.spacing;
+        for i in candidates {
+            if placements.len() >= settings.max_per_tile { break; }
+            let (x, z) = (i as usize % n, i as usize / n);
+            let jitter = Vec2::new(rng.next_f32(), rng.next_f32()) - 0.5;
+            let local = (Vec2::new(x as f32, z as f32) + jitter).clamp(Vec2::ZERO, Vec2::splat((n - 1) as f32));
+            let p = local * step;
+            if placements.iter().any(|c| c.transform.translation.xz().distance_squared(p) < spacing2) { continue; }
+
+            let up = normal(x, z);
+            // the normal leans downhill; its horizontal part is the fall line
+            let downhill = Vec3::new(up.x, 0.0, up.z);
+            let forward = (downhill - up * downhill.dot(up)).normalize_or(Vec3::NEG_Z);
+            let scale = rng.range(settings.scale_range.0, settings.scale_range.1);
+            let y = sample_bilinear(heights, n, local) * height_scale;
+            let position = Vec3::new(p.x, y, p.y) - up * settings.sink * scale;
+            let piece = (rng.next_u64() % settings.pieces.len() as u64) as usize;
+            placements.push(CliffPlacement {
+                transform: Transform::from_translation(position).looking_to(forward, up).with_scale(Vec3::splat(scale)),
+                piece,
+            });
+        }
+    }
+    CliffLayout { placements, texels }
+}
+
+pub fn collect_cliff_tasks_system(
+    mut commands: Commands,
+    settings: Res<CliffSettings>,
+    edits: Res<TerrainEdits>,
+    heightfield: Res<TerrainHeightfield>,
+    mut state: ResMut<TerrainState>,
+    materials: Option<Res<Assets<TerrainMaterial>>>,
+    mut images: Option<ResMut<Assets<Image>>>,
+    mut ready: EventWriter<CliffsReady>,
+    mut q_tasks: Query<(Entity, &mut CliffTask)>,
+) {
+    for (tile, mut t) in q_tasks.iter_mut() {
+        let Some(layout) = bevy::tasks::futures::check_ready(&mut t.task) else { continue };
+        commands.entity(tile).remove::<CliffTask>();
+        let coord = t.coord;
+
+        if !layout.placements.is_empty() {
+            commands
+                .spawn((
+                    Name::new(format!("Cliffs {coord:?}")),
+                    CliffGroup { coord },
+                    Transform::IDENTITY,
+                    Visibility::Inherited,
+                    ChildOf(tile),
+                ))
+                .with_children(|group| {
+                    for p in &layout.placements {
+                        let Some(piece) = settings.pieces.get(p.piece) else { continue };
+                        group.spawn((Mesh3d(piece.mesh.clone()), MeshMaterial3d(piece.material.clone()), p.transform));
+                    }
+                });
+        }
+
+        let rock = settings.paint_rock && !layout.texels.is_empty();
+        if rock || t.repaint {
+            paint_rock_texels(coord, heightfield.resolution, &layout.texels, rock, &edits, &mut state, materials.as_deref(), images.as_deref_mut());
+        }
+        ready.write(CliffsReady { coord, tile, placements: layout.placements.into() });
+    }
+}
+
+/// Rewrite a loaded `n`×`n` tile's splat override texture from its painted edits,
+/// plus full rock weight on `texels` when `rock` is set.
+fn paint_rock_texels(
+    coord: IVec2,
+    n: usize,
+    texels: &[u32],
+    rock: bool,
+    edits: &TerrainEdits,
+    state: &mut TerrainState,
+    materials: Option<&Assets<TerrainMaterial>>,
+    images: Option<&mut Assets<Image>>,
+) {
+    let (Some(materials), Some(images)) = (materials, images) else { return };
+    let Some(loaded) = state.tiles.get_mut(&coord) else { return };
+    let Some(img) = materials.get(&loaded.material).and_then(|m| images.get_mut(&m.splat_override_tex)) else { return };
+    let compressed = img.texture_descriptor.format.is_compressed();
+    // nothing was ever painted into a texture that's still compressed
+    if compressed && !rock { return; }
+    let mut bytes = edits.current_tile(coord).map_or_else(|| vec![0; n * n * 4], |e| e.splat_bytes(n));
+    if rock {
+        for &i in texels {
+            let i = i as usize * 4;
+            if let Some(texel) = bytes.get_mut(i..i + 4) { texel.copy_from_slice(&[0, 255, 0, 0]); }
+        }
+    }
+    if compressed {
+        let grown = decompress_tile_texture(img, n, 1, bytes);
+        loaded.texture_bytes_saved = loaded.texture_bytes_saved.saturating_sub(grown);
+    } else {
+        img.data = Some(bytes);
+    }
+}
@@ -0,0 +1,133 @@
+//! Weather-driven terrain coloring.
+//!
+//! `ClimateState` is the climate you ask for (from gameplay, a season cycle
+//! or the debug panel). `AppliedClimate` is what tiles currently show: it
+//! eases toward `ClimateState` over `ClimateSettings::transition_seconds` and
+//! feeds the splat uniform of every loaded tile, so raising the snow line
+//! melts snow everywhere without regenerating anything. Both are owned by
+//! `TerrainPlugin`.
+//!
+//! `Season` blends two named `SeasonPalette`s of `TerrainShadingSettings`
+//! into the splat layer colors and shifts the shown snow line. Advancing
+//! `Season::blend` over time gives a gradual transition; like the climate it
+//! only touches materials.
+
+use bevy::prelude::*;
+
+use super::shading::TerrainShadingSettings;
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct ClimateState {
+    /// Height where snow starts, measured like the other splat bands (see `HeightRef`).
+    pub snow_line_height: f32,
+    /// Width of the snow blend zone, world units.
+    pub snow_blend: f32,
+    /// 0 = dry, 1 = soaked: darker, glossier ground.
+    pub wetness: f32,
+}
+impl Default for ClimateState {
+    fn default() -> Self {
+        Self { snow_line_height: 8.0, snow_blend: 1.5, wetness: 0.0 }
+    }
+}
+
+impl ClimateState {
+    pub fn lerp(&self, to: &Self, t: f32) -> Self {
+        Self {
+            snow_line_height: self.snow_line_height.lerp(to.snow_line_height, t),
+            snow_blend: self.snow_blend.lerp(to.snow_blend, t),
+            wetness: self.wetness.lerp(to.wetness, t),
+        }
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct ClimateSettings {
+    /// Time to ease from the shown climate to a new `ClimateState`; 0 = instant.
+    pub transition_seconds: f32,
+}
+impl Default for ClimateSettings {
+    fn default() -> Self {
+        Self { transition_seconds: 4.0 }
+    }
+}
+
+/// Blend between two seasonal palettes, by name (see `SeasonPalette`).
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Season {
+    pub from: String,
+    pub to: String,
+    /// 0 = all `from`, 1 = all `to`.
+    pub blend: f32,
+}
+impl Default for Season {
+    fn default() -> Self {
+        Self::new("summer")
+    }
+}
+
+impl Season {
+    /// Fully in one season.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self { from: name.clone(), to: name, blend: 0.0 }
+    }
+}
+
+/// The climate tiles are shaded with right now, seasonal snow offset included. Read-only for users, write
+/// `ClimateState` instead.
+#[derive(Resource, Clone, Copy, Debug, Default, Deref)]
+pub struct AppliedClimate(pub(crate) ClimateState);
+
+/// The eased climate (before the seasonal offset) and a running
+/// transition: where it started, where it goes, seconds in.
+#[derive(Default)]
+pub struct ClimateTransition {
+    shown: ClimateState,
+    running: Option<(ClimateState, ClimateState, f32)>,
+    started: bool,
+}
+
+/// Ease `AppliedClimate` toward `ClimateState`. A target changed mid-way
+/// starts a new transition from wherever the shown climate is.
+pub fn ease_climate_system(
+    time: Res<Time>,
+    target: Res<ClimateState>,
+    settings: Res<ClimateSettings>,
+    season: Res<Season>,
+    shading: Res<TerrainShadingSettings>,
+    mut applied: ResMut<AppliedClimate>,
+    mut transition: Local<ClimateTransition>,
+) {
+    let transition = &mut *transition;
+    if !transition.started {
+        // the climate an app starts with shows right away
+        transition.started = true;
+        transition.shown = *target;
+    } else if transition.running.is_none_or(|(_, to, _)| to != *target) && transition.shown != *target {
+        transition.running = Some((transition.shown, *target, 0.0));
+    }
+    if let Some((from, to, elapsed)) = transition.running.as_mut() {
+        *elapsed += time.delta_secs();
+        let t = if settings.transition_seconds > 0.0 { (*elapsed / settings.transition_seconds).min(1.0) } else { 1.0 };
+        transition.shown = from.lerp(to, t * t * (3.0 - 2.0 * t));
+        if t >= 1.0 {
+            transition.running = None;
+        }
+    }
+
+    let mut shown = transition.shown;
+    shown.snow_line_height += shading.seasonal(&season).map_or(0.0, |(_, offset)| offset);
+    if applied.0 != shown {
+        applied.0 = shown;
+    }
+}
+
+/// Blend the seasonal palettes into `TerrainShadingSettings::layer_colors`.
+/// Unknown palette names leave the colors alone.
+pub fn apply_season_system(season: Res<Season>, mut shading: ResMut<TerrainShadingSettings>) {
+    let Some((colors, _)) = shading.seasonal(&season) else { return };
+    if shading.layer_colors != colors {
+        shading.layer_colors = colors;
+    }
+}
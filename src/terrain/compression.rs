@@ -0,0 +1,254 @@
+//! Block compression of the generated normal and splat textures
+//! (`texture-compression` feature, `TerrainConfig::compress_textures`).
+//!
+//! Tiles are built off the main thread, so the build task encodes them
+//! right there: BC7 keeps all four channels, so `terrain.wgsl` samples the
+//! same values as from RGBA8 at a quarter of the memory. The encoder only
+//! uses BC7 mode 6 (one subset, RGBA endpoints, 4-bit indices) with
+//! endpoints fit to each block's bounding box along its main axis of
+//! variation. That's far from the best quality a BC7 encoder can reach, but
+//! smooth normals and sparse splat weights compress well with it and it
+//! takes well under a millisecond per tile.
+//!
+//! BC textures come in 4×4 blocks, so a compressed texture is padded to the
+//! next multiple of 4 by repeating its last row and column; shaders address
+//! texels by `TileParams::texels_per_side` and never see the padding.
+//! Devices without `TEXTURE_COMPRESSION_BC` (WebGL2, most mobile) get the
+//! plain RGBA8 textures.
+
+use bevy::render::render_resource::TextureFormat;
+
+use super::meshgen::{mip_level_count, normalmap_mips};
+
+pub const COMPRESSED_FORMAT: TextureFormat = TextureFormat::Bc7RgbaUnorm;
+
+const BLOCK: usize = 4;
+const BLOCK_BYTES: usize = 16;
+/// Interpolation weights of 4-bit BC7 indices, in 64ths.
+const WEIGHTS: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// `n` rounded up to whole blocks.
+pub fn padded_size(n: usize) -> usize {
+    n.div_ceil(BLOCK) * BLOCK
+}
+
+/// An `n`×`n` RGBA8 texture padded to `padded_size(n)` by repeating the
+/// last row and column.
+pub fn pad_rgba(n: usize, rgba: &[u8]) -> Vec<u8> {
+    let p = padded_size(n);
+    let mut out = vec![0; p * p * 4];
+    for z in 0..p {
+        for x in 0..p {
+            let src = (z.min(n - 1) * n + x.min(n - 1)) * 4;
+            let dst = (z * p + x) * 4;
+            out[dst..dst + 4].copy_from_slice(&rgba[src..src + 4]);
+        }
+    }
+    out
+}
+
+/// BC7 data of an `n`×`n` RGBA8 normal map, padded; with `mipmapped`, the
+/// full chain of the padded size follows (`mip_level_count(padded_size(n))`).
+pub fn compress_normals(n: usize, normals: &[u8], mipmapped: bool) -> Vec<u8> {
+    let p = padded_size(n);
+    let padded = pad_rgba(n, &normals[..n * n * 4]);
+    if !mipmapped {
+        return compress_bc7(p, p, &padded);
+    }
+    let chain = normalmap_mips(p, &padded);
+    let mut out = Vec::new();
+    let mut start = 0;
+    for level in 0..mip_level_count(p) {
+        let size = (p >> level).max(1);
+        let len = size * size * 4;
+        out.extend(compress_bc7(size, size, &chain[start..start + len]));
+        start += len;
+    }
+    out
+}
+
+/// BC7 data of an `n`×`n` RGBA8 splat map, padded.
+pub fn compress_splat(n: usize, splat: &[u8]) -> Vec<u8> {
+    let p = padded_size(n);
+    compress_bc7(p, p, &pad_rgba(n, splat))
+}
+
+/// Bytes of a `size`×`size` RGBA8 texture with `mip_levels` levels.
+pub fn rgba8_bytes(size: usize, mip_levels: u32) -> usize {
+    (0..mip_levels).map(|l| (size >> l).max(1).pow(2) * 4).sum()
+}
+
+/// Encode a `width`×`height` RGBA8 image as BC7 blocks, row by row. Partial
+/// blocks at the edges repeat the last row and column.
+pub fn compress_bc7(width: usize, height: usize, rgba: &[u8]) -> Vec<u8> {
+    let (bw, bh) = (width.div_ceil(BLOCK), height.div_ceil(BLOCK));
+    let mut out = Vec::with_capacity(bw * bh * BLOCK_BYTES);
+    let mut texels = [[0u8; 4]; 16];
+    for by in 0..bh {
+        for bx in 0..bw {
+            for (t, texel) in texels.iter_mut().enumerate() {
+                let x = (bx * BLOCK + t % BLOCK).min(width - 1);
+                let y = (by * BLOCK + t / BLOCK).min(height - 1);
+                let i = (y * width + x) * 4;
+                texel.copy_from_slice(&rgba[i..i + 4]);
+            }
+            out.extend_from_slice(&encode_mode6(&texels));
+        }
+    }
+    out
+}
+
+/// One BC7 mode 6 block.
+fn encode_mode6(texels: &[[u8; 4]; 16]) -> [u8; BLOCK_BYTES] {
+    let (lo, hi) = endpoints(texels);
+    let (mut e0, mut p0) = quantize(lo);
+    let (mut e1, mut p1) = quantize(hi);
+    let a = e0.map(|c| ((c as u32) << 1) | p0 as u32);
+    let b = e1.map(|c| ((c as u32) << 1) | p1 as u32);
+    let colors: [[u32; 4]; 16] = WEIGHTS.map(|w| std::array::from_fn(|c| ((64 - w) * a[c] + w * b[c] + 32) >> 6));
+    let mut indices = texels.map(|t| {
+        (0..16)
+            .min_by_key(|&i| (0..4).map(|c| (colors[i][c] as i32 - t[c] as i32).pow(2)).sum::<i32>())
+            .unwrap_or(0) as u8
+    });
+    // the first index is stored without its top bit
+    if indices[0] >= 8 {
+        std::mem::swap(&mut e0, &mut e1);
+        std::mem::swap(&mut p0, &mut p1);
+        for i in &mut indices {
+            *i = 15 - *i;
+        }
+    }
+
+    let mut bits = 0u128;
+    let mut at = 0;
+    let mut put = |value: u32, count: u32| {
+        bits |= (value as u128) << at;
+        at += count;
+    };
+    put(1 << 6, 7);
+    for c in 0..4 {
+        put(e0[c] as u32, 7);
+        put(e1[c] as u32, 7);
+    }
+    put(p0 as u32, 1);
+    put(p1 as u32, 1);
+    put(indices[0] as u32, 3);
+    for &i in &indices[1..] {
+        put(i as u32, 4);
+    }
+    bits.to_le_bytes()
+}
+
+/// Bounding box corners of the block along its main axis: channels that
+/// fall while the widest one rises get their ends swapped.
+fn endpoints(texels: &[[u8; 4]; 16]) -> ([u8; 4], [u8; 4]) {
+    let mut lo = [255u8; 4];
+    let mut hi = [0u8; 4];
+    let mut mean = [0f32; 4];
+    for t in texels {
+        for c in 0..4 {
+            lo[c] = lo[c].min(t[c]);
+            hi[c] = hi[c].max(t[c]);
+            mean[c] += t[c] as f32 / 16.0;
+        }
+    }
+    let widest = (0..4).max_by_key(|&c| hi[c] - lo[c]).unwrap_or(0);
+    for c in 0..4 {
+        let covariance: f32 = texels
+            .iter()
+            .map(|t| (t[widest] as f32 - mean[widest]) * (t[c] as f32 - mean[c]))
+            .sum();
+        if covariance < 0.0 {
+            std::mem::swap(&mut lo[c], &mut hi[c]);
+        }
+    }
+    (lo, hi)
+}
+
+/// 7-bit endpoint and shared p-bit closest to an 8-bit color.
+fn quantize(color: [u8; 4]) -> ([u8; 4], u8) {
+    let fit = |p: u8| {
+        let q = color.map(|c| ((c as i32 - p as i32 + 1) / 2).clamp(0, 127) as u8);
+        let error: i32 = (0..4).map(|c| (((q[c] as i32) << 1 | p as i32) - color[c] as i32).pow(2)).sum();
+        (q, error)
+    };
+    let (q0, err0) = fit(0);
+    let (q1, err1) = fit(1);
+    if err1 < err0 { (q1, 1) } else { (q0, 0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference mode 6 decoder, written from the format description rather
+    /// than the encoder.
+    fn decode_mode6(block: &[u8; BLOCK_BYTES]) -> [[u8; 4]; 16] {
+        let bits = u128::from_le_bytes(*block);
+        let mut at = 0;
+        let mut get = |count: u32| {
+            let value = (bits >> at) as u32 & ((1 << count) - 1);
+            at += count;
+            value
+        };
+        assert_eq!(get(7), 1 << 6, "not a mode 6 block");
+        // R0 R1 G0 G1 B0 B1 A0 A1
+        let channels: [[u32; 2]; 4] = std::array::from_fn(|_| [get(7), get(7)]);
+        let p = [get(1), get(1)];
+        let ends = [0, 1].map(|e| channels.map(|c| c[e] << 1 | p[e]));
+        let indices: [u32; 16] = std::array::from_fn(|i| get(if i == 0 { 3 } else { 4 }));
+        indices.map(|i| {
+            let w = WEIGHTS[i as usize];
+            std::array::from_fn(|c| (((64 - w) * ends[0][c] + w * ends[1][c] + 32) >> 6) as u8)
+        })
+    }
+
+    fn max_error(texels: &[[u8; 4]; 16]) -> [u8; 4] {
+        let decoded = decode_mode6(&encode_mode6(texels));
+        std::array::from_fn(|c| (0..16).map(|t| texels[t][c].abs_diff(decoded[t][c])).max().unwrap())
+    }
+
+    fn gradient(from: [u8; 4], to: [u8; 4]) -> [[u8; 4]; 16] {
+        std::array::from_fn(|t| std::array::from_fn(|c| (from[c] as f32 + (to[c] as f32 - from[c] as f32) * t as f32 / 15.0).round() as u8))
+    }
+
+    #[test]
+    fn constant_block_round_trips() {
+        // the shared p-bit costs at most one step in half the channels
+        assert!(max_error(&[[120, 200, 33, 255]; 16]).iter().all(|e| *e <= 1));
+        assert_eq!(max_error(&[[128, 64, 254, 200]; 16]), [0; 4]);
+    }
+
+    #[test]
+    fn gradient_round_trips() {
+        let (a, b) = ([10, 40, 200, 255], [240, 180, 20, 60]);
+        let error = max_error(&gradient(a, b));
+        assert!(error.iter().all(|e| *e <= 3), "{error:?}");
+    }
+
+    #[test]
+    fn first_index_over_7_swaps_the_endpoints() {
+        // the first texel sits at the high end, so its index is 15 before the swap
+        let (a, b) = ([10, 40, 200, 255], [240, 180, 20, 60]);
+        let texels = gradient(b, a);
+        let block = encode_mode6(&texels);
+        let red0 = (u128::from_le_bytes(block) >> 7) as u8 & 0x7f;
+        assert!(red0.abs_diff(b[0] / 2) <= 1, "endpoints not swapped");
+        let error = max_error(&texels);
+        assert!(error.iter().all(|e| *e <= 3), "{error:?}");
+        let decoded = decode_mode6(&block);
+        assert!((0..4).all(|c| decoded[0][c].abs_diff(b[c]) <= 1));
+    }
+
+    #[test]
+    fn compressed_normals_cover_the_padded_mip_chain() {
+        let n = 13;
+        let normals = vec![128; n * n * 4];
+        // padded to 16: 4×4, 2×2, 1, 1 and 1 blocks for 16, 8, 4, 2 and 1
+        assert_eq!(mip_level_count(padded_size(n)), 5);
+        assert_eq!(compress_normals(n, &normals, true).len(), (16 + 4 + 1 + 1 + 1) * BLOCK_BYTES);
+        assert_eq!(compress_normals(n, &normals, false).len(), 16 * BLOCK_BYTES);
+        assert_eq!(compress_splat(n, &normals).len(), 16 * BLOCK_BYTES);
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use super::origin::WorldOffset;
+use super::shading::TerrainShadingSettings;
+use super::systems::{TerrainConfig, TerrainState, TileLoader, world_to_coord};
+
+/// Gizmo overlay for streaming/seam debugging. Off by default.
+///
+/// Loaded tiles are drawn as boxes spanning their min/max height (green),
+/// pending tiles as flat outlines (yellow) and tiles despawned within the last
+/// `despawn_flash_seconds` in red. Each loader's desired square is outlined too.
+/// With `build_time_heatmap` loaded tiles are instead tinted green (fast) to
+/// red (`heatmap_max_seconds` or slower).
+#[derive(Resource)]
+pub struct TerrainDebugOverlay {
+    pub enabled: bool,
+    pub build_time_heatmap: bool,
+    pub heatmap_max_seconds: f32,
+    /// Tiles whose center is farther than this from every camera are skipped.
+    pub max_distance: f32,
+    pub show_envelope: bool,
+    pub despawn_flash_seconds: f32,
+}
+impl Default for TerrainDebugOverlay {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            build_time_heatmap: false,
+            heatmap_max_seconds: 0.05,
+            max_distance: 512.0,
+            show_envelope: true,
+            despawn_flash_seconds: 1.0,
+        }
+    }
+}
+
+const LOADED_COLOR: Color = Color::srgb(0.2, 0.9, 0.2);
+const PENDING_COLOR: Color = Color::srgb(0.95, 0.85, 0.1);
+const DESPAWNED_COLOR: Color = Color::srgb(0.95, 0.15, 0.1);
+const ENVELOPE_COLOR: Color = Color::srgb(0.2, 0.6, 1.0);
+
+pub fn draw_debug_overlay_system(
+    time: Res<Time>,
+    overlay: Res<TerrainDebugOverlay>,
+    cfg: Res<TerrainConfig>,
+    shading: Res<TerrainShadingSettings>,
+    state: Res<TerrainState>,
+    offset: Res<WorldOffset>,
+    q_cameras: Query<&GlobalTransform, With<Camera3d>>,
+    q_loaders: Query<(&Transform, &TileLoader)>,
+    // last frame's loaded tiles (min/max) and when each vanished
+    mut seen: Local<HashMap<IVec2, (f32, f32)>>,
+    mut despawned: Local<HashMap<IVec2, (f32, f32, f32)>>,
+    mut gizmos: Gizmos,
+) {
+    let now = time.elapsed_secs();
+    let size = cfg.tile_size;
+    let scale = shading.height_scale;
+
+    // Track despawns by diffing against the previous frame
+    for (c, (lo, hi)) in seen.iter() {
+        if !state.tiles.contains_key(c) {
+            despawned.insert(*c, (*lo, *hi, now));
+        }
+    }
+    seen.clear();
+    for (c, tile) in state.tiles.iter() {
+        seen.insert(*c, (tile.min_height, tile.max_height));
+        despawned.remove(c);
+    }
+    despawned.retain(|_, (_, _, t)| now - *t < overlay.despawn_flash_seconds);
+
+    let cameras: Vec<Vec2> = q_cameras.iter().map(|t| t.translation().xz()).collect();
+    let max_d2 = overlay.max_distance * overlay.max_distance;
+    let visible = |c: IVec2| -> bool {
+        let center = offset.tile_origin(c, size) + 0.5 * size;
+        cameras.iter().any(|p| p.distance_squared(center) <= max_d2)
+    };
+    let tile_box = |c: IVec2, lo: f32, hi: f32| -> Transform {
+        let (lo, hi) = (lo * scale, hi * scale);
+        let center = offset.tile_origin(c, size) + 0.5 * size;
+        Transform::from_xyz(center.x, (lo + hi) * 0.5, center.y)
+            .with_scale(Vec3::new(size, (hi - lo).max(0.01), size))
+    };
+
+    for (c, tile) in state.tiles.iter() {
+        if !visible(*c) { continue; }
+        let xf = tile_box(*c, tile.min_height, tile.max_height);
+        let color = if overlay.build_time_heatmap {
+            let t = (tile.build_seconds / overlay.heatmap_max_seconds.max(1e-6)).clamp(0.0, 1.0);
+            LOADED_COLOR.mix(&DESPAWNED_COLOR, t)
+        } else {
+            LOADED_COLOR
+        };
+        gizmos.cuboid(xf, color);
+        // axis marker at the tile center, +X/+Z show the coord direction
+        gizmos.axes(Transform::from_translation(xf.translation), size * 0.1);
+    }
+    for c in state.pending.keys() {
+        if !visible(*c) { continue; }
+        gizmos.cuboid(tile_box(*c, 0.0, 0.0), PENDING_COLOR);
+    }
+    for (c, (lo, hi, _)) in despawned.iter() {
+        if !visible(*c) { continue; }
+        gizmos.cuboid(tile_box(*c, *lo, *hi), DESPAWNED_COLOR);
+    }
+
+    if overlay.show_envelope {
+        for (xf, loader) in &q_loaders {
+            let center = world_to_coord(xf.translation, size, &offset);
+            let r = loader.radius_tiles as f32;
+            let mid = offset.tile_origin(center, size) + 0.5 * size;
+            let extent = (2.0 * r + 1.0) * size;
+            gizmos.rect(
+                Isometry3d::new(Vec3::new(mid.x, 0.0, mid.y), Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
+                Vec2::splat(extent),
+                ENVELOPE_COLOR,
+            );
+        }
+    }
+}
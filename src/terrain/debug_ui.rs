@@ -0,0 +1,148 @@
+//! Live terrain tuning panel (`egui` feature).
+//!
+//! The sliders edit a draft of `TerrainConfig` that is written back once it
+//! has been left alone for `TerrainDebugUi::debounce_seconds`, so dragging a
+//! slider rebuilds the terrain once instead of every frame. The write goes
+//! through normal change detection: only generation settings regenerate,
+//! budgets apply on the next dispatch.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiPrimaryContextPass};
+use std::hash::BuildHasher;
+
+use super::climate::{AppliedClimate, ClimateSettings, ClimateState};
+use super::diagnostics::TerrainDiagnostics;
+use super::systems::{RearCull, RegenerateTerrain, TerrainConfig, TerrainState, TileLoader};
+
+/// Adds `EguiPlugin` unless the app already has it.
+pub struct TerrainDebugUiPlugin;
+impl Plugin for TerrainDebugUiPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        app
+            .init_resource::<TerrainDebugUi>()
+            .add_systems(EguiPrimaryContextPass, terrain_debug_ui_system);
+    }
+}
+
+#[derive(Resource)]
+pub struct TerrainDebugUi {
+    pub open: bool,
+    /// Quiet time after the last edit before the draft is applied.
+    pub debounce_seconds: f32,
+    draft: Option<TerrainConfig>,
+    edited_at: f32,
+}
+impl Default for TerrainDebugUi {
+    fn default() -> Self {
+        Self { open: true, debounce_seconds: 0.3, draft: None, edited_at: 0.0 }
+    }
+}
+
+pub fn terrain_debug_ui_system(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    mut panel: ResMut<TerrainDebugUi>,
+    mut cfg: ResMut<TerrainConfig>,
+    state: Res<TerrainState>,
+    diagnostics: Res<TerrainDiagnostics>,
+    mut climate: ResMut<ClimateState>,
+    mut climate_settings: ResMut<ClimateSettings>,
+    applied: Res<AppliedClimate>,
+    mut regenerate: EventWriter<RegenerateTerrain>,
+    mut q_loaders: Query<&mut TileLoader>,
+) -> Result {
+    if !panel.open { return Ok(()); }
+    let ctx = contexts.ctx_mut()?;
+    let now = time.elapsed_secs();
+    let panel = &mut *panel;
+
+    let had_draft = panel.draft.is_some();
+    let mut draft = panel.draft.take().unwrap_or_else(|| cfg.clone());
+    let mut radius = q_loaders.iter().map(|l| l.radius_tiles).max().unwrap_or(0);
+    let mut rear_cull = q_loaders.iter().any(|l| l.rear_cull.is_some());
+    let (mut edited, mut radius_edited, mut regen, mut reroll) = (false, false, false, false);
+    let mut rear_cull_edited = false;
+
+    egui::Window::new("Terrain").show(ctx, |ui| {
+        ui.label(format!(
+            "loaded {}  pending {}  queued {}",
+            state.tiles.len(),
+            state.pending.len(),
+            diagnostics.tiles_queued,
+        ));
+        ui.separator();
+
+        edited |= ui.add(egui::DragValue::new(&mut draft.seed).prefix("seed ")).changed();
+        edited |= ui.add(egui::Slider::new(&mut draft.noise_octaves, 1..=12).text("octaves")).changed();
+        edited |= ui
+            .add(egui::Slider::new(&mut draft.noise_frequency, 0.001..=1.0).logarithmic(true).text("frequency"))
+            .changed();
+        edited |= ui.add(egui::Slider::new(&mut draft.noise_amplitude, 0.0..=200.0).text("amplitude")).changed();
+        edited |= ui.add(egui::Slider::new(&mut draft.noise_lacunarity, 1.0..=4.0).text("lacunarity")).changed();
+        edited |= ui.add(egui::Slider::new(&mut draft.noise_persistence, 0.0..=1.0).text("persistence")).changed();
+        // rebuilds the tiles; compare toward the horizon
+        edited |= ui.checkbox(&mut draft.normal_mipmaps, "normal map mips").changed();
+        ui.separator();
+
+        radius_edited = ui.add(egui::Slider::new(&mut radius, 0..=32).text("load radius")).changed();
+        rear_cull_edited = ui.checkbox(&mut rear_cull, "skip tiles behind the camera").changed();
+        edited |= ui.add(egui::Slider::new(&mut draft.max_spawns_per_frame, 1..=64).text("spawns / frame")).changed();
+        edited |= ui
+            .add(egui::Slider::new(&mut draft.max_despawns_per_frame, 1..=128).text("despawns / frame"))
+            .changed();
+        edited |= ui.add(egui::Slider::new(&mut draft.max_in_flight_tasks, 1..=64).text("tasks in flight")).changed();
+        ui.separator();
+
+        // climate applies right away and eases in; no regeneration involved
+        ui.label(format!("snow line {:.1}  wetness {:.2}", applied.snow_line_height, applied.wetness));
+        let mut target = *climate;
+        ui.add(egui::Slider::new(&mut target.snow_line_height, -50.0..=200.0).text("snow line"));
+        ui.add(egui::Slider::new(&mut target.snow_blend, 0.0..=20.0).text("snow blend"));
+        ui.add(egui::Slider::new(&mut target.wetness, 0.0..=1.0).text("wetness"));
+        climate.set_if_neq(target);
+        let mut seconds = climate_settings.transition_seconds;
+        if ui.add(egui::Slider::new(&mut seconds, 0.0..=30.0).text("transition s")).changed() {
+            climate_settings.transition_seconds = seconds;
+        }
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            regen = ui.button("Regenerate").clicked();
+            reroll = ui.button("Reroll seed").clicked();
+        });
+    });
+
+    if radius_edited {
+        for mut loader in q_loaders.iter_mut() {
+            loader.radius_tiles = radius;
+        }
+    }
+    if rear_cull_edited {
+        for mut loader in q_loaders.iter_mut() {
+            loader.rear_cull = rear_cull.then(RearCull::default);
+        }
+    }
+    if reroll {
+        draft.seed = std::collections::hash_map::RandomState::new().hash_one(now.to_bits()) as u32;
+    }
+    if edited {
+        panel.edited_at = now;
+    }
+
+    // buttons apply right away; slider edits wait for the debounce
+    let settled = now - panel.edited_at >= panel.debounce_seconds;
+    if edited || reroll || had_draft {
+        if regen || reroll || settled {
+            *cfg = draft;
+        } else {
+            panel.draft = Some(draft);
+        }
+    }
+    if regen {
+        regenerate.write(RegenerateTerrain);
+    }
+    Ok(())
+}
@@ -0,0 +1,227 @@
+//! Decals splashed over the terrain albedo (craters, scorch marks, spell
+//! effects) without touching the heightfield.
+//!
+//! Gameplay adds `TerrainDecal`s to the `TerrainDecals` resource (owned by
+//! `TerrainPlugin`, so headless servers can keep track of them too) and gets
+//! a `DecalId` back to remove them with. `sync_tile_decals_system` bins the
+//! decals to the loaded tiles they overlap and hands each tile up to
+//! `MAX_TILE_DECALS` of them, the ones nearest its center: positions go into
+//! the tile's `DecalParams` relative to its min corner, textures into the
+//! material's decal slots. A decal over a tile border is in both tiles'
+//! lists, so it lines up across the seam. Tiles without decals have
+//! `count = 0` and skip the blend entirely.
+//!
+//! Decals with a `fade_time` fade out linearly and are removed once it has
+//! passed; their tiles' materials are updated every frame until then.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::f32::consts::SQRT_2;
+
+use super::material::{DecalParams, TerrainMaterial, TileDecal, MAX_TILE_DECALS};
+use super::origin::{WorldOffset, WorldRebased};
+use super::systems::{collect_finished_tasks_system, TerrainConfig, TerrainState, TileSpawned};
+
+pub struct TerrainDecalPlugin;
+impl Plugin for TerrainDecalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (rebase_decals_system, expire_decals_system, sync_tile_decals_system)
+                .chain()
+                .after(collect_finished_tasks_system),
+        );
+    }
+}
+
+/// A texture laid flat over the terrain.
+#[derive(Clone, Debug)]
+pub struct TerrainDecal {
+    /// Local-space XZ; shifted along on `WorldRebased`.
+    pub center: Vec2,
+    /// Half the side of the square the texture covers, world units.
+    pub radius: f32,
+    /// Blended over the ground by its alpha.
+    pub texture: Handle<Image>,
+    /// Radians, counter-clockwise seen from above.
+    pub rotation: f32,
+    /// Seconds until the decal has faded out and is removed; `None` stays.
+    pub fade_time: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DecalId(u64);
+
+struct PlacedDecal {
+    decal: TerrainDecal,
+    /// Seconds since it was added.
+    age: f32,
+}
+
+impl PlacedDecal {
+    fn alpha(&self) -> f32 {
+        match self.decal.fade_time {
+            Some(t) => (1.0 - self.age / t.max(1e-3)).clamp(0.0, 1.0),
+            None => 1.0,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TerrainDecals {
+    decals: HashMap<DecalId, PlacedDecal>,
+    next_id: u64,
+}
+
+impl TerrainDecals {
+    /// Later decals draw over earlier ones.
+    pub fn add(&mut self, decal: TerrainDecal) -> DecalId {
+        let id = DecalId(self.next_id);
+        self.next_id += 1;
+        self.decals.insert(id, PlacedDecal { decal, age: 0.0 });
+        id
+    }
+
+    pub fn remove(&mut self, id: DecalId) -> Option<TerrainDecal> {
+        self.decals.remove(&id).map(|p| p.decal)
+    }
+
+    pub fn get(&self, id: DecalId) -> Option<&TerrainDecal> {
+        self.decals.get(&id).map(|p| &p.decal)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (DecalId, &TerrainDecal)> {
+        self.decals.iter().map(|(id, p)| (*id, &p.decal))
+    }
+
+    pub fn len(&self) -> usize {
+        self.decals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.decals.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.decals.clear();
+    }
+}
+
+pub fn rebase_decals_system(mut rebased: EventReader<WorldRebased>, mut decals: ResMut<TerrainDecals>) {
+    for ev in rebased.read() {
+        let shift = ev.delta.as_vec2();
+        for placed in decals.decals.values_mut() {
+            placed.decal.center -= shift;
+        }
+    }
+}
+
+/// Age fading decals and drop the expired ones. Only a removal counts as a
+/// change of `TerrainDecals`; the fade itself is picked up by
+/// `sync_tile_decals_system` through `fading`.
+pub fn expire_decals_system(time: Res<Time>, mut decals: ResMut<TerrainDecals>) {
+    let dt = time.delta_secs();
+    let mut expired = false;
+    for placed in decals.bypass_change_detection().decals.values_mut() {
+        if let Some(t) = placed.decal.fade_time {
+            placed.age += dt;
+            expired |= placed.age >= t;
+        }
+    }
+    if expired {
+        decals.decals.retain(|_, p| p.decal.fade_time.is_none_or(|t| p.age < t));
+    }
+}
+
+/// Decals of each tile coord, and the tiles showing a fading one.
+#[derive(Default)]
+pub struct DecalBins {
+    tiles: HashMap<IVec2, Vec<DecalId>>,
+    fading: Vec<IVec2>,
+}
+
+/// Rebin decals when they, the tile size or `WorldOffset` change and push
+/// every loaded tile's decals into its material; otherwise only update new
+/// tiles and those with fading decals.
+pub fn sync_tile_decals_system(
+    cfg: Res<TerrainConfig>,
+    state: Res<TerrainState>,
+    offset: Res<WorldOffset>,
+    decals: Res<TerrainDecals>,
+    mut spawned: EventReader<TileSpawned>,
+    mut bins: Local<DecalBins>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+) {
+    let size = cfg.tile_size;
+    let coords: Vec<IVec2> = if decals.is_changed() || cfg.is_changed() || offset.is_changed() {
+        spawned.clear();
+        bins.tiles.clear();
+        for (id, placed) in &decals.decals {
+            // the texture square's corners reach radius·√2 when rotated
+            let reach = placed.decal.radius * SQRT_2;
+            let center = offset.to_world(placed.decal.center);
+            let min = ((center - reach as f64) / size as f64).floor().as_ivec2();
+            let max = ((center + reach as f64) / size as f64).floor().as_ivec2();
+            for z in min.y..=max.y {
+                for x in min.x..=max.x {
+                    bins.tiles.entry(IVec2::new(x, z)).or_default().push(*id);
+                }
+            }
+        }
+        let fading: Vec<IVec2> = bins
+            .tiles
+            .iter()
+            .filter(|(_, ids)| ids.iter().any(|id| decals.decals[id].decal.fade_time.is_some()))
+            .map(|(coord, _)| *coord)
+            .collect();
+        bins.fading = fading;
+        state.tiles.keys().copied().collect()
+    } else {
+        let mut coords: Vec<IVec2> = spawned.read().map(|ev| ev.coord).collect();
+        coords.extend(bins.fading.iter().filter(|c| state.tiles.contains_key(c)));
+        coords
+    };
+
+    for coord in coords {
+        let Some(tile) = state.tiles.get(&coord) else { continue };
+        let origin = offset.tile_origin(coord, size);
+        let tile_center = origin + Vec2::splat(size * 0.5);
+        let mut picked: Vec<(DecalId, &PlacedDecal)> = bins
+            .tiles
+            .get(&coord)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| decals.decals.get(id).map(|p| (*id, p)))
+            .collect();
+        // nearest first, then back into draw order
+        picked.sort_by(|a, b| {
+            a.1.decal.center.distance_squared(tile_center).total_cmp(&b.1.decal.center.distance_squared(tile_center))
+        });
+        picked.truncate(MAX_TILE_DECALS);
+        // a lookup, so tiles that never had decals aren't marked modified
+        if picked.is_empty() && materials.get(&tile.material).is_none_or(|m| m.decals.count == 0) {
+            continue;
+        }
+        let Some(mat) = materials.get_mut(&tile.material) else { continue };
+        picked.sort_by_key(|(id, _)| *id);
+
+        let mut params = DecalParams { count: picked.len() as u32, ..default() };
+        let mut textures: [Option<Handle<Image>>; MAX_TILE_DECALS] = default();
+        for (i, (_, placed)) in picked.iter().enumerate() {
+            let d = &placed.decal;
+            params.decals[i] = TileDecal {
+                center: d.center - origin,
+                rotation: Vec2::from_angle(d.rotation),
+                radius: d.radius.max(1e-3),
+                alpha: placed.alpha(),
+            };
+            textures[i] = Some(d.texture.clone());
+        }
+        let [t0, t1, t2, t3] = textures;
+        mat.decals = params;
+        mat.decal_tex_0 = t0;
+        mat.decal_tex_1 = t1;
+        mat.decal_tex_2 = t2;
+        mat.decal_tex_3 = t3;
+    }
+}
@@ -0,0 +1,181 @@
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
+use bevy::prelude::*;
+
+use super::heightfield::TerrainHeightfield;
+use super::systems::TerrainState;
+
+pub const TILE_BUILD_TIME: DiagnosticPath = DiagnosticPath::const_new("terrain/tile_build_ms");
+pub const TILES_LOADED: DiagnosticPath = DiagnosticPath::const_new("terrain/tiles_loaded");
+pub const TILES_PENDING: DiagnosticPath = DiagnosticPath::const_new("terrain/tiles_pending");
+/// Desired tiles not yet loaded or building.
+pub const TILES_QUEUED: DiagnosticPath = DiagnosticPath::const_new("terrain/tiles_queued");
+/// Loaded tiles held by `TerrainState::pin` (also included in `TILES_LOADED`).
+pub const TILES_PINNED: DiagnosticPath = DiagnosticPath::const_new("terrain/tiles_pinned");
+/// Loaded tiles hidden by `cull_distant_tiles_system`.
+pub const TILES_VIEW_CULLED: DiagnosticPath = DiagnosticPath::const_new("terrain/tiles_view_culled");
+/// Unloaded tile entities waiting for reuse.
+pub const TILES_POOLED: DiagnosticPath = DiagnosticPath::const_new("terrain/tiles_pooled");
+/// Texture memory of the loaded tiles saved by `TerrainConfig::compress_textures`.
+pub const TEXTURE_COMPRESSION_SAVED: DiagnosticPath = DiagnosticPath::const_new("terrain/texture_compression_saved_kib");
+/// CPU height/curvature copies kept in `TerrainHeightfield`.
+pub const HEIGHTFIELD_MEMORY: DiagnosticPath = DiagnosticPath::const_new("terrain/heightfield_kib");
+
+/// Terrain counters shared by the diagnostics store, debug overlays and logs.
+///
+/// Build systems call `record_build`; `terrain_diagnostics_system` forwards the
+/// samples into Bevy's `DiagnosticsStore` and periodically logs the slowest tiles.
+#[derive(Resource)]
+pub struct TerrainDiagnostics {
+    /// How many of the slowest tiles to log per interval (0 disables logging).
+    pub log_worst_tiles: usize,
+    pub log_interval_seconds: f32,
+    pub tiles_built_total: u64,
+    /// Desired tiles waiting for a task slot, as of the last dispatch.
+    pub tiles_queued: usize,
+    /// Loaded tiles hidden for being beyond the view distance, as of the last cull.
+    pub tiles_view_culled: usize,
+    recent_builds: Vec<(IVec2, f32)>,
+    interval_builds: Vec<(IVec2, f32)>,
+    last_log: f32,
+}
+impl Default for TerrainDiagnostics {
+    fn default() -> Self {
+        Self {
+            log_worst_tiles: 5,
+            log_interval_seconds: 60.0,
+            tiles_built_total: 0,
+            tiles_queued: 0,
+            tiles_view_culled: 0,
+            recent_builds: Vec::new(),
+            interval_builds: Vec::new(),
+            last_log: 0.0,
+        }
+    }
+}
+
+impl TerrainDiagnostics {
+    pub fn record_build(&mut self, coord: IVec2, seconds: f32) {
+        self.tiles_built_total += 1;
+        self.recent_builds.push((coord, seconds));
+    }
+}
+
+pub(crate) fn register_terrain_diagnostics(app: &mut App) {
+    app
+        .init_resource::<TerrainDiagnostics>()
+        .register_diagnostic(Diagnostic::new(TILE_BUILD_TIME).with_suffix("ms"))
+        .register_diagnostic(Diagnostic::new(TILES_LOADED))
+        .register_diagnostic(Diagnostic::new(TILES_PENDING))
+        .register_diagnostic(Diagnostic::new(TILES_QUEUED))
+        .register_diagnostic(Diagnostic::new(TILES_PINNED))
+        .register_diagnostic(Diagnostic::new(TILES_VIEW_CULLED))
+        .register_diagnostic(Diagnostic::new(TILES_POOLED))
+        .register_diagnostic(Diagnostic::new(TEXTURE_COMPRESSION_SAVED).with_suffix("KiB"))
+        .register_diagnostic(Diagnostic::new(HEIGHTFIELD_MEMORY).with_suffix("KiB"));
+}
+
+pub fn terrain_diagnostics_system(
+    time: Res<Time>,
+    state: Res<TerrainState>,
+    heightfield: Res<TerrainHeightfield>,
+    mut terrain_diag: ResMut<TerrainDiagnostics>,
+    mut diagnostics: Diagnostics,
+) {
+    let terrain_diag = &mut *terrain_diag;
+    for (_, secs) in terrain_diag.recent_builds.iter() {
+        diagnostics.add_measurement(&TILE_BUILD_TIME, || *secs as f64 * 1000.0);
+    }
+    diagnostics.add_measurement(&TILES_LOADED, || state.tiles.len() as f64);
+    diagnostics.add_measurement(&TILES_PENDING, || state.pending.len() as f64);
+    diagnostics.add_measurement(&TILES_QUEUED, || terrain_diag.tiles_queued as f64);
+    diagnostics.add_measurement(&TILES_PINNED, || {
+        state.pinned().filter(|c| state.tiles.contains_key(*c)).count() as f64
+    });
+    diagnostics.add_measurement(&TILES_VIEW_CULLED, || terrain_diag.tiles_view_culled as f64);
+    diagnostics.add_measurement(&TILES_POOLED, || state.pooled() as f64);
+    diagnostics.add_measurement(&TEXTURE_COMPRESSION_SAVED, || {
+        state.tiles.values().map(|t| t.texture_bytes_saved).sum::<usize>() as f64 / 1024.0
+    });
+    diagnostics.add_measurement(&HEIGHTFIELD_MEMORY, || heightfield.memory_bytes() as f64 / 1024.0);
+
+    if terrain_diag.log_worst_tiles == 0 {
+        terrain_diag.recent_builds.clear();
+        return;
+    }
+    terrain_diag.interval_builds.append(&mut terrain_diag.recent_builds);
+
+    let now = time.elapsed_secs();
+    if now - terrain_diag.last_log < terrain_diag.log_interval_seconds { return; }
+    terrain_diag.last_log = now;
+    if terrain_diag.interval_builds.is_empty() { return; }
+
+    let builds = &mut terrain_diag.interval_builds;
+    builds.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let worst: Vec<String> = builds
+        .iter()
+        .take(terrain_diag.log_worst_tiles)
+        .map(|(c, s)| format!("{:?} {:.1}ms", c, s * 1000.0))
+        .collect();
+    info!("terrain: {} tiles built, slowest: {}", builds.len(), worst.join(", "));
+    builds.clear();
+}
+
+/// On-screen readout of the terrain counters, a `Text` node in the top right
+/// corner while `enabled`. Off by default.
+#[derive(Resource)]
+pub struct TerrainStatsOverlay {
+    pub enabled: bool,
+    pub font_size: f32,
+}
+impl Default for TerrainStatsOverlay {
+    fn default() -> Self {
+        Self { enabled: false, font_size: 14.0 }
+    }
+}
+
+/// The overlay's text node.
+#[derive(Component)]
+pub struct TerrainStatsText;
+
+pub fn terrain_stats_overlay_system(
+    mut commands: Commands,
+    overlay: Res<TerrainStatsOverlay>,
+    state: Res<TerrainState>,
+    heightfield: Res<TerrainHeightfield>,
+    terrain_diag: Res<TerrainDiagnostics>,
+    mut q_text: Query<(Entity, &mut Text), With<TerrainStatsText>>,
+) {
+    if !overlay.enabled {
+        for (e, _) in &q_text {
+            commands.entity(e).despawn();
+        }
+        return;
+    }
+    let pinned = state.pinned().filter(|c| state.tiles.contains_key(*c)).count();
+    let readout = format!(
+        "tiles {} loaded ({pinned} pinned), {} pending, {} queued, {} pooled, {} culled\nbuilt {}, heightfield {} KiB",
+        state.tiles.len(),
+        state.pending.len(),
+        terrain_diag.tiles_queued,
+        state.pooled(),
+        terrain_diag.tiles_view_culled,
+        terrain_diag.tiles_built_total,
+        heightfield.memory_bytes() / 1024,
+    );
+    match q_text.single_mut() {
+        Ok((_, mut text)) => {
+            if text.0 != readout { text.0 = readout; }
+        }
+        Err(_) => {
+            commands.spawn((
+                Name::new("Terrain stats"),
+                TerrainStatsText,
+                Node { position_type: PositionType::Absolute, top: Val::Px(12.0), right: Val::Px(12.0), ..default() },
+                Text::new(readout),
+                TextFont { font_size: overlay.font_size, ..default() },
+                TextColor(Color::WHITE),
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+            ));
+        }
+    }
+}
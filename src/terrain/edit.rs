@@ -0,0 +1,1050 @@
+//! Runtime terraforming.
+//!
+//! `TerrainEdits` keeps sparse per-texel height deltas on top of the
+//! procedural heights, keyed by tile. The tile build task re-applies them, so
+//! edits survive unload/reload. Brush strokes patch loaded tiles in place:
+//! CPU heights, the touched rows of the height texture and the normal and
+//! curvature texels around them — no tile rebuild. Texels on a shared tile
+//! border exist in both tiles and receive the same delta, so no seam opens.
+//!
+//! Under `TerrainRenderMode::CpuMesh` the edited tiles' meshes are rebaked
+//! from the patched heights. Tiles without CPU heights to patch (GPU-built,
+//! or with their build still running) are dropped and built again on the
+//! CPU with the edits, so they vanish for the few frames that takes.
+//!
+//! Painted splat overrides are stored the same way (per-texel RGBA weights)
+//! and written into each tile's `splat_override_tex`. So are the texels cut
+//! out by `TerrainHoles`, which become the tile's hole mask.
+//!
+//! Brush strokes can be undone: `TerrainEditHistory` records the height
+//! deltas of each stroke, and `UndoTerrainEdit`/`RedoTerrainEdit` take them
+//! back out or put them in again. Paint isn't recorded.
+//!
+//! Every tile's edits are stamped with `TerrainConfig::generation_hash()`;
+//! edits made against different generation parameters are not applied.
+//! `TerrainEdits::save`/`load` use a small versioned binary format.
+
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureFormat};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::heightfield::TerrainHeightfield;
+use super::material::{TerrainMaterial, SPLAT_LAYERS};
+use super::flatmesh::displaced_grid_mesh;
+use super::meshgen::{mip_level_count, normalmap_mips};
+use super::systems::{collect_finished_tasks_system, HeightEncoding, TerrainConfig, TerrainRenderMode, TerrainState};
+
+/// Brush tool and stroke handling. `TerrainEdits` itself is owned by
+/// `TerrainPlugin` so the build pipeline can read it without this plugin.
+pub struct TerrainEditPlugin;
+impl Plugin for TerrainEditPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TerrainBrush>()
+            .init_resource::<PaintBrush>()
+            .init_resource::<TerrainEditHistory>()
+            .add_event::<TerrainBrushStroke>()
+            .add_event::<TerrainPaintStroke>()
+            .add_event::<TileHeightsEdited>()
+            .add_event::<UndoTerrainEdit>()
+            .add_event::<RedoTerrainEdit>()
+            .add_systems(
+                Update,
+                (apply_brush_strokes_system, apply_paint_strokes_system)
+                    .after(collect_finished_tasks_system),
+            )
+            .add_systems(Update, undo_terrain_edits_system.after(apply_brush_strokes_system))
+            .add_systems(Update, refresh_cpu_meshes_system.after(undo_terrain_edits_system))
+            .add_systems(
+                Update,
+                autosave_edits_system
+                    .run_if(resource_exists::<TerrainEditsAutosave>)
+                    .after(apply_brush_strokes_system)
+                    .after(apply_paint_strokes_system),
+            );
+
+        #[cfg(feature = "picking")]
+        app.add_systems(
+            Update,
+            brush_pointer_system.before(apply_brush_strokes_system).before(apply_paint_strokes_system),
+        );
+    }
+}
+
+/// Sparse edits of one tile. Keys are texel indices (`z * resolution + x`).
+#[derive(Clone, Default, Debug)]
+pub struct TileEdits {
+    /// Unscaled height offsets, added to the generated heights.
+    pub height_deltas: HashMap<u32, f32>,
+    /// Painted splat weights, one channel per layer.
+    pub splat: HashMap<u32, [u8; SPLAT_LAYERS]>,
+    /// Texels cut out of the surface, see `TerrainHoles`.
+    pub holes: HashSet<u32>,
+    /// `TerrainConfig::generation_hash()` the edits were made against.
+    pub base_hash: u64,
+}
+
+impl TileEdits {
+    pub fn is_empty(&self) -> bool {
+        self.height_deltas.is_empty() && self.splat.is_empty() && self.holes.is_empty()
+    }
+
+    /// R8 hole mask of the tile (0 = hole, 255 = solid), `None` without holes.
+    pub fn hole_mask(&self, resolution: usize) -> Option<Vec<u8>> {
+        if self.holes.is_empty() { return None; }
+        let mut mask = vec![255; resolution * resolution];
+        for &i in &self.holes {
+            if let Some(texel) = mask.get_mut(i as usize) { *texel = 0; }
+        }
+        Some(mask)
+    }
+
+    /// RGBA8 contents of the tile's splat override texture.
+    pub fn splat_bytes(&self, resolution: usize) -> Vec<u8> {
+        let mut bytes = vec![0; resolution * resolution * 4];
+        for (&i, w) in &self.splat {
+            let i = i as usize * 4;
+            if let Some(texel) = bytes.get_mut(i..i + 4) { texel.copy_from_slice(w); }
+        }
+        bytes
+    }
+
+    pub fn apply_heights(&self, heights: &mut [f32]) {
+        for (&i, &d) in &self.height_deltas {
+            if let Some(h) = heights.get_mut(i as usize) { *h += d; }
+        }
+    }
+
+    /// `apply_heights` on the interior of an `(n+2)²` apron grid.
+    pub fn apply_padded_heights(&self, n: usize, padded: &mut [f32]) {
+        for (&i, &d) in &self.height_deltas {
+            let (x, z) = (i as usize % n, i as usize / n);
+            if let Some(h) = padded.get_mut((z + 1) * (n + 2) + x + 1) { *h += d; }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct TerrainEdits {
+    tiles: HashMap<IVec2, TileEdits>,
+    /// Stamp for newly edited tiles; kept in sync with `TerrainConfig`.
+    base_hash: u64,
+}
+
+const EDITS_MAGIC: &[u8; 4] = b"TEDT";
+/// 2 added hole texels; version 1 files still load.
+const EDITS_VERSION: u32 = 2;
+
+impl TerrainEdits {
+    pub fn tile(&self, coord: IVec2) -> Option<&TileEdits> {
+        self.tiles.get(&coord)
+    }
+
+    /// Edits for `coord` if they were made against the current generation
+    /// parameters. Stale edits are skipped; `sync_edits_base_hash_system`
+    /// warns about them once when the stamp changes.
+    pub fn current_tile(&self, coord: IVec2) -> Option<&TileEdits> {
+        self.tiles.get(&coord).filter(|tile| tile.base_hash == self.base_hash)
+    }
+
+    /// Height deltas the neighbours of `coord` put on the one-sample apron
+    /// around it, as `(index into the (n+2)² grid, delta)`. Edges are shared,
+    /// so the apron is the row or column next to each neighbour's edge.
+    pub fn apron_deltas(&self, coord: IVec2, n: usize) -> Vec<(usize, f32)> {
+        let last = n as i64 - 1;
+        let mut out = Vec::new();
+        for z in -1..=n as i64 {
+            for x in -1..=n as i64 {
+                if (0..n as i64).contains(&x) && (0..n as i64).contains(&z) { continue; }
+                let side = IVec2::new(((x > last) as i32) - ((x < 0) as i32), ((z > last) as i32) - ((z < 0) as i32));
+                let Some(tile) = self.current_tile(coord + side) else { continue };
+                let (nx, nz) = (x - side.x as i64 * last, z - side.y as i64 * last);
+                if let Some(d) = tile.height_deltas.get(&((nz * n as i64 + nx) as u32)) {
+                    out.push(((z + 1) as usize * (n + 2) + (x + 1) as usize, *d));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = (&IVec2, &TileEdits)> {
+        self.tiles.iter()
+    }
+
+    pub fn base_hash(&self) -> u64 {
+        self.base_hash
+    }
+
+    /// Edits of `coord` for writing; stale edits of that tile are discarded
+    /// since they no longer describe the generated surface.
+    fn tile_mut(&mut self, coord: IVec2) -> &mut TileEdits {
+        let hash = self.base_hash;
+        let tile = self.tiles.entry(coord).or_insert_with(|| TileEdits { base_hash: hash, ..default() });
+        if tile.base_hash != hash {
+            *tile = TileEdits { base_hash: hash, ..default() };
+        }
+        tile
+    }
+
+    pub fn add_height(&mut self, coord: IVec2, texel: u32, delta: f32) {
+        *self.tile_mut(coord).height_deltas.entry(texel).or_insert(0.0) += delta;
+    }
+
+    pub fn splat(&self, coord: IVec2, texel: u32) -> [u8; SPLAT_LAYERS] {
+        self.tiles.get(&coord).and_then(|t| t.splat.get(&texel)).copied().unwrap_or_default()
+    }
+
+    pub fn set_splat(&mut self, coord: IVec2, texel: u32, weights: [u8; SPLAT_LAYERS]) {
+        self.tile_mut(coord).splat.insert(texel, weights);
+    }
+
+    pub fn is_hole(&self, coord: IVec2, texel: u32) -> bool {
+        self.tiles.get(&coord).is_some_and(|t| t.holes.contains(&texel))
+    }
+
+    pub fn set_hole(&mut self, coord: IVec2, texel: u32, hole: bool) {
+        let holes = &mut self.tile_mut(coord).holes;
+        if hole { holes.insert(texel); } else { holes.remove(&texel); }
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Write all edits to `path`, see `to_bytes`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let out = self.to_bytes();
+        // write-then-rename so a crash mid-save keeps the previous file
+        let tmp = path.as_ref().with_extension("tmp");
+        std::fs::File::create(&tmp)?.write_all(&out)?;
+        std::fs::rename(tmp, path)
+    }
+
+    /// All edits as little-endian binary: magic, version, tile count, then
+    /// per tile coord, base hash, height deltas, splat texels and hole texels.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(EDITS_MAGIC);
+        out.extend_from_slice(&EDITS_VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.tiles.len() as u32).to_le_bytes());
+        for (coord, tile) in &self.tiles {
+            out.extend_from_slice(&coord.x.to_le_bytes());
+            out.extend_from_slice(&coord.y.to_le_bytes());
+            out.extend_from_slice(&tile.base_hash.to_le_bytes());
+            out.extend_from_slice(&(tile.height_deltas.len() as u32).to_le_bytes());
+            for (i, d) in &tile.height_deltas {
+                out.extend_from_slice(&i.to_le_bytes());
+                out.extend_from_slice(&d.to_le_bytes());
+            }
+            out.extend_from_slice(&(tile.splat.len() as u32).to_le_bytes());
+            for (i, w) in &tile.splat {
+                out.extend_from_slice(&i.to_le_bytes());
+                out.extend_from_slice(w);
+            }
+            out.extend_from_slice(&(tile.holes.len() as u32).to_le_bytes());
+            for i in &tile.holes {
+                out.extend_from_slice(&i.to_le_bytes());
+            }
+        }
+        out
+    }
+
+    /// Read edits written by `save`. Entries keep their stored stamps; those
+    /// that don't match the current `TerrainConfig` are skipped at build time.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        std::fs::File::open(path)?.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Read edits written by `to_bytes`, see `load`.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut r = ByteReader(bytes);
+
+        if r.take(4)? != EDITS_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a terrain edits file"));
+        }
+        let version = r.u32()?;
+        if !(1..=EDITS_VERSION).contains(&version) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported terrain edits version {version}"),
+            ));
+        }
+        let mut edits = Self::default();
+        for _ in 0..r.u32()? {
+            let coord = IVec2::new(r.u32()? as i32, r.u32()? as i32);
+            let mut tile = TileEdits { base_hash: r.u64()?, ..default() };
+            for _ in 0..r.u32()? {
+                let i = r.u32()?;
+                tile.height_deltas.insert(i, f32::from_bits(r.u32()?));
+            }
+            for _ in 0..r.u32()? {
+                let i = r.u32()?;
+                tile.splat.insert(i, r.take(SPLAT_LAYERS)?.try_into().unwrap());
+            }
+            if version >= 2 {
+                for _ in 0..r.u32()? {
+                    tile.holes.insert(r.u32()?);
+                }
+            }
+            edits.tiles.insert(coord, tile);
+        }
+        Ok(edits)
+    }
+}
+
+/// Little-endian reader shared by the crate's binary save formats.
+pub(crate) struct ByteReader<'a>(pub(crate) &'a [u8]);
+impl<'a> ByteReader<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated file"));
+        }
+        let (head, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    pub(crate) fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn f32(&mut self) -> io::Result<f32> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    pub(crate) fn f64(&mut self) -> io::Result<f64> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+}
+
+/// Insert to save `TerrainEdits` to `path` every `interval_seconds` while
+/// they have unsaved changes.
+#[derive(Resource, Clone)]
+pub struct TerrainEditsAutosave {
+    pub path: PathBuf,
+    pub interval_seconds: f32,
+}
+
+pub fn autosave_edits_system(
+    time: Res<Time>,
+    autosave: Res<TerrainEditsAutosave>,
+    edits: Res<TerrainEdits>,
+    mut dirty: Local<bool>,
+    mut last_save: Local<f32>,
+) {
+    *dirty |= edits.is_changed() && !edits.is_added();
+    let now = time.elapsed_secs();
+    if !*dirty || now - *last_save < autosave.interval_seconds { return; }
+    match edits.save(&autosave.path) {
+        Ok(()) => info!("Saved terrain edits to {}", autosave.path.display()),
+        Err(e) => warn!("Failed to save terrain edits to {}: {e}", autosave.path.display()),
+    }
+    *dirty = false;
+    *last_save = now;
+}
+
+/// Keep the edit stamp in sync with the generation parameters (also covers
+/// a freshly loaded `TerrainEdits`), warning once about the tiles whose
+/// edits no longer apply.
+pub fn sync_edits_base_hash_system(cfg: Res<TerrainConfig>, mut edits: ResMut<TerrainEdits>) {
+    let hash = cfg.generation_hash();
+    if edits.base_hash == hash { return; }
+    edits.base_hash = hash;
+    let mut stale: Vec<IVec2> = edits.tiles.iter().filter(|(_, t)| t.base_hash != hash).map(|(c, _)| *c).collect();
+    if stale.is_empty() { return; }
+    stale.sort_by_key(|c| (c.y, c.x));
+    warn!(
+        "Skipping terrain edits of {} tiles (e.g. {:?}): made with different terrain parameters",
+        stale.len(),
+        &stale[..stale.len().min(4)],
+    );
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BrushMode {
+    Raise,
+    Lower,
+    /// Blend toward `target_height` (world units), or the height under the
+    /// stroke center when `None`.
+    Flatten { target_height: Option<f32> },
+    /// Blend toward a gaussian average of the surrounding heights.
+    Smooth,
+}
+
+#[derive(Resource, Clone)]
+pub struct TerrainBrush {
+    /// Pointer strokes are only generated while enabled.
+    pub enabled: bool,
+    pub radius: f32,
+    /// Raise/Lower: world units per second at the brush center.
+    /// Flatten/Smooth: blend rate per second toward the target.
+    pub strength: f32,
+    /// Fraction of the radius over which the effect fades out (0 = hard edge).
+    pub falloff: f32,
+    pub mode: BrushMode,
+}
+impl Default for TerrainBrush {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 6.0,
+            strength: 4.0,
+            falloff: 0.6,
+            mode: BrushMode::Raise,
+        }
+    }
+}
+
+impl TerrainBrush {
+    /// Brush weight (0..1) at `distance` from the center.
+    pub fn weight(&self, distance: f32) -> f32 {
+        brush_weight(distance, self.radius, self.falloff)
+    }
+}
+
+/// Flat core, smoothstep edge over the outer `falloff` fraction of `radius`.
+fn brush_weight(distance: f32, radius: f32, falloff: f32) -> f32 {
+    let t = distance / radius.max(1e-4);
+    if t >= 1.0 { return 0.0; }
+    let inner = 1.0 - falloff.clamp(0.0, 1.0);
+    if t <= inner { return 1.0; }
+    let u = 1.0 - (t - inner) / (1.0 - inner);
+    u * u * (3.0 - 2.0 * u)
+}
+
+/// Paints splat layer overrides.
+#[derive(Resource, Clone)]
+pub struct PaintBrush {
+    /// Pointer strokes are only generated while enabled.
+    pub enabled: bool,
+    /// Splat layer index (`0..SPLAT_LAYERS`).
+    pub layer: usize,
+    /// Blend rate per second toward a full override of `layer`.
+    pub strength: f32,
+    pub radius: f32,
+    pub falloff: f32,
+}
+impl Default for PaintBrush {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            layer: 0,
+            strength: 2.0,
+            radius: 4.0,
+            falloff: 0.5,
+        }
+    }
+}
+
+/// Apply the current `TerrainBrush` once at a world-space point (e.g. a
+/// terrain picking hit). Strength is scaled by the frame time.
+#[derive(Event, Clone, Copy)]
+pub struct TerrainBrushStroke {
+    pub center: Vec3,
+}
+
+/// Apply the current `PaintBrush` once at a world-space point.
+#[derive(Event, Clone, Copy)]
+pub struct TerrainPaintStroke {
+    pub center: Vec3,
+}
+
+/// A loaded tile's heights changed in place; `min..=max` is the touched texel
+/// rectangle. Anything derived from heights (colliders, scatter) should refresh.
+#[derive(Event, Clone, Copy)]
+pub struct TileHeightsEdited {
+    pub coord: IVec2,
+    pub entity: Entity,
+    pub min: UVec2,
+    pub max: UVec2,
+}
+
+/// Take back the last brush stroke in `TerrainEditHistory`.
+#[derive(Event, Clone, Copy, Default)]
+pub struct UndoTerrainEdit;
+
+/// Put back the last undone brush stroke.
+#[derive(Event, Clone, Copy, Default)]
+pub struct RedoTerrainEdit;
+
+/// Height deltas of one stroke, by tile and texel.
+type StrokeDeltas = HashMap<IVec2, HashMap<u32, f32>>;
+
+/// Undo and redo stacks of brush strokes. A stroke is the
+/// `TerrainBrushStroke`s of consecutive frames (one drag); it closes on the
+/// first frame without any. Cleared when the edits are replaced or stop
+/// applying (`TerrainConfig` changed), since the deltas no longer fit them.
+#[derive(Resource)]
+pub struct TerrainEditHistory {
+    /// Strokes kept for undo; the oldest are dropped beyond this.
+    pub max_strokes: usize,
+    undo: Vec<StrokeDeltas>,
+    redo: Vec<StrokeDeltas>,
+    /// The stroke being drawn.
+    open: StrokeDeltas,
+    base_hash: u64,
+}
+impl Default for TerrainEditHistory {
+    fn default() -> Self {
+        Self {
+            max_strokes: 64,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            open: HashMap::new(),
+            base_hash: 0,
+        }
+    }
+}
+
+impl TerrainEditHistory {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || !self.open.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.open.clear();
+    }
+
+    fn record(&mut self, coord: IVec2, deltas: &[(u32, f32)]) {
+        let tile = self.open.entry(coord).or_default();
+        for &(i, d) in deltas {
+            *tile.entry(i).or_insert(0.0) += d;
+        }
+        self.redo.clear();
+    }
+
+    fn close_stroke(&mut self) {
+        if self.open.is_empty() { return; }
+        self.undo.push(std::mem::take(&mut self.open));
+        let excess = self.undo.len().saturating_sub(self.max_strokes);
+        self.undo.drain(..excess);
+    }
+
+    /// Drop everything if `edits` isn't what the strokes were recorded on.
+    fn sync(&mut self, edits: &TerrainEdits, replaced: bool) {
+        if replaced || self.base_hash != edits.base_hash {
+            self.clear();
+            self.base_hash = edits.base_hash;
+        }
+    }
+}
+
+pub fn apply_brush_strokes_system(
+    time: Res<Time>,
+    brush: Res<TerrainBrush>,
+    mut strokes: EventReader<TerrainBrushStroke>,
+    mut edits: ResMut<TerrainEdits>,
+    mut history: ResMut<TerrainEditHistory>,
+    mut heightfield: ResMut<TerrainHeightfield>,
+    mut state: ResMut<TerrainState>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut edited: EventWriter<TileHeightsEdited>,
+) {
+    history.sync(&edits, edits.is_added());
+    if strokes.is_empty() {
+        history.close_stroke();
+        return;
+    }
+    let rate = brush.strength * time.delta_secs();
+    let (n, step) = (heightfield.resolution, heightfield.cell_size());
+    let scale = heightfield.height_scale;
+    // smoothing kernel half-width in texels
+    let smooth_reach = ((brush.radius / step) * 0.25).round().max(1.0) as i32;
+
+    for stroke in strokes.read() {
+        let center = stroke.center.xz();
+        let target = match brush.mode {
+            BrushMode::Flatten { target_height: Some(h) } => Some(h),
+            BrushMode::Flatten { target_height: None } => heightfield.height_at(center),
+            _ => None,
+        };
+        let lo = heightfield.world_to_coord(center - brush.radius);
+        let hi = heightfield.world_to_coord(center + brush.radius);
+
+        // Kernels read the pre-stroke surface, so shared border texels get
+        // identical results in both tiles; deltas are applied afterwards.
+        let mut tile_deltas: Vec<(IVec2, UVec2, UVec2, Vec<(u32, f32)>)> = Vec::new();
+        for cz in lo.y..=hi.y {
+            for cx in lo.x..=hi.x {
+                let coord = IVec2::new(cx, cz);
+                let origin = heightfield.tile_origin(coord);
+                let local = (center - origin) / step;
+                let reach = brush.radius / step;
+                let min = (local - reach).floor().max(Vec2::ZERO).as_uvec2();
+                let max = (local + reach).ceil().min(Vec2::splat((n - 1) as f32)).as_uvec2();
+                if min.x > max.x || min.y > max.y { continue; }
+
+                let loaded = heightfield.tile(coord);
+                let mut deltas = Vec::new();
+                for z in min.y..=max.y {
+                    for x in min.x..=max.x {
+                        let p = origin + Vec2::new(x as f32, z as f32) * step;
+                        let w = brush.weight(p.distance(center));
+                        if w <= 0.0 { continue; }
+                        let i = z * n as u32 + x;
+                        let current = loaded.map(|t| t.heights[i as usize] * scale);
+                        let blend = (rate * w).min(1.0);
+                        // world-space change; Flatten/Smooth need the tile loaded
+                        let delta = match brush.mode {
+                            BrushMode::Raise => Some(rate * w),
+                            BrushMode::Lower => Some(-rate * w),
+                            BrushMode::Flatten { .. } => current.zip(target).map(|(h, t)| (t - h) * blend),
+                            BrushMode::Smooth => current.map(|h| {
+                                (smoothed_height(&heightfield, p, step, smooth_reach).unwrap_or(h) - h) * blend
+                            }),
+                        };
+                        if let Some(d) = delta.filter(|d| *d != 0.0) {
+                            deltas.push((i, d / scale.max(1e-4)));
+                        }
+                    }
+                }
+                if !deltas.is_empty() {
+                    tile_deltas.push((coord, min, max, deltas));
+                }
+            }
+        }
+
+        let mut patched = Vec::new();
+        for (coord, min, max, deltas) in tile_deltas {
+            history.record(coord, &deltas);
+            if add_height_deltas(coord, &deltas, &mut edits, &mut heightfield, &mut state) {
+                patched.push((coord, min, max));
+            }
+        }
+        patch_edited_tiles(&patched, &mut heightfield, &mut state, &mut materials, &mut images, &mut edited);
+    }
+}
+
+/// Add `deltas` (texel, unscaled delta) to the edits of `coord` and to its
+/// CPU heights; false if it has none, e.g. GPU-built tiles.
+fn add_height_deltas(
+    coord: IVec2,
+    deltas: &[(u32, f32)],
+    edits: &mut TerrainEdits,
+    heightfield: &mut TerrainHeightfield,
+    state: &mut TerrainState,
+) -> bool {
+    // Deltas are recorded even for unloaded tiles; the build task picks them up.
+    for &(i, d) in deltas {
+        edits.add_height(coord, i, d);
+    }
+    let Some(mut tile) = heightfield.tile(coord).cloned() else {
+        // GPU-built or still building: nothing to patch, so build it again with the edits
+        if state.tiles.contains_key(&coord) || state.pending.contains_key(&coord) {
+            state.rebuild(coord);
+        }
+        return false;
+    };
+    let mut heights = tile.heights.to_vec();
+    for &(i, d) in deltas {
+        heights[i as usize] += d;
+    }
+    tile.heights = Arc::from(heights);
+    heightfield.insert(coord, tile);
+    true
+}
+
+/// Refresh the textures of the tiles `add_height_deltas` changed, each over
+/// its texel rectangle `min..=max`. Runs once all of them have their new
+/// heights, and also patches the neighbours whose border texels read the
+/// changed ones, so normals on both sides of an edited seam agree.
+fn patch_edited_tiles(
+    patched: &[(IVec2, UVec2, UVec2)],
+    heightfield: &mut TerrainHeightfield,
+    state: &mut TerrainState,
+    materials: &mut Assets<TerrainMaterial>,
+    images: &mut Assets<Image>,
+    edited: &mut EventWriter<TileHeightsEdited>,
+) {
+    let n = heightfield.resolution as i32;
+    let mut rects: Vec<(IVec2, UVec2, UVec2)> = patched.to_vec();
+    for &(coord, min, max) in patched {
+        for side in (-1..=1).flat_map(|z| (-1..=1).map(move |x| IVec2::new(x, z))) {
+            let neighbour = coord + side;
+            if side == IVec2::ZERO || heightfield.tile(neighbour).is_none() { continue; }
+            // the changed texels in the neighbour's texel space, one past its edge at most
+            let (lo, hi) = (min.as_ivec2() - side * (n - 1), max.as_ivec2() - side * (n - 1));
+            if hi.cmplt(IVec2::splat(-1)).any() || lo.cmpgt(IVec2::splat(n)).any() { continue; }
+            let clamp = |v: IVec2| v.clamp(IVec2::ZERO, IVec2::splat(n - 1)).as_uvec2();
+            rects.push((neighbour, clamp(lo), clamp(hi)));
+        }
+    }
+    for (coord, min, max) in rects {
+        let Some(heights) = heightfield.tile(coord).map(|t| t.heights.to_vec()) else { continue };
+        if let Some(entity) = patch_tile_heights(coord, heights, min, max, heightfield, state, materials, images) {
+            edited.write(TileHeightsEdited { coord, entity, min, max });
+        }
+    }
+}
+
+/// Undo takes a stroke's deltas back out of the edits and the loaded tiles,
+/// redo adds them again.
+pub fn undo_terrain_edits_system(
+    mut undo: EventReader<UndoTerrainEdit>,
+    mut redo: EventReader<RedoTerrainEdit>,
+    mut edits: ResMut<TerrainEdits>,
+    mut history: ResMut<TerrainEditHistory>,
+    mut heightfield: ResMut<TerrainHeightfield>,
+    mut state: ResMut<TerrainState>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut edited: EventWriter<TileHeightsEdited>,
+) {
+    let (undos, redos) = (undo.read().count(), redo.read().count());
+    if undos + redos == 0 { return; }
+    history.sync(&edits, edits.is_added());
+    history.close_stroke();
+    let n = heightfield.resolution as u32;
+    for (sign, count) in [(-1.0, undos), (1.0, redos)] {
+        for _ in 0..count {
+            let popped = match sign < 0.0 { true => history.undo.pop(), false => history.redo.pop() };
+            let Some(stroke) = popped else { break };
+            let mut patched = Vec::new();
+            for (&coord, texels) in &stroke {
+                let deltas: Vec<(u32, f32)> = texels.iter().map(|(&i, &d)| (i, d * sign)).collect();
+                let texel = |i: u32| UVec2::new(i % n, i / n);
+                let min = deltas.iter().fold(UVec2::MAX, |m, (i, _)| m.min(texel(*i)));
+                let max = deltas.iter().fold(UVec2::ZERO, |m, (i, _)| m.max(texel(*i)));
+                if add_height_deltas(coord, &deltas, &mut edits, &mut heightfield, &mut state) {
+                    patched.push((coord, min, max));
+                }
+            }
+            patch_edited_tiles(&patched, &mut heightfield, &mut state, &mut materials, &mut images, &mut edited);
+            match sign < 0.0 { true => history.redo.push(stroke), false => history.undo.push(stroke) };
+        }
+    }
+}
+
+/// Gaussian-weighted average of world heights around `p`, sampled on the
+/// texel grid across tile borders. `None` if nothing around is loaded.
+fn smoothed_height(heightfield: &TerrainHeightfield, p: Vec2, step: f32, reach: i32) -> Option<f32> {
+    let sigma2 = (reach as f32 * 0.5).max(0.5).powi(2);
+    let (mut sum, mut weight) = (0.0, 0.0);
+    for dz in -reach..=reach {
+        for dx in -reach..=reach {
+            let Some(h) = heightfield.height_at(p + Vec2::new(dx as f32, dz as f32) * step) else { continue };
+            let w = (-((dx * dx + dz * dz) as f32) / (2.0 * sigma2)).exp();
+            sum += h * w;
+            weight += w;
+        }
+    }
+    (weight > 0.0).then(|| sum / weight)
+}
+
+pub fn apply_paint_strokes_system(
+    time: Res<Time>,
+    brush: Res<PaintBrush>,
+    mut strokes: EventReader<TerrainPaintStroke>,
+    mut edits: ResMut<TerrainEdits>,
+    heightfield: Res<TerrainHeightfield>,
+    mut state: ResMut<TerrainState>,
+    materials: Res<Assets<TerrainMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if brush.layer >= SPLAT_LAYERS { return; }
+    let rate = brush.strength * time.delta_secs();
+    let (n, step) = (heightfield.resolution, heightfield.cell_size());
+    let mut target = [0.0; SPLAT_LAYERS];
+    target[brush.layer] = 255.0;
+
+    for stroke in strokes.read() {
+        let center = stroke.center.xz();
+        let lo = heightfield.world_to_coord(center - brush.radius);
+        let hi = heightfield.world_to_coord(center + brush.radius);
+        for cz in lo.y..=hi.y {
+            for cx in lo.x..=hi.x {
+                let coord = IVec2::new(cx, cz);
+                let origin = heightfield.tile_origin(coord);
+                let local = (center - origin) / step;
+                let reach = brush.radius / step;
+                let min = (local - reach).floor().max(Vec2::ZERO).as_uvec2();
+                let max = (local + reach).ceil().min(Vec2::splat((n - 1) as f32)).as_uvec2();
+                if min.x > max.x || min.y > max.y { continue; }
+
+                // Shared border texels start equal in both tiles and get the
+                // same blend, so painted seams stay continuous.
+                let loaded = state.tiles.get_mut(&coord);
+                let splat_tex = loaded.as_ref().and_then(|t| materials.get(&t.material)).map(|m| m.splat_override_tex.id());
+                let mut image = splat_tex.and_then(|id| images.get_mut(id));
+                if let (Some(img), Some(loaded)) = (image.as_deref_mut(), loaded) {
+                    if img.texture_descriptor.format.is_compressed() {
+                        let painted = edits.current_tile(coord).map_or_else(|| vec![0; n * n * 4], |e| e.splat_bytes(n));
+                        let grown = decompress_tile_texture(img, n, 1, painted);
+                        loaded.texture_bytes_saved = loaded.texture_bytes_saved.saturating_sub(grown);
+                    }
+                }
+                let mut data = image.and_then(|img| img.data.as_mut());
+                for z in min.y..=max.y {
+                    for x in min.x..=max.x {
+                        let p = origin + Vec2::new(x as f32, z as f32) * step;
+                        let w = brush_weight(p.distance(center), brush.radius, brush.falloff);
+                        if w <= 0.0 { continue; }
+                        let i = z * n as u32 + x;
+                        let blend = (rate * w).min(1.0);
+                        let current = edits.splat(coord, i);
+                        let mut next = [0u8; SPLAT_LAYERS];
+                        for c in 0..SPLAT_LAYERS {
+                            let v = current[c] as f32;
+                            let mut moved = (v + (target[c] - v) * blend).round();
+                            // move at least one unit so slow strokes don't stall on rounding
+                            if moved == v && target[c] != v { moved += (target[c] - v).signum(); }
+                            next[c] = moved.clamp(0.0, 255.0) as u8;
+                        }
+                        edits.set_splat(coord, i, next);
+                        if let Some(data) = data.as_mut() {
+                            let i = i as usize * 4;
+                            data[i..i + 4].copy_from_slice(&next);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Store edited heights for a loaded tile and refresh the GPU textures for the
+/// texel rectangle `min..=max` (normals and curvature with a 1-texel border).
+/// An `R16` height texture the edit leaves the range of is re-encoded whole.
+pub(crate) fn patch_tile_heights(
+    coord: IVec2,
+    heights: Vec<f32>,
+    min: UVec2,
+    max: UVec2,
+    heightfield: &mut TerrainHeightfield,
+    state: &mut TerrainState,
+    materials: &mut Assets<TerrainMaterial>,
+    images: &mut Assets<Image>,
+) -> Option<Entity> {
+    let n = heightfield.resolution;
+    let step = heightfield.cell_size();
+    let loaded = state.tiles.get_mut(&coord)?;
+    let min_height = heights.iter().copied().fold(f32::INFINITY, f32::min);
+    let max_height = heights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+    let material = materials.get(&loaded.material)?;
+    let format = images.get(&material.height_tex)?.texture_descriptor.format;
+    let encoding = HeightEncoding::of(format, &material.params);
+    let reencode = (!encoding.covers(min_height, max_height)).then(|| {
+        // headroom, so a stroke doesn't re-encode the tile every frame
+        let margin = (max_height - min_height).max(1.0) * 0.125;
+        HeightEncoding::r16(min_height - margin, max_height + margin)
+    });
+    if let Some(encoding) = reencode {
+        let material = materials.get_mut(&loaded.material)?;
+        material.params.height_offset = encoding.offset;
+        material.params.height_range = encoding.range;
+    }
+    let material = materials.get(&loaded.material)?;
+
+    let border_min = min.saturating_sub(UVec2::ONE);
+    let border_max = (max + UVec2::ONE).min(UVec2::splat(n as u32 - 1));
+    let at = |x: i64, z: i64| height_across_border(heightfield, coord, &heights, x, z);
+
+    if let Some(data) = images.get_mut(&material.height_tex).and_then(|img| img.data.as_mut()) {
+        match reencode {
+            Some(encoding) => *data = encoding.encode(&heights),
+            None => {
+                for z in min.y..=max.y {
+                    for x in min.x..=max.x {
+                        let i = z as usize * n + x as usize;
+                        encoding.write(data, i, heights[i]);
+                    }
+                }
+            }
+        }
+    }
+
+    // Same stencils as `normalmap_from_height` / `curvature_from_height`.
+    // A compressed normal map can't be patched; it's rebuilt whole as RGBA8.
+    let compressed = images.get(&material.normal_tex).is_some_and(|img| img.texture_descriptor.format.is_compressed());
+    let (region_min, region_max) = match compressed {
+        true => (UVec2::ZERO, UVec2::splat(n as u32 - 1)),
+        false => (border_min, border_max),
+    };
+    let mut curvature = heightfield.tile(coord)?.curvature.to_vec();
+    let mut normals = Vec::new();
+    let inv_step2 = 1.0 / (step * step);
+    for z in region_min.y..=region_max.y {
+        for x in region_min.x..=region_max.x {
+            let (xi, zi) = (x as i64, z as i64);
+            let i = z as usize * n + x as usize;
+            let dx = (at(xi + 1, zi) - at(xi - 1, zi)) / (2.0 * step);
+            let dz = (at(xi, zi + 1) - at(xi, zi - 1)) / (2.0 * step);
+            let nv = Vec3::new(-dx, 1.0, -dz).normalize();
+            normals.push((i, [nv.x, nv.y, nv.z].map(|c| ((c * 0.5 + 0.5) * 255.0) as u8)));
+            let sum = at(xi - 1, zi) + at(xi + 1, zi) + at(xi, zi - 1) + at(xi, zi + 1);
+            curvature[i] = (sum - 4.0 * at(xi, zi)) * inv_step2;
+        }
+    }
+    if let Some(img) = images.get_mut(&material.normal_tex) {
+        let mipmapped = img.texture_descriptor.mip_level_count > 1;
+        if compressed {
+            let levels = if mipmapped { mip_level_count(n) } else { 1 };
+            let grown = decompress_tile_texture(img, n, levels, vec![255; n * n * 4]);
+            loaded.texture_bytes_saved = loaded.texture_bytes_saved.saturating_sub(grown);
+        }
+        if let Some(data) = img.data.as_mut() {
+            // level 0 comes first; the coarser levels are rebuilt from it
+            for (i, rgb) in normals {
+                data[i * 4..i * 4 + 3].copy_from_slice(&rgb);
+            }
+            if mipmapped {
+                *data = normalmap_mips(n, data);
+            }
+        }
+    }
+    if let Some(data) = images.get_mut(&material.curvature_tex).and_then(|img| img.data.as_mut()) {
+        for z in border_min.y..=border_max.y {
+            for x in border_min.x..=border_max.x {
+                let i = z as usize * n + x as usize;
+                data[i * 4..i * 4 + 4].copy_from_slice(&curvature[i].to_le_bytes());
+            }
+        }
+    }
+
+    loaded.min_height = min_height;
+    loaded.max_height = max_height;
+    let entity = loaded.entity;
+
+    let mut tile = heightfield.tile(coord)?.clone();
+    tile.heights = Arc::from(heights);
+    tile.curvature = Arc::from(curvature);
+    tile.min_height = min_height;
+    tile.max_height = max_height;
+    heightfield.insert(coord, tile);
+    Some(entity)
+}
+
+/// Turn a block-compressed tile texture (`compression.rs`) into a plain
+/// `n`×`n` RGBA8 one holding `data` (`mip_levels` levels), so edits can patch
+/// its texels. Returns how many bytes it grew by.
+pub(crate) fn decompress_tile_texture(img: &mut Image, n: usize, mip_levels: u32, data: Vec<u8>) -> usize {
+    let before = img.data.as_ref().map_or(0, Vec::len);
+    let after = data.len();
+    img.texture_descriptor.format = TextureFormat::Rgba8Unorm;
+    img.texture_descriptor.size = Extent3d { width: n as u32, height: n as u32, depth_or_array_layers: 1 };
+    img.texture_descriptor.mip_level_count = mip_levels;
+    img.data = Some(data);
+    after.saturating_sub(before)
+}
+
+/// Height of texel `(x, z)` of tile `coord`, whose heights are `heights`.
+/// Texels past the edge come from the neighbouring tile (edges are shared),
+/// falling back to clamping when it isn't loaded.
+fn height_across_border(heightfield: &TerrainHeightfield, coord: IVec2, heights: &[f32], x: i64, z: i64) -> f32 {
+    let n = heightfield.resolution;
+    let last = n as i64 - 1;
+    let side = IVec2::new(((x > last) as i32) - ((x < 0) as i32), ((z > last) as i32) - ((z < 0) as i32));
+    let neighbour = (side != IVec2::ZERO).then(|| heightfield.tile(coord + side)).flatten();
+    match neighbour {
+        Some(t) => {
+            let (nx, nz) = (x - side.x as i64 * last, z - side.y as i64 * last);
+            t.heights[(nz.clamp(0, last) as usize) * n + nx.clamp(0, last) as usize]
+        }
+        None => heights[(z.clamp(0, last) as usize) * n + x.clamp(0, last) as usize],
+    }
+}
+
+/// Rebake the meshes of edited tiles under `TerrainRenderMode::CpuMesh`.
+pub fn refresh_cpu_meshes_system(
+    cfg: Res<TerrainConfig>,
+    heightfield: Res<TerrainHeightfield>,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    mut edited: EventReader<TileHeightsEdited>,
+    q_meshes: Query<&Mesh3d>,
+) {
+    let Some(mut meshes) = meshes.filter(|_| cfg.render_mode == TerrainRenderMode::CpuMesh) else {
+        edited.clear();
+        return;
+    };
+    let n = heightfield.resolution;
+    let mut done = Vec::new();
+    for ev in edited.read() {
+        if done.contains(&ev.entity) { continue; }
+        done.push(ev.entity);
+        let (Some(tile), Ok(mesh)) = (heightfield.tile(ev.coord), q_meshes.get(ev.entity)) else { continue };
+        let padded: Vec<f32> = (-1..=n as i64)
+            .flat_map(|z| (-1..=n as i64).map(move |x| (x, z)))
+            .map(|(x, z)| height_across_border(&heightfield, ev.coord, &tile.heights, x, z))
+            .collect();
+        meshes.insert(&mesh.0, displaced_grid_mesh(n, heightfield.tile_size, &padded, heightfield.height_scale));
+    }
+}
+
+/// Left mouse over terrain strokes whichever brushes are enabled.
+#[cfg(feature = "picking")]
+pub fn brush_pointer_system(
+    brush: Res<TerrainBrush>,
+    paint: Res<PaintBrush>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    pointers: Query<&bevy::picking::pointer::PointerInteraction>,
+    q_tiles: Query<(), With<super::systems::Tile>>,
+    mut strokes: EventWriter<TerrainBrushStroke>,
+    mut paint_strokes: EventWriter<TerrainPaintStroke>,
+) {
+    if !(brush.enabled || paint.enabled) || !mouse.pressed(MouseButton::Left) { return; }
+    for interaction in &pointers {
+        let hit = interaction
+            .iter()
+            .find(|(entity, hit)| q_tiles.contains(*entity) && hit.position.is_some());
+        let Some(center) = hit.and_then(|(_, hit)| hit.position) else { continue };
+        if brush.enabled { strokes.write(TerrainBrushStroke { center }); }
+        if paint.enabled { paint_strokes.write(TerrainPaintStroke { center }); }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::meshgen::{crop_apron, generate_padded_height_field, normalmap_from_height};
+
+    /// Normals of tile `coord`, built the way the tile task builds them.
+    fn tile_normals(cfg: &TerrainConfig, edits: &TerrainEdits, coord: IVec2) -> Vec<u8> {
+        let (n, size) = (cfg.tile_resolution, cfg.tile_size);
+        let mut padded = generate_padded_height_field(n, size, coord.as_dvec2() * size as f64, &cfg.height_source());
+        if let Some(tile) = edits.current_tile(coord) {
+            tile.apply_padded_heights(n, &mut padded);
+        }
+        for (i, d) in edits.apron_deltas(coord, n) {
+            padded[i] += d;
+        }
+        crop_apron(n, &normalmap_from_height(n + 2, size / (n as f32 - 1.0), &padded), 4)
+    }
+
+    #[test]
+    fn border_normals_match_across_an_edited_seam() {
+        let cfg = TerrainConfig { tile_size: 16.0, tile_resolution: 17, ..default() };
+        let n = cfg.tile_resolution as u32;
+        let (left, right) = (IVec2::ZERO, IVec2::X);
+        let mut edits = TerrainEdits::default();
+        // a lopsided ridge on the seam; the shared edge texels are in both tiles
+        for z in 0..n {
+            edits.add_height(left, z * n + n - 2, 3.0);
+            edits.add_height(left, z * n + n - 1, 1.0);
+            edits.add_height(right, z * n, 1.0);
+            edits.add_height(right, z * n + 1, 0.5);
+        }
+        let (a, b) = (tile_normals(&cfg, &edits, left), tile_normals(&cfg, &edits, right));
+        for z in 0..n as usize {
+            let (i, j) = ((z * n as usize + n as usize - 1) * 4, z * n as usize * 4);
+            let (a, b) = (&a[i..i + 3], &b[j..j + 3]);
+            assert!(a.iter().zip(b).all(|(a, b)| a.abs_diff(*b) <= 1), "row {z}: {a:?} vs {b:?}");
+        }
+    }
+}
@@ -3,6 +3,27 @@ use bevy::render::mesh::{Indices, PrimitiveTopology};
 use super::systems::TerrainConfig;
 
 pub fn flat_grid_mesh(n: usize, size: f32) -> Mesh {
+    grid_mesh(n, size, |_, _| (0.0, Vec3::Y, Vec3::X))
+}
+
+/// A tile mesh with its heights baked in, for `TerrainRenderMode::CpuMesh`.
+/// `padded` is the unscaled `(n+2)²` apron grid, so border normals see
+/// the neighbours like the normal map does. Tangents follow the slope
+/// along +X, so the TBN stays orthonormal for normal-mapped materials.
+pub fn displaced_grid_mesh(n: usize, size: f32, padded: &[f32], height_scale: f32) -> Mesh {
+    let step = size / (n as f32 - 1.0);
+    let h = |x: usize, z: usize| padded[z * (n + 2) + x] * height_scale;
+    grid_mesh(n, size, |x, z| {
+        let (x, z) = (x + 1, z + 1);
+        let dx = (h(x + 1, z) - h(x - 1, z)) / (2.0 * step);
+        let dz = (h(x, z + 1) - h(x, z - 1)) / (2.0 * step);
+        (h(x, z), Vec3::new(-dx, 1.0, -dz).normalize(), Vec3::new(1.0, dx, 0.0).normalize())
+    })
+}
+
+/// `n×n` grid over `size`, with height, normal and tangent per sample from
+/// `sample(x, z)`.
+fn grid_mesh(n: usize, size: f32, sample: impl Fn(usize, usize) -> (f32, Vec3, Vec3)) -> Mesh {
     let step = size / (n as f32 - 1.0);
     let mut positions = Vec::with_capacity(n*n);
     let mut uvs       = Vec::with_capacity(n*n);
@@ -11,11 +32,12 @@ pub fn flat_grid_mesh(n: usize, size: f32) -> Mesh {
 
     for z in 0..n {
         for x in 0..n {
-            positions.push([x as f32 * step, 0.0, z as f32 * step]);
+            let (height, normal, tangent) = sample(x, z);
+            positions.push([x as f32 * step, height, z as f32 * step]);
             uvs.push([x as f32 / (n as f32 - 1.0), z as f32 / (n as f32 - 1.0)]);
-            // Up normals, +X tangent with handedness +1 (needed by PBR vertex layout)
-            normals.push([0.0, 1.0, 0.0]);
-            tangents.push([1.0, 0.0, 0.0, 1.0]);
+            // handedness +1 (needed by PBR vertex layout)
+            normals.push(normal.to_array());
+            tangents.push(tangent.extend(1.0).to_array());
         }
     }
 
@@ -57,3 +79,81 @@ pub fn init_shared_mesh(
     let h = meshes.add(m);
     commands.insert_resource(SharedMeshes { flat: h });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::mesh::VertexAttributeValues;
+
+    fn positions(mesh: &Mesh) -> &[[f32; 3]] {
+        match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+            Some(VertexAttributeValues::Float32x3(p)) => p,
+            _ => panic!("no positions"),
+        }
+    }
+
+    fn indices(mesh: &Mesh) -> &[u32] {
+        match mesh.indices() {
+            Some(Indices::U32(i)) => i,
+            _ => panic!("no u32 indices"),
+        }
+    }
+
+    #[test]
+    fn flat_grid_indices_cover_the_tile() {
+        for n in [2, 3, 17, 65] {
+            let mesh = flat_grid_mesh(n, 32.0);
+            let (p, i) = (positions(&mesh), indices(&mesh));
+            assert_eq!(p.len(), n * n);
+            assert_eq!(i.len(), (n - 1) * (n - 1) * 6);
+            assert!(i.iter().all(|&i| (i as usize) < n * n));
+            // every vertex is used, and the grid spans the tile exactly
+            let mut used = vec![false; n * n];
+            for &i in i { used[i as usize] = true; }
+            assert!(used.iter().all(|u| *u), "n = {n}");
+            assert_eq!(p[0], [0.0, 0.0, 0.0]);
+            assert_eq!(p[n * n - 1], [32.0, 0.0, 32.0]);
+            // counter-clockwise seen from above
+            for tri in i.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|k| Vec3::from_array(p[tri[k] as usize]));
+                assert!((b - a).cross(c - a).y > 0.0, "n = {n}: {tri:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn grid_meshes_are_deterministic() {
+        let n = 17;
+        let padded: Vec<f32> = (0..(n + 2) * (n + 2)).map(|i| ((i * 7919) % 13) as f32 * 0.1).collect();
+        let (a, b) = (displaced_grid_mesh(n, 16.0, &padded, 3.0), displaced_grid_mesh(n, 16.0, &padded, 3.0));
+        assert_eq!(positions(&a), positions(&b));
+        assert_eq!(indices(&a), indices(&b));
+        assert_eq!(indices(&a), indices(&flat_grid_mesh(n, 16.0)));
+        // heights come from the interior of the apron grid
+        for z in 0..n {
+            for x in 0..n {
+                assert_eq!(positions(&a)[z * n + x][1], padded[(z + 1) * (n + 2) + x + 1] * 3.0);
+            }
+        }
+    }
+
+    #[test]
+    fn displaced_mesh_has_matching_attributes_and_tangents() {
+        let n = 17;
+        let padded: Vec<f32> = (0..(n + 2) * (n + 2)).map(|i| ((i % (n + 2)) as f32 * 0.7).sin() * 2.0 + (i / (n + 2)) as f32 * 0.3).collect();
+        let mut mesh = displaced_grid_mesh(n, 16.0, &padded, 3.0);
+        for (attribute, values) in mesh.attributes() {
+            assert_eq!(values.len(), n * n, "{}", attribute.name);
+        }
+        let (Some(VertexAttributeValues::Float32x3(normals)), Some(VertexAttributeValues::Float32x4(tangents))) =
+            (mesh.attribute(Mesh::ATTRIBUTE_NORMAL), mesh.attribute(Mesh::ATTRIBUTE_TANGENT))
+        else {
+            panic!("no normals or tangents");
+        };
+        for (normal, tangent) in normals.iter().zip(tangents) {
+            let (normal, tangent) = (Vec3::from_array(*normal), Vec4::from_array(*tangent));
+            assert!(normal.dot(tangent.xyz()).abs() < 1e-5 && tangent.xyz().is_normalized() && tangent.w == 1.0);
+        }
+        mesh.generate_tangents().unwrap();
+    }
+}
@@ -0,0 +1,516 @@
+//! Compute-shader tile generation.
+//!
+//! With `TerrainConfig::gpu_generation`, the streamer hands tiles that
+//! nothing on the CPU needs (farther than `gpu_cpu_radius_tiles` from every
+//! loader, not pinned, requested or edited) to the GPU instead of the task
+//! pool. Such a tile is spawned as `AwaitingGpuGeneration` with storage
+//! textures and its material; the render world runs `shaders/terrain_gen.wgsl`
+//! (heights with a one-sample apron, then height, normal and curvature
+//! textures) and reports back, and the tile turns into a regular `Tile`.
+//!
+//! Both paths sample `HashedFbm`, so a tile looks the same whichever built
+//! it. GPU-built tiles start without CPU heights: they are missing from
+//! `TerrainHeightfield` (queries return `None`, scatter and colliders skip
+//! them), `LoadedTile::cpu_heights` is false and their height range is the
+//! conservative `TerrainConfig::height_bounds`. Brush edits on them are
+//! recorded but only show once the tile is rebuilt.
+//!
+//! Their height textures are then copied back, nearest to a loader first,
+//! within the `HeightReadbackSettings` budget. A landed readback fills in
+//! the heightfield (with curvature and flow from the tile alone) and the
+//! real height range, and fires `TileHeightsReady`.
+
+use bevy::math::DVec2;
+use bevy::pbr::MeshMaterial3d;
+use bevy::prelude::*;
+use bevy::render::gpu_readback::{Readback, ReadbackComplete};
+use bevy::render::render_asset::{RenderAssetUsages, RenderAssets};
+use bevy::render::render_resource::binding_types::{storage_buffer_sized, texture_storage_2d, uniform_buffer};
+use bevy::render::render_resource::{
+    BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, BufferDescriptor, BufferUsages,
+    CachedComputePipelineId, CommandEncoderDescriptor, ComputePassDescriptor, ComputePipelineDescriptor,
+    Extent3d, PipelineCache, ShaderStages, ShaderType, StorageTextureAccess, TextureDimension, TextureFormat,
+    TextureUsages, UniformBuffer,
+};
+use bevy::render::renderer::{RenderDevice, RenderQueue};
+use bevy::render::texture::GpuImage;
+use bevy::render::{ExtractSchedule, MainWorld, Render, RenderApp, RenderSet};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::biome::{BiomeSampler, BiomeSettings};
+use super::climate::AppliedClimate;
+use super::diagnostics::TerrainDiagnostics;
+use super::flatmesh::SharedMeshes;
+use super::heightfield::{HeightTile, TerrainHeightfield, TileFlow};
+use super::material::TerrainMaterial;
+use super::meshgen::{curvature_from_height, d8_flow_directions, flow_accumulation};
+use super::metadata::TileMetadata;
+use super::origin::WorldOffset;
+use super::shading::TerrainShadingSettings;
+use super::systems::{
+    tile_components, tile_material, world_to_coord, HeightEncoding, LoadedTile, TerrainConfig, TerrainState,
+    Tile, TileLoader, TileSpawned, TileTextures,
+};
+use super::water::WaterSettings;
+
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Added by `TerrainPlugin` when rendering.
+pub struct TerrainGpuGenerationPlugin;
+impl Plugin for TerrainGpuGenerationPlugin {
+    fn build(&self, app: &mut App) {
+        let generation = GpuGeneration::default();
+        let finished = generation.finished.clone();
+        app
+            .insert_resource(generation)
+            .init_resource::<HeightReadbackSettings>()
+            .init_resource::<HeightReadbacks>()
+            .add_event::<TileHeightsReady>();
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else { return };
+        render_app
+            .insert_resource(GpuFinished(finished))
+            .init_resource::<GpuJobs>()
+            .add_systems(ExtractSchedule, extract_gpu_jobs_system)
+            .add_systems(Render, dispatch_gpu_jobs_system.in_set(RenderSet::PrepareBindGroups));
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else { return };
+        render_app.init_resource::<TerrainGenPipeline>();
+    }
+}
+
+/// A tile waiting for the compute shader; replaced by `Tile` when done.
+#[derive(Component)]
+pub struct AwaitingGpuGeneration {
+    pub coord: IVec2,
+    /// True (unshifted) world position of the tile's min corner.
+    pub origin: DVec2,
+    /// `Time::elapsed_secs` when queued, for the build-time diagnostics.
+    pub requested_at: f32,
+}
+
+/// Jobs on their way to the render world, and tiles coming back from it.
+/// Present when rendering; its absence makes the streamer build on the CPU.
+#[derive(Resource, Default)]
+pub struct GpuGeneration {
+    outgoing: Vec<GpuJob>,
+    finished: Arc<Mutex<Vec<Entity>>>,
+}
+
+/// Mirrors `GenParams` in `terrain_gen.wgsl`.
+#[derive(Clone, Copy, ShaderType, Default)]
+struct GenParams {
+    /// World XZ of the apron's first sample.
+    origin: Vec2,
+    step: f32,
+    /// Tile resolution; the apron grid is `n + 2` wide.
+    n: u32,
+    seed: u32,
+    octaves: u32,
+    lacunarity: f32,
+    persistence: f32,
+    frequency: f32,
+    amplitude: f32,
+    falloff_enabled: u32,
+    falloff_radius: f32,
+    falloff_center: Vec2,
+    falloff_width: f32,
+    falloff_edge: f32,
+}
+
+/// Budget for copying GPU-built heights back to the CPU. Each readback
+/// is a `tile_resolution²` R32F copy through a staging buffer.
+#[derive(Resource, Clone)]
+pub struct HeightReadbackSettings {
+    /// Off leaves GPU-built tiles without CPU heights.
+    pub enabled: bool,
+    /// Readbacks started per frame.
+    pub per_frame: usize,
+    /// Readbacks waiting on the GPU at once.
+    pub max_in_flight: usize,
+}
+impl Default for HeightReadbackSettings {
+    fn default() -> Self {
+        Self { enabled: true, per_frame: 2, max_in_flight: 8 }
+    }
+}
+
+/// A GPU-built tile's heights arrived on the CPU; anything derived from
+/// heights (colliders, scatter) can now be built for it.
+/// Its `TileMetadata` is set by then.
+#[derive(Event, Clone, Copy)]
+pub struct TileHeightsReady {
+    pub coord: IVec2,
+    pub entity: Entity,
+}
+
+/// Readbacks on the way (coord -> readback entity, tile entity) and the
+/// raw texture bytes of finished ones.
+#[derive(Resource, Default)]
+pub struct HeightReadbacks {
+    in_flight: HashMap<IVec2, (Entity, Entity)>,
+    completed: Vec<(IVec2, Entity, Vec<u8>)>,
+}
+
+#[derive(Clone)]
+struct GpuJob {
+    entity: Entity,
+    params: GenParams,
+    // strong handles keep the textures alive until the dispatch
+    height: Handle<Image>,
+    normal: Handle<Image>,
+    curvature: Handle<Image>,
+}
+
+/// A render-world-only texture the compute shader writes and the material samples.
+fn storage_image(n: u32, format: TextureFormat) -> Image {
+    let size = Extent3d { width: n, height: n, depth_or_array_layers: 1 };
+    let mut image = Image::new_uninit(size, TextureDimension::D2, format, RenderAssetUsages::RENDER_WORLD);
+    // COPY_SRC for the height readback
+    image.texture_descriptor.usage |= TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC;
+    image
+}
+
+/// Give newly queued GPU tiles their textures and material, and send them
+/// to the render world.
+pub fn dispatch_gpu_tiles_system(
+    mut commands: Commands,
+    mut generation: ResMut<GpuGeneration>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+    cfg: Res<TerrainConfig>,
+    shading: Res<TerrainShadingSettings>,
+    water: Res<WaterSettings>,
+    climate: Res<AppliedClimate>,
+    q_new: Query<(Entity, &AwaitingGpuGeneration), Added<AwaitingGpuGeneration>>,
+) {
+    let n = cfg.tile_resolution as u32;
+    let step = cfg.tile_size / (n as f32 - 1.0);
+    let fbm = cfg.hashed_fbm();
+    let falloff = cfg.world_extent;
+    for (e, awaiting) in &q_new {
+        let textures = TileTextures {
+            height: images.add(storage_image(n, TextureFormat::R32Float)),
+            normal: images.add(storage_image(n, TextureFormat::Rgba8Unorm)),
+            curvature: images.add(storage_image(n, TextureFormat::R32Float)),
+            // no edits by construction
+            splat: images.add(Image::new_fill(
+                Extent3d { width: n, height: n, depth_or_array_layers: 1 },
+                TextureDimension::D2,
+                &[0; 4],
+                TextureFormat::Rgba8Unorm,
+                RenderAssetUsages::default(),
+            )),
+            // the compute shader writes R32Float
+            height_encoding: HeightEncoding::R32,
+            // storage textures get no mip chain
+            normal_mips: false,
+            holes: None,
+        };
+        generation.outgoing.push(GpuJob {
+            entity: e,
+            params: GenParams {
+                origin: (awaiting.origin - DVec2::splat(step as f64)).as_vec2(),
+                step,
+                n,
+                seed: fbm.seed,
+                octaves: fbm.octaves.max(1),
+                lacunarity: fbm.lacunarity,
+                persistence: fbm.persistence,
+                frequency: fbm.frequency,
+                amplitude: cfg.noise_amplitude,
+                falloff_enabled: falloff.is_some() as u32,
+                falloff_radius: falloff.map_or(0.0, |f| f.radius),
+                falloff_center: falloff.map_or(Vec2::ZERO, |f| f.center),
+                falloff_width: falloff.map_or(1.0, |f| f.falloff_width.max(1e-3)),
+                falloff_edge: falloff.map_or(0.0, |f| f.edge_height),
+            },
+            height: textures.height.clone(),
+            normal: textures.normal.clone(),
+            curvature: textures.curvature.clone(),
+        });
+        let material = tile_material(awaiting.coord, textures, &cfg, &shading, &water, &climate);
+        commands.entity(e).insert(MeshMaterial3d(materials.add(material)));
+    }
+}
+
+/// Turn tiles the render world finished into regular tiles.
+pub fn finish_gpu_tiles_system(
+    time: Res<Time>,
+    mut commands: Commands,
+    generation: Res<GpuGeneration>,
+    mut state: ResMut<TerrainState>,
+    cfg: Res<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    shared: Option<Res<SharedMeshes>>,
+    mut diagnostics: ResMut<TerrainDiagnostics>,
+    mut spawned: EventWriter<TileSpawned>,
+    q_awaiting: Query<(&AwaitingGpuGeneration, &MeshMaterial3d<TerrainMaterial>)>,
+) {
+    let finished = std::mem::take(&mut *generation.finished.lock().unwrap());
+    let now = time.elapsed_secs();
+    let (min_height, max_height) = cfg.height_bounds();
+    for e in finished {
+        // cancelled or regenerated while on the GPU
+        let Ok((awaiting, material)) = q_awaiting.get(e) else { continue };
+        let coord = awaiting.coord;
+        if state.pending.get(&coord) != Some(&e) { continue; }
+        let build_seconds = now - awaiting.requested_at;
+        state.pending.remove(&coord);
+        state.tiles.insert(coord, LoadedTile {
+            entity: e,
+            material: material.0.clone(),
+            min_height,
+            max_height,
+            build_seconds,
+            cpu_heights: false,
+            texture_bytes_saved: 0,
+            metadata: None,
+        });
+        state.last_touched.insert(coord, now);
+        diagnostics.record_build(coord, build_seconds);
+
+        let mut tile = commands.entity(e);
+        tile.remove::<AwaitingGpuGeneration>().insert(tile_components(coord, offset.to_local(awaiting.origin), None));
+        if let Some(shared) = shared.as_deref() {
+            tile.insert(Mesh3d(shared.flat.clone()));
+        }
+        spawned.write(TileSpawned { coord, entity: e, metadata: None });
+    }
+}
+
+/// Start height readbacks for GPU-built tiles, nearest to a loader first.
+pub fn queue_height_readbacks_system(
+    mut commands: Commands,
+    settings: Res<HeightReadbackSettings>,
+    mut readbacks: ResMut<HeightReadbacks>,
+    state: Res<TerrainState>,
+    cfg: Res<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    materials: Res<Assets<TerrainMaterial>>,
+    q_loaders: Query<&Transform, With<TileLoader>>,
+) {
+    if !settings.enabled { return; }
+    let budget = settings.per_frame.min(settings.max_in_flight.saturating_sub(readbacks.in_flight.len()));
+    if budget == 0 { return; }
+
+    let loaders: Vec<IVec2> =
+        q_loaders.iter().map(|t| world_to_coord(t.translation, cfg.tile_size, &offset)).collect();
+    let mut waiting: Vec<(i32, IVec2, &LoadedTile)> = state
+        .tiles
+        .iter()
+        .filter(|(c, t)| !t.cpu_heights && !readbacks.in_flight.contains_key(*c))
+        .map(|(c, t)| {
+            let distance = loaders.iter().map(|l| (*l - *c).abs().max_element()).min().unwrap_or(0);
+            (distance, *c, t)
+        })
+        .collect();
+    waiting.sort_by_key(|(distance, c, _)| (*distance, c.x, c.y));
+
+    for (_, coord, loaded) in waiting.into_iter().take(budget) {
+        let Some(material) = materials.get(&loaded.material) else { continue };
+        let tile = loaded.entity;
+        let readback = commands
+            .spawn(Readback::texture(material.height_tex.clone()))
+            .observe(
+                move |trigger: Trigger<ReadbackComplete>, mut commands: Commands, mut readbacks: ResMut<HeightReadbacks>| {
+                    readbacks.completed.push((coord, tile, trigger.event().0.clone()));
+                    // `Readback` repeats every frame while it exists
+                    commands.entity(trigger.target()).despawn();
+                },
+            )
+            .id();
+        readbacks.in_flight.insert(coord, (readback, tile));
+    }
+}
+
+/// Move landed readbacks into `TerrainHeightfield`.
+pub fn apply_height_readbacks_system(
+    mut commands: Commands,
+    mut readbacks: ResMut<HeightReadbacks>,
+    mut state: ResMut<TerrainState>,
+    cfg: Res<TerrainConfig>,
+    shading: Res<TerrainShadingSettings>,
+    (biomes, water): (Res<BiomeSettings>, Res<WaterSettings>),
+    offset: Res<WorldOffset>,
+    mut heightfield: ResMut<TerrainHeightfield>,
+    mut ready: EventWriter<TileHeightsReady>,
+) {
+    let HeightReadbacks { in_flight, completed } = &mut *readbacks;
+    // tiles that unloaded or rebuilt while their copy was on the way
+    in_flight.retain(|coord, (readback, tile)| {
+        let alive = state.tiles.get(coord).is_some_and(|t| t.entity == *tile);
+        if !alive {
+            commands.entity(*readback).try_despawn();
+        }
+        alive
+    });
+
+    let n = cfg.tile_resolution;
+    let step = cfg.tile_size / (n as f32 - 1.0);
+    // texture rows are padded to the copy alignment
+    let row_bytes = RenderDevice::align_copy_bytes_per_row(n * 4);
+    for (coord, tile, bytes) in completed.drain(..) {
+        in_flight.remove(&coord);
+        let Some(loaded) = state.tiles.get_mut(&coord).filter(|t| t.entity == tile && !t.cpu_heights) else {
+            continue;
+        };
+        let heights: Vec<f32> = bytes
+            .chunks_exact(row_bytes)
+            .take(n)
+            .flat_map(|row| row[..n * 4].chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])))
+            .collect();
+        if heights.len() != n * n { continue; }
+
+        // no apron here, so borders are one-sided like an edge tile's
+        let curvature = curvature_from_height(n, step, &heights);
+        let flow = cfg.compute_flow.then(|| {
+            let directions = d8_flow_directions(n, &heights);
+            let accumulation = flow_accumulation(n, &heights, &directions);
+            TileFlow { directions: directions.into(), accumulation: accumulation.into() }
+        });
+        let min_height = heights.iter().copied().fold(f32::INFINITY, f32::min);
+        let max_height = heights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        (loaded.min_height, loaded.max_height, loaded.cpu_heights) = (min_height, max_height, true);
+        let sampler = BiomeSampler::new(&biomes, cfg.seed);
+        let metadata = TileMetadata::compute(coord, &heights, n, cfg.tile_size, shading.height_scale, water.sea_level, &sampler, cfg.seed);
+        let metadata = Arc::new(metadata);
+        loaded.metadata = Some(metadata.clone());
+        commands.entity(tile).insert(Tile { coord, metadata: Some(metadata) });
+
+        heightfield.tile_size = cfg.tile_size;
+        heightfield.resolution = n;
+        heightfield.height_scale = shading.height_scale;
+        heightfield.world_offset = offset.0;
+        heightfield.insert(coord, HeightTile {
+            heights: heights.into(),
+            curvature: curvature.into(),
+            flow,
+            // tiles with edits are built on the CPU
+            holes: None,
+            min_height,
+            max_height,
+        });
+        ready.write(TileHeightsReady { coord, entity: tile });
+    }
+}
+
+// ---- render world ----
+
+#[derive(Resource, Default, Deref, DerefMut)]
+struct GpuJobs(Vec<GpuJob>);
+
+#[derive(Resource)]
+struct GpuFinished(Arc<Mutex<Vec<Entity>>>);
+
+#[derive(Resource)]
+struct TerrainGenPipeline {
+    layout: BindGroupLayout,
+    heights: CachedComputePipelineId,
+    derive: CachedComputePipelineId,
+}
+
+impl FromWorld for TerrainGenPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let layout = world.resource::<RenderDevice>().create_bind_group_layout(
+            "terrain_gen_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    uniform_buffer::<GenParams>(false),
+                    storage_buffer_sized(false, None),
+                    texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::WriteOnly),
+                    texture_storage_2d(TextureFormat::Rgba8Unorm, StorageTextureAccess::WriteOnly),
+                    texture_storage_2d(TextureFormat::R32Float, StorageTextureAccess::WriteOnly),
+                ),
+            ),
+        );
+        let shader = world.load_asset("shaders/terrain_gen.wgsl");
+        let cache = world.resource::<PipelineCache>();
+        let queue = |entry_point: &'static str| {
+            cache.queue_compute_pipeline(ComputePipelineDescriptor {
+                label: Some(format!("terrain_gen_{entry_point}").into()),
+                layout: vec![layout.clone()],
+                push_constant_ranges: Vec::new(),
+                shader: shader.clone(),
+                shader_defs: Vec::new(),
+                entry_point: entry_point.into(),
+                zero_initialize_workgroup_memory: false,
+            })
+        };
+        let heights = queue("heights");
+        let derive = queue("derive");
+        Self { layout, heights, derive }
+    }
+}
+
+fn extract_gpu_jobs_system(mut main_world: ResMut<MainWorld>, mut jobs: ResMut<GpuJobs>) {
+    if let Some(mut generation) = main_world.get_resource_mut::<GpuGeneration>() {
+        jobs.append(&mut generation.outgoing);
+    }
+}
+
+/// Encode every job whose textures are on the GPU; the rest wait for a
+/// later frame (as do all while the pipelines compile).
+fn dispatch_gpu_jobs_system(
+    mut jobs: ResMut<GpuJobs>,
+    pipeline: Res<TerrainGenPipeline>,
+    cache: Res<PipelineCache>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+    finished: Res<GpuFinished>,
+) {
+    if jobs.is_empty() { return; }
+    let (Some(heights), Some(derive)) =
+        (cache.get_compute_pipeline(pipeline.heights), cache.get_compute_pipeline(pipeline.derive))
+    else {
+        return;
+    };
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: Some("terrain_gen") });
+    let mut done = Vec::new();
+    jobs.retain(|job| {
+        let (Some(height), Some(normal), Some(curvature)) =
+            (gpu_images.get(&job.height), gpu_images.get(&job.normal), gpu_images.get(&job.curvature))
+        else {
+            return true;
+        };
+        let padded_n = job.params.n + 2;
+        let mut params = UniformBuffer::from(job.params);
+        params.write_buffer(&device, &queue);
+        let padded = device.create_buffer(&BufferDescriptor {
+            label: Some("terrain_gen_padded"),
+            size: (padded_n * padded_n) as u64 * 4,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(
+            "terrain_gen",
+            &pipeline.layout,
+            &BindGroupEntries::sequential((
+                &params,
+                padded.as_entire_binding(),
+                &height.texture_view,
+                &normal.texture_view,
+                &curvature.texture_view,
+            )),
+        );
+        // dispatches in one pass are ordered, so `derive` sees all heights
+        let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor { label: Some("terrain_gen"), timestamp_writes: None });
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.set_pipeline(heights);
+        pass.dispatch_workgroups(padded_n.div_ceil(WORKGROUP_SIZE), padded_n.div_ceil(WORKGROUP_SIZE), 1);
+        pass.set_pipeline(derive);
+        let n = job.params.n;
+        pass.dispatch_workgroups(n.div_ceil(WORKGROUP_SIZE), n.div_ceil(WORKGROUP_SIZE), 1);
+        done.push(job.entity);
+        false
+    });
+    if done.is_empty() { return; }
+    // submitted ahead of the frame's render graph, which samples the results
+    queue.submit([encoder.finish()]);
+    finished.0.lock().unwrap().extend(done);
+}
@@ -0,0 +1,529 @@
+use bevy::math::DVec2;
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::meshgen::D8_OFFSETS;
+
+/// CPU copy of one loaded tile's height samples (row-major, `resolution²`).
+#[derive(Clone)]
+pub struct HeightTile {
+    pub heights: Arc<[f32]>,
+    /// Unscaled Laplacian of `heights`, see `curvature_from_height`.
+    pub curvature: Arc<[f32]>,
+    /// Present when built with `TerrainConfig::compute_flow`.
+    pub flow: Option<TileFlow>,
+    /// Hole mask (0 = hole, 255 = solid) like the tile's `hole_tex`; `None`
+    /// when the tile has no holes.
+    pub holes: Option<Arc<[u8]>>,
+    pub min_height: f32,
+    pub max_height: f32,
+}
+
+/// D8 drainage of one tile, computed from the generated heights (brush
+/// edits don't update it). Row-major `resolution²` like the heights.
+#[derive(Clone)]
+pub struct TileFlow {
+    /// Index into `D8_OFFSETS`, or `FLOW_SINK`.
+    pub directions: Arc<[u8]>,
+    /// Samples draining through each sample, see `flow_accumulation`.
+    pub accumulation: Arc<[f32]>,
+}
+
+impl HeightTile {
+    /// Unscaled bilinear height at `local` (in sample units, `0..resolution-1`).
+    pub fn sample(&self, resolution: usize, local: Vec2) -> f32 {
+        sample_bilinear(&self.heights, resolution, local)
+    }
+
+    /// Whether `local` (in sample units) falls in a hole: the bilinear mask
+    /// below one half, the same outline `terrain.wgsl` discards.
+    pub fn is_hole(&self, resolution: usize, local: Vec2) -> bool {
+        let Some(holes) = &self.holes else { return false };
+        let max = (resolution - 1) as f32;
+        let (fx, fz) = (local.x.clamp(0.0, max), local.y.clamp(0.0, max));
+        let (x0, z0) = ((fx.floor() as usize).min(resolution - 2), (fz.floor() as usize).min(resolution - 2));
+        let (tx, tz) = (fx - x0 as f32, fz - z0 as f32);
+        let m = |x: usize, z: usize| holes[z * resolution + x] as f32 / 255.0;
+        let top = m(x0, z0) + (m(x0 + 1, z0) - m(x0, z0)) * tx;
+        let bot = m(x0, z0 + 1) + (m(x0 + 1, z0 + 1) - m(x0, z0 + 1)) * tx;
+        top + (bot - top) * tz < 0.5
+    }
+}
+
+/// Bilinear lookup into a row-major `n×n` grid; `local` is in sample units.
+pub fn sample_bilinear(heights: &[f32], n: usize, local: Vec2) -> f32 {
+    let max = (n - 1) as f32;
+    let (fx, fz) = (local.x.clamp(0.0, max), local.y.clamp(0.0, max));
+    let (x0, z0) = ((fx.floor() as usize).min(n - 2), (fz.floor() as usize).min(n - 2));
+    let (tx, tz) = (fx - x0 as f32, fz - z0 as f32);
+    let h = |x: usize, z: usize| heights[z * n + x];
+    let top = h(x0, z0) + (h(x0 + 1, z0) - h(x0, z0)) * tx;
+    let bot = h(x0, z0 + 1) + (h(x0 + 1, z0 + 1) - h(x0, z0 + 1)) * tx;
+    top + (bot - top) * tz
+}
+
+/// Central-difference normal of a row-major `n×n` grid with sample spacing
+/// `step`, matching `normalmap_from_height`. `local` is in sample units.
+pub fn grid_normal(heights: &[f32], n: usize, step: f32, height_scale: f32, local: Vec2) -> Vec3 {
+    let h = |dx: f32, dz: f32| sample_bilinear(heights, n, local + Vec2::new(dx, dz)) * height_scale;
+    let dx = (h(1.0, 0.0) - h(-1.0, 0.0)) / (2.0 * step);
+    let dz = (h(0.0, 1.0) - h(0.0, -1.0)) / (2.0 * step);
+    Vec3::new(-dx, 1.0, -dz).normalize()
+}
+
+/// CPU height fields of all loaded tiles, kept in sync with `TerrainState`.
+///
+/// This is the terrain query API: picking, gameplay and physics read heights
+/// here instead of re-running the noise. Stored heights are unscaled; queries
+/// return world-space values (`height_scale` applied). Positions are local
+/// (render) space; `world_offset` mirrors `WorldOffset`.
+#[derive(Resource)]
+pub struct TerrainHeightfield {
+    pub tile_size: f32,
+    pub resolution: usize,
+    pub height_scale: f32,
+    pub world_offset: DVec2,
+    tiles: HashMap<IVec2, HeightTile>,
+}
+impl Default for TerrainHeightfield {
+    fn default() -> Self {
+        Self {
+            tile_size: 32.0,
+            resolution: 129,
+            height_scale: 1.0,
+            world_offset: DVec2::ZERO,
+            tiles: HashMap::new(),
+        }
+    }
+}
+
+/// One point of `TerrainHeightfield::sample_polyline`.
+#[derive(Clone, Copy, Debug)]
+pub struct PolylineSample {
+    pub pos: Vec3,
+    pub normal: Vec3,
+    /// Distance along the polyline from its first point.
+    pub t: f32,
+}
+
+/// Result of `TerrainHeightfield::line_of_sight`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LosResult {
+    Clear,
+    /// First point where the segment enters the terrain.
+    Blocked { at: Vec3 },
+    /// The segment crosses an unloaded tile before anything blocks it.
+    Unknown,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Unloaded {
+    /// Rays pass through unloaded tiles.
+    PassThrough,
+    /// Report `March::Unloaded`; a start below the surface counts as a hit.
+    Stop,
+}
+
+enum March {
+    Hit(f32),
+    Miss,
+    Unloaded,
+}
+
+pub struct TerrainRayHit {
+    pub coord: IVec2,
+    pub distance: f32,
+    pub position: Vec3,
+    pub normal: Vec3,
+}
+
+impl TerrainHeightfield {
+    pub fn insert(&mut self, coord: IVec2, tile: HeightTile) {
+        self.tiles.insert(coord, tile);
+    }
+
+    pub fn remove(&mut self, coord: IVec2) -> Option<HeightTile> {
+        self.tiles.remove(&coord)
+    }
+
+    pub fn tile(&self, coord: IVec2) -> Option<&HeightTile> {
+        self.tiles.get(&coord)
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = (&IVec2, &HeightTile)> {
+        self.tiles.iter()
+    }
+
+    /// World distance between two adjacent height samples.
+    pub fn cell_size(&self) -> f32 {
+        self.tile_size / (self.resolution as f32 - 1.0)
+    }
+
+    pub fn world_to_coord(&self, world_xz: Vec2) -> IVec2 {
+        ((world_xz.as_dvec2() + self.world_offset) / self.tile_size as f64).floor().as_ivec2()
+    }
+
+    /// Local-space position of a tile's min corner.
+    pub fn tile_origin(&self, coord: IVec2) -> Vec2 {
+        (coord.as_dvec2() * self.tile_size as f64 - self.world_offset).as_vec2()
+    }
+
+    /// Bilinearly interpolated world height, `None` over unloaded tiles and
+    /// inside holes.
+    pub fn height_at(&self, world_xz: Vec2) -> Option<f32> {
+        let coord = self.world_to_coord(world_xz);
+        let tile = self.tiles.get(&coord)?;
+        let local = (world_xz - self.tile_origin(coord)) / self.cell_size();
+        if tile.is_hole(self.resolution, local) { return None; }
+        Some(tile.sample(self.resolution, local) * self.height_scale)
+    }
+
+    /// Whether a local-space position is inside a hole of a loaded tile.
+    pub fn is_hole(&self, world_xz: Vec2) -> bool {
+        let coord = self.world_to_coord(world_xz);
+        let Some(tile) = self.tiles.get(&coord) else { return false };
+        tile.is_hole(self.resolution, (world_xz - self.tile_origin(coord)) / self.cell_size())
+    }
+
+    /// Bilinearly interpolated curvature (positive = concave), `None` over unloaded tiles.
+    pub fn curvature_at(&self, world_xz: Vec2) -> Option<f32> {
+        let coord = self.world_to_coord(world_xz);
+        let tile = self.tiles.get(&coord)?;
+        let local = (world_xz - self.tile_origin(coord)) / self.cell_size();
+        Some(sample_bilinear(&tile.curvature, self.resolution, local) * self.height_scale)
+    }
+
+    /// Bilinear flow accumulation (in samples), `None` over unloaded tiles
+    /// or tiles built without `TerrainConfig::compute_flow`.
+    pub fn flow_accumulation_at(&self, world_xz: Vec2) -> Option<f32> {
+        let coord = self.world_to_coord(world_xz);
+        let flow = self.tiles.get(&coord)?.flow.as_ref()?;
+        let local = (world_xz - self.tile_origin(coord)) / self.cell_size();
+        Some(sample_bilinear(&flow.accumulation, self.resolution, local))
+    }
+
+    /// Unit XZ direction water flows from the nearest sample; `Some(ZERO)` in pits.
+    pub fn flow_direction_at(&self, world_xz: Vec2) -> Option<Vec2> {
+        let coord = self.world_to_coord(world_xz);
+        let flow = self.tiles.get(&coord)?.flow.as_ref()?;
+        let max = self.resolution - 1;
+        let local = ((world_xz - self.tile_origin(coord)) / self.cell_size()).round().as_uvec2();
+        let (x, z) = ((local.x as usize).min(max), (local.y as usize).min(max));
+        Some(match D8_OFFSETS.get(flow.directions[z * self.resolution + x] as usize) {
+            Some((dx, dz)) => Vec2::new(*dx as f32, *dz as f32).normalize(),
+            None => Vec2::ZERO,
+        })
+    }
+
+    /// Local-space bounds of a loaded tile (relative to its min corner), for culling.
+    pub fn tile_aabb(&self, coord: IVec2) -> Option<Aabb> {
+        let tile = self.tiles.get(&coord)?;
+        let (a, b) = (tile.min_height * self.height_scale, tile.max_height * self.height_scale);
+        Some(Aabb::from_min_max(
+            Vec3::new(0.0, a.min(b), 0.0),
+            Vec3::new(self.tile_size, a.max(b), self.tile_size),
+        ))
+    }
+
+    /// Bytes held by the CPU height and curvature copies.
+    pub fn memory_bytes(&self) -> usize {
+        self.tiles
+            .values()
+            .map(|t| (t.heights.len() + t.curvature.len()) * size_of::<f32>())
+            .sum()
+    }
+
+    /// World height range covered by all loaded tiles.
+    pub fn height_range(&self) -> Option<(f32, f32)> {
+        let (lo, hi) = self.tiles.values().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), t| {
+            (lo.min(t.min_height), hi.max(t.max_height))
+        });
+        if lo > hi { return None; }
+        let (a, b) = (lo * self.height_scale, hi * self.height_scale);
+        Some((a.min(b), a.max(b)))
+    }
+
+    /// Raymarch the loaded height fields. Rays pass through unloaded tiles
+    /// and holes.
+    pub fn raycast(&self, ray: Ray3d, max_distance: f32) -> Option<TerrainRayHit> {
+        let (lo, hi) = self.height_range()?;
+        let dir = *ray.direction;
+
+        // Only march the part of the ray inside the loaded height band
+        let (mut t0, mut t1) = (0.0_f32, max_distance);
+        if dir.y.abs() > 1e-6 {
+            let ta = (lo - ray.origin.y) / dir.y;
+            let tb = (hi - ray.origin.y) / dir.y;
+            t0 = t0.max(ta.min(tb));
+            t1 = t1.min(ta.max(tb));
+        } else if ray.origin.y < lo || ray.origin.y > hi {
+            return None;
+        }
+        if t0 > t1 { return None; }
+
+        match self.march(ray, t0, t1, self.cell_size() * 0.5, Unloaded::PassThrough) {
+            March::Hit(distance) => {
+                let position = ray.get_point(distance);
+                Some(TerrainRayHit {
+                    coord: self.world_to_coord(position.xz()),
+                    distance,
+                    position,
+                    normal: self.normal_at(position.xz()).unwrap_or(Vec3::Y),
+                })
+            }
+            March::Miss | March::Unloaded => None,
+        }
+    }
+
+    /// Whether the straight segment `from`–`to` stays above the terrain,
+    /// sampled every height sample; steps shrink near the surface, so a
+    /// grazing segment still finds the ridge it touches.
+    pub fn line_of_sight(&self, from: Vec3, to: Vec3) -> LosResult {
+        self.line_of_sight_with_step(from, to, self.cell_size())
+    }
+
+    /// `line_of_sight` with an explicit sampling step (world units). Larger
+    /// steps are cheaper but can miss ridges thinner than the step.
+    pub fn line_of_sight_with_step(&self, from: Vec3, to: Vec3, step: f32) -> LosResult {
+        let Ok(dir) = Dir3::new(to - from) else {
+            return match self.height_at(from.xz()) {
+                None if self.is_hole(from.xz()) => LosResult::Clear,
+                None => LosResult::Unknown,
+                Some(h) if from.y < h => LosResult::Blocked { at: from },
+                Some(_) => LosResult::Clear,
+            };
+        };
+        let ray = Ray3d::new(from, dir);
+        match self.march(ray, 0.0, from.distance(to), step, Unloaded::Stop) {
+            March::Hit(t) => LosResult::Blocked { at: ray.get_point(t) },
+            March::Miss => LosResult::Clear,
+            March::Unloaded => LosResult::Unknown,
+        }
+    }
+
+    /// Steps along `ray` over `t0..=t1` until it goes below the surface,
+    /// then bisects the crossing. Steps shrink where the ray grazes the
+    /// surface so thin ridges aren't stepped over, and a tile the ray stays
+    /// above (by its `max_height`) is crossed in one jump.
+    fn march(&self, ray: Ray3d, t0: f32, t1: f32, step: f32, unloaded: Unloaded) -> March {
+        let cell = self.cell_size();
+        let gap_in = |p: Vec3, coord: IVec2| {
+            let tile = self.tiles.get(&coord)?;
+            let local = (p.xz() - self.tile_origin(coord)) / cell;
+            // holes are open air, not unloaded ground
+            if tile.is_hole(self.resolution, local) { return Some(f32::INFINITY); }
+            Some(p.y - tile.sample(self.resolution, local) * self.height_scale)
+        };
+        let gap = |t: f32| {
+            let p = ray.get_point(t);
+            gap_in(p, self.world_to_coord(p.xz()))
+        };
+        let step = step.max(1e-3);
+        let fine = step * 0.25;
+
+        // last sample known to be above the surface
+        let mut above: Option<f32> = None;
+        // tile last checked for a jump
+        let mut checked: Option<IVec2> = None;
+        let mut t = t0;
+        loop {
+            let tc = t.min(t1);
+            let p = ray.get_point(tc);
+            let coord = self.world_to_coord(p.xz());
+            if checked != Some(coord) {
+                checked = Some(coord);
+                if let Some(leave) = self.passes_over(ray, coord, tc, t1) {
+                    if leave >= t1 { return March::Miss; }
+                    above = Some(leave);
+                    t = leave;
+                    continue;
+                }
+            }
+            match gap_in(p, coord) {
+                Some(g) if g < 0.0 => match above {
+                    Some(ta) => {
+                        // Refine the crossing between the last sample above and this one
+                        let (mut a, mut b) = (ta, tc);
+                        for _ in 0..10 {
+                            let m = 0.5 * (a + b);
+                            if gap(m).is_none_or(|g| g >= 0.0) { a = m; } else { b = m; }
+                        }
+                        return March::Hit(b);
+                    }
+                    // a segment that starts underground is blocked right away
+                    None if unloaded == Unloaded::Stop => return March::Hit(tc),
+                    None => t = tc + step,
+                },
+                Some(g) => {
+                    above = Some(tc);
+                    t = tc + if g < step { g.max(fine) } else { step };
+                }
+                None if unloaded == Unloaded::Stop => return March::Unloaded,
+                None => {
+                    above = None;
+                    t = tc + step;
+                }
+            }
+            if tc >= t1 { return March::Miss; }
+        }
+    }
+
+    /// Where `ray` leaves tile `coord` (or `t1`, if sooner), provided it stays
+    /// above the tile's highest sample from `t` until then.
+    fn passes_over(&self, ray: Ray3d, coord: IVec2, t: f32, t1: f32) -> Option<f32> {
+        let tile = self.tiles.get(&coord)?;
+        let top = (tile.min_height * self.height_scale).max(tile.max_height * self.height_scale);
+        let min = self.tile_origin(coord);
+        let exit = |origin: f32, dir: f32, lo: f32| {
+            if dir > 0.0 {
+                (lo + self.tile_size - origin) / dir
+            } else if dir < 0.0 {
+                (lo - origin) / dir
+            } else {
+                f32::INFINITY
+            }
+        };
+        let leave = exit(ray.origin.x, ray.direction.x, min.x).min(exit(ray.origin.z, ray.direction.z, min.y)).min(t1);
+        (leave > t && ray.get_point(t).y > top && ray.get_point(leave).y > top).then_some(leave)
+    }
+
+    /// Central-difference normal one sample spacing apart, matching
+    /// `normalmap_from_height`. Samples across a tile border read the
+    /// neighbour; next to an unloaded tile the difference turns one-sided.
+    /// `None` over unloaded tiles.
+    pub fn normal_at(&self, world_xz: Vec2) -> Option<Vec3> {
+        let s = self.cell_size();
+        let c = self.height_at(world_xz)?;
+        let h = |dx: f32, dz: f32| self.height_at(world_xz + Vec2::new(dx, dz));
+        let slope = |lo: Option<f32>, hi: Option<f32>| match (lo, hi) {
+            (Some(lo), Some(hi)) => (hi - lo) / (2.0 * s),
+            (Some(lo), None) => (c - lo) / s,
+            (None, Some(hi)) => (hi - c) / s,
+            (None, None) => 0.0,
+        };
+        let dx = slope(h(-s, 0.0), h(s, 0.0));
+        let dz = slope(h(0.0, -s), h(0.0, s));
+        Some(Vec3::new(-dx, 1.0, -dz).normalize())
+    }
+
+    /// Resamples `points` every `spacing` world units (plus the last point)
+    /// and looks up height and normal at each sample. Samples over unloaded
+    /// tiles are skipped; `t` still tells where along the path each one is.
+    pub fn sample_polyline(&self, points: &[Vec2], spacing: f32) -> Vec<PolylineSample> {
+        let spacing = spacing.max(1e-3);
+        let mut out = Vec::new();
+        let mut push = |xz: Vec2, t: f32| {
+            if let (Some(y), Some(normal)) = (self.height_at(xz), self.normal_at(xz)) {
+                out.push(PolylineSample { pos: Vec3::new(xz.x, y, xz.y), normal, t });
+            }
+        };
+
+        // `next` is the distance of the next sample from the start of the path
+        let (mut start, mut next) = (0.0, 0.0);
+        for seg in points.windows(2) {
+            let len = seg[0].distance(seg[1]);
+            while next <= start + len {
+                push(seg[0].lerp(seg[1], (next - start) / len.max(1e-6)), next);
+                next += spacing;
+            }
+            start += len;
+        }
+        match points {
+            [] => {}
+            [only] => push(*only, 0.0),
+            [.., last] if next - spacing < start => push(*last, start),
+            _ => {}
+        }
+        out
+    }
+
+    /// Angle between the surface and the horizontal, in degrees.
+    pub fn slope_at(&self, world_xz: Vec2) -> Option<f32> {
+        Some(self.normal_at(world_xz)?.y.clamp(-1.0, 1.0).acos().to_degrees())
+    }
+}
+
+#[cfg(test)]
+impl TerrainHeightfield {
+    /// Tiles at `coords` sampled from `height(world_xz)`, for tests.
+    pub(crate) fn from_fn(tile_size: f32, resolution: usize, coords: &[IVec2], height: impl Fn(Vec2) -> f32) -> Self {
+        let mut field = Self { tile_size, resolution, ..default() };
+        let step = field.cell_size();
+        for &coord in coords {
+            let origin = field.tile_origin(coord);
+            let heights: Vec<f32> = (0..resolution * resolution)
+                .map(|i| height(origin + Vec2::new((i % resolution) as f32, (i / resolution) as f32) * step))
+                .collect();
+            let min_height = heights.iter().copied().fold(f32::INFINITY, f32::min);
+            let max_height = heights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            field.insert(coord, HeightTile {
+                heights: heights.into(),
+                curvature: vec![0.0; resolution * resolution].into(),
+                flow: None,
+                holes: None,
+                min_height,
+                max_height,
+            });
+        }
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(p: Vec2) -> f32 {
+        0.5 * p.x + 0.25 * p.y
+    }
+
+    #[test]
+    fn ramp_normals_are_exact_across_borders_and_at_the_edge() {
+        // two tiles side by side, one sample per world unit
+        let field = TerrainHeightfield::from_fn(8.0, 9, &[IVec2::ZERO, IVec2::X], ramp);
+        let expected = Vec3::new(-0.5, 1.0, -0.25).normalize();
+        // interior, on the shared border (central differences read the
+        // neighbour), and next to unloaded tiles (one-sided)
+        for p in [Vec2::new(3.0, 4.0), Vec2::new(8.0, 4.0), Vec2::new(7.5, 2.5), Vec2::new(15.0, 0.0), Vec2::new(0.0, 7.0)] {
+            let normal = field.normal_at(p).unwrap();
+            assert!(normal.abs_diff_eq(expected, 1e-5), "{p}: {normal} != {expected}");
+        }
+        assert_eq!(field.normal_at(Vec2::new(-1.0, 4.0)), None);
+    }
+
+    #[test]
+    fn border_normal_turns_one_sided_when_the_neighbour_unloads() {
+        // a crease on the border: the two tiles slope differently
+        let crease = |p: Vec2| if p.x <= 8.0 { 0.5 * p.x } else { 4.0 + 1.5 * (p.x - 8.0) };
+        let mut field = TerrainHeightfield::from_fn(8.0, 9, &[IVec2::ZERO, IVec2::X], crease);
+        let central = field.normal_at(Vec2::new(7.5, 4.0)).unwrap();
+        assert!(central.abs_diff_eq(Vec3::new(-0.75, 1.0, 0.0).normalize(), 1e-5), "{central}");
+
+        field.remove(IVec2::X);
+        let one_sided = field.normal_at(Vec2::new(7.5, 4.0)).unwrap();
+        assert!(one_sided.abs_diff_eq(Vec3::new(-0.5, 1.0, 0.0).normalize(), 1e-5), "{one_sided}");
+    }
+
+    #[test]
+    fn marching_jumps_low_tiles_but_not_the_ridge_behind_them() {
+        // three tiles in a row, flat but for a ridge at x = 40 in the last one
+        let coords = [IVec2::ZERO, IVec2::X, IVec2::new(2, 0)];
+        let field = TerrainHeightfield::from_fn(16.0, 17, &coords, |p| if (p.x - 40.0).abs() < 1.5 { 20.0 } else { 0.0 });
+        let (from, to) = (Vec3::new(2.0, 10.0, 8.0), Vec3::new(46.0, 10.0, 8.0));
+        match field.line_of_sight(from, to) {
+            LosResult::Blocked { at } => assert!((at.x - 38.5).abs() < 0.05, "{at}"),
+            other => panic!("{other:?}"),
+        }
+        assert_eq!(field.line_of_sight(from + 15.0 * Vec3::Y, to + 15.0 * Vec3::Y), LosResult::Clear);
+        assert_eq!(field.line_of_sight(from + 15.0 * Vec3::Y, to + Vec3::new(50.0, 15.0, 0.0)), LosResult::Unknown);
+
+        // jumps the flat tiles and hits the ridge's slope, 20 * (x - 38)
+        let ray = Ray3d::new(Vec3::new(1.0, 30.0, 8.0), Dir3::new(Vec3::new(39.0, -15.0, 0.0)).unwrap());
+        let hit = field.raycast(ray, 100.0).unwrap();
+        let x = (790.0 + 15.0 / 39.0) / (20.0 + 15.0 / 39.0);
+        assert!((hit.position.x - x).abs() < 0.01, "{} != {x}", hit.position);
+        assert_eq!(hit.coord, IVec2::new(2, 0));
+    }
+}
@@ -0,0 +1,123 @@
+//! Holes in the terrain surface, e.g. for cave entrances.
+//!
+//! Cut texels are stored in `TerrainEdits` (`TileEdits::holes`), so they
+//! survive unload/reload and `save`/`load` like the other edits. A tile with
+//! holes gets an R8 mask (`TerrainMaterial::hole_tex`, 0 = hole) that
+//! `terrain.wgsl` and `terrain_prepass.wgsl` discard by, so the hole casts
+//! no shadow either. The mask is interpolated bilinearly between texels and
+//! cut at one half; `TerrainHeightfield::is_hole` uses the same outline, and
+//! height queries return `None` inside it.
+//!
+//! Texels on a shared tile border exist in both tiles and are cut in both.
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use std::sync::Arc;
+
+use super::edit::{TerrainEdits, TileHeightsEdited};
+use super::heightfield::TerrainHeightfield;
+use super::material::TerrainMaterial;
+use super::systems::TerrainState;
+
+/// Queue of hole edits, applied by `apply_terrain_holes_system`. Owned by
+/// `TerrainPlugin`. Positions are local-space XZ.
+#[derive(Resource, Default)]
+pub struct TerrainHoles {
+    pending: Vec<HoleEdit>,
+}
+
+#[derive(Clone, Copy)]
+struct HoleEdit {
+    center: Vec2,
+    radius: f32,
+    /// Cut when true, fill back in otherwise.
+    cut: bool,
+}
+
+impl TerrainHoles {
+    /// Cut the texels within `radius` of `center`.
+    pub fn add_circle(&mut self, center: Vec2, radius: f32) {
+        self.pending.push(HoleEdit { center, radius, cut: true });
+    }
+
+    /// Close holes within `radius` of `center` again.
+    pub fn fill_circle(&mut self, center: Vec2, radius: f32) {
+        self.pending.push(HoleEdit { center, radius, cut: false });
+    }
+}
+
+/// Write queued hole edits into `TerrainEdits`, then refresh the hole masks
+/// of the touched loaded tiles (CPU copy and texture).
+pub fn apply_terrain_holes_system(
+    mut holes: ResMut<TerrainHoles>,
+    mut edits: ResMut<TerrainEdits>,
+    mut heightfield: ResMut<TerrainHeightfield>,
+    state: Res<TerrainState>,
+    mut materials: Option<ResMut<Assets<TerrainMaterial>>>,
+    mut images: Option<ResMut<Assets<Image>>>,
+    mut edited: Option<ResMut<Events<TileHeightsEdited>>>,
+) {
+    if holes.pending.is_empty() { return; }
+    let (n, step) = (heightfield.resolution, heightfield.cell_size());
+    let mut touched: Vec<IVec2> = Vec::new();
+    for edit in std::mem::take(&mut holes.pending) {
+        let lo = heightfield.world_to_coord(edit.center - edit.radius);
+        let hi = heightfield.world_to_coord(edit.center + edit.radius);
+        for cz in lo.y..=hi.y {
+            for cx in lo.x..=hi.x {
+                let coord = IVec2::new(cx, cz);
+                let local = (edit.center - heightfield.tile_origin(coord)) / step;
+                let reach = edit.radius / step;
+                let min = (local - reach).ceil().max(Vec2::ZERO).as_uvec2();
+                let max = (local + reach).floor().min(Vec2::splat((n - 1) as f32)).as_uvec2();
+                if min.x > max.x || min.y > max.y { continue; }
+                for z in min.y..=max.y {
+                    for x in min.x..=max.x {
+                        if Vec2::new(x as f32, z as f32).distance(local) > reach { continue; }
+                        let i = z * n as u32 + x;
+                        if edits.is_hole(coord, i) != edit.cut {
+                            edits.set_hole(coord, i, edit.cut);
+                        }
+                    }
+                }
+                if !touched.contains(&coord) { touched.push(coord); }
+            }
+        }
+    }
+
+    for coord in touched {
+        let Some(loaded) = state.tiles.get(&coord) else { continue };
+        let mask: Option<Arc<[u8]>> = edits.current_tile(coord).and_then(|e| e.hole_mask(n)).map(Arc::from);
+        let Some(mut tile) = heightfield.tile(coord).cloned() else { continue };
+        tile.holes = mask.clone();
+        heightfield.insert(coord, tile);
+
+        // headless apps only keep the CPU side
+        let (Some(materials), Some(images)) = (materials.as_deref_mut(), images.as_deref_mut()) else { continue };
+        let Some(material) = materials.get_mut(&loaded.material) else { continue };
+        match mask {
+            Some(mask) => {
+                let existing = material.hole_tex.as_ref().and_then(|h| images.get_mut(h));
+                match existing {
+                    Some(img) => img.data = Some(mask.to_vec()),
+                    None => {
+                        let size = Extent3d { width: n as u32, height: n as u32, depth_or_array_layers: 1 };
+                        let image = Image::new(size, TextureDimension::D2, mask.to_vec(), TextureFormat::R8Unorm, RenderAssetUsages::default());
+                        material.hole_tex = Some(images.add(image));
+                    }
+                }
+            }
+            None => material.hole_tex = None,
+        }
+        // colliders and scatter built from the heights should refresh
+        if let Some(events) = edited.as_deref_mut() {
+            events.send(TileHeightsEdited {
+                coord,
+                entity: loaded.entity,
+                min: UVec2::ZERO,
+                max: UVec2::splat(n as u32 - 1),
+            });
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Camera-facing billboard impostors for scattered props.
+//!
+//! Every placement of a layer in a tile becomes one quad of a merged mesh
+//! (one draw per tile). The quad's four vertices all carry the instance
+//! position; the vertex shader expands them around it to face the camera
+//! and dithers instances in across the layer's fade band. The top corners
+//! sway with the `Wind`.
+
+use bevy::asset::Asset;
+use bevy::pbr::{Material, MaterialPlugin};
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef, ShaderType};
+
+use super::scatter::PropPlacement;
+use super::wind::WindParams;
+
+pub struct ImpostorMaterialPlugin;
+impl Plugin for ImpostorMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        // The billboard expansion lives in our vertex shader only, so the
+        // default prepass/shadow pipelines would draw collapsed quads.
+        app.add_plugins(MaterialPlugin::<ImpostorMaterial> {
+            prepass_enabled: false,
+            shadows_enabled: false,
+            ..default()
+        });
+    }
+}
+
+#[derive(Clone, Copy, ShaderType, Default)]
+pub struct ImpostorParams {
+    /// Billboard width/height at scale 1.
+    pub size: Vec2,
+    /// Camera distance band over which instances dither in (equal = hard switch).
+    pub fade_start: f32,
+    pub fade_end: f32,
+    pub cull_distance: f32,
+    pub alpha_cutoff: f32,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+pub struct ImpostorMaterial {
+    #[uniform(0)]
+    pub params: ImpostorParams,
+
+    #[texture(1)]
+    #[sampler(2)]
+    pub texture: Handle<Image>,
+
+    /// Written every frame by `update_impostor_wind_system`.
+    #[uniform(3)]
+    pub wind: WindParams,
+}
+
+impl Material for ImpostorMaterial {
+    fn vertex_shader() -> ShaderRef { "shaders/impostor.wgsl".into() }
+    fn fragment_shader() -> ShaderRef { "shaders/impostor.wgsl".into() }
+}
+
+/// One quad per placement of `kind`, in tile-local space. `UV_1.x` holds the
+/// instance scale.
+pub fn impostor_mesh(placements: &[PropPlacement], kind: usize) -> Option<Mesh> {
+    const CORNERS: [[f32; 2]; 4] = [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]];
+    let (mut positions, mut uvs, mut scales, mut indices) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for p in placements.iter().filter(|p| p.kind == kind) {
+        let base = positions.len() as u32;
+        for uv in CORNERS {
+            positions.push(p.position.to_array());
+            uvs.push(uv);
+            scales.push([p.scale, 0.0]);
+        }
+        indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+    if indices.is_empty() { return None; }
+
+    Some(
+        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_1, scales)
+            .with_inserted_indices(Indices::U32(indices)),
+    )
+}
@@ -1,8 +1,11 @@
 use bevy::asset::Asset;
-use bevy::pbr::{Material, MaterialPlugin};
+use bevy::pbr::{Material, MaterialPipeline, MaterialPipelineKey, MaterialPlugin};
 use bevy::prelude::*;
 use bevy::reflect::TypePath;
-use bevy::render::render_resource::{AsBindGroup, ShaderRef, ShaderType};
+use bevy::render::mesh::MeshVertexBufferLayoutRef;
+use bevy::render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, ShaderRef, ShaderType, SpecializedMeshPipelineError,
+};
 
 pub struct TerrainMaterialPlugin;
 impl Plugin for TerrainMaterialPlugin {
@@ -12,29 +15,260 @@ impl Plugin for TerrainMaterialPlugin {
 }
 
 #[derive(Clone, Copy, ShaderType, Default)]
+#[cfg_attr(feature = "inspector", derive(Reflect))]
 pub struct TileParams {
     pub tile_size: f32,
     pub height_scale: f32,
     pub texels_per_side: u32,
-    pub _pad: u32,
+    pub debug_mode: u32,
     pub tile_color: Vec4,
+    /// A height texel `v` decodes to `height_offset + v * height_range`:
+    /// `0, 1` for `R32Float`, the tile's range for `HeightFormat::R16`.
+    pub height_offset: f32,
+    pub height_range: f32,
+    /// Added to the detail texture coordinates, see `DetailSettings`.
+    pub detail_offset: Vec2,
+    /// Quarter turns of the detail texture about the tile center.
+    pub detail_rotation: u32,
+}
+
+/// Number of splat layers; one per channel of the override texture.
+pub const SPLAT_LAYERS: usize = 4;
+
+/// Procedural splat rules (world units) and per-layer colors:
+/// 0 = grass, 1 = rock (steep), 2 = sand (low), 3 = snow (high).
+#[derive(Clone, Copy, ShaderType, Default)]
+pub struct SplatParams {
+    pub layer_colors: [Vec4; SPLAT_LAYERS],
+    pub sand_height: f32,
+    pub snow_height: f32,
+    /// Slope (degrees) where rock takes over.
+    pub rock_slope: f32,
+    /// Width of the blend zones, in world units / degrees.
+    pub blend: f32,
+    /// Blend width of the snow line, from `ClimateState`.
+    pub snow_blend: f32,
+    /// 0..1, darkens and smooths the ground.
+    pub wetness: f32,
+    pub sea_level: f32,
+    /// rgb multiplied in below `sea_level`, a = strength.
+    pub underwater_tint: Vec4,
+}
+
+/// Overlays drawn over the lit terrain, in world space so they line up
+/// across tile borders.
+#[derive(Clone, Copy, ShaderType, Default)]
+pub struct OverlayParams {
+    /// Contour lines on when non-zero.
+    pub contour_enabled: u32,
+    /// Every Nth contour is drawn at double width.
+    pub contour_major_every: u32,
+    /// Height between contour lines, world units.
+    pub contour_interval: f32,
+    pub contour_width_px: f32,
+    pub contour_color: Vec4,
+}
+
+/// Detail texture over the splat colors, see `DetailSettings`.
+#[derive(Clone, Copy, ShaderType, Default)]
+pub struct DetailParams {
+    pub enabled: u32,
+    /// World units per texture repeat.
+    pub scale: f32,
+    pub strength: f32,
+    pub stochastic: u32,
+    pub stochastic_sharpness: f32,
+    pub stochastic_distance: f32,
+}
+
+/// Parallax occlusion mapping of the detail texture, see `PomSettings`.
+#[derive(Clone, Copy, ShaderType, Default)]
+pub struct PomParams {
+    pub enabled: u32,
+    pub max_steps: u32,
+    pub depth_scale: f32,
+    pub fade_distance: f32,
+    pub self_shadow: u32,
+}
+
+/// Decal slots per tile; matches the `decals` array in `terrain.wgsl`.
+pub const MAX_TILE_DECALS: usize = 4;
+
+/// One of a tile's decals, see `TerrainDecals`.
+#[derive(Clone, Copy, ShaderType, Default)]
+pub struct TileDecal {
+    /// Relative to the tile's min corner, world units.
+    pub center: Vec2,
+    /// Cosine and sine of the decal's rotation.
+    pub rotation: Vec2,
+    pub radius: f32,
+    /// Fade, multiplied into the texture's alpha.
+    pub alpha: f32,
+}
+
+/// The tile's decals in draw order; `decals[i]` uses `decal_tex_i`.
+#[derive(Clone, Copy, ShaderType, Default)]
+pub struct DecalParams {
+    pub count: u32,
+    pub decals: [TileDecal; MAX_TILE_DECALS],
+}
+
+/// World-space grid overlay, see `GridOverlaySettings`.
+#[derive(Clone, Copy, ShaderType, Default)]
+pub struct GridParams {
+    pub enabled: u32,
+    pub major_every: u32,
+    pub spacing: f32,
+    pub width_px: f32,
+    pub color: Vec4,
+    pub major_color: Vec4,
+    /// `WorldOffset` modulo the major spacing, added to local XZ so lines
+    /// stay put across rebases without losing `f32` precision.
+    pub phase: Vec2,
+    pub fade_start: f32,
+    pub fade_end: f32,
 }
 
 #[derive(Asset, TypePath, AsBindGroup, Clone)]
+#[bind_group_data(TerrainMaterialKey)]
 pub struct TerrainMaterial {
     #[uniform(0)]
     pub params: TileParams,
 
-    // Heightmap (R32Float). No sampler; we use textureLoad().
-    #[texture(1, sample_type = "float")]
+    // Heightmap (R32Float, or R16Unorm per `TerrainConfig::height_format`). No
+    // sampler; we use textureLoad(), which also works on WebGL2 where R32Float
+    // isn't filterable.
+    #[texture(1, sample_type = "float", filterable = false)]
     pub height_tex: Handle<Image>,
 
-    // Normal map (RGBA8Unorm). Bound now; used later for lighting.
+    // Normal map (RGBA8Unorm). Slope for splatting and lighting; sampled
+    // trilinearly through its mips when `normal_mips` is set.
     #[texture(2, sample_type = "float")]
+    #[sampler(8)]
     pub normal_tex: Handle<Image>,
+
+    // Curvature (R32Float, Laplacian of height). Splatting + debug view.
+    #[texture(3, sample_type = "float", filterable = false)]
+    pub curvature_tex: Handle<Image>,
+
+    // Painted splat overrides (RGBA8Unorm, one layer per channel). The channel
+    // sum is how much the painted weights replace the procedural ones.
+    #[texture(4, sample_type = "float")]
+    pub splat_override_tex: Handle<Image>,
+
+    #[uniform(5)]
+    pub splat: SplatParams,
+
+    #[uniform(6)]
+    pub overlay: OverlayParams,
+
+    #[uniform(7)]
+    pub grid: GridParams,
+
+    #[uniform(9)]
+    pub detail: DetailParams,
+
+    // Detail texture (any color format, repeating sampler); the fallback
+    // white image when `None`.
+    #[texture(10)]
+    #[sampler(11)]
+    pub detail_tex: Option<Handle<Image>>,
+
+    // Detail heights for parallax (red channel, repeating sampler).
+    #[texture(12)]
+    #[sampler(13)]
+    pub detail_height_tex: Option<Handle<Image>>,
+
+    #[uniform(14)]
+    pub pom: PomParams,
+
+    // Filled in by `sync_tile_decals_system`.
+    #[uniform(15)]
+    pub decals: DecalParams,
+
+    // Decal textures (blended by alpha), all sampled with the first one's
+    // sampler to stay clear of the per-stage sampler limit.
+    #[texture(16)]
+    #[sampler(20)]
+    pub decal_tex_0: Option<Handle<Image>>,
+    #[texture(17)]
+    pub decal_tex_1: Option<Handle<Image>>,
+    #[texture(18)]
+    pub decal_tex_2: Option<Handle<Image>>,
+    #[texture(19)]
+    pub decal_tex_3: Option<Handle<Image>>,
+
+    // Hole mask (R8Unorm, 0 = hole), read with textureLoad like the heights.
+    // Only tiles with holes have one; they render alpha-masked so the
+    // prepass and shadow passes run `terrain_prepass.wgsl`'s discard too.
+    #[texture(21, sample_type = "float", filterable = false)]
+    pub hole_tex: Option<Handle<Image>>,
+
+    /// Faceted per-triangle normals (`FLAT_SHADING` shader def) instead of the normal map.
+    pub flat_shading: bool,
+
+    /// The mesh already carries the heights (`TerrainRenderMode::CpuMesh`);
+    /// skips the vertex displacement (`CPU_DISPLACED` shader def).
+    pub cpu_displaced: bool,
+
+    /// `normal_tex` has mips (`NORMAL_MIPS` shader def); otherwise it's read
+    /// texel by texel.
+    pub normal_mips: bool,
+}
+
+/// Pipeline specialization for `TerrainMaterial`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TerrainMaterialKey {
+    flat_shading: bool,
+    cpu_displaced: bool,
+    normal_mips: bool,
+    holes: bool,
+}
+
+impl From<&TerrainMaterial> for TerrainMaterialKey {
+    fn from(material: &TerrainMaterial) -> Self {
+        Self {
+            flat_shading: material.flat_shading,
+            cpu_displaced: material.cpu_displaced,
+            normal_mips: material.normal_mips,
+            holes: material.hole_tex.is_some(),
+        }
+    }
 }
 
 impl Material for TerrainMaterial {
     fn vertex_shader() -> ShaderRef { "shaders/terrain.wgsl".into() }
     fn fragment_shader() -> ShaderRef { "shaders/terrain.wgsl".into() }
+    // displaced depth for the prepass and shadow maps
+    fn prepass_vertex_shader() -> ShaderRef { "shaders/terrain_prepass.wgsl".into() }
+    // only runs for alpha-masked tiles (holes) or with normal/motion prepasses
+    fn prepass_fragment_shader() -> ShaderRef { "shaders/terrain_prepass.wgsl".into() }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        if self.hole_tex.is_some() { AlphaMode::Mask(0.5) } else { AlphaMode::Opaque }
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            if key.bind_group_data.holes {
+                fragment.shader_defs.push("TERRAIN_HOLES".into());
+            }
+            if key.bind_group_data.flat_shading {
+                fragment.shader_defs.push("FLAT_SHADING".into());
+            }
+            if key.bind_group_data.normal_mips {
+                fragment.shader_defs.push("NORMAL_MIPS".into());
+            }
+        }
+        // also reaches the prepass vertex shader, which is specialized here too
+        if key.bind_group_data.cpu_displaced {
+            descriptor.vertex.shader_defs.push("CPU_DISPLACED".into());
+        }
+        Ok(())
+    }
 }
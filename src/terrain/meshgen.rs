@@ -1,23 +1,14 @@
+use bevy::math::DVec2;
 use bevy::prelude::*;
 use noiz::prelude::*;
 
-/// Generate an n×n height field over a tile of world-space `tile_world_size`,
-/// sampling Perlin fBm at world coordinates starting at `origin`.
-pub fn generate_height_field(
-    n: usize,
-    tile_world_size: f32,
-    origin: Vec2,
-    seed: u32,
-    octaves: u32,
-    lacunarity: f32,
-    persistence: f32,
-    frequency: f32,
-    amplitude: f32,
-) -> Vec<f32> {
-    // Build Perlin-fBm with noiz
-    type PerlinBase = MixCellGradients<noiz::cells::OrthoGrid, noiz::curves::Smoothstep, noiz::cell_noise::QuickGradients>;
-    type PerlinFbm = Noise<LayeredNoise<Normed<f32>, Persistence, FractalLayers<Octave<PerlinBase>>>>;
+use super::systems::TerrainConfig;
 
+type PerlinBase = MixCellGradients<noiz::cells::OrthoGrid, noiz::curves::Smoothstep, noiz::cell_noise::QuickGradients>;
+pub type PerlinFbm = Noise<LayeredNoise<Normed<f32>, Persistence, FractalLayers<Octave<PerlinBase>>>>;
+
+/// Build Perlin-fBm with noiz
+pub fn perlin_fbm(seed: u32, octaves: u32, lacunarity: f32, persistence: f32, frequency: f32) -> PerlinFbm {
     let layered = LayeredNoise::new(
         Normed::default(),
         Persistence(persistence),
@@ -30,20 +21,334 @@ pub fn generate_height_field(
     let mut fbm: PerlinFbm = Noise::from(layered);
     fbm.set_seed(seed);
     fbm.set_frequency(frequency);
+    fbm
+}
 
-    let step = tile_world_size / (n as f32 - 1.0);
-    let mut heights = vec![0.0; n * n];
-    for z in 0..n {
-        for x in 0..n {
-            let wx = origin.x + x as f32 * step;
-            let wz = origin.y + z as f32 * step;
-            let h: f32 = fbm.sample(Vec2::new(wx, wz));
-            heights[z * n + x] = h * amplitude;
+/// Perlin fBm with integer-hashed gradients, written so `terrain_gen.wgsl`
+/// can do the same operations in the same order: GPU-built tiles match
+/// this within float rounding. Replaces the noiz fBm (a different terrain
+/// for the same seed) when `TerrainConfig::gpu_generation` or `f64_noise`
+/// is on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HashedFbm {
+    pub seed: u32,
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub persistence: f32,
+    pub frequency: f32,
+}
+
+impl HashedFbm {
+    /// Roughly in -1..=1 (normalized by the summed octave amplitudes).
+    pub fn sample(&self, p: Vec2) -> f32 {
+        let (mut sum, mut total, mut amplitude, mut frequency) = (0.0, 0.0, 1.0, self.frequency);
+        for octave in 0..self.octaves.max(1) {
+            sum += amplitude * hashed_perlin(p * frequency, self.seed.wrapping_add(octave));
+            total += amplitude;
+            amplitude *= self.persistence;
+            frequency *= self.lacunarity;
+        }
+        sum / total.max(1e-6)
+    }
+
+    /// `sample` with the lattice cell and the offset in it found in f64, so
+    /// the result stays smooth at any distance from the origin. Near the
+    /// origin it matches `sample` within float rounding.
+    pub fn sample_f64(&self, p: DVec2) -> f32 {
+        let (mut sum, mut total, mut amplitude, mut frequency) = (0.0, 0.0, 1.0, self.frequency);
+        for octave in 0..self.octaves.max(1) {
+            let q = p * frequency as f64;
+            let cell = q.floor();
+            // the i32 wrap matches the shader's cells for every lattice it can reach
+            let cell_i = IVec2::new(cell.x as i64 as i32, cell.y as i64 as i32);
+            sum += amplitude * hashed_perlin_cell(cell_i, (q - cell).as_vec2(), self.seed.wrapping_add(octave));
+            total += amplitude;
+            amplitude *= self.persistence;
+            frequency *= self.lacunarity;
+        }
+        sum / total.max(1e-6)
+    }
+
+    /// `sample` at `start + (i·step, 0)` for each `i` in `out`, with the same
+    /// results. Each octave's seed hash is found once per row and corner
+    /// gradients are reused while the samples stay in a lattice cell, or
+    /// shifted along when they step into the next one.
+    pub fn sample_row(&self, start: DVec2, step: f64, out: &mut [f32]) {
+        self.fill_row(out, |frequency, i| {
+            let q = (start + DVec2::new(i as f64 * step, 0.0)).as_vec2() * frequency;
+            let cell = q.floor();
+            (cell.as_ivec2(), q - cell)
+        });
+    }
+
+    /// `sample_row` for `sample_f64`.
+    pub fn sample_row_f64(&self, start: DVec2, step: f64, out: &mut [f32]) {
+        self.fill_row(out, |frequency, i| {
+            let q = (start + DVec2::new(i as f64 * step, 0.0)) * frequency as f64;
+            let cell = q.floor();
+            (IVec2::new(cell.x as i64 as i32, cell.y as i64 as i32), (q - cell).as_vec2())
+        });
+    }
+
+    /// `lattice(frequency, i)` is the cell and the offset in it of sample `i`.
+    fn fill_row(&self, out: &mut [f32], lattice: impl Fn(f32, usize) -> (IVec2, Vec2)) {
+        out.fill(0.0);
+        let (mut total, mut amplitude, mut frequency) = (0.0, 1.0, self.frequency);
+        for octave in 0..self.octaves.max(1) {
+            let seed_hash = pcg_hash(self.seed.wrapping_add(octave));
+            let mut cached: Option<(IVec2, [u32; 2], [Vec2; 4])> = None;
+            for (i, h) in out.iter_mut().enumerate() {
+                let (cell, f) = lattice(frequency, i);
+                let (rows, g) = match cached {
+                    Some((c, rows, g)) if c == cell => (rows, g),
+                    Some((c, rows, g)) if c + IVec2::X == cell => {
+                        let x = cell.x.wrapping_add(1);
+                        (rows, [g[1], lattice_gradient(x, rows[0]), g[3], lattice_gradient(x, rows[1])])
+                    }
+                    _ => {
+                        let rows = lattice_rows(cell.y, seed_hash);
+                        (rows, cell_gradients(cell.x, rows))
+                    }
+                };
+                cached = Some((cell, rows, g));
+                *h += amplitude * perlin_in_cell(g, f);
+            }
+            total += amplitude;
+            amplitude *= self.persistence;
+            frequency *= self.lacunarity;
+        }
+        for h in out.iter_mut() {
+            *h /= total.max(1e-6);
+        }
+    }
+}
+
+/// PCG hash; `pcg_hash` in `terrain_gen.wgsl`.
+fn pcg_hash(x: u32) -> u32 {
+    let state = x.wrapping_mul(747_796_405).wrapping_add(2_891_336_453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277_803_737);
+    (word >> 22) ^ word
+}
+
+/// Eight unit gradients, picked exactly by hash rather than via sin/cos
+/// (which differ between CPU and GPU).
+const GRADIENTS: [Vec2; 8] = [
+    Vec2::new(1.0, 0.0),
+    Vec2::new(-1.0, 0.0),
+    Vec2::new(0.0, 1.0),
+    Vec2::new(0.0, -1.0),
+    Vec2::new(std::f32::consts::FRAC_1_SQRT_2, std::f32::consts::FRAC_1_SQRT_2),
+    Vec2::new(-std::f32::consts::FRAC_1_SQRT_2, std::f32::consts::FRAC_1_SQRT_2),
+    Vec2::new(std::f32::consts::FRAC_1_SQRT_2, -std::f32::consts::FRAC_1_SQRT_2),
+    Vec2::new(-std::f32::consts::FRAC_1_SQRT_2, -std::f32::consts::FRAC_1_SQRT_2),
+];
+
+/// `hashed_perlin` in `terrain_gen.wgsl`.
+fn hashed_perlin(p: Vec2, seed: u32) -> f32 {
+    let cell = p.floor();
+    hashed_perlin_cell(cell.as_ivec2(), p - cell, seed)
+}
+
+/// Perlin noise at offset `f` (0..1) into lattice cell `cell`.
+fn hashed_perlin_cell(cell: IVec2, f: Vec2, seed: u32) -> f32 {
+    perlin_in_cell(cell_gradients(cell.x, lattice_rows(cell.y, pcg_hash(seed))), f)
+}
+
+/// Corner gradients of the cell at `x` between the two `rows`, in the
+/// order `perlin_in_cell` takes them.
+fn cell_gradients(x: i32, rows: [u32; 2]) -> [Vec2; 4] {
+    let x1 = x.wrapping_add(1);
+    [lattice_gradient(x, rows[0]), lattice_gradient(x1, rows[0]), lattice_gradient(x, rows[1]), lattice_gradient(x1, rows[1])]
+}
+
+/// Hashes of lattice rows `z` and `z + 1`, for `lattice_gradient`.
+fn lattice_rows(z: i32, seed_hash: u32) -> [u32; 2] {
+    [pcg_hash(z as u32 ^ seed_hash), pcg_hash(z.wrapping_add(1) as u32 ^ seed_hash)]
+}
+
+/// Gradient of lattice point `x` on a row hashed by `lattice_rows`.
+fn lattice_gradient(x: i32, row: u32) -> Vec2 {
+    GRADIENTS[(pcg_hash(x as u32 ^ row) & 7) as usize]
+}
+
+/// Perlin noise at offset `f` into a cell with corner gradients `g`, in
+/// the order (0, 0), (1, 0), (0, 1), (1, 1).
+fn perlin_in_cell(g: [Vec2; 4], f: Vec2) -> f32 {
+    let corner = |k: usize| g[k].dot(f - Vec2::new((k & 1) as f32, (k >> 1) as f32));
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    let bottom = corner(0) + (corner(1) - corner(0)) * u.x;
+    let top = corner(2) + (corner(3) - corner(2)) * u.x;
+    // 2D Perlin peaks at sqrt(1/2)
+    (bottom + (top - bottom) * u.y) * std::f32::consts::SQRT_2
+}
+
+/// A terrain height function over true (unshifted) world positions.
+///
+/// Tile generation samples it through `generate_height_field`; anything that
+/// needs the unedited terrain away from loaded tiles (spawn placement,
+/// offline tools) can sample the same source instead of duplicating the noise.
+pub trait HeightSource: Send + Sync + 'static {
+    /// Unscaled height (before `TerrainShadingSettings::height_scale`).
+    fn height_at(&self, world_xz: DVec2) -> f32;
+
+    /// Fill `out` with heights along +X from `start`, `step` apart, the same
+    /// as `height_at` would give. Sources that can share work along a row
+    /// override this; the default is scalar.
+    fn sample_row(&self, start: DVec2, step: f64, out: &mut [f32]) {
+        for (i, h) in out.iter_mut().enumerate() {
+            *h = self.height_at(start + DVec2::new(i as f64 * step, 0.0));
+        }
+    }
+}
+
+/// Radial island mask: heights blend to `edge_height` between `radius` and
+/// `radius + falloff_width` from `center` (world XZ).
+///
+/// `edge_height` is unscaled like every generated height; keep
+/// `edge_height * height_scale` below `WaterSettings::sea_level` so the
+/// coast ends underwater.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "inspector", derive(Reflect))]
+pub struct WorldFalloff {
+    pub center: Vec2,
+    pub radius: f32,
+    pub falloff_width: f32,
+    pub edge_height: f32,
+}
+
+impl WorldFalloff {
+    /// 1 inside `radius`, smoothly 0 at `radius + falloff_width` and beyond.
+    pub fn mask(&self, world_xz: DVec2) -> f32 {
+        let d = world_xz.distance(self.center.as_dvec2()) as f32;
+        let t = ((d - self.radius) / self.falloff_width.max(1e-3)).clamp(0.0, 1.0);
+        1.0 - t * t * (3.0 - 2.0 * t)
+    }
+
+    pub fn apply(&self, world_xz: DVec2, height: f32) -> f32 {
+        self.edge_height + (height - self.edge_height) * self.mask(world_xz)
+    }
+
+    /// Whether any part of the axis-aligned `min..max` rect is inside the falloff edge.
+    pub fn touches(&self, min: DVec2, max: DVec2) -> bool {
+        let c = self.center.as_dvec2();
+        c.clamp(min, max).distance(c) < (self.radius + self.falloff_width) as f64
+    }
+}
+
+/// The default generator: Perlin fBm scaled by an amplitude, optionally
+/// shaped by a `WorldFalloff`.
+pub struct FbmHeightSource {
+    fbm: Fbm,
+    amplitude: f32,
+    falloff: Option<WorldFalloff>,
+}
+
+enum Fbm {
+    Noiz(PerlinFbm),
+    Hashed(HashedFbm),
+    HashedF64(HashedFbm),
+}
+
+impl FbmHeightSource {
+    pub fn new(seed: u32, octaves: u32, lacunarity: f32, persistence: f32, frequency: f32, amplitude: f32) -> Self {
+        let fbm = Fbm::Noiz(perlin_fbm(seed, octaves, lacunarity, persistence, frequency));
+        Self { fbm, amplitude, falloff: None }
+    }
+
+    /// The GPU-matching variant, see `HashedFbm`.
+    pub fn hashed(fbm: HashedFbm, amplitude: f32) -> Self {
+        Self { fbm: Fbm::Hashed(fbm), amplitude, falloff: None }
+    }
+
+    /// `HashedFbm` sampled in f64, see `HashedFbm::sample_f64`.
+    pub fn hashed_f64(fbm: HashedFbm, amplitude: f32) -> Self {
+        Self { fbm: Fbm::HashedF64(fbm), amplitude, falloff: None }
+    }
+
+    pub fn with_falloff(mut self, falloff: Option<WorldFalloff>) -> Self {
+        self.falloff = falloff;
+        self
+    }
+}
+
+impl HeightSource for FbmHeightSource {
+    fn height_at(&self, world_xz: DVec2) -> f32 {
+        // noiz (and the shader's noise) take f32 positions, see `TerrainConfig::f64_noise`
+        let h: f32 = match &self.fbm {
+            Fbm::Noiz(fbm) => fbm.sample(world_xz.as_vec2()),
+            Fbm::Hashed(fbm) => fbm.sample(world_xz.as_vec2()),
+            Fbm::HashedF64(fbm) => fbm.sample_f64(world_xz),
+        };
+        let h = h * self.amplitude;
+        self.falloff.map_or(h, |f| f.apply(world_xz, h))
+    }
+
+    fn sample_row(&self, start: DVec2, step: f64, out: &mut [f32]) {
+        let at = |i: usize| start + DVec2::new(i as f64 * step, 0.0);
+        match &self.fbm {
+            Fbm::Noiz(fbm) => {
+                for (i, h) in out.iter_mut().enumerate() {
+                    *h = fbm.sample(at(i).as_vec2());
+                }
+            }
+            Fbm::Hashed(fbm) => fbm.sample_row(start, step, out),
+            Fbm::HashedF64(fbm) => fbm.sample_row_f64(start, step, out),
         }
+        for (i, h) in out.iter_mut().enumerate() {
+            *h *= self.amplitude;
+            if let Some(f) = &self.falloff {
+                *h = f.apply(at(i), *h);
+            }
+        }
+    }
+}
+
+/// Generate an n×n height field over a tile of world-space `tile_world_size`,
+/// sampling `source` at world coordinates starting at `origin`.
+///
+/// Sample positions are built in f64 so far from the origin they don't
+/// drift with accumulated `origin + x * step` error.
+pub fn generate_height_field(n: usize, tile_world_size: f32, origin: DVec2, source: &impl HeightSource) -> Vec<f32> {
+    let step = tile_world_size as f64 / (n as f64 - 1.0);
+    let mut heights = vec![0.0; n * n];
+    for (z, row) in heights.chunks_exact_mut(n).enumerate() {
+        source.sample_row(origin + DVec2::new(0.0, z as f64 * step), step, row);
+    }
+    heights
+}
+
+/// `generate_height_field` with one more sample on every side, `(n+2)²`,
+/// for `crop_apron`. The apron is stepped in f64 from `origin` like the
+/// interior, so it lands on the neighbours' samples.
+pub fn generate_padded_height_field(n: usize, tile_world_size: f32, origin: DVec2, source: &impl HeightSource) -> Vec<f32> {
+    let step = tile_world_size as f64 / (n as f64 - 1.0);
+    let mut heights = vec![0.0; (n + 2) * (n + 2)];
+    for (z, row) in heights.chunks_exact_mut(n + 2).enumerate() {
+        source.sample_row(origin + DVec2::new(-1.0, z as f64 - 1.0) * step, step, row);
     }
     heights
 }
 
+/// Unedited heights of one tile as the streamer generates them (row-major,
+/// `tile_resolution²`, unscaled), without building textures or a task.
+pub fn generate_tile_heights(coord: IVec2, cfg: &TerrainConfig) -> Vec<f32> {
+    let origin = coord.as_dvec2() * cfg.tile_size as f64;
+    generate_height_field(cfg.tile_resolution, cfg.tile_size, origin, &cfg.height_source())
+}
+
+/// Interior `n×n` block of an `(n+2)×(n+2)` grid that carries a one-sample
+/// apron around the tile, with `components` values per sample.
+///
+/// Tiles sample their neighbours' edge heights into the apron so the
+/// border normals and curvature match what the neighbour computes.
+pub fn crop_apron<T: Copy>(n: usize, padded: &[T], components: usize) -> Vec<T> {
+    let row = (n + 2) * components;
+    (1..=n)
+        .flat_map(|z| &padded[z * row + components..z * row + (n + 1) * components])
+        .copied()
+        .collect()
+}
+
 /// Make an RGBA8 normal map (world-space, encoded 0..1) from heights.
 pub fn normalmap_from_height(n: usize, step: f32, heights: &[f32]) -> Vec<u8> {
     let mut out = vec![0u8; n*n*4];
@@ -62,7 +367,7 @@ pub fn normalmap_from_height(n: usize, step: f32, heights: &[f32]) -> Vec<u8> {
             let dz = (h_u - h_d) / (2.0 * step);
             let nvec = Vec3::new(-dx, 1.0, -dz).normalize();
             let i = (z as usize * n + x as usize) * 4;
-            out[i+0] = ((nvec.x * 0.5 + 0.5) * 255.0) as u8;
+            out[i] = ((nvec.x * 0.5 + 0.5) * 255.0) as u8;
             out[i+1] = ((nvec.y * 0.5 + 0.5) * 255.0) as u8;
             out[i+2] = ((nvec.z * 0.5 + 0.5) * 255.0) as u8;
             out[i+3] = 255;
@@ -70,3 +375,171 @@ pub fn normalmap_from_height(n: usize, step: f32, heights: &[f32]) -> Vec<u8> {
     }
     out
 }
+
+/// Mip levels of a full chain for an `n`×`n` texture.
+pub fn mip_level_count(n: usize) -> u32 {
+    usize::BITS - n.max(1).leading_zeros()
+}
+
+/// An `n`×`n` RGBA8 normal map followed by its mips, level after level as
+/// wgpu sizes them (`n >> level`). Each texel averages the 2×2 normals under
+/// it (clamped at odd edges) and renormalizes, so distant tiles see the mean
+/// slope instead of one aliased texel.
+pub fn normalmap_mips(n: usize, normals: &[u8]) -> Vec<u8> {
+    let mut out = normals[..n * n * 4].to_vec();
+    let decode = |t: &[u8]| Vec3::new(t[0] as f32, t[1] as f32, t[2] as f32) / 255.0 * 2.0 - Vec3::ONE;
+    let (mut src_start, mut src_n) = (0, n);
+    for level in 1..mip_level_count(n) {
+        let dst_n = (n >> level).max(1);
+        let dst_start = out.len();
+        out.resize(dst_start + dst_n * dst_n * 4, 0);
+        for z in 0..dst_n {
+            for x in 0..dst_n {
+                let mut sum = Vec3::ZERO;
+                for (dx, dz) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sx = (2 * x + dx).min(src_n - 1);
+                    let sz = (2 * z + dz).min(src_n - 1);
+                    let i = src_start + (sz * src_n + sx) * 4;
+                    sum += decode(&out[i..i + 3]);
+                }
+                let nvec = sum.normalize_or(Vec3::Y);
+                let i = dst_start + (z * dst_n + x) * 4;
+                out[i] = ((nvec.x * 0.5 + 0.5) * 255.0) as u8;
+                out[i + 1] = ((nvec.y * 0.5 + 0.5) * 255.0) as u8;
+                out[i + 2] = ((nvec.z * 0.5 + 0.5) * 255.0) as u8;
+                out[i + 3] = 255;
+            }
+        }
+        (src_start, src_n) = (dst_start, dst_n);
+    }
+    out
+}
+
+/// Neighbour offsets `(dx, dz)` indexed by a D8 flow direction.
+pub const D8_OFFSETS: [(isize, isize); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+/// Flow direction of pits and flats: no lower neighbour.
+pub const FLOW_SINK: u8 = u8::MAX;
+
+/// D8 flow directions: for each sample, the index into `D8_OFFSETS` of its
+/// steepest downhill neighbour (diagonals weighted by their longer run).
+pub fn d8_flow_directions(n: usize, heights: &[f32]) -> Vec<u8> {
+    let mut out = vec![FLOW_SINK; n * n];
+    for z in 0..n as isize {
+        for x in 0..n as isize {
+            let h = heights[z as usize * n + x as usize];
+            let mut best = 0.0;
+            for (d, (dx, dz)) in D8_OFFSETS.iter().enumerate() {
+                let (nx, nz) = (x + dx, z + dz);
+                if nx < 0 || nz < 0 || nx >= n as isize || nz >= n as isize { continue; }
+                let run = if dx * dz == 0 { 1.0 } else { std::f32::consts::SQRT_2 };
+                let drop = (h - heights[nz as usize * n + nx as usize]) / run;
+                if drop > best {
+                    best = drop;
+                    out[z as usize * n + x as usize] = d as u8;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Number of samples (itself included) draining through each sample.
+/// Only sees this grid, so it undercounts water arriving from outside.
+pub fn flow_accumulation(n: usize, heights: &[f32], directions: &[u8]) -> Vec<f32> {
+    let mut order: Vec<usize> = (0..n * n).collect();
+    order.sort_by(|a, b| heights[*b].total_cmp(&heights[*a]));
+    let mut acc = vec![1.0; n * n];
+    // highest first, so every upstream sample has been added before its receiver passes it on
+    for i in order {
+        let Some((dx, dz)) = D8_OFFSETS.get(directions[i] as usize) else { continue };
+        let (x, z) = ((i % n) as isize + dx, (i / n) as isize + dz);
+        acc[z as usize * n + x as usize] += acc[i];
+    }
+    acc
+}
+
+/// Discrete Laplacian of the height field (per world unit²).
+/// Positive = concave (valleys, cliff bases), negative = convex (ridges).
+pub fn curvature_from_height(n: usize, step: f32, heights: &[f32]) -> Vec<f32> {
+    let mut out = vec![0.0; n*n];
+    let idx = |x: isize, z: isize| -> usize {
+        let xi = x.clamp(0, (n-1) as isize) as usize;
+        let zi = z.clamp(0, (n-1) as isize) as usize;
+        zi*n + xi
+    };
+    let inv_step2 = 1.0 / (step * step);
+    for z in 0..n as isize {
+        for x in 0..n as isize {
+            let h = heights[idx(x, z)];
+            let sum = heights[idx(x-1, z)] + heights[idx(x+1, z)] + heights[idx(x, z-1)] + heights[idx(x, z+1)];
+            out[z as usize * n + x as usize] = (sum - 4.0 * h) * inv_step2;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FBM: HashedFbm = HashedFbm { seed: 12345, octaves: 6, lacunarity: 2.0, persistence: 0.5, frequency: 0.08 };
+
+    #[test]
+    fn sample_f64_matches_sample_near_origin() {
+        for i in 0..400 {
+            let p = DVec2::new(i as f64 * 0.37 - 70.0, i as f64 * 0.61 - 120.0);
+            let (a, b) = (FBM.sample(p.as_vec2()), FBM.sample_f64(p));
+            assert!((a - b).abs() < 1e-4, "{p}: {a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn rows_match_single_samples() {
+        let falloff = WorldFalloff { center: Vec2::new(40.0, -10.0), radius: 30.0, falloff_width: 20.0, edge_height: -3.0 };
+        let sources = [
+            FbmHeightSource::hashed(FBM, 7.0),
+            FbmHeightSource::hashed_f64(FBM, 7.0).with_falloff(Some(falloff)),
+            FbmHeightSource::new(3, 5, 2.0, 0.5, 0.05, 7.0),
+        ];
+        for source in &sources {
+            for (start, step) in [(DVec2::new(-13.7, 5.2), 0.25), (DVec2::new(1.0e5, -2.5e5), 0.37), (DVec2::ZERO, 3.1)] {
+                let mut row = vec![0.0; 130];
+                source.sample_row(start, step, &mut row);
+                for (i, h) in row.iter().enumerate() {
+                    // bit for bit, so rows and `height_at` callers agree exactly
+                    assert_eq!(*h, source.height_at(start + DVec2::new(i as f64 * step, 0.0)), "{start} + {i}·{step}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tile_heights_are_the_padded_interior() {
+        let cfg = TerrainConfig { tile_size: 16.0, tile_resolution: 17, ..default() };
+        for coord in [IVec2::ZERO, IVec2::new(-3, 7), IVec2::new(20_000, -9_000)] {
+            let heights = generate_tile_heights(coord, &cfg);
+            assert_eq!(heights.len(), 17 * 17);
+            assert_eq!(heights, generate_tile_heights(coord, &cfg));
+            let padded = generate_padded_height_field(17, 16.0, coord.as_dvec2() * 16.0, &cfg.height_source());
+            assert_eq!(crop_apron(17, &padded, 1), heights, "{coord}");
+        }
+    }
+
+    #[test]
+    fn far_samples_are_not_quantized() {
+        // f32 positions are 1/32 apart at 300k, so a 1/128 grid lands on the
+        // same position four samples at a time
+        let n = 65;
+        let origin = DVec2::new(300_000.0, -300_000.0) + 0.3;
+        let row = |source: &FbmHeightSource| generate_height_field(n, 0.5, origin, source)[..n].to_vec();
+        let stepped = row(&FbmHeightSource::hashed(FBM, 1.0));
+        assert!(stepped.windows(2).filter(|w| w[0] == w[1]).count() > n / 2);
+
+        let smooth = row(&FbmHeightSource::hashed_f64(FBM, 1.0));
+        let near = generate_height_field(n, 0.5, DVec2::splat(0.3), &FbmHeightSource::hashed_f64(FBM, 1.0));
+        let max_d2 = |h: &[f32]| h.windows(3).map(|w| (w[2] - 2.0 * w[1] + w[0]).abs()).fold(0.0, f32::max);
+        assert!(smooth.windows(2).all(|w| w[0] != w[1]), "neighbouring samples repeat: {smooth:?}");
+        // as smooth as the same grid near the origin, up to f32 output rounding
+        assert!(max_d2(&smooth) < max_d2(&near[..n]) * 4.0 + 1e-5, "{} vs {}", max_d2(&smooth), max_d2(&near[..n]));
+    }
+}
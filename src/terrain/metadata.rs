@@ -0,0 +1,109 @@
+//! Facts about each generated tile, for quest and encounter placement.
+//!
+//! `TileMetadata` is computed next to the heights: in the build task for
+//! CPU-built tiles, when the height readback lands for GPU-built ones. It is
+//! kept in `TerrainState` (`metadata`, `metadata_at_world`), on the `Tile`
+//! component and sent with `TileSpawned`, so a system can decide "this tile
+//! gets a ruin" the frame it appears. GPU-built tiles spawn without it; read
+//! it after `TileHeightsReady`.
+//!
+//! Everything is derived from `(seed, coord)` and the tile's edits, so a
+//! tile reloads with the same metadata. It describes the heights as built;
+//! later brush strokes don't update it until the tile is rebuilt.
+
+use bevy::prelude::*;
+
+use super::biome::BiomeSampler;
+use super::heightfield::grid_normal;
+use super::rng::TileRng;
+
+/// Samples per side of the grid the biome mix is measured on.
+const BIOME_SAMPLES: usize = 8;
+
+#[derive(Clone, Debug)]
+pub struct TileMetadata {
+    pub coord: IVec2,
+    /// World heights (`height_scale` applied).
+    pub min_height: f32,
+    pub max_height: f32,
+    pub mean_height: f32,
+    /// Share of height samples below `WaterSettings::sea_level`.
+    pub water_fraction: f32,
+    /// Steepest slope of any sample, degrees.
+    pub steepest_slope_deg: f32,
+    /// Share of the tile each biome covers, as `(index into
+    /// BiomeSettings::biomes, share)`, largest first. Shares add up to less
+    /// than one where no biome covers the climate.
+    pub biomes: Vec<(usize, f32)>,
+    seed: u32,
+}
+
+impl TileMetadata {
+    /// Measure an `n`×`n` tile of unscaled `heights`, `tile_size` wide.
+    pub fn compute(
+        coord: IVec2,
+        heights: &[f32],
+        n: usize,
+        tile_size: f32,
+        height_scale: f32,
+        sea_level: f32,
+        biomes: &BiomeSampler,
+        seed: u32,
+    ) -> Self {
+        let step = tile_size / (n as f32 - 1.0);
+        let (mut min_height, mut max_height, mut sum, mut wet) = (f32::INFINITY, f32::NEG_INFINITY, 0.0_f64, 0usize);
+        for h in heights.iter().map(|h| h * height_scale) {
+            min_height = min_height.min(h);
+            max_height = max_height.max(h);
+            sum += h as f64;
+            if h < sea_level { wet += 1; }
+        }
+        let count = heights.len().max(1);
+        let mut flattest_y: f32 = 1.0;
+        for z in 0..n {
+            for x in 0..n {
+                flattest_y = flattest_y.min(grid_normal(heights, n, step, height_scale, Vec2::new(x as f32, z as f32)).y);
+            }
+        }
+
+        let origin = coord.as_vec2() * tile_size;
+        let mut shares: Vec<(usize, f32)> = Vec::new();
+        let biome_step = tile_size / BIOME_SAMPLES as f32;
+        for z in 0..BIOME_SAMPLES {
+            for x in 0..BIOME_SAMPLES {
+                let p = origin + (Vec2::new(x as f32, z as f32) + 0.5) * biome_step;
+                for (i, w) in biomes.weights_at(p) {
+                    match shares.iter_mut().find(|(j, _)| *j == i) {
+                        Some((_, s)) => *s += w,
+                        None => shares.push((i, w)),
+                    }
+                }
+            }
+        }
+        let samples = (BIOME_SAMPLES * BIOME_SAMPLES) as f32;
+        for (_, s) in shares.iter_mut() { *s /= samples; }
+        shares.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        Self {
+            coord,
+            min_height,
+            max_height,
+            mean_height: (sum / count as f64) as f32,
+            water_fraction: wet as f32 / count as f32,
+            steepest_slope_deg: flattest_y.clamp(-1.0, 1.0).acos().to_degrees(),
+            biomes: shares,
+            seed,
+        }
+    }
+
+    /// Index of the biome covering most of the tile.
+    pub fn dominant_biome(&self) -> Option<usize> {
+        self.biomes.first().map(|(i, _)| *i)
+    }
+
+    /// A fresh random stream for this tile, the same on every load. Use
+    /// `salt` to keep independent decisions apart.
+    pub fn rng(&self, salt: u32) -> TileRng {
+        TileRng::new(self.seed, self.coord, 0x4D45_5441 ^ salt) // "META"
+    }
+}
@@ -0,0 +1,307 @@
+//! Overhead minimap composited from loaded tiles.
+//!
+//! `Minimap::image` covers `tiles_across²` tiles around the first
+//! `TileLoader`, `texels_per_tile` texels per tile, north (-Z) up. A tile's
+//! block is drawn once when it spawns: splat colors from the CPU heights with
+//! hill shading, water below sea level. When the loader crosses a tile border
+//! the image scrolls by whole blocks and only newly exposed blocks are drawn.
+//! All blocks are redrawn once the shown snow line has moved noticeably.
+
+use bevy::math::DVec2;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use super::climate::{AppliedClimate, ClimateState};
+use super::heightfield::TerrainHeightfield;
+use super::origin::WorldOffset;
+use super::shading::TerrainShadingSettings;
+use super::systems::{
+    garbage_collect_tiles_system, world_to_coord, TerrainConfig, TerrainState, TileDespawned, TileLoader, TileSpawned,
+};
+use super::water::WaterSettings;
+
+const BACKGROUND: [u8; 4] = [18, 20, 24, 255];
+/// Snow line movement (world units) that triggers a full redraw.
+const CLIMATE_REDRAW_THRESHOLD: f32 = 0.5;
+
+pub struct MinimapPlugin;
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<MinimapSettings>()
+            .add_systems(Startup, init_minimap)
+            .add_systems(
+                Update,
+                (update_minimap_system, update_minimap_marker_system)
+                    .chain()
+                    .after(garbage_collect_tiles_system),
+            );
+    }
+}
+
+/// Read once at startup.
+#[derive(Resource, Clone)]
+pub struct MinimapSettings {
+    pub tiles_across: u32,
+    pub texels_per_tile: u32,
+    /// Dim the blocks of despawned tiles instead of clearing them.
+    pub fade_despawned: bool,
+    /// Spawn a corner UI node with the map and a camera marker.
+    pub show_ui: bool,
+    /// Side of that node, in logical pixels.
+    pub ui_size: f32,
+}
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self { tiles_across: 32, texels_per_tile: 8, fade_despawned: true, show_ui: true, ui_size: 200.0 }
+    }
+}
+
+#[derive(Resource)]
+pub struct Minimap {
+    /// Show this in your own UI, or use `MinimapSettings::show_ui`.
+    pub image: Handle<Image>,
+    /// Tile drawn in the top-left block.
+    origin: IVec2,
+    tiles_across: u32,
+    texels_per_tile: u32,
+}
+
+impl Minimap {
+    pub fn resolution(&self) -> u32 {
+        self.tiles_across * self.texels_per_tile
+    }
+
+    /// Tile drawn in the top-left block.
+    pub fn origin(&self) -> IVec2 {
+        self.origin
+    }
+
+    /// Image UV (0..1 inside the map) of a true world position.
+    pub fn world_to_uv(&self, world_xz: DVec2, tile_size: f32) -> Vec2 {
+        ((world_xz / tile_size as f64 - self.origin.as_dvec2()) / self.tiles_across as f64).as_vec2()
+    }
+
+    /// True world position at an image UV.
+    pub fn uv_to_world(&self, uv: Vec2, tile_size: f32) -> DVec2 {
+        (uv.as_dvec2() * self.tiles_across as f64 + self.origin.as_dvec2()) * tile_size as f64
+    }
+
+    fn contains(&self, coord: IVec2) -> bool {
+        in_window(self.origin, self.tiles_across, coord)
+    }
+}
+
+fn in_window(origin: IVec2, tiles_across: u32, coord: IVec2) -> bool {
+    let b = coord - origin;
+    b.cmpge(IVec2::ZERO).all() && b.cmplt(IVec2::splat(tiles_across as i32)).all()
+}
+
+/// The camera marker on the minimap UI node.
+#[derive(Component)]
+pub struct MinimapMarker;
+
+pub fn init_minimap(mut commands: Commands, mut images: ResMut<Assets<Image>>, settings: Res<MinimapSettings>) {
+    let tiles_across = settings.tiles_across.max(1);
+    let texels_per_tile = settings.texels_per_tile.max(1);
+    let size = tiles_across * texels_per_tile;
+    let image = images.add(Image::new_fill(
+        Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &BACKGROUND,
+        TextureFormat::Rgba8UnormSrgb,
+        // kept in the main world so blocks can be blitted in place
+        RenderAssetUsages::default(),
+    ));
+    let half = IVec2::splat(tiles_across as i32 / 2);
+    commands.insert_resource(Minimap { image: image.clone(), origin: -half, tiles_across, texels_per_tile });
+
+    if !settings.show_ui { return; }
+    commands
+        .spawn((
+            Name::new("Minimap"),
+            ImageNode::new(image),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(12.0),
+                bottom: Val::Px(12.0),
+                width: Val::Px(settings.ui_size),
+                height: Val::Px(settings.ui_size),
+                ..default()
+            },
+        ))
+        .with_child((
+            MinimapMarker,
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(6.0),
+                height: Val::Px(6.0),
+                margin: UiRect::all(Val::Px(-3.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(1.0, 0.2, 0.2)),
+        ));
+}
+
+pub fn update_minimap_system(
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    settings: Res<MinimapSettings>,
+    cfg: Res<TerrainConfig>,
+    state: Res<TerrainState>,
+    heightfield: Res<TerrainHeightfield>,
+    shading: Res<TerrainShadingSettings>,
+    water: Res<WaterSettings>,
+    climate: Res<AppliedClimate>,
+    offset: Res<WorldOffset>,
+    mut spawned: EventReader<TileSpawned>,
+    mut despawned: EventReader<TileDespawned>,
+    q_loaders: Query<&Transform, With<TileLoader>>,
+    mut drawn_climate: Local<Option<ClimateState>>,
+) {
+    let half = IVec2::splat(minimap.tiles_across as i32 / 2);
+    let origin = q_loaders
+        .iter()
+        .next()
+        .map(|xf| world_to_coord(xf.translation, cfg.tile_size, &offset) - half)
+        .unwrap_or(minimap.origin);
+    let spawned: Vec<IVec2> = spawned.read().map(|ev| ev.coord).collect();
+    let despawned: Vec<IVec2> = despawned.read().map(|ev| ev.coord).collect();
+    let redraw_all = drawn_climate.is_some_and(|c| {
+        (c.snow_line_height - climate.snow_line_height).abs() > CLIMATE_REDRAW_THRESHOLD
+            || (c.snow_blend - climate.snow_blend).abs() > CLIMATE_REDRAW_THRESHOLD
+    });
+    if drawn_climate.is_none() || redraw_all {
+        *drawn_climate = Some(**climate);
+    }
+    if origin == minimap.origin && spawned.is_empty() && despawned.is_empty() && !redraw_all { return; }
+
+    // only touch the asset when something changed, get_mut re-uploads it
+    let Some(data) = images.get_mut(&minimap.image).and_then(|img| img.data.as_mut()) else { return };
+    let draw = |data: &mut Vec<u8>, minimap: &Minimap, coord: IVec2| {
+        let tile_origin = heightfield.tile_origin(coord);
+        draw_block(data, minimap, coord, tile_origin, cfg.tile_size, |xz| {
+            thumbnail_color(&heightfield, &shading, &water, &climate, xz)
+        });
+    };
+
+    if origin != minimap.origin {
+        let old_origin = minimap.origin;
+        scroll(data, minimap.resolution() as usize, origin - old_origin, minimap.texels_per_tile as i32);
+        minimap.origin = origin;
+        let exposed = |c: IVec2| minimap.contains(c) && !in_window(old_origin, minimap.tiles_across, c);
+        for coord in state.tiles.keys().copied().filter(|c| exposed(*c)) {
+            draw(data, &minimap, coord);
+        }
+    }
+    if redraw_all {
+        for coord in state.tiles.keys().copied().filter(|c| minimap.contains(*c)) {
+            draw(data, &minimap, coord);
+        }
+    }
+    for coord in despawned {
+        if state.tiles.contains_key(&coord) || !minimap.contains(coord) { continue; }
+        let fade = if settings.fade_despawned { 0.6 } else { 1.0 };
+        map_block(data, &minimap, coord, |px| {
+            for (c, bg) in px.iter_mut().zip(BACKGROUND) {
+                *c = (*c as f32 + (bg as f32 - *c as f32) * fade) as u8;
+            }
+        });
+    }
+    for coord in spawned {
+        if state.tiles.contains_key(&coord) && minimap.contains(coord) {
+            draw(data, &minimap, coord);
+        }
+    }
+}
+
+pub fn update_minimap_marker_system(
+    minimap: Res<Minimap>,
+    cfg: Res<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    q_loaders: Query<&Transform, With<TileLoader>>,
+    mut q_marker: Query<&mut Node, With<MinimapMarker>>,
+) {
+    let Some(xf) = q_loaders.iter().next() else { return };
+    let uv = minimap.world_to_uv(offset.to_world(xf.translation.xz()), cfg.tile_size).clamp(Vec2::ZERO, Vec2::ONE);
+    for mut node in q_marker.iter_mut() {
+        node.left = Val::Percent(uv.x * 100.0);
+        node.top = Val::Percent(uv.y * 100.0);
+    }
+}
+
+/// Move the image content by `delta` blocks; exposed blocks are cleared.
+fn scroll(data: &mut Vec<u8>, res: usize, delta: IVec2, texels_per_tile: i32) {
+    let shift = delta * texels_per_tile;
+    let mut out: Vec<u8> = BACKGROUND.repeat(res * res);
+    let x0 = (-shift.x).max(0) as usize;
+    let x1 = (res as i32 - shift.x).clamp(0, res as i32) as usize;
+    if x0 < x1 {
+        for y in 0..res {
+            let sy = y as i32 + shift.y;
+            if sy < 0 || sy >= res as i32 { continue; }
+            let sx0 = (x0 as i32 + shift.x) as usize;
+            let src = (sy as usize * res + sx0) * 4;
+            let dst = (y * res + x0) * 4;
+            let len = (x1 - x0) * 4;
+            out[dst..dst + len].copy_from_slice(&data[src..src + len]);
+        }
+    }
+    *data = out;
+}
+
+/// Calls `f` with every texel of `coord`'s block (RGBA8) and its local
+/// position inside the tile, in texels.
+fn for_block(data: &mut [u8], minimap: &Minimap, coord: IVec2, mut f: impl FnMut(&mut [u8], UVec2)) {
+    let tpt = minimap.texels_per_tile;
+    let res = minimap.resolution();
+    let block = (coord - minimap.origin).as_uvec2() * tpt;
+    for z in 0..tpt {
+        for x in 0..tpt {
+            let i = (((block.y + z) * res + block.x + x) * 4) as usize;
+            f(&mut data[i..i + 4], UVec2::new(x, z));
+        }
+    }
+}
+
+fn map_block(data: &mut [u8], minimap: &Minimap, coord: IVec2, mut f: impl FnMut(&mut [u8])) {
+    for_block(data, minimap, coord, |px, _| f(px));
+}
+
+/// Fills `coord`'s block with `color` sampled at each texel center (local space).
+fn draw_block(data: &mut [u8], minimap: &Minimap, coord: IVec2, tile_origin: Vec2, tile_size: f32, color: impl Fn(Vec2) -> [u8; 4]) {
+    let step = tile_size / minimap.texels_per_tile as f32;
+    for_block(data, minimap, coord, |px, t| {
+        px.copy_from_slice(&color(tile_origin + (t.as_vec2() + 0.5) * step));
+    });
+}
+
+/// Splat color with hill shading at a local-space position, water below sea level.
+fn thumbnail_color(
+    heightfield: &TerrainHeightfield,
+    shading: &TerrainShadingSettings,
+    water: &WaterSettings,
+    climate: &ClimateState,
+    xz: Vec2,
+) -> [u8; 4] {
+    let Some(height) = heightfield.height_at(xz) else { return BACKGROUND };
+    let normal = heightfield.normal_at(xz).unwrap_or(Vec3::Y);
+    let slope = normal.y.clamp(-1.0, 1.0).acos().to_degrees();
+    let weights = shading.procedural_weights(climate, height, slope, water.sea_level);
+
+    let mut color = Vec3::ZERO;
+    for (w, c) in weights.iter().zip(shading.layer_colors) {
+        color += *w * c.to_linear().to_vec3();
+    }
+    // light from the north-west, like a printed relief map
+    let light = Vec3::new(-1.0, 1.5, -1.0).normalize();
+    color *= 0.55 + 0.45 * normal.dot(light).max(0.0);
+
+    if height < water.sea_level {
+        let depth = water.sea_level - height;
+        let t = (0.5 + depth * water.absorption).min(1.0);
+        color = color.lerp(water.deep_color.to_linear().to_vec3(), t);
+    }
+    Color::LinearRgba(LinearRgba::from_vec3(color)).to_srgba().to_u8_array()
+}
@@ -1,7 +1,41 @@
 pub mod material;
+pub mod biome;
+pub mod climate;
+pub mod cliffs;
+#[cfg(feature = "texture-compression")]
+pub mod compression;
+pub mod debug;
+pub mod decal;
+#[cfg(feature = "egui")]
+pub mod debug_ui;
+pub mod diagnostics;
+pub mod edit;
 pub mod flatmesh;
+pub mod gpu_generation;
+pub mod heightfield;
+pub mod holes;
+pub mod impostor;
 pub mod meshgen;
+pub mod metadata;
+pub mod minimap;
+#[cfg(feature = "nav")]
+pub mod nav;
+pub mod origin;
+pub mod pathfinding;
+pub mod shading;
+pub mod shadows;
+pub mod snapshot;
+pub mod spawn_point;
 pub mod systems;
+pub mod texture_pool;
 pub mod plugin;
+pub mod requests;
+pub mod rng;
+pub mod scatter;
+pub mod vegetation;
+pub mod water;
+pub mod wind;
+#[cfg(feature = "picking")]
+pub mod picking;
 
 pub use plugin::TerrainPlugin;
@@ -0,0 +1,199 @@
+//! Navigation mesh input from the streamed tiles (`nav` feature).
+//!
+//! Recast-style bakers (oxidized_navigation and the like) want triangle
+//! geometry per area. `TerrainNavPlugin` turns every loaded tile's CPU
+//! heights into a displaced triangle soup (`NavTileSource`), keeps it in
+//! `NavTiles` and announces it with `NavTileUpdated` / `NavTileRemoved`,
+//! following `TileSpawned` / `TileDespawned`, brush edits, GPU height
+//! readbacks and rebases. A baker integration listens to those events and
+//! (re)bakes the matching nav tile.
+//!
+//! The soup has no skirts. Border vertices sit exactly on the shared edge
+//! samples, so neighbouring tiles meet, and `NavSettings::border_cells` adds
+//! a ring of the neighbours' cells for bakers that erode or filter across
+//! tile edges. Cells touching a hole or an unloaded neighbour are left out.
+//! Positions are local (render) space, like everything else here; they are
+//! rebuilt after a `WorldRebased`.
+
+use bevy::ecs::event::EventCursor;
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::edit::TileHeightsEdited;
+use super::gpu_generation::TileHeightsReady;
+use super::heightfield::TerrainHeightfield;
+use super::origin::WorldRebased;
+use super::systems::{collect_finished_tasks_system, TileDespawned, TileSpawned};
+
+pub struct TerrainNavPlugin;
+impl Plugin for TerrainNavPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<NavSettings>()
+            .init_resource::<NavTiles>()
+            .add_event::<NavTileUpdated>()
+            .add_event::<NavTileRemoved>()
+            .add_systems(Update, sync_nav_tiles_system.after(collect_finished_tasks_system));
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct NavSettings {
+    /// Rings of neighbouring cells included around each tile.
+    pub border_cells: u32,
+    /// Use every Nth height sample; 1 = full resolution.
+    pub stride: u32,
+}
+impl Default for NavSettings {
+    fn default() -> Self {
+        Self { border_cells: 1, stride: 2 }
+    }
+}
+
+/// Triangle soup of one tile, local-space positions.
+#[derive(Clone, Debug, Default)]
+pub struct NavTileGeometry {
+    pub coord: IVec2,
+    pub positions: Vec<Vec3>,
+    /// Counter-clockwise seen from above, three per triangle.
+    pub indices: Vec<u32>,
+}
+
+/// Where nav geometry comes from; implemented by `TerrainHeightfield`.
+pub trait NavTileSource {
+    /// Geometry of a loaded tile, `None` if it has no CPU heights.
+    fn nav_geometry(&self, coord: IVec2, settings: &NavSettings) -> Option<NavTileGeometry>;
+}
+
+impl NavTileSource for TerrainHeightfield {
+    fn nav_geometry(&self, coord: IVec2, settings: &NavSettings) -> Option<NavTileGeometry> {
+        self.tile(coord)?;
+        let stride = settings.stride.max(1) as i64;
+        let last = self.resolution as i64 - 1;
+        // the last sample is always included, even when stride doesn't divide it
+        let mut samples: Vec<i64> = (0..last).step_by(stride as usize).chain([last]).collect();
+        for ring in 1..=settings.border_cells as i64 {
+            samples.insert(0, -ring * stride);
+            samples.push(last + ring * stride);
+        }
+
+        let origin = self.tile_origin(coord);
+        let step = self.cell_size();
+        let m = samples.len();
+        let mut positions = Vec::with_capacity(m * m);
+        let mut index: Vec<Option<u32>> = Vec::with_capacity(m * m);
+        for &z in &samples {
+            for &x in &samples {
+                let p = origin + Vec2::new(x as f32, z as f32) * step;
+                index.push(self.height_at(p).map(|y| {
+                    positions.push(Vec3::new(p.x, y, p.y));
+                    positions.len() as u32 - 1
+                }));
+            }
+        }
+        let mut indices = Vec::with_capacity((m - 1) * (m - 1) * 6);
+        for z in 0..m - 1 {
+            for x in 0..m - 1 {
+                let (a, b, c, d) = (index[z * m + x], index[z * m + x + 1], index[(z + 1) * m + x], index[(z + 1) * m + x + 1]);
+                let (Some(a), Some(b), Some(c), Some(d)) = (a, b, c, d) else { continue };
+                // normals point up (+Y)
+                indices.extend_from_slice(&[a, c, b, b, c, d]);
+            }
+        }
+        Some(NavTileGeometry { coord, positions, indices })
+    }
+}
+
+/// Nav geometry of every loaded tile with CPU heights.
+#[derive(Resource, Default)]
+pub struct NavTiles {
+    tiles: HashMap<IVec2, Arc<NavTileGeometry>>,
+}
+
+impl NavTiles {
+    pub fn get(&self, coord: IVec2) -> Option<&Arc<NavTileGeometry>> {
+        self.tiles.get(&coord)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&IVec2, &Arc<NavTileGeometry>)> {
+        self.tiles.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+}
+
+/// A tile's nav geometry is new or changed; (re)bake its nav tile.
+#[derive(Event, Clone)]
+pub struct NavTileUpdated {
+    pub coord: IVec2,
+    pub geometry: Arc<NavTileGeometry>,
+}
+
+/// The tile unloaded; drop its nav tile.
+#[derive(Event, Clone, Copy)]
+pub struct NavTileRemoved {
+    pub coord: IVec2,
+}
+
+/// Cursors for the events of optional plugins, read only when registered.
+#[derive(Default)]
+pub struct NavEventCursors {
+    edited: EventCursor<TileHeightsEdited>,
+    readbacks: EventCursor<TileHeightsReady>,
+}
+
+pub fn sync_nav_tiles_system(
+    settings: Res<NavSettings>,
+    heightfield: Res<TerrainHeightfield>,
+    mut nav: ResMut<NavTiles>,
+    mut spawned: EventReader<TileSpawned>,
+    mut despawned: EventReader<TileDespawned>,
+    mut rebased: EventReader<WorldRebased>,
+    edited: Option<Res<Events<TileHeightsEdited>>>,
+    readbacks: Option<Res<Events<TileHeightsReady>>>,
+    mut cursors: Local<NavEventCursors>,
+    mut updated: EventWriter<NavTileUpdated>,
+    mut removed: EventWriter<NavTileRemoved>,
+) {
+    for ev in despawned.read() {
+        if nav.tiles.remove(&ev.coord).is_some() {
+            removed.write(NavTileRemoved { coord: ev.coord });
+        }
+    }
+
+    let mut dirty: Vec<IVec2> = spawned.read().map(|ev| ev.coord).collect();
+    if let Some(events) = edited {
+        dirty.extend(cursors.edited.read(&events).map(|ev| ev.coord));
+    }
+    if let Some(events) = readbacks {
+        dirty.extend(cursors.readbacks.read(&events).map(|ev| ev.coord));
+    }
+    // a new tile fills in its neighbours' border rings
+    if settings.border_cells > 0 {
+        let around: Vec<IVec2> = dirty
+            .iter()
+            .flat_map(|c| (-1..=1).flat_map(move |z| (-1..=1).map(move |x| *c + IVec2::new(x, z))))
+            .filter(|c| nav.tiles.contains_key(c))
+            .collect();
+        dirty.extend(around);
+    }
+    if settings.is_changed() || rebased.read().count() > 0 {
+        dirty.extend(heightfield.tiles().map(|(c, _)| *c));
+    }
+    dirty.sort_by_key(|c| (c.x, c.y));
+    dirty.dedup();
+
+    for coord in dirty {
+        let Some(geometry) = heightfield.nav_geometry(coord, &settings) else { continue };
+        let geometry = Arc::new(geometry);
+        nav.tiles.insert(coord, geometry.clone());
+        updated.write(NavTileUpdated { coord, geometry });
+    }
+}
@@ -0,0 +1,99 @@
+//! Floating origin for very large worlds.
+//!
+//! Far from the origin, `f32` transforms lose precision and the terrain starts
+//! to jitter. `FloatingOriginPlugin` watches the active camera and, once it
+//! strays past `FloatingOrigin::threshold`, shifts tiles, cameras and loaders
+//! back by a whole number of tiles. The accumulated shift is published as
+//! `WorldOffset`, so `true position = local position + offset`.
+//!
+//! Tile coords are always true (unshifted) coords: generation, edits and
+//! biomes are unaffected by rebasing. Anything else that keeps root-level
+//! world positions should listen for `WorldRebased` and move itself.
+
+use bevy::math::DVec2;
+use bevy::prelude::*;
+
+use super::heightfield::TerrainHeightfield;
+use super::systems::{queue_and_spawn_tasks_system, TerrainConfig, Tile, TileAttachment, TileLoader};
+
+/// Shift between local (render) space and true world space on the XZ plane.
+///
+/// Always a whole number of tiles. Owned by `TerrainPlugin` so coord math is
+/// offset-aware even without `FloatingOriginPlugin` (it just stays zero).
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq)]
+pub struct WorldOffset(pub DVec2);
+
+impl WorldOffset {
+    /// True world XZ of a local-space position.
+    pub fn to_world(&self, local_xz: Vec2) -> DVec2 {
+        local_xz.as_dvec2() + self.0
+    }
+
+    /// Local-space XZ of a true world position.
+    pub fn to_local(&self, world_xz: DVec2) -> Vec2 {
+        (world_xz - self.0).as_vec2()
+    }
+
+    /// Local-space position of a tile's min corner.
+    pub fn tile_origin(&self, coord: IVec2, tile_size: f32) -> Vec2 {
+        self.to_local(coord.as_dvec2() * tile_size as f64)
+    }
+}
+
+/// Sent after a rebase. `delta` was added to `WorldOffset` and subtracted
+/// from every shifted transform.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct WorldRebased {
+    pub delta: DVec2,
+}
+
+#[derive(Resource, Clone)]
+pub struct FloatingOrigin {
+    /// Horizontal distance from the local origin at which the camera triggers a rebase.
+    pub threshold: f32,
+}
+impl Default for FloatingOrigin {
+    fn default() -> Self {
+        Self { threshold: 4096.0 }
+    }
+}
+
+/// Opt-in: without it, `WorldOffset` stays zero and nothing is shifted.
+pub struct FloatingOriginPlugin;
+impl Plugin for FloatingOriginPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<FloatingOrigin>()
+            .add_systems(Update, rebase_origin_system.before(queue_and_spawn_tasks_system));
+    }
+}
+
+pub fn rebase_origin_system(
+    settings: Res<FloatingOrigin>,
+    cfg: Res<TerrainConfig>,
+    mut offset: ResMut<WorldOffset>,
+    mut heightfield: ResMut<TerrainHeightfield>,
+    mut rebased: EventWriter<WorldRebased>,
+    q_camera: Query<(Entity, &Camera), With<Camera3d>>,
+    mut q_shift: Query<
+        &mut Transform,
+        (Without<ChildOf>, Or<(With<Tile>, With<Camera>, With<TileLoader>, With<TileAttachment>)>),
+    >,
+) {
+    let Some((camera, _)) = q_camera.iter().find(|(_, c)| c.is_active) else { return };
+    let Ok(xf) = q_shift.get(camera) else { return };
+    let p = xf.translation.xz();
+    if p.length() < settings.threshold { return; }
+
+    let size = cfg.tile_size as f64;
+    let delta = (p.as_dvec2() / size).round() * size;
+    let shift = delta.as_vec2();
+    for mut xf in q_shift.iter_mut() {
+        xf.translation.x -= shift.x;
+        xf.translation.z -= shift.y;
+    }
+    offset.0 += delta;
+    heightfield.world_offset = offset.0;
+    info!("terrain: rebased origin by {:?}, world offset now {:?}", delta, offset.0);
+    rebased.write(WorldRebased { delta });
+}
@@ -0,0 +1,308 @@
+//! Grid A* over the CPU heights, for games that don't want a nav mesh.
+//!
+//! The grid is anchored at the start point, `PathOptions::cell_size` apart,
+//! with 8-connected moves. A move costs its length times
+//! `1 + slope_cost · grade` and is blocked when its grade is steeper than
+//! `max_slope_deg`; cells in holes or below `sea_level` are blocked. The
+//! exact end points are joined to the grid under the same slope limit. The
+//! cell path is then shortened by skipping to the farthest point that is in
+//! line of sight (`TerrainHeightfield::line_of_sight`) and walkable all the
+//! way. Points are on the ground, local space.
+//!
+//! The search never leaves the loaded tiles. When it fails after running
+//! into unloaded ones, `PathError::Unloaded` lists them, so the caller can
+//! send `RequestTiles` for them and try again once `TilesReady` fires.
+
+use bevy::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use super::heightfield::{LosResult, TerrainHeightfield};
+
+#[derive(Clone, Copy, Debug)]
+pub struct PathOptions {
+    /// Steepest move allowed, degrees.
+    pub max_slope_deg: f32,
+    /// Grid spacing, world units.
+    pub cell_size: f32,
+    /// Give up after expanding this many cells.
+    pub max_expansions: usize,
+    /// Cells below this height are blocked (`WaterSettings::sea_level`).
+    pub sea_level: Option<f32>,
+    /// Extra cost per unit of grade; 0 = shortest path.
+    pub slope_cost: f32,
+    /// Height above the ground at which shortcuts are checked for line of sight.
+    pub clearance: f32,
+}
+impl Default for PathOptions {
+    fn default() -> Self {
+        Self {
+            max_slope_deg: 35.0,
+            cell_size: 1.0,
+            max_expansions: 20_000,
+            sea_level: None,
+            slope_cost: 2.0,
+            clearance: 0.5,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathError {
+    /// No walkable route within the loaded tiles.
+    NoPath,
+    /// `max_expansions` was reached.
+    TooFar,
+    /// An endpoint, or every route found so far, needs these tiles loaded.
+    Unloaded { coords: Vec<IVec2> },
+}
+
+/// Open-set entry, ordered for a min-heap on `f`.
+#[derive(Clone, Copy, PartialEq)]
+struct Open {
+    f: f32,
+    cell: IVec2,
+}
+impl Eq for Open {}
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.f.total_cmp(&self.f).then_with(|| (other.cell.x, other.cell.y).cmp(&(self.cell.x, self.cell.y)))
+    }
+}
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+const MOVES: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+enum Ground {
+    Walkable(f32),
+    Blocked,
+    Unloaded(IVec2),
+}
+
+impl TerrainHeightfield {
+    /// Walkable route from `from` to `to`, both ends included; `None` if
+    /// there is none within the loaded tiles and `opts` limits.
+    pub fn find_path(&self, from: Vec2, to: Vec2, opts: PathOptions) -> Option<Vec<Vec3>> {
+        self.try_find_path(from, to, opts).ok()
+    }
+
+    /// `find_path`, telling why it failed.
+    pub fn try_find_path(&self, from: Vec2, to: Vec2, opts: PathOptions) -> Result<Vec<Vec3>, PathError> {
+        let opts = PathOptions { cell_size: opts.cell_size.max(1e-2), ..opts };
+        let max_grade = opts.max_slope_deg.clamp(0.0, 89.9).to_radians().tan();
+        // fail fast on endpoints that can't be reached at all
+        let (start_y, goal_y) = match (self.ground(from, &opts), self.ground(to, &opts)) {
+            (Ground::Walkable(a), Ground::Walkable(b)) => (a, b),
+            (a, b) => {
+                let coords: Vec<IVec2> = [a, b]
+                    .into_iter()
+                    .filter_map(|g| if let Ground::Unloaded(c) = g { Some(c) } else { None })
+                    .collect();
+                if coords.is_empty() { return Err(PathError::NoPath); }
+                return Err(PathError::Unloaded { coords });
+            }
+        };
+
+        let step = opts.cell_size;
+        let pos = |cell: IVec2| from + cell.as_vec2() * step;
+        let goal = ((to - from) / step).round().as_ivec2();
+        let heuristic = |cell: IVec2| (goal - cell).as_vec2().length() * step;
+
+        // cell -> (cost so far, parent, height)
+        let mut nodes: HashMap<IVec2, (f32, IVec2, f32)> = HashMap::new();
+        let mut open = BinaryHeap::new();
+        let mut closed: HashSet<IVec2> = HashSet::new();
+        let mut unloaded: HashSet<IVec2> = HashSet::new();
+        nodes.insert(IVec2::ZERO, (0.0, IVec2::ZERO, start_y));
+        open.push(Open { f: heuristic(IVec2::ZERO), cell: IVec2::ZERO });
+
+        let mut expansions = 0;
+        let mut reached = false;
+        while let Some(Open { cell, .. }) = open.pop() {
+            if !closed.insert(cell) { continue; }
+            if cell == goal {
+                reached = true;
+                break;
+            }
+            expansions += 1;
+            if expansions > opts.max_expansions { return Err(PathError::TooFar); }
+
+            let (g, _, y) = nodes[&cell];
+            for m in MOVES {
+                let next = cell + m;
+                if closed.contains(&next) { continue; }
+                let ny = match self.ground(pos(next), &opts) {
+                    Ground::Walkable(h) => h,
+                    Ground::Blocked => continue,
+                    Ground::Unloaded(c) => {
+                        unloaded.insert(c);
+                        continue;
+                    }
+                };
+                let run = m.as_vec2().length() * step;
+                let grade = (ny - y).abs() / run;
+                if grade > max_grade { continue; }
+                let cost = g + run * (1.0 + opts.slope_cost.max(0.0) * grade);
+                if nodes.get(&next).is_some_and(|n| n.0 <= cost) { continue; }
+                nodes.insert(next, (cost, cell, ny));
+                open.push(Open { f: cost + heuristic(next), cell: next });
+            }
+        }
+        if !reached {
+            if unloaded.is_empty() { return Err(PathError::NoPath); }
+            let mut coords: Vec<IVec2> = unloaded.into_iter().collect();
+            coords.sort_by_key(|c| (c.y, c.x));
+            return Err(PathError::Unloaded { coords });
+        }
+
+        let mut cells = vec![goal];
+        if goal == IVec2::ZERO { cells.push(goal); }
+        while let Some(&c) = cells.last() {
+            if c == IVec2::ZERO { break; }
+            cells.push(nodes[&c].1);
+        }
+        cells.reverse();
+        let mut points: Vec<Vec3> = cells
+            .iter()
+            .map(|c| {
+                let p = pos(*c);
+                Vec3::new(p.x, nodes[c].2, p.y)
+            })
+            .collect();
+        // the ends go exactly where asked, not to the nearest cell; the
+        // search only checked the cell centers, so check the new segments
+        points[0] = Vec3::new(from.x, start_y, from.y);
+        if let Some(last) = points.last_mut() {
+            *last = Vec3::new(to.x, goal_y, to.y);
+        }
+        let last = points.len() - 1;
+        if !self.ground_walkable(points[0], points[1], &opts, max_grade)
+            || !self.ground_walkable(points[last - 1], points[last], &opts, max_grade)
+        {
+            return Err(PathError::NoPath);
+        }
+        Ok(self.shortcut_path(&points, &opts, max_grade))
+    }
+
+    fn ground(&self, xz: Vec2, opts: &PathOptions) -> Ground {
+        match self.height_at(xz) {
+            Some(h) if opts.sea_level.is_some_and(|s| h < s) => Ground::Blocked,
+            Some(h) => Ground::Walkable(h),
+            None if self.is_hole(xz) => Ground::Blocked,
+            None => Ground::Unloaded(self.world_to_coord(xz)),
+        }
+    }
+
+    /// From each kept point, jump to the farthest later one reachable in a
+    /// straight line.
+    fn shortcut_path(&self, points: &[Vec3], opts: &PathOptions, max_grade: f32) -> Vec<Vec3> {
+        let mut out = vec![points[0]];
+        let mut i = 0;
+        while i + 1 < points.len() {
+            let j = (i + 2..points.len())
+                .rev()
+                .find(|&j| self.straight_walkable(points[i], points[j], opts, max_grade))
+                .unwrap_or(i + 1);
+            out.push(points[j]);
+            i = j;
+        }
+        out
+    }
+
+    /// Line of sight at `clearance`, and the ground under the segment walkable.
+    fn straight_walkable(&self, a: Vec3, b: Vec3, opts: &PathOptions, max_grade: f32) -> bool {
+        let lift = Vec3::Y * opts.clearance;
+        self.line_of_sight(a + lift, b + lift) == LosResult::Clear && self.ground_walkable(a, b, opts, max_grade)
+    }
+
+    /// The ground under the segment walkable every half cell.
+    fn ground_walkable(&self, a: Vec3, b: Vec3, opts: &PathOptions, max_grade: f32) -> bool {
+        let len = a.xz().distance(b.xz());
+        let samples = (len / (opts.cell_size * 0.5)).ceil().max(1.0) as usize;
+        let run = len / samples as f32;
+        let mut prev = a.y;
+        for k in 1..=samples {
+            let xz = a.xz().lerp(b.xz(), k as f32 / samples as f32);
+            let Ground::Walkable(y) = self.ground(xz, opts) else { return false };
+            if (y - prev).abs() > max_grade * run { return false; }
+            prev = y;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two 8×8 tiles side by side, one sample per world unit.
+    fn field(height: impl Fn(Vec2) -> f32) -> TerrainHeightfield {
+        TerrainHeightfield::from_fn(8.0, 9, &[IVec2::ZERO, IVec2::X], height)
+    }
+
+    fn opts() -> PathOptions {
+        PathOptions { cell_size: 2.0, ..default() }
+    }
+
+    #[test]
+    fn flat_ground_shortcuts_to_a_straight_line() {
+        let field = field(|_| 1.0);
+        let path = field.try_find_path(Vec2::new(1.0, 1.0), Vec2::new(14.0, 6.0), opts()).unwrap();
+        assert_eq!(path, vec![Vec3::new(1.0, 1.0, 1.0), Vec3::new(14.0, 1.0, 6.0)]);
+    }
+
+    #[test]
+    fn steep_wall_blocks_and_gentle_ramp_does_not() {
+        let (from, to) = (Vec2::new(1.0, 4.0), Vec2::new(15.0, 4.0));
+        let wall = field(|p| if p.x >= 8.0 { 6.0 } else { 0.0 });
+        assert!(wall.try_find_path(from, to, opts()).is_err());
+
+        let ramp = field(|p| 0.3 * p.x);
+        let path = ramp.try_find_path(from, to, opts()).unwrap();
+        let max_grade = opts().max_slope_deg.to_radians().tan();
+        for seg in path.windows(2) {
+            assert!((seg[1].y - seg[0].y).abs() <= max_grade * seg[0].xz().distance(seg[1].xz()) + 1e-4);
+        }
+    }
+
+    #[test]
+    fn last_segment_is_slope_checked() {
+        // `to` sits on a cliff face between its grid cell (x = 13) and the next
+        let cliff = field(|p| if p.x >= 14.0 { 10.0 } else { 0.0 });
+        let to = Vec2::new(13.9, 4.0);
+        assert!(cliff.height_at(to).is_some_and(|h| h > 8.0));
+        assert_eq!(cliff.try_find_path(Vec2::new(1.0, 4.0), to, opts()), Err(PathError::NoPath));
+        assert!(cliff.try_find_path(Vec2::new(1.0, 4.0), Vec2::new(12.9, 4.0), opts()).is_ok());
+    }
+
+    #[test]
+    fn sea_level_blocks_endpoints() {
+        let field = field(|p| if p.x < 4.0 { -1.0 } else { 1.0 });
+        let sea = PathOptions { sea_level: Some(0.0), ..opts() };
+        assert_eq!(field.try_find_path(Vec2::new(1.0, 4.0), Vec2::new(12.0, 4.0), sea), Err(PathError::NoPath));
+        let path = field.try_find_path(Vec2::new(6.0, 4.0), Vec2::new(12.0, 4.0), sea).unwrap();
+        assert!(path.iter().all(|p| p.y >= 0.0));
+    }
+
+    #[test]
+    fn unloaded_goal_lists_its_tile() {
+        let field = field(|_| 0.0);
+        assert_eq!(
+            field.try_find_path(Vec2::new(1.0, 4.0), Vec2::new(20.0, 4.0), opts()),
+            Err(PathError::Unloaded { coords: vec![IVec2::new(2, 0)] })
+        );
+    }
+}
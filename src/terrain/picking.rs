@@ -0,0 +1,54 @@
+//! Picking backend for the streamed terrain.
+//!
+//! Tiles share one flat mesh and are displaced in the vertex shader, so
+//! `MeshPickingPlugin` would hit them at y=0. This backend raymarches the CPU
+//! height fields in `TerrainHeightfield` instead and reports the tile entity.
+
+use bevy::picking::backend::prelude::*;
+use bevy::picking::backend::HitData;
+use bevy::prelude::*;
+
+use super::heightfield::TerrainHeightfield;
+use super::systems::TerrainState;
+
+/// Added by `TerrainPlugin` when the `picking` feature is enabled.
+pub struct TerrainPickingPlugin;
+impl Plugin for TerrainPickingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TerrainPickingSettings>()
+            .add_systems(PreUpdate, terrain_picking_system.in_set(PickSet::Backend));
+    }
+}
+
+#[derive(Resource)]
+pub struct TerrainPickingSettings {
+    /// Rays are marched at most this far from the camera.
+    pub max_distance: f32,
+}
+impl Default for TerrainPickingSettings {
+    fn default() -> Self {
+        Self { max_distance: 4000.0 }
+    }
+}
+
+pub fn terrain_picking_system(
+    settings: Res<TerrainPickingSettings>,
+    ray_map: Res<RayMap>,
+    heightfield: Res<TerrainHeightfield>,
+    state: Res<TerrainState>,
+    q_cameras: Query<&Camera>,
+    q_pickable: Query<&Pickable>,
+    mut output: EventWriter<PointerHits>,
+) {
+    for (&ray_id, &ray) in ray_map.iter() {
+        let Ok(camera) = q_cameras.get(ray_id.camera) else { continue };
+        let Some(hit) = heightfield.raycast(ray, settings.max_distance) else { continue };
+        // Pending/unloaded tiles never produce hits (no CPU heights yet)
+        let Some(tile) = state.tiles.get(&hit.coord) else { continue };
+        if q_pickable.get(tile.entity).is_ok_and(|p| !p.is_hoverable) { continue; }
+
+        let data = HitData::new(ray_id.camera, hit.distance, Some(hit.position), Some(hit.normal));
+        output.write(PointerHits::new(ray_id.pointer, vec![(tile.entity, data)], camera.order as f32));
+    }
+}
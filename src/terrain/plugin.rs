@@ -1,29 +1,169 @@
 use bevy::prelude::*;
+use bevy::render::view::VisibilitySystems;
+use bevy::render::RenderPlugin;
 use crate::terrain::material::TerrainMaterialPlugin;
+use crate::terrain::shadows::TerrainShadowPlugin;
+use crate::terrain::texture_pool::{TileTexturePool, retire_tile_textures};
+use crate::terrain::gpu_generation::{
+    TerrainGpuGenerationPlugin, apply_height_readbacks_system, dispatch_gpu_tiles_system,
+    finish_gpu_tiles_system, queue_height_readbacks_system,
+};
 use crate::terrain::flatmesh::init_shared_mesh;
+use crate::terrain::heightfield::TerrainHeightfield;
+use crate::terrain::holes::{TerrainHoles, apply_terrain_holes_system};
+use crate::terrain::biome::BiomeSettings;
+use crate::terrain::climate::{
+    AppliedClimate, ClimateSettings, ClimateState, Season, apply_season_system, ease_climate_system,
+};
+use crate::terrain::diagnostics::{
+    TerrainStatsOverlay, register_terrain_diagnostics, terrain_diagnostics_system, terrain_stats_overlay_system,
+};
+use crate::terrain::debug::{TerrainDebugOverlay, draw_debug_overlay_system};
+use crate::terrain::decal::{TerrainDecalPlugin, TerrainDecals};
+use crate::terrain::edit::{TerrainEdits, sync_edits_base_hash_system};
+use crate::terrain::origin::{WorldOffset, WorldRebased};
+use crate::terrain::spawn_point::{FlatAreaDebug, draw_flat_area_debug_system};
+use crate::terrain::water::WaterSettings;
+use crate::terrain::wind::Wind;
+use crate::terrain::requests::{
+    TileRequests, RequestTiles, ReleaseTiles, TilesReady,
+    process_tile_requests_system, tiles_ready_system,
+};
+use crate::terrain::shading::{
+    GridOverlaySettings, TerrainShadingSettings,
+    apply_shading_settings_system, log_splat_coverage_system, sync_grid_overlay_system, toggle_contours_system,
+};
 use crate::terrain::systems::{
-    TerrainConfig, TerrainState,
+    RegenerateTerrain, TerrainConfig, TerrainState, TerrainStreaming, TileSpawned, TileDespawned,
     queue_and_spawn_tasks_system,
     collect_finished_tasks_system,
     garbage_collect_tiles_system,
+    regenerate_on_config_change_system,
+    sync_tile_bounds_system,
+    cull_distant_tiles_system,
 };
 
 pub struct TerrainPlugin;
 
 impl Plugin for TerrainPlugin {
     fn build(&self, app: &mut App) {
+        register_terrain_diagnostics(app);
+        // editing these in an inspector marks them changed like any ResMut write
+        #[cfg(feature = "inspector")]
+        app
+            .register_type::<TerrainConfig>()
+            .register_type::<crate::terrain::systems::TileLoader>()
+            .register_type::<crate::terrain::material::TileParams>();
         app
             .init_resource::<TerrainConfig>()
             .init_resource::<TerrainState>()
-            .add_plugins(TerrainMaterialPlugin) // <- this must be the new one
-            .add_systems(Startup, init_shared_mesh)
+            .init_resource::<TerrainStreaming>()
+            .init_resource::<TerrainShadingSettings>()
+            .init_resource::<GridOverlaySettings>()
+            .init_resource::<TerrainDebugOverlay>()
+            .init_resource::<TerrainHeightfield>()
+            .init_resource::<WaterSettings>()
+            .init_resource::<BiomeSettings>()
+            .init_resource::<ClimateState>()
+            .init_resource::<ClimateSettings>()
+            .init_resource::<AppliedClimate>()
+            .init_resource::<Season>()
+            .init_resource::<Wind>()
+            .init_resource::<TerrainEdits>()
+            .init_resource::<TileRequests>()
+            .init_resource::<WorldOffset>()
+            .init_resource::<TileTexturePool>()
+            .init_resource::<TerrainDecals>()
+            .init_resource::<TerrainHoles>()
+            .init_resource::<FlatAreaDebug>()
+            .add_event::<TileSpawned>()
+            .add_event::<TileDespawned>()
+            .add_event::<RequestTiles>()
+            .add_event::<ReleaseTiles>()
+            .add_event::<TilesReady>()
+            .add_event::<WorldRebased>()
+            .add_event::<RegenerateTerrain>()
             .add_systems(
                 Update,
                 (
-                    queue_and_spawn_tasks_system,
-                    collect_finished_tasks_system,
-                    garbage_collect_tiles_system,
+                    regenerate_on_config_change_system
+                        .run_if(
+                            resource_changed::<TerrainConfig>
+                                .or(resource_changed::<TerrainShadingSettings>)
+                                .or(on_event::<RegenerateTerrain>),
+                        ),
+                    sync_edits_base_hash_system,
+                    process_tile_requests_system,
+                    queue_and_spawn_tasks_system.run_if(TerrainStreaming::dispatching),
+                    collect_finished_tasks_system.run_if(TerrainStreaming::collecting),
+                    tiles_ready_system,
+                    garbage_collect_tiles_system.run_if(TerrainStreaming::dispatching),
+                    apply_season_system.run_if(resource_changed::<Season>),
+                    ease_climate_system,
+                    apply_shading_settings_system.run_if(
+                        resource_changed::<TerrainShadingSettings>
+                            .or(resource_changed::<WaterSettings>)
+                            .or(resource_changed::<AppliedClimate>),
+                    ),
                 ).chain(),
+            )
+            .add_systems(Update, terrain_diagnostics_system.after(garbage_collect_tiles_system))
+            .add_systems(Update, apply_terrain_holes_system.after(collect_finished_tasks_system));
+
+        // Headless apps (servers, tests on `MinimalPlugins`) only get the
+        // streaming and CPU height layer. Add `DefaultPlugins` first.
+        if !app.is_plugin_added::<RenderPlugin>() {
+            return;
+        }
+        app
+            .add_plugins((TerrainMaterialPlugin, TerrainShadowPlugin, TerrainGpuGenerationPlugin, TerrainDecalPlugin))
+            .add_systems(Startup, init_shared_mesh)
+            .add_observer(retire_tile_textures)
+            .add_systems(
+                Update,
+                (
+                    dispatch_gpu_tiles_system,
+                    finish_gpu_tiles_system.run_if(TerrainStreaming::collecting),
+                    apply_height_readbacks_system,
+                    queue_height_readbacks_system,
+                )
+                    .chain()
+                    .after(collect_finished_tasks_system)
+                    .before(tiles_ready_system),
+            )
+            .add_systems(
+                PostUpdate,
+                sync_tile_bounds_system
+                    .after(VisibilitySystems::CalculateBounds)
+                    .before(VisibilitySystems::CheckVisibility),
+            )
+            .add_systems(Update, cull_distant_tiles_system.before(terrain_diagnostics_system))
+            .init_resource::<TerrainStatsOverlay>()
+            .add_systems(Update, terrain_stats_overlay_system.after(terrain_diagnostics_system))
+            .add_systems(Update, toggle_contours_system.before(apply_shading_settings_system))
+            .add_systems(Update, log_splat_coverage_system.after(collect_finished_tasks_system))
+            .add_systems(
+                Update,
+                sync_grid_overlay_system
+                    .run_if(
+                        resource_changed::<GridOverlaySettings>
+                            .or(resource_changed::<WorldOffset>)
+                            .or(on_event::<TileSpawned>),
+                    )
+                    .after(collect_finished_tasks_system),
+            )
+            .add_systems(
+                Update,
+                draw_debug_overlay_system
+                    .run_if(|overlay: Res<TerrainDebugOverlay>| overlay.enabled)
+                    .after(garbage_collect_tiles_system),
+            )
+            .add_systems(
+                Update,
+                draw_flat_area_debug_system.run_if(|debug: Res<FlatAreaDebug>| debug.enabled),
             );
+
+        #[cfg(feature = "picking")]
+        app.add_plugins(crate::terrain::picking::TerrainPickingPlugin);
     }
 }
@@ -0,0 +1,145 @@
+//! Scripted tile loading.
+//!
+//! `RequestTiles` adds coords to the streaming desired set until they are
+//! released with `ReleaseTiles` or the request times out; `TilesReady` fires
+//! once every coord of a request is loaded (e.g. to end a loading screen).
+//! Each request holds one reference per coord, so overlapping requests keep
+//! a tile alive until all of them let go.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use super::systems::{TerrainConfig, TerrainState};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TilePriority {
+    /// Queued with loader tiles by distance.
+    #[default]
+    Normal,
+    /// Dispatched before any loader-driven tile.
+    High,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct RequestTiles {
+    /// Caller-chosen id echoed by `TilesReady`.
+    pub request_id: u64,
+    pub coords: Vec<IVec2>,
+    pub priority: TilePriority,
+    /// Release automatically after this many seconds.
+    pub timeout_seconds: Option<f32>,
+}
+
+/// Drops one reference per coord, taken from the oldest request holding it.
+#[derive(Event, Clone, Debug)]
+pub struct ReleaseTiles {
+    pub coords: Vec<IVec2>,
+}
+
+#[derive(Event, Clone, Copy, Debug)]
+pub struct TilesReady {
+    pub request_id: u64,
+}
+
+struct ActiveRequest {
+    request_id: u64,
+    coords: Vec<IVec2>,
+    /// Coords this request still holds a reference on.
+    held: Vec<IVec2>,
+    priority: TilePriority,
+    expires_at: Option<f32>,
+    ready_sent: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct TileRequests {
+    /// Oldest first.
+    active: Vec<ActiveRequest>,
+    refcounts: HashMap<IVec2, u32>,
+}
+
+impl TileRequests {
+    /// Coords currently held by any request.
+    pub fn coords(&self) -> impl Iterator<Item = &IVec2> {
+        self.refcounts.keys()
+    }
+
+    pub fn contains(&self, coord: IVec2) -> bool {
+        self.refcounts.contains_key(&coord)
+    }
+
+    pub fn is_high_priority(&self, coord: IVec2) -> bool {
+        self.active.iter().any(|r| r.priority == TilePriority::High && r.held.contains(&coord))
+    }
+
+    fn retain(&mut self, coord: IVec2) {
+        *self.refcounts.entry(coord).or_insert(0) += 1;
+    }
+
+    fn release(&mut self, coord: IVec2) {
+        if let Some(n) = self.refcounts.get_mut(&coord) {
+            *n -= 1;
+            if *n == 0 { self.refcounts.remove(&coord); }
+        }
+    }
+}
+
+pub fn process_tile_requests_system(
+    time: Res<Time>,
+    mut requests: ResMut<TileRequests>,
+    mut requested: EventReader<RequestTiles>,
+    mut released: EventReader<ReleaseTiles>,
+) {
+    let now = time.elapsed_secs();
+    for req in requested.read() {
+        let mut held = req.coords.clone();
+        held.sort_by_key(|c| (c.x, c.y));
+        held.dedup();
+        for c in &held {
+            requests.retain(*c);
+        }
+        requests.active.push(ActiveRequest {
+            request_id: req.request_id,
+            coords: held.clone(),
+            held,
+            priority: req.priority,
+            expires_at: req.timeout_seconds.map(|t| now + t),
+            ready_sent: false,
+        });
+    }
+
+    for rel in released.read() {
+        for coord in &rel.coords {
+            let Some(req) = requests.active.iter_mut().find(|r| r.held.contains(coord)) else { continue };
+            req.held.retain(|c| c != coord);
+            requests.release(*coord);
+        }
+    }
+
+    // Timeouts release whatever the request still holds
+    let mut expired = Vec::new();
+    for req in requests.active.iter_mut() {
+        if req.expires_at.is_some_and(|t| now >= t) {
+            expired.append(&mut req.held);
+        }
+    }
+    for c in expired {
+        requests.release(c);
+    }
+    requests.active.retain(|r| !r.held.is_empty());
+}
+
+pub fn tiles_ready_system(
+    state: Res<TerrainState>,
+    cfg: Res<TerrainConfig>,
+    mut requests: ResMut<TileRequests>,
+    mut ready: EventWriter<TilesReady>,
+) {
+    for req in requests.active.iter_mut().filter(|r| !r.ready_sent) {
+        // coords rejected by `TerrainConfig::in_bounds` never load
+        if req.coords.iter().all(|c| state.tiles.contains_key(c) || !cfg.in_bounds(*c)) {
+            req.ready_sent = true;
+            ready.write(TilesReady { request_id: req.request_id });
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+
+/// Small deterministic RNG (SplitMix64) seeded from `(seed, coord, salt)`.
+///
+/// Everything placed per tile (vegetation, props, metadata) derives its stream
+/// from this so a given world seed always produces the same tile contents.
+#[derive(Clone)]
+pub struct TileRng(u64);
+
+impl TileRng {
+    pub fn new(seed: u32, coord: IVec2, salt: u32) -> Self {
+        let mut s = (seed as u64) << 32 | salt as u64;
+        s ^= (coord.x as u32 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        s ^= (coord.y as u32 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F).rotate_left(31);
+        let mut rng = Self(s);
+        rng.next_u64();
+        rng
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, lo: f32, hi: f32) -> f32 {
+        lo + (hi - lo) * self.next_f32()
+    }
+}
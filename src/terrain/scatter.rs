@@ -0,0 +1,450 @@
+//! Deterministic prop placement (trees, rocks, bushes) per tile.
+//!
+//! Each `ScatterLayer` runs a Poisson-disk pass over the tile, filtered by
+//! slope/height rules. Results are published as `PropPlacementReady`; layers
+//! with a `scene` are also spawned automatically as children of the tile.
+//! Densities are scaled by the blended `BiomeSettings` multipliers; changing
+//! either resource re-scatters loaded tiles without regenerating terrain.
+//!
+//! Spawned props are grouped per tile and layer. Past `impostor_distance` a
+//! group swaps to a billboard batch (see `impostor`), past `cull_distance`
+//! both are hidden.
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use std::sync::Arc;
+
+use super::biome::{BiomeSampler, BiomeSettings};
+use super::heightfield::{grid_normal, sample_bilinear, TerrainHeightfield};
+use super::impostor::{impostor_mesh, ImpostorMaterial, ImpostorMaterialPlugin, ImpostorParams};
+use super::origin::WorldOffset;
+use super::rng::TileRng;
+use super::systems::{collect_finished_tasks_system, TerrainConfig, TerrainState, TileSpawned};
+use super::wind::Wind;
+
+pub struct ScatterPlugin;
+impl Plugin for ScatterPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(ImpostorMaterialPlugin)
+            .init_resource::<ScatterSettings>()
+            .init_resource::<BiomeSettings>()
+            .init_resource::<ImpostorMaterials>()
+            .add_event::<PropPlacementReady>()
+            .add_systems(
+                Update,
+                (
+                    update_impostor_materials_system.run_if(resource_changed::<ScatterSettings>),
+                    spawn_scatter_tasks_system,
+                    collect_scatter_tasks_system,
+                    prop_lod_system,
+                )
+                    .chain()
+                    .after(collect_finished_tasks_system),
+            )
+            .add_systems(Update, update_impostor_wind_system.after(update_impostor_materials_system));
+    }
+}
+
+#[derive(Clone)]
+pub struct ScatterLayer {
+    pub name: String,
+    /// Minimum distance between two placements of this layer (Poisson-disk radius).
+    pub min_spacing: f32,
+    /// Fraction of Poisson candidates kept, 0..1.
+    pub density: f32,
+    /// Allowed slope band in degrees.
+    pub slope_range: (f32, f32),
+    /// -1 prefers convex ground (ridges), +1 concave (valleys, cliff bases), 0 ignores curvature.
+    pub curvature_bias: f32,
+    /// Allowed world height band.
+    pub height_range: (f32, f32),
+    pub scale_range: (f32, f32),
+    /// Spawned automatically for each placement when set.
+    pub scene: Option<Handle<Scene>>,
+    /// Billboard texture used past `impostor_distance`; without one the
+    /// full props stay up to `cull_distance`.
+    pub impostor: Option<Handle<Image>>,
+    /// Billboard width/height at scale 1 (base at the placement point).
+    pub impostor_size: Vec2,
+    /// Camera distance (to the tile center) where the tile swaps to impostors.
+    pub impostor_distance: f32,
+    /// Camera distance past which the layer is hidden on that tile.
+    pub cull_distance: f32,
+    /// Multiplier on `Wind::strength` for this layer's impostors; 0 = rigid.
+    pub wind_amplitude: f32,
+}
+
+impl ScatterLayer {
+    pub fn new(name: impl Into<String>, min_spacing: f32, density: f32) -> Self {
+        Self {
+            name: name.into(),
+            min_spacing,
+            density,
+            slope_range: (0.0, 90.0),
+            curvature_bias: 0.0,
+            height_range: (f32::NEG_INFINITY, f32::INFINITY),
+            scale_range: (1.0, 1.0),
+            scene: None,
+            impostor: None,
+            impostor_size: Vec2::new(4.0, 8.0),
+            impostor_distance: 200.0,
+            cull_distance: 600.0,
+            wind_amplitude: 1.0,
+        }
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct ScatterSettings {
+    pub layers: Vec<ScatterLayer>,
+    /// Width of the distance band around `impostor_distance` where both
+    /// representations are shown and impostors dither in per instance.
+    /// 0 switches whole tiles at once.
+    pub crossfade_width: f32,
+}
+impl Default for ScatterSettings {
+    fn default() -> Self {
+        Self {
+            crossfade_width: 16.0,
+            layers: vec![
+                ScatterLayer { slope_range: (0.0, 25.0), scale_range: (0.8, 1.2), wind_amplitude: 2.0, ..ScatterLayer::new("trees", 6.0, 0.6) },
+                ScatterLayer { slope_range: (25.0, 50.0), curvature_bias: 0.8, scale_range: (0.5, 1.5), wind_amplitude: 0.0, ..ScatterLayer::new("rocks", 3.0, 0.3) },
+                ScatterLayer { slope_range: (0.0, 30.0), scale_range: (0.7, 1.1), ..ScatterLayer::new("bushes", 2.5, 0.4) },
+            ],
+        }
+    }
+}
+
+/// One prop instance, in the tile's local space.
+#[derive(Clone, Copy, Debug)]
+pub struct PropPlacement {
+    pub position: Vec3,
+    pub rotation: Quat,
+    pub scale: f32,
+    /// Index into `ScatterSettings::layers`.
+    pub kind: usize,
+}
+
+/// All placements for a finished tile. Spawn entities as children of `tile`
+/// so they despawn with it.
+#[derive(Event, Clone)]
+pub struct PropPlacementReady {
+    pub coord: IVec2,
+    pub tile: Entity,
+    pub placements: Arc<[PropPlacement]>,
+}
+
+/// One layer's props on one tile, in either representation. Scene props are
+/// children of the `Full` entity; the `Impostor` entity is a single batch.
+#[derive(Component)]
+pub struct PropLod {
+    pub coord: IVec2,
+    pub kind: usize,
+    pub tier: PropTier,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PropTier {
+    Full,
+    Impostor,
+}
+
+/// Shared impostor material per layer (index = layer kind).
+#[derive(Resource, Default)]
+pub struct ImpostorMaterials(pub Vec<Option<Handle<ImpostorMaterial>>>);
+
+#[derive(Component)]
+pub struct ScatterTask {
+    pub coord: IVec2,
+    pub task: Task<Vec<PropPlacement>>,
+}
+
+pub fn spawn_scatter_tasks_system(
+    mut commands: Commands,
+    mut spawned: EventReader<TileSpawned>,
+    settings: Res<ScatterSettings>,
+    biomes: Res<BiomeSettings>,
+    cfg: Res<TerrainConfig>,
+    state: Res<TerrainState>,
+    heightfield: Res<TerrainHeightfield>,
+    q_props: Query<Entity, With<PropLod>>,
+) {
+    let mut tiles: Vec<(IVec2, Entity)> = spawned.read().map(|ev| (ev.coord, ev.entity)).collect();
+
+    // Config edits re-scatter every loaded tile (terrain itself is untouched)
+    let edited = |changed: bool, added: bool| changed && !added;
+    if edited(settings.is_changed(), settings.is_added()) || edited(biomes.is_changed(), biomes.is_added()) {
+        for e in &q_props {
+            commands.entity(e).despawn();
+        }
+        tiles = state.tiles.iter().map(|(c, t)| (*c, t.entity)).collect();
+    }
+
+    let pool = AsyncComputeTaskPool::get();
+    for (coord, entity) in tiles {
+        let Some(tile) = heightfield.tile(coord) else { continue };
+        let heights = tile.heights.clone();
+        let curvature = tile.curvature.clone();
+        let layers = settings.layers.clone();
+        let biomes = biomes.clone();
+        let (n, size, scale) = (heightfield.resolution, heightfield.tile_size, heightfield.height_scale);
+        let seed = cfg.seed;
+
+        let task = pool.spawn(async move {
+            let biomes = BiomeSampler::new(&biomes, seed);
+            let origin = coord.as_vec2() * size;
+            let mut out = Vec::new();
+            for (kind, layer) in layers.iter().enumerate() {
+                let rng = TileRng::new(seed, coord, 0x5CA7_0000 + kind as u32);
+                scatter_layer(layer, kind, &heights, &curvature, n, size, scale, origin, &biomes, rng, &mut out);
+            }
+            out
+        });
+        commands.entity(entity).insert(ScatterTask { coord, task });
+    }
+}
+
+/// Bridson Poisson-disk sampling over `[0, tile_size)²`, then rule filtering.
+fn scatter_layer(
+    layer: &ScatterLayer,
+    kind: usize,
+    heights: &[f32],
+    curvature: &[f32],
+    n: usize,
+    tile_size: f32,
+    height_scale: f32,
+    origin: Vec2,
+    biomes: &BiomeSampler,
+    mut rng: TileRng,
+    out: &mut Vec<PropPlacement>,
+) {
+    let step = tile_size / (n as f32 - 1.0);
+    for p in poisson_disk(tile_size, layer.min_spacing, &mut rng) {
+        let (yaw, scale, keep) = (rng.range(0.0, std::f32::consts::TAU), rng.range(layer.scale_range.0, layer.scale_range.1), rng.next_f32());
+        let local = p / step;
+        // bias acceptance by concavity: factor in [1 - |bias|, 1 + |bias|]
+        let concavity = (sample_bilinear(curvature, n, local) * height_scale * 4.0).tanh();
+        let density = layer.density * biomes.density_at(origin + p, &layer.name);
+        if keep >= density * (1.0 + layer.curvature_bias * concavity) { continue; }
+
+        let y = sample_bilinear(heights, n, local) * height_scale;
+        if y < layer.height_range.0 || y > layer.height_range.1 { continue; }
+        let slope = grid_normal(heights, n, step, height_scale, local).y.clamp(-1.0, 1.0).acos().to_degrees();
+        if slope < layer.slope_range.0 || slope > layer.slope_range.1 { continue; }
+
+        out.push(PropPlacement {
+            position: Vec3::new(p.x, y, p.y),
+            rotation: Quat::from_rotation_y(yaw),
+            scale,
+            kind,
+        });
+    }
+}
+
+pub fn poisson_disk(extent: f32, radius: f32, rng: &mut TileRng) -> Vec<Vec2> {
+    const ATTEMPTS: usize = 30;
+    let radius = radius.max(1e-3);
+    let cell = radius / std::f32::consts::SQRT_2;
+    let dim = (extent / cell).ceil() as usize;
+    let mut grid: Vec<Option<usize>> = vec![None; dim * dim];
+    let cell_of = |p: Vec2| ((p.x / cell) as usize).min(dim - 1) + ((p.y / cell) as usize).min(dim - 1) * dim;
+
+    let mut points = Vec::new();
+    let mut active = Vec::new();
+    let first = Vec2::new(rng.next_f32(), rng.next_f32()) * extent;
+    grid[cell_of(first)] = Some(0);
+    points.push(first);
+    active.push(0);
+
+    while !active.is_empty() {
+        let slot = (rng.next_u64() % active.len() as u64) as usize;
+        let base = points[active[slot]];
+        let mut found = false;
+        for _ in 0..ATTEMPTS {
+            let angle = rng.range(0.0, std::f32::consts::TAU);
+            let dist = rng.range(radius, 2.0 * radius);
+            let p = base + Vec2::from_angle(angle) * dist;
+            if p.x < 0.0 || p.y < 0.0 || p.x >= extent || p.y >= extent { continue; }
+
+            let (cx, cz) = ((p.x / cell) as isize, (p.y / cell) as isize);
+            let clear = (cz - 2..=cz + 2).all(|z| {
+                (cx - 2..=cx + 2).all(|x| {
+                    if x < 0 || z < 0 || x >= dim as isize || z >= dim as isize { return true; }
+                    grid[z as usize * dim + x as usize].is_none_or(|i| points[i].distance_squared(p) >= radius * radius)
+                })
+            });
+            if clear {
+                grid[cell_of(p)] = Some(points.len());
+                active.push(points.len());
+                points.push(p);
+                found = true;
+                break;
+            }
+        }
+        if !found { active.swap_remove(slot); }
+    }
+    points
+}
+
+pub fn update_impostor_materials_system(
+    settings: Res<ScatterSettings>,
+    mut impostors: ResMut<ImpostorMaterials>,
+    mut materials: ResMut<Assets<ImpostorMaterial>>,
+) {
+    let fade = settings.crossfade_width.max(0.0) * 0.5;
+    impostors.0 = settings
+        .layers
+        .iter()
+        .map(|layer| {
+            let texture = layer.impostor.clone()?;
+            // without a cross-fade band every instance is fully opaque
+            let (fade_start, fade_end) = if fade > 0.0 {
+                (layer.impostor_distance - fade, layer.impostor_distance + fade)
+            } else {
+                (0.0, 0.0)
+            };
+            Some(materials.add(ImpostorMaterial {
+                params: ImpostorParams {
+                    size: layer.impostor_size,
+                    fade_start,
+                    fade_end,
+                    cull_distance: layer.cull_distance,
+                    alpha_cutoff: 0.5,
+                },
+                texture,
+                wind: default(),
+            }))
+        })
+        .collect();
+}
+
+pub fn update_impostor_wind_system(
+    time: Res<Time>,
+    wind: Res<Wind>,
+    settings: Res<ScatterSettings>,
+    impostors: Res<ImpostorMaterials>,
+    mut materials: ResMut<Assets<ImpostorMaterial>>,
+) {
+    for (layer, handle) in settings.layers.iter().zip(&impostors.0) {
+        let Some(mat) = handle.as_ref().and_then(|h| materials.get_mut(h)) else { continue };
+        mat.wind = wind.params(time.elapsed_secs_f64(), layer.wind_amplitude);
+    }
+}
+
+pub fn collect_scatter_tasks_system(
+    mut commands: Commands,
+    settings: Res<ScatterSettings>,
+    impostors: Res<ImpostorMaterials>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut ready: EventWriter<PropPlacementReady>,
+    mut q_tasks: Query<(Entity, &mut ScatterTask)>,
+) {
+    for (tile, mut t) in q_tasks.iter_mut() {
+        let Some(placements) = bevy::tasks::futures::check_ready(&mut t.task) else { continue };
+        commands.entity(tile).remove::<ScatterTask>();
+        let coord = t.coord;
+
+        for (kind, layer) in settings.layers.iter().enumerate() {
+            if let Some(scene) = &layer.scene {
+                let mut props = placements.iter().filter(|p| p.kind == kind).peekable();
+                if props.peek().is_some() {
+                    commands
+                        .spawn((
+                            Name::new(format!("{} {:?}", layer.name, coord)),
+                            PropLod { coord, kind, tier: PropTier::Full },
+                            Transform::IDENTITY,
+                            Visibility::Hidden,
+                            ChildOf(tile),
+                        ))
+                        .with_children(|group| {
+                            for p in props {
+                                group.spawn((
+                                    SceneRoot(scene.clone()),
+                                    Transform::from_translation(p.position)
+                                        .with_rotation(p.rotation)
+                                        .with_scale(Vec3::splat(p.scale)),
+                                ));
+                            }
+                        });
+                }
+            }
+
+            let Some(Some(material)) = impostors.0.get(kind) else { continue };
+            let Some(mesh) = impostor_mesh(&placements, kind) else { continue };
+            commands.spawn((
+                Name::new(format!("{} impostors {:?}", layer.name, coord)),
+                PropLod { coord, kind, tier: PropTier::Impostor },
+                Mesh3d(meshes.add(mesh)),
+                MeshMaterial3d(material.clone()),
+                // the mesh AABB only covers the quad anchors, not the expanded billboards
+                bevy::render::view::NoFrustumCulling,
+                Transform::IDENTITY,
+                Visibility::Hidden,
+                ChildOf(tile),
+            ));
+        }
+        ready.write(PropPlacementReady { coord, tile, placements: placements.into() });
+    }
+}
+
+/// Per-tile LOD switch between full props, impostors and nothing.
+pub fn prop_lod_system(
+    settings: Res<ScatterSettings>,
+    cfg: Res<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    q_cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut q_lods: Query<(&PropLod, &mut Visibility)>,
+) {
+    let cameras: Vec<Vec2> = q_cameras.iter().map(|t| t.translation().xz()).collect();
+    let half_diagonal = cfg.tile_size * std::f32::consts::FRAC_1_SQRT_2;
+    let fade = settings.crossfade_width.max(0.0) * 0.5;
+
+    for (lod, mut vis) in q_lods.iter_mut() {
+        let Some(layer) = settings.layers.get(lod.kind) else { continue };
+        let center = offset.tile_origin(lod.coord, cfg.tile_size) + 0.5 * cfg.tile_size;
+        let d = cameras.iter().map(|c| c.distance(center)).fold(f32::INFINITY, f32::min);
+
+        let show = d < layer.cull_distance
+            && match (lod.tier, layer.impostor.is_some()) {
+                (PropTier::Full, false) => true,
+                // hard switch on the tile center, or keep both while any part
+                // of the tile overlaps the cross-fade band
+                (PropTier::Full, true) if fade > 0.0 => d - half_diagonal < layer.impostor_distance + fade,
+                (PropTier::Full, true) => d < layer.impostor_distance,
+                (PropTier::Impostor, _) if fade > 0.0 => d + half_diagonal >= layer.impostor_distance - fade,
+                (PropTier::Impostor, _) => d >= layer.impostor_distance,
+            };
+        vis.set_if_neq(if show { Visibility::Inherited } else { Visibility::Hidden });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::meshgen::{curvature_from_height, generate_tile_heights};
+
+    /// Every default layer's placements on `coord`, as the scatter task makes them.
+    fn scatter(seed: u32, coord: IVec2) -> Vec<(Vec3, f32, usize)> {
+        let cfg = TerrainConfig { seed, ..default() };
+        let (n, size) = (cfg.tile_resolution, cfg.tile_size);
+        let heights = generate_tile_heights(coord, &cfg);
+        let curvature = curvature_from_height(n, size / (n as f32 - 1.0), &heights);
+        let biomes = BiomeSampler::new(&BiomeSettings::default(), seed);
+        let mut out = Vec::new();
+        for (kind, layer) in ScatterSettings::default().layers.iter().enumerate() {
+            let rng = TileRng::new(seed, coord, 0x5CA7_0000 + kind as u32);
+            scatter_layer(layer, kind, &heights, &curvature, n, size, 1.0, coord.as_vec2() * size, &biomes, rng, &mut out);
+        }
+        out.into_iter().map(|p| (p.position, p.scale, p.kind)).collect()
+    }
+
+    #[test]
+    fn placements_follow_the_seed() {
+        let coord = IVec2::new(3, -2);
+        let first = scatter(7, coord);
+        assert!(!first.is_empty());
+        assert_eq!(scatter(7, coord), first);
+        assert_ne!(scatter(8, coord), first);
+    }
+}
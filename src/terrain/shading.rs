@@ -0,0 +1,681 @@
+use bevy::math::DVec2;
+use bevy::prelude::*;
+
+use super::climate::{AppliedClimate, ClimateState, Season};
+use super::heightfield::{grid_normal, TerrainHeightfield};
+use super::material::{DetailParams, GridParams, OverlayParams, PomParams, SplatParams, TerrainMaterial, TileParams, SPLAT_LAYERS};
+use super::origin::WorldOffset;
+use super::rng::TileRng;
+use super::systems::{TerrainConfig, TerrainState, TileSpawned};
+use super::water::WaterSettings;
+
+/// Material-only terrain parameters. Changing these updates every loaded
+/// tile's `TerrainMaterial` in place; no tiles are rebuilt.
+///
+/// Generation inputs (`seed`, noise settings, `tile_resolution`, `tile_size`)
+/// stay on `TerrainConfig` because they change the baked height/normal data
+/// and need a regeneration.
+#[derive(Resource, Clone)]
+pub struct TerrainShadingSettings {
+    pub height_scale: f32,
+    pub tint: Color,
+    /// 0 = every tile uses `tint`, 1 = full per-tile debug palette.
+    pub variation_strength: f32,
+    pub debug_view: TerrainDebugView,
+    pub style: TerrainShading,
+    /// Colors of the splat layers (grass, rock, sand, snow).
+    pub layer_colors: [Color; SPLAT_LAYERS],
+    /// What `sand_height` and `ClimateState::snow_line_height` are measured from.
+    pub height_ref: HeightRef,
+    /// Below this height sand takes over.
+    pub sand_height: f32,
+    /// Slope in degrees above which rock takes over.
+    pub rock_slope: f32,
+    pub splat_blend: f32,
+    /// Multiplied into terrain below `WaterSettings::sea_level`; alpha is the strength.
+    pub underwater_tint: Color,
+    pub contours: ContourSettings,
+    pub detail: DetailSettings,
+    pub pom: PomSettings,
+    /// Named seasonal palettes, blended by the `Season` resource into
+    /// `layer_colors` and the snow line. Spring to winter presets by default.
+    pub palettes: Vec<SeasonPalette>,
+}
+
+/// Colors and snow rule of one season.
+#[derive(Clone, Debug)]
+pub struct SeasonPalette {
+    pub name: String,
+    pub layer_colors: [Color; SPLAT_LAYERS],
+    /// Added to `ClimateState::snow_line_height`; negative = more snow.
+    pub snow_line_offset: f32,
+}
+
+impl SeasonPalette {
+    pub fn spring() -> Self {
+        Self {
+            name: "spring".into(),
+            layer_colors: [
+                Color::srgb(0.35, 0.58, 0.22),
+                Color::srgb(0.45, 0.42, 0.4),
+                Color::srgb(0.8, 0.72, 0.5),
+                Color::srgb(0.95, 0.95, 0.97),
+            ],
+            snow_line_offset: -2.0,
+        }
+    }
+
+    /// Same as the default `layer_colors` and snow line.
+    pub fn summer() -> Self {
+        Self {
+            name: "summer".into(),
+            layer_colors: TerrainShadingSettings::default_layer_colors(),
+            snow_line_offset: 0.0,
+        }
+    }
+
+    pub fn autumn() -> Self {
+        Self {
+            name: "autumn".into(),
+            layer_colors: [
+                Color::srgb(0.52, 0.42, 0.18),
+                Color::srgb(0.43, 0.39, 0.36),
+                Color::srgb(0.74, 0.65, 0.45),
+                Color::srgb(0.95, 0.95, 0.97),
+            ],
+            snow_line_offset: -3.0,
+        }
+    }
+
+    pub fn winter() -> Self {
+        Self {
+            name: "winter".into(),
+            layer_colors: [
+                Color::srgb(0.42, 0.38, 0.26),
+                Color::srgb(0.4, 0.4, 0.42),
+                Color::srgb(0.7, 0.66, 0.55),
+                Color::srgb(0.97, 0.97, 1.0),
+            ],
+            snow_line_offset: -8.0,
+        }
+    }
+
+    pub fn presets() -> Vec<Self> {
+        vec![Self::spring(), Self::summer(), Self::autumn(), Self::winter()]
+    }
+}
+
+/// Topographic contour lines, drawn from world height so they run on
+/// across tile borders.
+#[derive(Clone, Debug)]
+pub struct ContourSettings {
+    pub enabled: bool,
+    /// Height between lines, world units.
+    pub interval: f32,
+    /// Every Nth line is drawn bolder; 0 = no major lines.
+    pub major_every: u32,
+    /// Line color; alpha is the strength.
+    pub color: Color,
+    /// Minor line width in screen pixels, anti-aliased.
+    pub width_px: f32,
+    /// Flips `enabled`, see `toggle_contours_system`.
+    pub toggle_key: Option<KeyCode>,
+}
+impl Default for ContourSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 5.0,
+            major_every: 5,
+            color: Color::srgba(0.15, 0.1, 0.05, 0.8),
+            width_px: 1.0,
+            toggle_key: Some(KeyCode::KeyK),
+        }
+    }
+}
+
+/// A texture multiplied over the splat colors for close-up detail; mid-gray
+/// leaves the color as is.
+#[derive(Clone, Debug)]
+pub struct DetailSettings {
+    /// Needs a repeating sampler (`ImageAddressMode::Repeat`). `None` = no detail.
+    pub texture: Option<Handle<Image>>,
+    /// World units per texture repeat.
+    pub scale: f32,
+    /// 0 = no detail, 1 = the full texture.
+    pub strength: f32,
+    /// Turn (by quarter turns) and shift the texture per tile, hashed from
+    /// the tile coord, so tiles don't repeat the same pattern seen from
+    /// altitude. The pattern then breaks at tile borders, which the splat
+    /// colors around it mostly hide; off, the detail runs on continuously.
+    pub tile_variation: bool,
+    /// Hide repetition within tiles too (large uniform areas such as deserts)
+    /// by hex-tile stochastic sampling: three samples at hashed offsets,
+    /// blended by their weights in a hex grid. Triples the detail samples.
+    pub stochastic: bool,
+    /// Exponent on the hex blend weights; higher = sharper, less blurry
+    /// transitions between the samples.
+    pub stochastic_sharpness: f32,
+    /// Past this camera distance the detail is one plain sample again.
+    pub stochastic_distance: f32,
+}
+impl Default for DetailSettings {
+    fn default() -> Self {
+        Self {
+            texture: None,
+            scale: 4.0,
+            strength: 0.5,
+            tile_variation: true,
+            stochastic: false,
+            stochastic_sharpness: 4.0,
+            stochastic_distance: 150.0,
+        }
+    }
+}
+
+/// Parallax occlusion mapping of the detail texture near the camera: the
+/// detail lookups march along the view ray through `height_texture`, so
+/// close-up ground reads as relief. The silhouette and depth stay flat. Needs
+/// `DetailSettings::texture` as well.
+#[derive(Clone, Debug)]
+pub struct PomSettings {
+    /// Detail heights in the red channel (1 = top), tiled like the detail
+    /// texture; needs a repeating sampler. `None` = no parallax.
+    pub height_texture: Option<Handle<Image>>,
+    /// Ray-march steps right at the camera, fading to none at `fade_distance`.
+    pub max_steps: u32,
+    /// Relief depth, world units.
+    pub depth_scale: f32,
+    pub fade_distance: f32,
+    /// Darken the relief where it faces away from the first directional
+    /// light, marching toward it too (up to twice the steps).
+    pub self_shadow: bool,
+}
+impl Default for PomSettings {
+    fn default() -> Self {
+        Self { height_texture: None, max_steps: 16, depth_scale: 0.1, fade_distance: 40.0, self_shadow: false }
+    }
+}
+
+/// Lighting normals for the terrain surface.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum TerrainShading {
+    /// Interpolated normals from the baked normal map.
+    #[default]
+    Smooth,
+    /// One normal per triangle, for a faceted low-poly look.
+    Flat,
+}
+
+/// Reference for the splat height bands.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum HeightRef {
+    /// World height.
+    #[default]
+    Absolute,
+    /// Height above `WaterSettings::sea_level`, so the beach follows the waterline.
+    AboveSea,
+}
+
+/// Debug visualizations selected in `terrain.wgsl` via `TileParams::debug_mode`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum TerrainDebugView {
+    #[default]
+    None = 0,
+    /// Blue = concave, red = convex.
+    Curvature = 1,
+    /// The hex grid of `DetailSettings::stochastic`, one color per blended sample.
+    HexTiles = 2,
+    /// Magenta where the splat layer weights don't add up to one, i.e. the
+    /// sand and snow bands overlap; each tile logs how many of its samples
+    /// do when it loads.
+    SplatCoverage = 3,
+}
+impl Default for TerrainShadingSettings {
+    fn default() -> Self {
+        Self {
+            height_scale: 1.0,
+            tint: Color::WHITE,
+            variation_strength: 0.0,
+            debug_view: TerrainDebugView::None,
+            style: TerrainShading::Smooth,
+            layer_colors: Self::default_layer_colors(),
+            height_ref: HeightRef::Absolute,
+            sand_height: -2.0,
+            rock_slope: 40.0,
+            splat_blend: 1.5,
+            underwater_tint: Color::srgba(0.45, 0.55, 0.6, 0.5),
+            contours: ContourSettings::default(),
+            detail: DetailSettings::default(),
+            pom: PomSettings::default(),
+            palettes: SeasonPalette::presets(),
+        }
+    }
+}
+
+impl TerrainShadingSettings {
+    fn default_layer_colors() -> [Color; SPLAT_LAYERS] {
+        [
+            Color::srgb(0.3, 0.5, 0.2),
+            Color::srgb(0.45, 0.42, 0.4),
+            Color::srgb(0.8, 0.72, 0.5),
+            Color::srgb(0.95, 0.95, 0.97),
+        ]
+    }
+
+    pub fn palette(&self, name: &str) -> Option<&SeasonPalette> {
+        self.palettes.iter().find(|p| p.name == name)
+    }
+
+    /// Layer colors and snow line offset between the two palettes of
+    /// `season`, `None` if either name is unknown.
+    pub fn seasonal(&self, season: &Season) -> Option<([Color; SPLAT_LAYERS], f32)> {
+        let (from, to) = (self.palette(&season.from)?, self.palette(&season.to)?);
+        let t = season.blend.clamp(0.0, 1.0);
+        let colors = std::array::from_fn(|i| from.layer_colors[i].mix(&to.layer_colors[i], t));
+        Some((colors, from.snow_line_offset.lerp(to.snow_line_offset, t)))
+    }
+}
+
+/// Metric grid projected on the terrain, independent of the contours.
+/// Lines are snapped in true world space, so they don't move when tiles
+/// stream or the floating origin rebases.
+#[derive(Resource, Clone, Debug)]
+pub struct GridOverlaySettings {
+    pub enabled: bool,
+    /// Distance between minor lines, world units.
+    pub spacing: f32,
+    /// Every Nth line is a major line; 0 = no major lines.
+    pub major_every: u32,
+    pub color: Color,
+    pub major_color: Color,
+    /// Minor line width in screen pixels; major lines are twice as wide.
+    pub width_px: f32,
+    /// Camera distance over which the grid fades out, against moiré.
+    pub fade_start: f32,
+    pub fade_end: f32,
+    /// Put major lines on tile borders: `spacing` is rounded so a whole
+    /// number of cells fits a tile and `major_every` is that number.
+    pub align_to_tiles: bool,
+}
+impl Default for GridOverlaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            spacing: 1.0,
+            major_every: 10,
+            color: Color::srgba(1.0, 1.0, 1.0, 0.35),
+            major_color: Color::srgba(1.0, 0.85, 0.2, 0.8),
+            width_px: 1.0,
+            fade_start: 150.0,
+            fade_end: 400.0,
+            align_to_tiles: false,
+        }
+    }
+}
+
+impl GridOverlaySettings {
+    pub fn grid_params(&self, tile_size: f32, offset: &WorldOffset) -> GridParams {
+        let (spacing, major_every) = if self.align_to_tiles {
+            let cells = (tile_size / self.spacing.max(1e-3)).round().max(1.0);
+            (tile_size / cells, cells as u32)
+        } else {
+            (self.spacing.max(1e-3), self.major_every)
+        };
+        // the pattern repeats every major cell; wrapping in f64 keeps the phase small
+        let period = spacing as f64 * major_every.max(1) as f64;
+        let phase = offset.0.rem_euclid(DVec2::splat(period)).as_vec2();
+        GridParams {
+            enabled: self.enabled as u32,
+            major_every,
+            spacing,
+            width_px: self.width_px.max(0.0),
+            color: self.color.to_linear().to_vec4(),
+            major_color: self.major_color.to_linear().to_vec4(),
+            phase,
+            fade_start: self.fade_start,
+            fade_end: self.fade_end.max(self.fade_start + 1e-3),
+        }
+    }
+}
+
+impl TerrainShadingSettings {
+    pub fn tile_params(&self, coord: IVec2, cfg: &TerrainConfig) -> TileParams {
+        // per-tile params (linear color)
+        let palette = color_for_coord(coord).to_linear().to_vec4();
+        let tint = self.tint.to_linear().to_vec4();
+        let tile_color = Vec4::ONE.lerp(palette, self.variation_strength) * tint;
+        let (detail_offset, detail_rotation) = if self.detail.tile_variation {
+            let mut rng = TileRng::new(cfg.seed, coord, 0x4445_5441); // "DETA"
+            (Vec2::new(rng.next_f32(), rng.next_f32()), (rng.next_u64() & 3) as u32)
+        } else {
+            // the tile's phase in the repeat, in f64 so far tiles stay continuous
+            let repeats = coord.as_dvec2() * cfg.tile_size as f64 / self.detail.scale.max(1e-3) as f64;
+            ((repeats - repeats.floor()).as_vec2(), 0)
+        };
+        TileParams {
+            tile_size: cfg.tile_size,
+            height_scale: self.height_scale,
+            texels_per_side: cfg.tile_resolution as u32,
+            debug_mode: self.debug_view as u32,
+            tile_color,
+            // raw heights; `tile_material` sets the tile's own encoding
+            height_offset: 0.0,
+            height_range: 1.0,
+            detail_offset,
+            detail_rotation,
+        }
+    }
+
+    pub fn pom_params(&self) -> PomParams {
+        let p = &self.pom;
+        PomParams {
+            enabled: (p.height_texture.is_some() && self.detail.texture.is_some() && p.max_steps > 0) as u32,
+            max_steps: p.max_steps.min(64),
+            depth_scale: p.depth_scale.max(0.0),
+            fade_distance: p.fade_distance.max(1e-3),
+            self_shadow: p.self_shadow as u32,
+        }
+    }
+
+    pub fn detail_params(&self) -> DetailParams {
+        let d = &self.detail;
+        DetailParams {
+            enabled: d.texture.is_some() as u32,
+            scale: d.scale.max(1e-3),
+            strength: d.strength.clamp(0.0, 1.0),
+            stochastic: d.stochastic as u32,
+            stochastic_sharpness: d.stochastic_sharpness.max(1.0),
+            stochastic_distance: d.stochastic_distance.max(0.0),
+        }
+    }
+}
+
+impl TerrainShadingSettings {
+    pub fn splat_params(&self, climate: &ClimateState, sea_level: f32) -> SplatParams {
+        let (sand_height, snow_height) = self.band_heights(climate, sea_level);
+        SplatParams {
+            layer_colors: self.layer_colors.map(|c| c.to_linear().to_vec4()),
+            sand_height,
+            snow_height,
+            rock_slope: self.rock_slope,
+            blend: self.splat_blend,
+            snow_blend: climate.snow_blend,
+            wetness: climate.wetness.clamp(0.0, 1.0),
+            sea_level,
+            underwater_tint: self.underwater_tint.to_linear().to_vec4(),
+        }
+    }
+
+    pub fn overlay_params(&self) -> OverlayParams {
+        let c = &self.contours;
+        OverlayParams {
+            contour_enabled: c.enabled as u32,
+            contour_major_every: c.major_every,
+            contour_interval: c.interval.max(1e-3),
+            contour_width_px: c.width_px.max(0.0),
+            contour_color: c.color.to_linear().to_vec4(),
+        }
+    }
+
+    /// World heights of the sand and snow bands.
+    pub fn band_heights(&self, climate: &ClimateState, sea_level: f32) -> (f32, f32) {
+        let snow = climate.snow_line_height;
+        match self.height_ref {
+            HeightRef::Absolute => (self.sand_height, snow),
+            HeightRef::AboveSea => (self.sand_height + sea_level, snow + sea_level),
+        }
+    }
+}
+
+impl TerrainShadingSettings {
+    /// Procedural splat weights (grass, rock, sand, snow) for a world height
+    /// and slope in degrees; mirrors `procedural_weights` in `terrain.wgsl`
+    /// so gameplay can ask what the ground looks like. Painted overrides are
+    /// not included.
+    pub fn procedural_weights(
+        &self,
+        climate: &ClimateState,
+        height: f32,
+        slope: f32,
+        sea_level: f32,
+    ) -> [f32; SPLAT_LAYERS] {
+        let (sand_height, snow_height) = self.band_heights(climate, sea_level);
+        let b = self.splat_blend.max(1e-3);
+        let sb = climate.snow_blend.max(1e-3);
+        let rock = smoothstep(self.rock_slope - b, self.rock_slope + b, slope);
+        let sand = (1.0 - smoothstep(sand_height - b, sand_height + b, height)) * (1.0 - rock);
+        let snow = smoothstep(snow_height - sb, snow_height + sb, height) * (1.0 - rock);
+        let grass = (1.0 - rock - sand - snow).max(0.0);
+        [grass, rock, sand, snow]
+    }
+
+    /// Samples of an `n`×`n` tile of unscaled `heights`, `step` apart, whose
+    /// procedural weights don't add up to one.
+    pub fn uncovered_samples(&self, climate: &ClimateState, heights: &[f32], n: usize, step: f32, sea_level: f32) -> usize {
+        let mut count = 0;
+        for z in 0..n {
+            for x in 0..n {
+                let normal = grid_normal(heights, n, step, self.height_scale, Vec2::new(x as f32, z as f32));
+                let slope = normal.y.clamp(-1.0, 1.0).acos().to_degrees();
+                let w = self.procedural_weights(climate, heights[z * n + x] * self.height_scale, slope, sea_level);
+                if (w.iter().sum::<f32>() - 1.0).abs() > 1e-3 { count += 1; }
+            }
+        }
+        count
+    }
+
+    /// `procedural_weights` at a local-space position, `None` over unloaded tiles.
+    pub fn weights_at(
+        &self,
+        heightfield: &TerrainHeightfield,
+        water: &WaterSettings,
+        climate: &ClimateState,
+        world_xz: Vec2,
+    ) -> Option<[f32; SPLAT_LAYERS]> {
+        let height = heightfield.height_at(world_xz)?;
+        let slope = heightfield.slope_at(world_xz)?;
+        Some(self.procedural_weights(climate, height, slope, water.sea_level))
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn color_for_coord(c: IVec2) -> Color {
+    let palette = [
+        Color::hsl(  2.0, 0.65, 0.55),
+        Color::hsl(120.0, 0.55, 0.50),
+        Color::hsl(230.0, 0.60, 0.52),
+        Color::hsl( 45.0, 0.70, 0.55),
+        Color::hsl(280.0, 0.55, 0.56),
+        Color::hsl(180.0, 0.55, 0.52),
+    ];
+    let idx = ((c.x & 1) + ((c.y & 1) << 1)) as usize;
+    palette[idx % palette.len()]
+}
+
+/// Push changed `TerrainShadingSettings` (or the shown climate) into the
+/// materials of all loaded tiles.
+pub fn apply_shading_settings_system(
+    shading: Res<TerrainShadingSettings>,
+    cfg: Res<TerrainConfig>,
+    state: Res<TerrainState>,
+    water: Res<WaterSettings>,
+    climate: Res<AppliedClimate>,
+    materials: Option<ResMut<Assets<TerrainMaterial>>>,
+    mut heightfield: ResMut<TerrainHeightfield>,
+) {
+    heightfield.height_scale = shading.height_scale;
+    let Some(mut materials) = materials else { return };
+    for (coord, tile) in state.tiles.iter() {
+        if let Some(mat) = materials.get_mut(&tile.material) {
+            // the height encoding belongs to the tile's texture, not the settings
+            mat.params = TileParams {
+                height_offset: mat.params.height_offset,
+                height_range: mat.params.height_range,
+                ..shading.tile_params(*coord, &cfg)
+            };
+            mat.splat = shading.splat_params(&climate, water.sea_level);
+            mat.overlay = shading.overlay_params();
+            mat.detail = shading.detail_params();
+            mat.detail_tex = shading.detail.texture.clone();
+            mat.pom = shading.pom_params();
+            mat.detail_height_tex = shading.pom.height_texture.clone();
+            mat.flat_shading = shading.style == TerrainShading::Flat;
+        }
+    }
+}
+
+/// With `TerrainDebugView::SplatCoverage`, warn about each new tile with
+/// samples the splat layers don't cover exactly.
+pub fn log_splat_coverage_system(
+    shading: Res<TerrainShadingSettings>,
+    water: Res<WaterSettings>,
+    climate: Res<AppliedClimate>,
+    heightfield: Res<TerrainHeightfield>,
+    mut spawned: EventReader<TileSpawned>,
+) {
+    if shading.debug_view != TerrainDebugView::SplatCoverage {
+        spawned.clear();
+        return;
+    }
+    let n = heightfield.resolution;
+    for ev in spawned.read() {
+        let Some(tile) = heightfield.tile(ev.coord) else { continue };
+        let uncovered = shading.uncovered_samples(&climate.0, &tile.heights, n, heightfield.cell_size(), water.sea_level);
+        if uncovered > 0 {
+            warn!("tile {}: splat weights don't add up to one at {uncovered} of {} samples; the sand and snow bands overlap", ev.coord, n * n);
+        }
+    }
+}
+
+pub fn toggle_contours_system(keys: Option<Res<ButtonInput<KeyCode>>>, mut shading: ResMut<TerrainShadingSettings>) {
+    let (Some(keys), Some(key)) = (keys, shading.contours.toggle_key) else { return };
+    if keys.just_pressed(key) {
+        shading.contours.enabled = !shading.contours.enabled;
+    }
+}
+
+/// Push `GridOverlaySettings` into tile materials: all tiles when the
+/// settings or `WorldOffset` change, otherwise just the newly spawned ones.
+pub fn sync_grid_overlay_system(
+    grid: Res<GridOverlaySettings>,
+    cfg: Res<TerrainConfig>,
+    state: Res<TerrainState>,
+    offset: Res<WorldOffset>,
+    mut spawned: EventReader<TileSpawned>,
+    mut materials: ResMut<Assets<TerrainMaterial>>,
+) {
+    let params = grid.grid_params(cfg.tile_size, &offset);
+    let handles: Vec<_> = if grid.is_changed() || offset.is_changed() {
+        spawned.clear();
+        state.tiles.values().map(|t| t.material.id()).collect()
+    } else {
+        spawned.read().filter_map(|ev| state.tiles.get(&ev.coord)).map(|t| t.material.id()).collect()
+    };
+    for id in handles {
+        if let Some(mat) = materials.get_mut(id) {
+            mat.grid = params;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRASS: usize = 0;
+    const ROCK: usize = 1;
+    const SAND: usize = 2;
+    const SNOW: usize = 3;
+    const EPS: f32 = 1e-3;
+
+    fn settings() -> TerrainShadingSettings {
+        TerrainShadingSettings { sand_height: -2.0, rock_slope: 40.0, splat_blend: 1.5, height_ref: HeightRef::Absolute, ..default() }
+    }
+
+    fn climate() -> ClimateState {
+        ClimateState { snow_line_height: 8.0, snow_blend: 1.0, ..default() }
+    }
+
+    fn weights(s: &TerrainShadingSettings, height: f32, slope: f32) -> [f32; SPLAT_LAYERS] {
+        let w = s.procedural_weights(&climate(), height, slope, 0.0);
+        assert!((w.iter().sum::<f32>() - 1.0).abs() < 1e-5, "{height}, {slope}: {w:?}");
+        w
+    }
+
+    fn near(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn rock_slope_boundary() {
+        let s = settings();
+        assert!(near(weights(&s, 2.0, 40.0)[ROCK], 0.5));
+        assert!(near(weights(&s, 2.0, 40.0 - 1.5 - EPS)[ROCK], 0.0));
+        assert!(near(weights(&s, 2.0, 40.0 + 1.5 + EPS)[ROCK], 1.0));
+        assert!(weights(&s, 2.0, 40.0 - EPS)[ROCK] < 0.5);
+        assert!(weights(&s, 2.0, 40.0 + EPS)[ROCK] > 0.5);
+    }
+
+    #[test]
+    fn sand_height_boundary() {
+        let s = settings();
+        assert!(near(weights(&s, -2.0, 0.0)[SAND], 0.5));
+        assert!(near(weights(&s, -2.0 - 1.5 - EPS, 0.0)[SAND], 1.0));
+        assert!(near(weights(&s, -2.0 + 1.5 + EPS, 0.0)[SAND], 0.0));
+        assert!(weights(&s, -2.0 - EPS, 0.0)[SAND] > 0.5);
+        assert!(weights(&s, -2.0 + EPS, 0.0)[SAND] < 0.5);
+        // rock wins on steep sand
+        assert!(near(weights(&s, -10.0, 60.0)[SAND], 0.0));
+    }
+
+    #[test]
+    fn snow_line_boundary() {
+        let s = settings();
+        assert!(near(weights(&s, 8.0, 0.0)[SNOW], 0.5));
+        assert!(near(weights(&s, 8.0 - 1.0 - EPS, 0.0)[SNOW], 0.0));
+        assert!(near(weights(&s, 8.0 + 1.0 + EPS, 0.0)[SNOW], 1.0));
+        assert!(weights(&s, 8.0 - EPS, 0.0)[SNOW] < 0.5);
+        assert!(weights(&s, 8.0 + EPS, 0.0)[SNOW] > 0.5);
+        assert!(near(weights(&s, 20.0, 60.0)[SNOW], 0.0));
+    }
+
+    #[test]
+    fn grass_between_the_bands() {
+        let s = settings();
+        assert!(near(weights(&s, 3.0, 10.0)[GRASS], 1.0));
+    }
+
+    #[test]
+    fn zero_blend_is_a_step() {
+        let s = TerrainShadingSettings { splat_blend: 0.0, ..settings() };
+        assert!(near(weights(&s, 2.0, 40.0 - 0.01)[ROCK], 0.0));
+        assert!(near(weights(&s, 2.0, 40.0 + 0.01)[ROCK], 1.0));
+        assert!(near(weights(&s, -2.0 - 0.01, 0.0)[SAND], 1.0));
+        assert!(near(weights(&s, -2.0 + 0.01, 0.0)[SAND], 0.0));
+    }
+
+    #[test]
+    fn overlapping_bands_leave_samples_uncovered() {
+        let heights = vec![5.0; 9 * 9];
+        let s = settings();
+        assert_eq!(s.uncovered_samples(&climate(), &heights, 9, 1.0, 0.0), 0);
+        // the sand band reaches up past the snow line
+        let s = TerrainShadingSettings { sand_height: 9.0, ..s };
+        let snowy = ClimateState { snow_line_height: 0.0, ..climate() };
+        assert_eq!(s.uncovered_samples(&snowy, &heights, 9, 1.0, 0.0), 9 * 9);
+    }
+
+    #[test]
+    fn bands_follow_the_sea_above_sea_level() {
+        let s = TerrainShadingSettings { height_ref: HeightRef::AboveSea, ..settings() };
+        let w = s.procedural_weights(&climate(), 10.0 - 2.0, 0.0, 10.0);
+        assert!(near(w[SAND], 0.5));
+        let w = s.procedural_weights(&climate(), 10.0 + 8.0, 0.0, 10.0);
+        assert!(near(w[SNOW], 0.5));
+    }
+}
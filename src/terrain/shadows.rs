@@ -0,0 +1,131 @@
+//! Directional-light shadows sized for streamed terrain.
+//!
+//! Bevy's default cascades end at 150 units, far short of a large load
+//! radius. `fit_sun_cascades_system` fits the `CascadeShadowConfig` of every
+//! light tagged `Sun` to the terrain actually loaded: the farthest loader
+//! distance plus the tallest terrain, split geometrically so the near
+//! cascade stays sharp. It refits when the load radius, tile size, height
+//! range or `TerrainShadowConfig` changes.
+//!
+//! Tiles farther than `caster_distance` from every camera stop casting
+//! shadows (`NotShadowCaster`); their shadows would land in the coarsest
+//! cascade anyway.
+
+use bevy::pbr::{CascadeShadowConfig, CascadeShadowConfigBuilder, NotShadowCaster};
+use bevy::prelude::*;
+
+use super::heightfield::TerrainHeightfield;
+use super::origin::WorldOffset;
+use super::systems::{TerrainConfig, Tile, TileLoader};
+
+/// Added by `TerrainPlugin` when rendering.
+pub struct TerrainShadowPlugin;
+impl Plugin for TerrainShadowPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TerrainShadowConfig>()
+            .add_systems(Update, (fit_sun_cascades_system, tile_shadow_casters_system));
+    }
+}
+
+/// Directional lights whose cascades follow the terrain.
+#[derive(Component, Clone, Copy, Default)]
+pub struct Sun;
+
+#[derive(Resource, Clone)]
+pub struct TerrainShadowConfig {
+    pub num_cascades: usize,
+    /// Shadow distance as a fraction of the load distance (`radius_tiles * tile_size`).
+    pub distance_fraction: f32,
+    /// Far bound of the first cascade; derived from the shadow distance when `None`.
+    pub first_cascade_far_bound: Option<f32>,
+    pub overlap_proportion: f32,
+    /// Tallest terrain to cover; the loaded height range when `None`.
+    pub max_height: Option<f32>,
+    /// Tiles farther than this from every camera don't cast shadows; `None` = all cast.
+    pub caster_distance: Option<f32>,
+}
+impl Default for TerrainShadowConfig {
+    fn default() -> Self {
+        Self {
+            num_cascades: 4,
+            distance_fraction: 1.0,
+            first_cascade_far_bound: None,
+            overlap_proportion: 0.2,
+            max_height: None,
+            caster_distance: None,
+        }
+    }
+}
+
+impl TerrainShadowConfig {
+    /// Cascades covering `load_distance` of terrain up to `max_height` tall.
+    pub fn cascades(&self, load_distance: f32, tile_size: f32, max_height: f32) -> CascadeShadowConfig {
+        let num_cascades = self.num_cascades.max(1);
+        // a tall ridge at the edge still casts into view
+        let maximum_distance = (load_distance * self.distance_fraction + max_height.max(0.0)).max(tile_size);
+        let first_cascade_far_bound = self.first_cascade_far_bound.unwrap_or_else(|| {
+            // each cascade roughly 4x the previous, but never under half a tile
+            (maximum_distance / 4f32.powi(num_cascades as i32 - 1)).max(tile_size * 0.5)
+        });
+        CascadeShadowConfigBuilder {
+            num_cascades,
+            minimum_distance: 0.1,
+            maximum_distance,
+            first_cascade_far_bound: first_cascade_far_bound.min(maximum_distance),
+            overlap_proportion: self.overlap_proportion,
+        }
+        .build()
+    }
+}
+
+pub fn fit_sun_cascades_system(
+    mut commands: Commands,
+    settings: Res<TerrainShadowConfig>,
+    cfg: Res<TerrainConfig>,
+    heightfield: Res<TerrainHeightfield>,
+    q_loaders: Query<&TileLoader>,
+    q_suns: Query<Entity, With<Sun>>,
+    q_new_suns: Query<(), Added<Sun>>,
+    mut fitted: Local<Option<(i32, u32, i32)>>,
+) {
+    let radius = q_loaders.iter().map(|l| l.radius_tiles).max().unwrap_or(0).max(1);
+    let max_height = settings
+        .max_height
+        .or_else(|| heightfield.height_range().map(|(_, hi)| hi))
+        .unwrap_or(0.0);
+    // heights move as tiles stream; only refit on steps of an eighth of a tile
+    let step = (cfg.tile_size / 8.0).max(1.0);
+    let key = (radius, cfg.tile_size.to_bits(), (max_height / step).ceil() as i32);
+    if *fitted == Some(key) && !settings.is_changed() && q_new_suns.is_empty() { return; }
+    *fitted = Some(key);
+
+    let cascades = settings.cascades(radius as f32 * cfg.tile_size, cfg.tile_size, key.2 as f32 * step);
+    for sun in &q_suns {
+        commands.entity(sun).insert(cascades.clone());
+    }
+}
+
+pub fn tile_shadow_casters_system(
+    mut commands: Commands,
+    settings: Res<TerrainShadowConfig>,
+    cfg: Res<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    q_cameras: Query<&GlobalTransform, With<Camera3d>>,
+    q_tiles: Query<(Entity, &Tile, Has<NotShadowCaster>)>,
+) {
+    let cameras: Vec<Vec2> = q_cameras.iter().map(|t| t.translation().xz()).collect();
+    // a tile casts if any part of it is within the caster distance
+    let reach = settings.caster_distance.map(|d| d + cfg.tile_size * std::f32::consts::FRAC_1_SQRT_2);
+    for (e, tile, not_caster) in &q_tiles {
+        let far = reach.is_some_and(|reach| {
+            let center = offset.tile_origin(tile.coord, cfg.tile_size) + 0.5 * cfg.tile_size;
+            cameras.iter().all(|c| c.distance_squared(center) > reach * reach)
+        });
+        if far && !not_caster {
+            commands.entity(e).insert(NotShadowCaster);
+        } else if !far && not_caster {
+            commands.entity(e).remove::<NotShadowCaster>();
+        }
+    }
+}
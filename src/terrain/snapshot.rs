@@ -0,0 +1,200 @@
+//! Terrain state for save games.
+//!
+//! `TerrainSnapshot` holds what it takes to bring the streamed world back:
+//! the generation config, pinned coords, per-tile gameplay flags
+//! (`TerrainState::set_tile_flags`), the edits (in the `TerrainEdits::save`
+//! format) and the loader positions. It has no entities or handles and
+//! derives serde's traits, so it can go into whatever format the game saves
+//! with. Every field has a default and unknown fields are ignored, so saves
+//! written by older and newer versions still load.
+//!
+//! `apply` restores all of it through `Commands` and rebuilds the tiles
+//! around the restored loaders; heights come out the same because they only
+//! depend on the config and the edits. Streaming-only settings (task
+//! budgets, pools, render mode) keep their current values.
+
+use bevy::math::{DVec2, DVec3};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::edit::TerrainEdits;
+use super::meshgen::WorldFalloff;
+use super::origin::WorldOffset;
+use super::systems::{RegenerateTerrain, TerrainConfig, TerrainState, TileLoader};
+
+/// Bumped when a field changes meaning; new fields alone don't need it.
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerrainSnapshot {
+    pub version: u32,
+    pub config: SnapshotConfig,
+    pub pinned: Vec<[i32; 2]>,
+    /// Non-zero tile flags by coord.
+    pub tile_flags: Vec<([i32; 2], u64)>,
+    /// `TerrainEdits::to_bytes`.
+    pub edits: Vec<u8>,
+    /// True world positions of the tile loaders, see `with_loaders`.
+    pub loaders: Vec<[f64; 3]>,
+}
+impl Default for TerrainSnapshot {
+    fn default() -> Self {
+        Self {
+            version: SNAPSHOT_VERSION,
+            config: default(),
+            pinned: Vec::new(),
+            tile_flags: Vec::new(),
+            edits: Vec::new(),
+            loaders: Vec::new(),
+        }
+    }
+}
+
+/// The `TerrainConfig` fields that shape the generated world.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    pub tile_size: f32,
+    pub tile_resolution: usize,
+    pub seed: u32,
+    pub noise_octaves: u32,
+    pub noise_lacunarity: f32,
+    pub noise_persistence: f32,
+    pub noise_frequency: f32,
+    pub noise_amplitude: f32,
+    /// Inclusive `[min_x, min_y, max_x, max_y]`.
+    pub bounds: Option<[i32; 4]>,
+    /// `[center_x, center_y, radius, falloff_width, edge_height]`.
+    pub world_extent: Option<[f32; 5]>,
+    pub compute_flow: bool,
+    pub gpu_generation: bool,
+    pub f64_noise: bool,
+}
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self::from(&TerrainConfig::default())
+    }
+}
+
+impl From<&TerrainConfig> for SnapshotConfig {
+    fn from(cfg: &TerrainConfig) -> Self {
+        Self {
+            tile_size: cfg.tile_size,
+            tile_resolution: cfg.tile_resolution,
+            seed: cfg.seed,
+            noise_octaves: cfg.noise_octaves,
+            noise_lacunarity: cfg.noise_lacunarity,
+            noise_persistence: cfg.noise_persistence,
+            noise_frequency: cfg.noise_frequency,
+            noise_amplitude: cfg.noise_amplitude,
+            bounds: cfg.bounds.map(|b| [b.min.x, b.min.y, b.max.x, b.max.y]),
+            world_extent: cfg.world_extent.map(|f| [f.center.x, f.center.y, f.radius, f.falloff_width, f.edge_height]),
+            compute_flow: cfg.compute_flow,
+            gpu_generation: cfg.gpu_generation,
+            f64_noise: cfg.f64_noise,
+        }
+    }
+}
+
+impl SnapshotConfig {
+    /// Overwrite the generation fields of `cfg`, leaving the rest alone.
+    pub fn restore(&self, cfg: &mut TerrainConfig) {
+        cfg.tile_size = self.tile_size;
+        cfg.tile_resolution = self.tile_resolution;
+        cfg.seed = self.seed;
+        cfg.noise_octaves = self.noise_octaves;
+        cfg.noise_lacunarity = self.noise_lacunarity;
+        cfg.noise_persistence = self.noise_persistence;
+        cfg.noise_frequency = self.noise_frequency;
+        cfg.noise_amplitude = self.noise_amplitude;
+        cfg.bounds = self.bounds.map(|[x0, y0, x1, y1]| IRect::new(x0, y0, x1, y1));
+        cfg.world_extent = self.world_extent.map(|[x, y, radius, falloff_width, edge_height]| WorldFalloff {
+            center: Vec2::new(x, y),
+            radius,
+            falloff_width,
+            edge_height,
+        });
+        cfg.compute_flow = self.compute_flow;
+        cfg.gpu_generation = self.gpu_generation;
+        cfg.f64_noise = self.f64_noise;
+    }
+}
+
+impl TerrainSnapshot {
+    pub fn capture(state: &TerrainState, cfg: &TerrainConfig, edits: &TerrainEdits) -> Self {
+        let mut pinned: Vec<[i32; 2]> = state.pinned().map(|c| c.to_array()).collect();
+        pinned.sort();
+        let mut tile_flags: Vec<([i32; 2], u64)> = state.flagged_tiles().map(|(c, f)| (c.to_array(), *f)).collect();
+        tile_flags.sort();
+        Self {
+            config: SnapshotConfig::from(cfg),
+            pinned,
+            tile_flags,
+            edits: edits.to_bytes(),
+            ..default()
+        }
+    }
+
+    /// Record loader positions given in local space, e.g. from
+    /// `Query<&Transform, With<TileLoader>>`.
+    pub fn with_loaders(mut self, offset: &WorldOffset, positions: impl IntoIterator<Item = Vec3>) -> Self {
+        self.loaders = positions
+            .into_iter()
+            .map(|p| {
+                let xz = offset.to_world(p.xz());
+                [xz.x, p.y as f64, xz.y]
+            })
+            .collect();
+        self
+    }
+
+    /// Restore the config, pins, flags and edits, move the tile loaders (in
+    /// entity order; extra positions are ignored) and rebuild every tile.
+    /// Edits that fail to decode are logged and the current ones kept.
+    pub fn apply(self, commands: &mut Commands) {
+        commands.queue(move |world: &mut World| {
+            if self.version > SNAPSHOT_VERSION {
+                warn!("terrain snapshot version {} is newer than {SNAPSHOT_VERSION}; restoring what is understood", self.version);
+            }
+            let edits = match self.edits.is_empty() {
+                true => Ok(TerrainEdits::default()),
+                false => TerrainEdits::from_bytes(&self.edits),
+            };
+            match edits {
+                Ok(edits) => world.insert_resource(edits),
+                Err(e) => warn!("terrain snapshot edits not restored: {e}"),
+            }
+            self.config.restore(&mut world.resource_mut::<TerrainConfig>());
+
+            let mut state = world.resource_mut::<TerrainState>();
+            let old: Vec<IVec2> = state.pinned().copied().collect();
+            for c in old {
+                state.unpin(c);
+            }
+            for c in &self.pinned {
+                state.pin(IVec2::from_array(*c));
+            }
+            let flagged: Vec<IVec2> = state.flagged_tiles().map(|(c, _)| *c).collect();
+            for c in flagged {
+                state.set_tile_flags(c, 0);
+            }
+            for (c, flags) in &self.tile_flags {
+                state.set_tile_flags(IVec2::from_array(*c), *flags);
+            }
+
+            let offset = world.get_resource::<WorldOffset>().map_or(DVec2::ZERO, |o| o.0);
+            let mut q_loaders = world.query_filtered::<(Entity, &mut Transform), With<TileLoader>>();
+            let mut loaders: Vec<(Entity, Mut<Transform>)> = q_loaders.iter_mut(world).collect();
+            loaders.sort_by_key(|(e, _)| *e);
+            for ((_, mut xf), p) in loaders.into_iter().zip(&self.loaders) {
+                let p = DVec3::from_array(*p);
+                let local = (p.xz() - offset).as_vec2();
+                xf.translation = Vec3::new(local.x, p.y as f32, local.y);
+            }
+
+            // same config still needs a rebuild for the restored edits to show
+            world.send_event(RegenerateTerrain);
+        });
+    }
+}
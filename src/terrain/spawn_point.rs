@@ -0,0 +1,180 @@
+//! Finding flat, buildable ground, e.g. for the player start.
+//!
+//! Candidate squares of side `FlatArea::min_size` are laid on a grid of half
+//! that spacing around `near` and tried in expanding square rings. A square
+//! passes when every sample in it is loaded ground (not a hole), above the
+//! sea if asked, and no two neighbouring samples are steeper apart than
+//! `max_slope_deg`. The flattest square of the first ring with a pass wins,
+//! ties going to the earlier one in scan order, so the result only depends
+//! on the heights: the same seed gives the same spawn.
+//!
+//! `TerrainConfig::find_flat_area` samples the height function directly, so
+//! it works at world-gen time before any tile streams in (edits are not
+//! applied). `TerrainHeightfield::find_flat_area` uses the loaded tiles.
+//! The `search_*` variants also return every candidate tried; put one in
+//! `FlatAreaDebug` to draw them.
+
+use bevy::prelude::*;
+
+use super::heightfield::TerrainHeightfield;
+use super::meshgen::HeightSource;
+use super::systems::TerrainConfig;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FlatArea {
+    /// Side of the square that must be flat, world units.
+    pub min_size: f32,
+    /// Steepest slope between neighbouring samples, degrees.
+    pub max_slope_deg: f32,
+    /// Reject squares with any sample below `sea_level`.
+    pub above_sea: bool,
+    /// `WaterSettings::sea_level`.
+    pub sea_level: f32,
+}
+impl Default for FlatArea {
+    fn default() -> Self {
+        Self { min_size: 8.0, max_slope_deg: 8.0, above_sea: true, sea_level: 0.0 }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FlatAreaCandidate {
+    /// Center of the square at surface height.
+    pub center: Vec3,
+    pub size: f32,
+    /// Steepest grade (rise over run) in the square; `None` if a sample
+    /// was missing or underwater.
+    pub grade: Option<f32>,
+    /// Met every requirement.
+    pub flat: bool,
+}
+
+/// Result of a `search_flat_area` call.
+#[derive(Clone, Debug, Default)]
+pub struct FlatAreaSearch {
+    pub best: Option<Vec3>,
+    /// Every square tried, in scan order.
+    pub candidates: Vec<FlatAreaCandidate>,
+}
+
+/// Draws the candidates of the last search. Off by default.
+#[derive(Resource, Default)]
+pub struct FlatAreaDebug {
+    pub enabled: bool,
+    pub search: Option<FlatAreaSearch>,
+}
+
+impl TerrainConfig {
+    /// Flat ground near the true world position `near`, from the generated
+    /// heights scaled by `height_scale` (`TerrainShadingSettings::height_scale`).
+    pub fn find_flat_area(&self, height_scale: f32, near: Vec2, search_radius: f32, requirements: FlatArea) -> Option<Vec3> {
+        self.search_flat_area(height_scale, near, search_radius, requirements).best
+    }
+
+    pub fn search_flat_area(&self, height_scale: f32, near: Vec2, search_radius: f32, requirements: FlatArea) -> FlatAreaSearch {
+        let source = self.height_source();
+        let step = self.tile_size / (self.tile_resolution as f32 - 1.0);
+        search(
+            |p| Some(source.height_at(p.as_dvec2()) * height_scale),
+            step,
+            near,
+            search_radius,
+            &requirements,
+        )
+    }
+}
+
+impl TerrainHeightfield {
+    /// Flat ground near the local-space position `near`, over loaded tiles.
+    pub fn find_flat_area(&self, near: Vec2, search_radius: f32, requirements: FlatArea) -> Option<Vec3> {
+        self.search_flat_area(near, search_radius, requirements).best
+    }
+
+    pub fn search_flat_area(&self, near: Vec2, search_radius: f32, requirements: FlatArea) -> FlatAreaSearch {
+        search(|p| self.height_at(p), self.cell_size(), near, search_radius, &requirements)
+    }
+}
+
+/// Ring search; `height` is `None` where there's no ground, `cell` the
+/// spacing of the underlying height samples.
+fn search(height: impl Fn(Vec2) -> Option<f32>, cell: f32, near: Vec2, radius: f32, req: &FlatArea) -> FlatAreaSearch {
+    let size = req.min_size.max(1e-2);
+    let spacing = size * 0.5;
+    // samples per side: the height grid's, within reason
+    let k = ((size / cell.max(1e-3)).ceil() as usize).clamp(2, 16);
+    let max_grade = req.max_slope_deg.clamp(0.0, 89.9).to_radians().tan();
+    let rings = (radius.max(0.0) / spacing).ceil() as i32;
+
+    let mut out = FlatAreaSearch::default();
+    for ring in 0..=rings {
+        let mut best: Option<(f32, Vec3)> = None;
+        for z in -ring..=ring {
+            for x in -ring..=ring {
+                if x.abs() != ring && z.abs() != ring { continue; }
+                let center = near + Vec2::new(x as f32, z as f32) * spacing;
+                if center.distance(near) > radius { continue; }
+                let (y, grade) = evaluate(&height, center, size, k, req);
+                let flat = grade.is_some_and(|g| g <= max_grade);
+                let candidate = FlatAreaCandidate { center: Vec3::new(center.x, y, center.y), size, grade, flat };
+                out.candidates.push(candidate);
+                let Some(grade) = grade.filter(|_| flat) else { continue };
+                if best.is_none_or(|(g, _)| grade < g) {
+                    best = Some((grade, candidate.center));
+                }
+            }
+        }
+        if let Some((_, center)) = best {
+            out.best = Some(center);
+            break;
+        }
+    }
+    out
+}
+
+/// Surface height at the center (NaN if missing) and the steepest grade
+/// between neighbouring samples, `None` if a sample is missing or underwater.
+fn evaluate(height: &impl Fn(Vec2) -> Option<f32>, center: Vec2, size: f32, k: usize, req: &FlatArea) -> (f32, Option<f32>) {
+    let y = height(center).unwrap_or(f32::NAN);
+    let step = size / k as f32;
+    let corner = center - Vec2::splat(size * 0.5);
+    let mut heights = Vec::with_capacity((k + 1) * (k + 1));
+    for z in 0..=k {
+        for x in 0..=k {
+            let Some(h) = height(corner + Vec2::new(x as f32, z as f32) * step) else { return (y, None) };
+            if req.above_sea && h < req.sea_level { return (y, None); }
+            heights.push(h);
+        }
+    }
+    let n = k + 1;
+    let mut rise: f32 = 0.0;
+    for z in 0..n {
+        for x in 0..n {
+            let h = heights[z * n + x];
+            if x + 1 < n { rise = rise.max((heights[z * n + x + 1] - h).abs()); }
+            if z + 1 < n { rise = rise.max((heights[(z + 1) * n + x] - h).abs()); }
+        }
+    }
+    (y, Some(rise / step))
+}
+
+const PASSED_COLOR: Color = Color::srgb(0.95, 0.85, 0.1);
+const FAILED_COLOR: Color = Color::srgb(0.95, 0.15, 0.1);
+const BEST_COLOR: Color = Color::srgb(0.2, 0.9, 0.2);
+
+/// Outline every candidate of `FlatAreaDebug::search` at its surface
+/// height: green for the pick, yellow for other flat squares, red otherwise.
+pub fn draw_flat_area_debug_system(debug: Res<FlatAreaDebug>, mut gizmos: Gizmos) {
+    let Some(search) = &debug.search else { return };
+    for c in &search.candidates {
+        if !c.center.y.is_finite() { continue; }
+        let color = if search.best == Some(c.center) {
+            BEST_COLOR
+        } else if c.flat {
+            PASSED_COLOR
+        } else {
+            FAILED_COLOR
+        };
+        let rotation = Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
+        gizmos.rect(Isometry3d::new(c.center + Vec3::Y * 0.1, rotation), Vec2::splat(c.size), color);
+    }
+}
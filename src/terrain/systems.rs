@@ -1,31 +1,340 @@
+use bevy::ecs::entity::Entities;
+use bevy::math::DVec2;
 use bevy::prelude::*;
+use bevy::platform::time::Instant;
 use bevy::tasks::{AsyncComputeTaskPool, Task};
-use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
-use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{TextureFormat, WgpuFeatures};
+use bevy::render::renderer::RenderDevice;
+use bevy::render::primitives::Aabb;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
+use super::biome::{BiomeSampler, BiomeSettings};
+use super::climate::{AppliedClimate, ClimateState};
+use super::diagnostics::TerrainDiagnostics;
+use super::edit::TerrainEdits;
+use super::requests::TileRequests;
 use super::flatmesh::SharedMeshes;
+use super::heightfield::{HeightTile, TerrainHeightfield, TileFlow};
 use super::material::{TerrainMaterial, TileParams};
-use super::meshgen::{generate_height_field, normalmap_from_height};
+use super::metadata::TileMetadata;
+use super::meshgen::{
+    crop_apron, curvature_from_height, d8_flow_directions, flow_accumulation,
+    generate_padded_height_field, mip_level_count, normalmap_from_height, normalmap_mips, FbmHeightSource, HashedFbm, WorldFalloff,
+};
+use super::flatmesh::displaced_grid_mesh;
+use super::gpu_generation::{AwaitingGpuGeneration, GpuGeneration};
+use super::origin::WorldOffset;
+use super::shading::{TerrainShading, TerrainShadingSettings};
+use super::texture_pool::TileTexturePool;
+use super::water::WaterSettings;
 
 #[derive(Component)]
+#[cfg_attr(feature = "inspector", derive(Reflect), reflect(Component))]
 pub struct TileLoader {
+    #[cfg_attr(feature = "inspector", reflect(@0..=64_i32))]
     pub radius_tiles: i32,
+    /// Skip tiles behind the loader's forward direction; `None` loads the full square.
+    pub rear_cull: Option<RearCull>,
+    pub mode: LoadMode,
+}
+impl Default for TileLoader {
+    fn default() -> Self {
+        Self { radius_tiles: 6, rear_cull: None, mode: LoadMode::Radius }
+    }
+}
+
+/// Shape of the area a `TileLoader` keeps loaded.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "inspector", derive(Reflect))]
+pub enum LoadMode {
+    /// The square of `radius_tiles` around the loader.
+    #[default]
+    Radius,
+    /// Tiles under the camera's view: the frustum's footprint on the lowest
+    /// loaded ground, reaching at most `radius_tiles * tile_size` (the
+    /// distance `LoadRadiusFog` fades out at), grown by `margin_tiles`. For
+    /// cameras on a known path. Loaders without a usable `Camera` (not
+    /// rendered yet, or no camera at all) fall back to `Radius`.
+    Frustum { margin_tiles: u32 },
+}
+
+/// Leaves out tiles behind a loader to save memory. A tile is skipped when
+/// it lies outside `keep_ring` and the direction to its center has a dot
+/// product below `min_dot` with the loader's forward (XZ). Skipped tiles
+/// expire like any tile that left the square; turning around streams them
+/// back in, ahead of other tiles at the same distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "inspector", derive(Reflect))]
+pub struct RearCull {
+    /// -1 keeps everything, 0 drops the rear half plane.
+    #[cfg_attr(feature = "inspector", reflect(@-1.0..=1.0_f32))]
+    pub min_dot: f32,
+    /// Chebyshev radius (in tiles) that is always loaded.
+    pub keep_ring: u32,
+}
+impl Default for RearCull {
+    fn default() -> Self {
+        Self { min_dot: -0.3, keep_ring: 2 }
+    }
+}
+
+/// What one loader wants this frame: a square of coords, optionally thinned.
+struct LoaderView {
+    entity: Entity,
+    square: IRect,
+    filter: LoaderFilter,
+}
+
+#[derive(Clone, PartialEq)]
+enum LoaderFilter {
+    All,
+    Rear { center: IVec2, pos: Vec2, forward: Vec2, cull: RearCull },
+    /// Convex footprint (local XZ) and how far around it tiles are kept.
+    Footprint { hull: Vec<Vec2>, margin: f32 },
+}
+
+impl LoaderView {
+    fn keeps(&self, coord: IVec2, tile_center: Vec2, tile_size: f32) -> bool {
+        if !self.square.contains(coord) { return false; }
+        match &self.filter {
+            LoaderFilter::All => true,
+            LoaderFilter::Rear { center, pos, forward, cull } => cull.keeps(*center, coord, *pos, *forward, tile_center),
+            LoaderFilter::Footprint { hull, margin } => {
+                distance_to_convex(hull, tile_center) <= margin + tile_size * std::f32::consts::FRAC_1_SQRT_2
+            }
+        }
+    }
+}
+
+/// Ground-plane footprint of a camera's view, as a convex polygon in local
+/// XZ. Corner rays that miss the plane (above the horizon) or hit beyond
+/// `far` are cut off at `far`, horizontally.
+fn frustum_footprint(camera: &Camera, xf: &GlobalTransform, ground_y: f32, far: f32) -> Option<Vec<Vec2>> {
+    let size = camera.logical_viewport_size()?;
+    let origin = xf.translation();
+    let mut points = vec![origin.xz()];
+    for corner in [Vec2::ZERO, Vec2::new(size.x, 0.0), size, Vec2::new(0.0, size.y)] {
+        let ray = camera.viewport_to_world(xf, corner).ok()?;
+        let flat = ray.direction.xz();
+        let hit = (ray.direction.y < -1e-4)
+            .then(|| ray.get_point((ground_y - ray.origin.y) / ray.direction.y).xz())
+            .filter(|p| p.distance(ray.origin.xz()) <= far);
+        points.push(hit.unwrap_or_else(|| ray.origin.xz() + flat.normalize_or_zero() * far));
+    }
+    Some(convex_hull(points))
+}
+
+/// Counter-clockwise hull (monotone chain); fine for a handful of points.
+fn convex_hull(mut points: Vec<Vec2>) -> Vec<Vec2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 { return points; }
+    let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp_dot(b - o);
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+/// 0 inside the polygon, else the distance to its nearest edge.
+fn distance_to_convex(hull: &[Vec2], p: Vec2) -> f32 {
+    match hull.len() {
+        0 => f32::INFINITY,
+        1 => hull[0].distance(p),
+        n => {
+            let mut inside = n >= 3;
+            let mut best = f32::INFINITY;
+            for i in 0..n {
+                let (a, b) = (hull[i], hull[(i + 1) % n]);
+                inside &= (b - a).perp_dot(p - a) >= 0.0;
+                let t = ((p - a).dot(b - a) / (b - a).length_squared().max(1e-12)).clamp(0.0, 1.0);
+                best = best.min(p.distance(a + (b - a) * t));
+            }
+            if inside { 0.0 } else { best }
+        }
+    }
+}
+
+impl RearCull {
+    /// Whether a loader at `pos` (local XZ) facing `forward` keeps `coord`,
+    /// whose center is at `center` (local XZ).
+    fn keeps(&self, loader_coord: IVec2, coord: IVec2, pos: Vec2, forward: Vec2, center: Vec2) -> bool {
+        (coord - loader_coord).abs().max_element() <= self.keep_ring as i32
+            || (center - pos).normalize_or_zero().dot(forward) >= self.min_dot
+    }
 }
 
-#[derive(Resource)]
+/// wasm32 has no worker threads: `AsyncComputeTaskPool` tasks run to
+/// completion on the main thread between frames.
+const SINGLE_THREADED: bool = cfg!(target_arch = "wasm32");
+
+#[derive(Resource, Clone)]
+#[cfg_attr(feature = "inspector", derive(Reflect), reflect(Resource))]
 pub struct TerrainConfig {
+    #[cfg_attr(feature = "inspector", reflect(@1.0..=1024.0_f32))]
     pub tile_size: f32,
+    #[cfg_attr(feature = "inspector", reflect(@3..=513_usize))]
     pub tile_resolution: usize,
     pub seed: u32,
+    #[cfg_attr(feature = "inspector", reflect(@1..=12_u32))]
     pub noise_octaves: u32,
+    #[cfg_attr(feature = "inspector", reflect(@1.0..=4.0_f32))]
     pub noise_lacunarity: f32,
+    #[cfg_attr(feature = "inspector", reflect(@0.0..=1.0_f32))]
     pub noise_persistence: f32,
+    #[cfg_attr(feature = "inspector", reflect(@0.001..=1.0_f32))]
     pub noise_frequency: f32,
+    #[cfg_attr(feature = "inspector", reflect(@0.0..=500.0_f32))]
     pub noise_amplitude: f32,
+    #[cfg_attr(feature = "inspector", reflect(@0.0..=30.0_f32))]
     pub despawn_grace_seconds: f32,
+    /// Tile tasks started per frame, and finished tiles spawned per frame.
+    #[cfg_attr(feature = "inspector", reflect(@1..=128_usize))]
     pub max_spawns_per_frame: usize,
+    /// Out-of-range tiles despawned per frame, farthest from any loader first.
+    #[cfg_attr(feature = "inspector", reflect(@1..=256_usize))]
+    pub max_despawns_per_frame: usize,
+    /// On wasm the task pool runs builds on the main thread, so the default
+    /// is one build (and one spawn) per frame to keep the page responsive.
+    #[cfg_attr(feature = "inspector", reflect(@1..=128_usize))]
     pub max_in_flight_tasks: usize,
+    /// Inclusive tile coord range for finite worlds; `None` streams forever.
+    pub bounds: Option<IRect>,
+    /// Island edge: heights fall to `edge_height` and tiles past it aren't generated.
+    pub world_extent: Option<WorldFalloff>,
+    /// Run the D8 flow analysis on every built tile, see `TileFlow`.
+    pub compute_flow: bool,
+    /// Generate far tiles with a compute shader, see `gpu_generation.rs`.
+    /// Switches the noise to `HashedFbm` on both paths. Ignored headless.
+    pub gpu_generation: bool,
+    /// Generate with `HashedFbm` sampled in f64 instead of noiz's fBm, which
+    /// only takes f32 positions and starts to step between neighbouring
+    /// samples a few hundred km from the origin; this stays smooth at any
+    /// distance. A different terrain for the same seed. Ignored with
+    /// `gpu_generation`, whose CPU tiles must match the shader's f32 noise.
+    pub f64_noise: bool,
+    /// With `gpu_generation`, tiles this close to a loader still build on
+    /// the CPU, so heights queries, scatter and colliders have data near it.
+    #[cfg_attr(feature = "inspector", reflect(@0..=16_i32))]
+    pub gpu_cpu_radius_tiles: i32,
+    pub render_mode: TerrainRenderMode,
+    /// Unloaded tile entities kept for reuse rather than despawned, which
+    /// spares the spawn path entity churn when a loader moves fast. 0 = no pool.
+    #[cfg_attr(feature = "inspector", reflect(@0..=1024_usize))]
+    pub max_pooled_tiles: usize,
+    /// Hide tiles whose center is farther than this from every camera; the
+    /// entities and assets stay loaded. `None` hides them just past where
+    /// a camera's `DistanceFog` turns opaque, and keeps all without fog.
+    pub max_view_distance: Option<f32>,
+    /// Image data (bytes) unloaded tiles keep around for new tiles to reuse,
+    /// see `TileTexturePool`. 0 = no reuse.
+    pub texture_pool_bytes: usize,
+    pub height_format: HeightFormat,
+    /// Build mips for the tiles' normal maps and sample them trilinearly, so
+    /// distant tiles don't shimmer. Off: one level read texel by texel,
+    /// crisper up close and a little cheaper. GPU-generated tiles have none.
+    pub normal_mipmaps: bool,
+    /// Store normal and splat textures as BC7 at a quarter of the memory
+    /// (`texture-compression` feature; ignored without it or on GPUs without
+    /// BC formats). Brush edits turn a tile's textures back to RGBA8.
+    pub compress_textures: bool,
+}
+
+/// How tile surfaces get their shape.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "inspector", derive(Reflect))]
+pub enum TerrainRenderMode {
+    /// One shared flat grid, displaced by the height texture in the vertex shader.
+    #[default]
+    GpuDisplacement,
+    /// Every tile gets its own mesh with the heights (times `height_scale`)
+    /// and normals baked in, readable in the main world for picking,
+    /// navmesh baking or export. Costs a mesh per tile, rebuilds tiles when
+    /// `height_scale` changes and disables `gpu_generation`.
+    CpuMesh,
+}
+
+/// Storage of the tiles' height textures. CPU queries always use the `f32`
+/// heights kept in `TerrainHeightfield`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "inspector", derive(Reflect))]
+pub enum HeightFormat {
+    /// `R32Float`, the heights as they are.
+    #[default]
+    R32,
+    /// `R16Unorm` spanning each tile's min..max height: half the memory and
+    /// bandwidth, in steps of 1/65535 of the tile's range (1.5 cm for a
+    /// 1000 m range). Needs `TEXTURE_FORMAT_16BIT_NORM`, which desktop GPUs
+    /// have and WebGL2 doesn't; tiles stay `R32` without it, as do tiles
+    /// built by `gpu_generation`.
+    R16,
+}
+
+/// How a height texture stores its heights: a texel `v` is the height
+/// `offset + v * range`, see `TileParams::height_offset`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeightEncoding {
+    pub format: TextureFormat,
+    pub offset: f32,
+    pub range: f32,
+}
+impl HeightEncoding {
+    pub const R32: Self = Self { format: TextureFormat::R32Float, offset: 0.0, range: 1.0 };
+
+    /// `R16Unorm` covering `min..=max`.
+    pub fn r16(min: f32, max: f32) -> Self {
+        Self { format: TextureFormat::R16Unorm, offset: min, range: (max - min).max(f32::EPSILON) }
+    }
+
+    /// The encoding `format` would give heights in `min..=max` on a device
+    /// with (or without) 16-bit normalized textures.
+    pub fn new(format: HeightFormat, unorm16: bool, min: f32, max: f32) -> Self {
+        match format {
+            HeightFormat::R16 if unorm16 => Self::r16(min, max),
+            _ => Self::R32,
+        }
+    }
+
+    /// The encoding of an existing texture under `params`.
+    pub fn of(format: TextureFormat, params: &TileParams) -> Self {
+        Self { format, offset: params.height_offset, range: params.height_range }
+    }
+
+    pub fn covers(&self, min: f32, max: f32) -> bool {
+        self.format == TextureFormat::R32Float || (min >= self.offset && max <= self.offset + self.range)
+    }
+
+    /// Texture bytes for `heights`.
+    pub fn encode(&self, heights: &[f32]) -> Vec<u8> {
+        let mut data = vec![0; heights.len() * self.texel_bytes()];
+        for (i, h) in heights.iter().enumerate() {
+            self.write(&mut data, i, *h);
+        }
+        data
+    }
+
+    /// Overwrite texel `i` of the texture bytes `data` with height `h`.
+    pub fn write(&self, data: &mut [u8], i: usize, h: f32) {
+        if self.format == TextureFormat::R16Unorm {
+            let v = ((h - self.offset) / self.range * 65535.0).round().clamp(0.0, 65535.0) as u16;
+            data[i * 2..i * 2 + 2].copy_from_slice(&v.to_le_bytes());
+        } else {
+            data[i * 4..i * 4 + 4].copy_from_slice(&h.to_le_bytes());
+        }
+    }
+
+    fn texel_bytes(&self) -> usize {
+        if self.format == TextureFormat::R16Unorm { 2 } else { 4 }
+    }
 }
 impl Default for TerrainConfig {
     fn default() -> Self {
@@ -39,52 +348,493 @@ impl Default for TerrainConfig {
             noise_frequency: 0.08,
             noise_amplitude: 10.0,
             despawn_grace_seconds: 1.0,
-            max_spawns_per_frame: 8,
-            max_in_flight_tasks: 16,
+            max_spawns_per_frame: if SINGLE_THREADED { 1 } else { 8 },
+            max_despawns_per_frame: 16,
+            max_in_flight_tasks: if SINGLE_THREADED { 1 } else { 16 },
+            bounds: None,
+            world_extent: None,
+            compute_flow: false,
+            gpu_generation: false,
+            f64_noise: false,
+            gpu_cpu_radius_tiles: 2,
+            render_mode: TerrainRenderMode::GpuDisplacement,
+            max_pooled_tiles: 64,
+            max_view_distance: None,
+            texture_pool_bytes: 16 * 1024 * 1024,
+            height_format: HeightFormat::R32,
+            normal_mipmaps: true,
+            compress_textures: cfg!(feature = "texture-compression"),
+        }
+    }
+}
+
+impl TerrainConfig {
+    /// Stable hash (FNV-1a) of everything that shapes the generated heights.
+    /// Terrain edits are only valid against the hash they were made with.
+    pub fn generation_hash(&self) -> u64 {
+        let mut words = vec![
+            self.tile_size.to_bits(),
+            self.tile_resolution as u32,
+            self.seed,
+            self.noise_octaves,
+            self.noise_lacunarity.to_bits(),
+            self.noise_persistence.to_bits(),
+            self.noise_frequency.to_bits(),
+            self.noise_amplitude.to_bits(),
+        ];
+        if let Some(f) = self.world_extent {
+            words.extend([f.center.x, f.center.y, f.radius, f.falloff_width, f.edge_height].map(f32::to_bits));
+        }
+        if self.gpu_generation {
+            // a different noise; appended so existing hashes stay valid
+            words.push(0x4750_5521); // "GPU!"
+        } else if self.f64_noise {
+            words.push(0x4636_3421); // "F64!"
+        }
+        words.iter().flat_map(|w| w.to_le_bytes()).fold(0xcbf2_9ce4_8422_2325, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+
+    /// Whether `coord` is inside `bounds` and touches `world_extent`.
+    pub fn in_bounds(&self, coord: IVec2) -> bool {
+        let size = self.tile_size as f64;
+        let min = coord.as_dvec2() * size;
+        self.bounds.is_none_or(|b| b.contains(coord))
+            && self.world_extent.is_none_or(|f| f.touches(min, min + size))
+    }
+
+    /// The height function tiles are generated from.
+    pub fn height_source(&self) -> FbmHeightSource {
+        if self.gpu_generation {
+            return FbmHeightSource::hashed(self.hashed_fbm(), self.noise_amplitude).with_falloff(self.world_extent);
+        }
+        if self.f64_noise {
+            return FbmHeightSource::hashed_f64(self.hashed_fbm(), self.noise_amplitude).with_falloff(self.world_extent);
+        }
+        FbmHeightSource::new(
+            self.seed,
+            self.noise_octaves,
+            self.noise_lacunarity,
+            self.noise_persistence,
+            self.noise_frequency,
+            self.noise_amplitude,
+        )
+        .with_falloff(self.world_extent)
+    }
+
+    /// The noise of `height_source` with `gpu_generation` or `f64_noise`,
+    /// before amplitude.
+    pub fn hashed_fbm(&self) -> HashedFbm {
+        HashedFbm {
+            seed: self.seed,
+            octaves: self.noise_octaves,
+            lacunarity: self.noise_lacunarity,
+            persistence: self.noise_persistence,
+            frequency: self.noise_frequency,
         }
     }
+
+    /// Bounds any generated (unedited) height falls in, unscaled.
+    pub fn height_bounds(&self) -> (f32, f32) {
+        let amplitude = self.noise_amplitude.abs();
+        let edge = self.world_extent.map_or(0.0, |f| f.edge_height);
+        ((-amplitude).min(edge), amplitude.max(edge))
+    }
+}
+
+/// Global switch for terrain streaming work.
+///
+/// While `paused`, no tile tasks are dispatched and no tiles are despawned.
+/// `finish_in_flight` keeps collecting tasks that were already running. On
+/// resume the desired set catches up with where the loaders are now, so
+/// stale queues are never replayed.
+#[derive(Resource, Clone)]
+pub struct TerrainStreaming {
+    pub paused: bool,
+    pub finish_in_flight: bool,
+}
+impl Default for TerrainStreaming {
+    fn default() -> Self {
+        Self { paused: false, finish_in_flight: true }
+    }
+}
+
+impl TerrainStreaming {
+    pub(crate) fn dispatching(streaming: Res<TerrainStreaming>) -> bool {
+        !streaming.paused
+    }
+
+    pub(crate) fn collecting(streaming: Res<TerrainStreaming>) -> bool {
+        !streaming.paused || streaming.finish_in_flight
+    }
 }
 
 #[derive(Resource, Default)]
 pub struct TerrainState {
-    pub tiles: HashMap<IVec2, Entity>,
+    pub tiles: HashMap<IVec2, LoadedTile>,
     pub pending: HashMap<IVec2, Entity>,
     pub last_touched: HashMap<IVec2, f32>,
+    /// Tiles kept loaded regardless of loaders, see `pin`.
+    pinned: HashSet<IVec2>,
+    /// Stripped entities waiting for a new tile, see `TerrainConfig::max_pooled_tiles`.
+    pool: Vec<Entity>,
+    /// Gameplay bits per coord, see `set_tile_flags`.
+    tile_flags: HashMap<IVec2, u64>,
+    /// Loaded or pending coords to drop and build again, see `rebuild`.
+    rebuild: HashSet<IVec2>,
+    /// Coords dropped since the streamer last ran; it queues the ones
+    /// loaders still want.
+    dropped: Vec<IVec2>,
+    /// Pending coords nothing wanted when the streamer last ran. Their
+    /// results are dropped on arrival instead of spawned.
+    undesired: HashSet<IVec2>,
+}
+
+/// A pooled tile entity: no tile data, hidden, no children.
+#[derive(Component)]
+pub struct PooledTile;
+
+impl TerrainState {
+    /// Keep `coord` loaded (and build it if needed) regardless of loader
+    /// positions, e.g. for background simulation far from the camera.
+    pub fn pin(&mut self, coord: IVec2) {
+        self.pinned.insert(coord);
+    }
+
+    /// Return `coord` to normal streaming; it unloads after the usual grace
+    /// period if no loader wants it.
+    pub fn unpin(&mut self, coord: IVec2) {
+        self.pinned.remove(&coord);
+    }
+
+    pub fn is_pinned(&self, coord: IVec2) -> bool {
+        self.pinned.contains(&coord)
+    }
+
+    pub fn pinned(&self) -> impl Iterator<Item = &IVec2> {
+        self.pinned.iter()
+    }
+
+    /// Store gameplay bits for `coord` (e.g. "ruin looted"); they don't
+    /// depend on the tile being loaded and go into `TerrainSnapshot`s.
+    /// 0 clears them.
+    pub fn set_tile_flags(&mut self, coord: IVec2, flags: u64) {
+        if flags == 0 {
+            self.tile_flags.remove(&coord);
+        } else {
+            self.tile_flags.insert(coord, flags);
+        }
+    }
+
+    pub fn tile_flags(&self, coord: IVec2) -> u64 {
+        self.tile_flags.get(&coord).copied().unwrap_or(0)
+    }
+
+    /// Coords with non-zero flags.
+    pub fn flagged_tiles(&self) -> impl Iterator<Item = (&IVec2, &u64)> {
+        self.tile_flags.iter()
+    }
+
+    /// Drop the tile (or the build in flight) at `coord` so the streamer
+    /// builds it again next frame, e.g. after edits it can't be patched with.
+    pub fn rebuild(&mut self, coord: IVec2) {
+        self.rebuild.insert(coord);
+    }
+
+    /// Entities waiting in the tile pool.
+    pub fn pooled(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Metadata of a loaded tile; `None` for GPU-built tiles until their
+    /// heights are read back.
+    pub fn metadata(&self, coord: IVec2) -> Option<&Arc<TileMetadata>> {
+        self.tiles.get(&coord)?.metadata.as_ref()
+    }
+
+    /// `metadata` of the tile under a true world position.
+    pub fn metadata_at_world(&self, cfg: &TerrainConfig, world_xz: DVec2) -> Option<&Arc<TileMetadata>> {
+        self.metadata((world_xz / cfg.tile_size as f64).floor().as_ivec2())
+    }
+
+    /// An entity for a new tile or build: a pooled one if any, else a new one.
+    pub(crate) fn take_entity(&mut self, commands: &mut Commands, entities: &Entities) -> Entity {
+        // skip pooled entities someone else despawned
+        while let Some(e) = self.pool.pop() {
+            if entities.contains(e) {
+                commands.entity(e).remove::<PooledTile>();
+                return e;
+            }
+        }
+        commands.spawn_empty().id()
+    }
+
+    /// Put a tile or build entity back in the pool: children despawned,
+    /// everything but its transform and visibility removed (dropping a
+    /// build task cancels it). Despawns it instead once the pool is full.
+    pub(crate) fn release_entity(&mut self, commands: &mut Commands, e: Entity, max_pooled: usize) {
+        if self.pool.len() >= max_pooled {
+            // despawn() is recursive: vegetation, props and other children go with the tile
+            commands.entity(e).despawn();
+            return;
+        }
+        commands
+            .entity(e)
+            .despawn_related::<Children>()
+            .retain::<(Transform, GlobalTransform, Visibility, InheritedVisibility, ViewVisibility)>()
+            .insert((PooledTile, Visibility::Hidden));
+        self.pool.push(e);
+    }
+}
+
+/// Bookkeeping for a finished tile. The material handle is kept so
+/// material-only settings can be re-applied without rebuilding the tile.
+pub struct LoadedTile {
+    pub entity: Entity,
+    pub material: Handle<TerrainMaterial>,
+    pub min_height: f32,
+    pub max_height: f32,
+    pub build_seconds: f32,
+    /// Heights are in `TerrainHeightfield`. False for GPU-built tiles until
+    /// their readback lands (see `gpu_generation.rs`); queries return `None` there.
+    pub cpu_heights: bool,
+    /// Texture memory saved by block compression, see `TerrainConfig::compress_textures`.
+    pub texture_bytes_saved: usize,
+    /// See `metadata.rs`; set once the tile has CPU heights.
+    pub metadata: Option<Arc<TileMetadata>>,
 }
 
 #[derive(Component)]
 pub struct Tile {
     pub coord: IVec2,
+    /// Same as `LoadedTile::metadata`.
+    pub metadata: Option<Arc<TileMetadata>>,
+}
+
+/// Fired when a tile finished building and its entity got its render components.
+#[derive(Event, Clone)]
+pub struct TileSpawned {
+    pub coord: IVec2,
+    pub entity: Entity,
+    /// `None` for GPU-built tiles, see `TileHeightsReady`.
+    pub metadata: Option<Arc<TileMetadata>>,
+}
+
+/// Send to drop and rebuild every tile even though the generation settings
+/// didn't change (e.g. after swapping a height source's inputs).
+#[derive(Event, Clone, Copy, Default)]
+pub struct RegenerateTerrain;
+
+/// Fired for every tile the garbage collector removes. Observers run before
+/// the entity is despawned or pooled; buffered readers see it after the
+/// fact, when the entity may already carry another tile (see
+/// `TerrainConfig::max_pooled_tiles`), so don't look it up then.
+#[derive(Event, Clone, Copy)]
+pub struct TileDespawned {
+    pub coord: IVec2,
+    pub entity: Entity,
+}
+
+/// Ties an entity that is *not* a child of the tile (e.g. kept in a side map)
+/// to a tile coord; it is despawned together with that tile.
+#[derive(Component)]
+pub struct TileAttachment {
+    pub coord: IVec2,
 }
 
 #[derive(Component)]
 pub struct TileBuildTask {
     pub coord: IVec2,
-    pub origin: Vec2,
+    /// True (unshifted) world position of the tile's min corner.
+    pub origin: DVec2,
     pub task: Task<TileBuildResult>,
 }
 
 pub struct TileBuildResult {
     pub coord: IVec2,
-    pub height_bytes: Vec<u8>, // R32f
-    pub normal_bytes: Vec<u8>, // RGBA8
+    pub height_bytes: Vec<u8>, // see `height_encoding`
+    pub height_encoding: HeightEncoding,
+    pub rgba: RgbaTextures,
+    pub heights: Arc<[f32]>,   // CPU copy for queries
+    pub curvature: Arc<[f32]>,
+    pub flow: Option<TileFlow>,
+    /// From the tile's edits, see `TileEdits::hole_mask`.
+    pub holes: Option<Arc<[u8]>>,
+    /// Displaced mesh, `TerrainRenderMode::CpuMesh` only.
+    pub mesh: Option<Mesh>,
+    pub min_height: f32,
+    pub max_height: f32,
+    pub metadata: TileMetadata,
+    pub build_seconds: f32,
 }
 
-fn color_for_coord(c: IVec2) -> Color {
-    let palette = [
-        Color::hsl(  2.0, 0.65, 0.55),
-        Color::hsl(120.0, 0.55, 0.50),
-        Color::hsl(230.0, 0.60, 0.52),
-        Color::hsl( 45.0, 0.70, 0.55),
-        Color::hsl(280.0, 0.55, 0.56),
-        Color::hsl(180.0, 0.55, 0.52),
-    ];
-    let idx = ((c.x & 1) + ((c.y & 1) << 1)) as usize;
-    palette[idx % palette.len()]
+/// A built tile's normal map and painted splat overrides as uploaded: RGBA8,
+/// or BC7 padded to whole blocks (`compression.rs`).
+pub struct RgbaTextures {
+    pub format: TextureFormat,
+    pub size: u32,
+    /// `normal_mip_levels` levels, level after level.
+    pub normal_bytes: Vec<u8>,
+    pub normal_mip_levels: u32,
+    pub splat_bytes: Vec<u8>,
+    /// Texture memory the compression saves over RGBA8.
+    pub bytes_saved: usize,
+}
+impl RgbaTextures {
+    /// From the `n`×`n` RGBA8 `normals` and `splat`; compressed with
+    /// `compress` when built with the `texture-compression` feature.
+    pub fn new(n: usize, normals: Vec<u8>, splat: Vec<u8>, mipmapped: bool, compress: bool) -> Self {
+        #[cfg(feature = "texture-compression")]
+        if compress {
+            use super::compression::{compress_normals, compress_splat, padded_size, rgba8_bytes, COMPRESSED_FORMAT};
+            let plain = rgba8_bytes(n, if mipmapped { mip_level_count(n) } else { 1 }) + rgba8_bytes(n, 1);
+            let size = padded_size(n);
+            let normal_bytes = compress_normals(n, &normals, mipmapped);
+            let splat_bytes = compress_splat(n, &splat);
+            return Self {
+                format: COMPRESSED_FORMAT,
+                size: size as u32,
+                bytes_saved: plain.saturating_sub(normal_bytes.len() + splat_bytes.len()),
+                normal_bytes,
+                normal_mip_levels: if mipmapped { mip_level_count(size) } else { 1 },
+                splat_bytes,
+            };
+        }
+        #[cfg(not(feature = "texture-compression"))]
+        let _ = compress;
+        let (normal_bytes, normal_mip_levels) = match mipmapped {
+            true => (normalmap_mips(n, &normals), mip_level_count(n)),
+            false => (normals, 1),
+        };
+        Self {
+            format: TextureFormat::Rgba8Unorm,
+            size: n as u32,
+            normal_bytes,
+            normal_mip_levels,
+            splat_bytes: splat,
+            bytes_saved: 0,
+        }
+    }
 }
 
-fn world_to_coord(p: Vec3, tile_size: f32) -> IVec2 {
-    IVec2::new((p.x / tile_size).floor() as i32, (p.z / tile_size).floor() as i32)
+/// Tile coord under a local-space position.
+pub(crate) fn world_to_coord(p: Vec3, tile_size: f32, offset: &WorldOffset) -> IVec2 {
+    (offset.to_world(p.xz()) / tile_size as f64).floor().as_ivec2()
+}
+
+/// Tiles the loaders want, kept across frames and updated incrementally.
+///
+/// `counts` holds how many loader squares cover each coord: a loader that
+/// moves only adds and removes the strips where its old and new squares
+/// differ, so a one-tile step costs O(radius) instead of rebuilding (2r+1)²
+/// coords. `wanted` is the covered coords some loader keeps after its
+/// filter. It is only re-checked where something changed: the strips of a
+/// plain loader that moved, the old and new squares of a filtered one that
+/// moved or turned. `entered` and `left` hold what joined and left `wanted`
+/// in the last update.
+#[derive(Default)]
+pub struct LoaderCoverage {
+    views: HashMap<Entity, (IRect, LoaderFilter)>,
+    counts: HashMap<IVec2, u32>,
+    wanted: HashSet<IVec2>,
+    entered: Vec<IVec2>,
+    left: Vec<IVec2>,
+}
+
+impl LoaderCoverage {
+    fn update(&mut self, views: &[LoaderView], tile_center: impl Fn(IVec2) -> Vec2, tile_size: f32) {
+        let Self { views: last, counts, wanted, entered, left } = self;
+        entered.clear();
+        left.clear();
+        let mut dirty: Vec<IVec2> = Vec::new();
+        for v in views {
+            let (old, old_filter) = match last.insert(v.entity, (v.square, v.filter.clone())) {
+                Some((square, filter)) => (square, Some(filter)),
+                None => (IRect::EMPTY, None),
+            };
+            if old == v.square && old_filter.as_ref() == Some(&v.filter) { continue; }
+            for_each_in_difference(v.square, old, |c| {
+                *counts.entry(c).or_insert(0) += 1;
+                dirty.push(c);
+            });
+            for_each_in_difference(old, v.square, |c| {
+                remove_coverage(counts, c);
+                dirty.push(c);
+            });
+            // a filter keeps a different part of the overlap after a step or turn
+            let plain = matches!(v.filter, LoaderFilter::All) && matches!(old_filter, None | Some(LoaderFilter::All));
+            if !plain {
+                for_each_in_difference(v.square, IRect::EMPTY, |c| dirty.push(c));
+            }
+        }
+        last.retain(|e, (square, _)| {
+            let keep = views.iter().any(|v| v.entity == *e);
+            if !keep {
+                for_each_in_difference(*square, IRect::EMPTY, |c| {
+                    remove_coverage(counts, c);
+                    dirty.push(c);
+                });
+            }
+            keep
+        });
+
+        let thinned = views.iter().any(|v| !matches!(v.filter, LoaderFilter::All));
+        dirty.sort_unstable_by_key(|c| (c.y, c.x));
+        dirty.dedup();
+        for c in dirty {
+            let wants = counts.contains_key(&c)
+                && (!thinned || views.iter().any(|v| v.keeps(c, tile_center(c), tile_size)));
+            if wants && wanted.insert(c) {
+                entered.push(c);
+            } else if !wants && wanted.remove(&c) {
+                left.push(c);
+            }
+        }
+    }
+
+    fn wants(&self, coord: IVec2) -> bool {
+        self.wanted.contains(&coord)
+    }
+}
+
+/// Streaming work that follows `LoaderCoverage` instead of rescanning it.
+#[derive(Default)]
+pub struct TileBacklog {
+    /// Desired coords that may still need a task.
+    queued: HashSet<IVec2>,
+    /// Loaded or pending coords that may have stopped being desired, to
+    /// unmark once their grace period is over.
+    fading: HashSet<IVec2>,
+    /// When the streamer last ran, i.e. when coords that just left the
+    /// loaders' view were last desired.
+    last_run: f32,
+}
+
+fn remove_coverage(counts: &mut HashMap<IVec2, u32>, coord: IVec2) {
+    if let Some(n) = counts.get_mut(&coord) {
+        *n -= 1;
+        if *n == 0 { counts.remove(&coord); }
+    }
+}
+
+/// Calls `f` for every coord in the inclusive rect `a` that is not in `b`,
+/// walking whole rows or the spans left and right of `b`.
+fn for_each_in_difference(a: IRect, b: IRect, mut f: impl FnMut(IVec2)) {
+    // not `IRect::is_empty`, which counts a single-tile rect as empty
+    let b_empty = b.min.x > b.max.x || b.min.y > b.max.y;
+    for z in a.min.y..=a.max.y {
+        let spans = if b_empty || z < b.min.y || z > b.max.y {
+            [(a.min.x, a.max.x), (1, 0)]
+        } else {
+            [(a.min.x, a.max.x.min(b.min.x - 1)), (a.min.x.max(b.max.x + 1), a.max.x)]
+        };
+        for (lo, hi) in spans {
+            for x in lo..=hi {
+                f(IVec2::new(x, z));
+            }
+        }
+    }
 }
 
 pub fn queue_and_spawn_tasks_system(
@@ -92,90 +842,260 @@ pub fn queue_and_spawn_tasks_system(
     mut commands: Commands,
     mut state: ResMut<TerrainState>,
     cfg: Res<TerrainConfig>,
-    q_loaders: Query<(&Transform, &TileLoader)>,
+    edits: Res<TerrainEdits>,
+    requests: Res<TileRequests>,
+    offset: Res<WorldOffset>,
+    mut diagnostics: ResMut<TerrainDiagnostics>,
+    heightfield: Res<TerrainHeightfield>,
+    shading: Res<TerrainShadingSettings>,
+    (biomes, water): (Res<BiomeSettings>, Res<WaterSettings>),
+    gpu: Option<Res<GpuGeneration>>,
+    device: Option<Res<RenderDevice>>,
+    entities: &Entities,
+    q_loaders: Query<(Entity, &Transform, &TileLoader, Option<(&Camera, &GlobalTransform)>)>,
+    (mut coverage, mut backlog): (Local<LoaderCoverage>, Local<TileBacklog>),
 ) {
-    // Desired tiles from all loaders
-    let mut desired: HashSet<IVec2> = HashSet::new();
-    for (xf, loader) in &q_loaders {
-        let center = world_to_coord(xf.translation, cfg.tile_size);
-        let r = loader.radius_tiles;
-        for dz in -r..=r {
-            for dx in -r..=r {
-                desired.insert(IVec2::new(center.x + dx, center.y + dz));
+    // Desired tiles: loader squares (or footprints), pins and requests, within bounds
+    let ground_y = heightfield.height_range().map_or(0.0, |(lo, _)| lo);
+    let views: Vec<LoaderView> = q_loaders
+        .iter()
+        .map(|(entity, xf, loader, camera)| {
+            let center = world_to_coord(xf.translation, cfg.tile_size, &offset);
+            let radius = loader.radius_tiles.max(0);
+            let square = IRect::from_center_half_size(center, IVec2::splat(radius));
+            let far = radius.max(1) as f32 * cfg.tile_size;
+            let footprint = match (loader.mode, camera) {
+                (LoadMode::Frustum { margin_tiles }, Some((camera, gxf))) => {
+                    frustum_footprint(camera, gxf, ground_y, far).map(|hull| (hull, margin_tiles as i32))
+                }
+                _ => None,
+            };
+            if let Some((hull, margin)) = footprint {
+                let to_coord = |p: Vec2| world_to_coord(Vec3::new(p.x, 0.0, p.y), cfg.tile_size, &offset);
+                let square = hull
+                    .iter()
+                    .fold(IRect::from_center_half_size(center, IVec2::ZERO), |r, p| r.union_point(to_coord(*p)))
+                    .inflate(margin);
+                let filter = LoaderFilter::Footprint { hull, margin: margin as f32 * cfg.tile_size };
+                return LoaderView { entity, square, filter };
             }
-        }
-    }
+            let filter = match loader.rear_cull {
+                Some(cull) => LoaderFilter::Rear {
+                    center,
+                    pos: xf.translation.xz(),
+                    forward: xf.forward().xz().normalize_or(Vec2::NEG_Y),
+                    cull,
+                },
+                None => LoaderFilter::All,
+            };
+            LoaderView { entity, square, filter }
+        })
+        .collect();
+    let tile_center = |c: IVec2| offset.tile_origin(c, cfg.tile_size) + 0.5 * cfg.tile_size;
+    coverage.update(&views, tile_center, cfg.tile_size);
+    // position and forward of loaders with a rear cull
+    let culling: Vec<(Vec2, Vec2)> = views
+        .iter()
+        .filter_map(|v| match v.filter {
+            LoaderFilter::Rear { pos, forward, .. } => Some((pos, forward)),
+            _ => None,
+        })
+        .collect();
+    let is_desired = |state: &TerrainState, c: IVec2| {
+        cfg.in_bounds(c) && (coverage.wants(c) || state.is_pinned(c) || requests.contains(c))
+    };
 
-    // Keep alive tiles we've touched
+    // Keep alive tiles as they enter the loaders' view, and note when the
+    // ones leaving it were last wanted. Pins and requests are touched every frame.
     let now = time.elapsed_secs();
-    for c in desired.iter() {
-        if state.tiles.contains_key(c) || state.pending.contains_key(c) {
-            state.last_touched.insert(*c, now);
+    let TileBacklog { queued, fading, last_run } = &mut *backlog;
+    if cfg.is_changed() {
+        queued.extend(coverage.wanted.iter().copied());
+    }
+    for c in std::mem::take(&mut state.dropped) {
+        if coverage.wants(c) { queued.insert(c); }
+    }
+    let held = state.pinned.iter().chain(requests.coords()).copied().collect::<Vec<_>>();
+    for &c in &coverage.left {
+        if state.tiles.contains_key(&c) || state.pending.contains_key(&c) {
+            state.last_touched.insert(c, *last_run);
+            fading.insert(c);
         }
     }
+    for &c in coverage.entered.iter().chain(&held) {
+        if state.tiles.contains_key(&c) || state.pending.contains_key(&c) {
+            state.last_touched.insert(c, now);
+            fading.insert(c);
+        } else if coverage.wants(c) {
+            queued.insert(c);
+        }
+    }
+    *last_run = now;
+    queued.retain(|c| coverage.wants(*c) && !state.tiles.contains_key(c) && !state.pending.contains_key(c));
 
-    // Missing tiles
-    let mut missing: Vec<IVec2> = desired
+    // Missing tiles (a pinned or requested coord may also be covered by a loader)
+    let mut missing: Vec<IVec2> = queued
         .iter()
-        .filter(|c| !state.tiles.contains_key(*c) && !state.pending.contains_key(*c))
+        .chain(&held)
         .copied()
+        .filter(|c| !state.tiles.contains_key(c) && !state.pending.contains_key(c))
         .collect();
+    let before = missing.len();
+    missing.retain(|c| cfg.in_bounds(*c));
+    if missing.len() < before {
+        warn_once!("terrain: tiles requested outside TerrainConfig::bounds/world_extent are not generated");
+    }
 
     // Sort by distance to nearest loader
     let centers: Vec<IVec2> = q_loaders
         .iter()
-        .map(|(t, _)| world_to_coord(t.translation, cfg.tile_size))
+        .map(|(_, t, _, _)| world_to_coord(t.translation, cfg.tile_size, &offset))
         .collect();
+    // high-priority requests jump the queue; in front of a rear-culled
+    // loader counts as up to half as far, so turning around fills the view first
     missing.sort_by_key(|c| {
-        centers
+        let distance = centers
             .iter()
             .map(|cc| (cc.x - c.x).abs() + (cc.y - c.y).abs())
             .min()
-            .unwrap_or(0)
+            .unwrap_or(0);
+        let facing = culling
+            .iter()
+            .map(|(pos, forward)| (tile_center(*c) - *pos).normalize_or_zero().dot(*forward))
+            .fold(-1.0_f32, f32::max);
+        let weighted = if culling.is_empty() { distance * 4 } else { (distance as f32 * (3.0 - facing)) as i32 };
+        (!requests.is_high_priority(*c), weighted, c.x, c.y)
     });
+    missing.dedup();
+    diagnostics.tiles_queued = missing.len();
+
+    // Cancel tasks for tiles that left the desired set, so a fast loader
+    // doesn't keep the task slots busy with tiles far behind it. Ones still
+    // in their grace period finish, but aren't spawned unless wanted again.
+    let cutoff = now - cfg.despawn_grace_seconds;
+    let undesired: HashSet<IVec2> = state.pending.keys().filter(|c| !is_desired(&state, **c)).copied().collect();
+    state.undesired = undesired;
+    let stale: Vec<IVec2> = state
+        .undesired
+        .iter()
+        .filter(|c| state.last_touched.get(*c).copied().unwrap_or(0.0) < cutoff)
+        .copied()
+        .collect();
+    for c in stale {
+        if let Some(e) = state.pending.remove(&c) {
+            state.release_entity(&mut commands, e, cfg.max_pooled_tiles);
+        }
+        state.last_touched.remove(&c);
+        state.undesired.remove(&c);
+    }
 
     // Task capacity
     let available = cfg.max_in_flight_tasks.saturating_sub(state.pending.len());
     let capacity = available.min(cfg.max_spawns_per_frame);
     if capacity == 0 { return; }
 
-    // Spawn tile build tasks
+    // Spawn tile build tasks; far tiles without CPU consumers go to the GPU
     let pool = AsyncComputeTaskPool::get();
+    let cpu_mesh = cfg.render_mode == TerrainRenderMode::CpuMesh;
+    let gpu_ready = cfg.gpu_generation && gpu.is_some() && !cpu_mesh;
+    let features = device.map(|d| d.features()).unwrap_or_default();
+    let unorm16 = features.contains(WgpuFeatures::TEXTURE_FORMAT_16BIT_NORM);
+    // WebGL2 and most mobile GPUs have no BC formats
+    let bc = features.contains(WgpuFeatures::TEXTURE_COMPRESSION_BC);
     for coord in missing.into_iter().take(capacity) {
-        let origin = Vec2::new(coord.x as f32 * cfg.tile_size, coord.y as f32 * cfg.tile_size);
+        let origin = coord.as_dvec2() * cfg.tile_size as f64;
+        let near = centers.iter().any(|cc| (*cc - coord).abs().max_element() <= cfg.gpu_cpu_radius_tiles);
+        let needs_cpu = near
+            || state.is_pinned(coord)
+            || requests.contains(coord)
+            || edits.current_tile(coord).is_some();
+        if gpu_ready && !needs_cpu {
+            let e = state.take_entity(&mut commands, entities);
+            commands.entity(e).insert(AwaitingGpuGeneration { coord, origin, requested_at: now });
+            state.pending.insert(coord, e);
+            state.last_touched.insert(coord, now);
+            continue;
+        }
         let n = cfg.tile_resolution;
         let size = cfg.tile_size;
 
-        let seed = cfg.seed;
-        let (oct, lac, per, freq, amp) = (
-            cfg.noise_octaves,
-            cfg.noise_lacunarity,
-            cfg.noise_persistence,
-            cfg.noise_frequency,
-            cfg.noise_amplitude,
-        );
+        let source = cfg.height_source();
+        let tile_edits = edits.current_tile(coord).cloned();
+        let apron_deltas = edits.apron_deltas(coord, n);
+        let compute_flow = cfg.compute_flow;
+        let height_scale = shading.height_scale;
+        let height_format = cfg.height_format;
+        let normal_mipmaps = cfg.normal_mipmaps;
+        let compress = cfg.compress_textures && bc;
+        let (biomes, sea_level, seed) = (biomes.clone(), water.sea_level, cfg.seed);
 
         let task: Task<TileBuildResult> = pool.spawn(async move {
-            let heights = generate_height_field(n, size, origin, seed, oct, lac, per, freq, amp);
-            let height_bytes: Vec<u8> = heights.iter().flat_map(|h| h.to_le_bytes()).collect();
+            let started = Instant::now();
+            // one extra sample on every side so border normals see the neighbours
             let step = size / (n as f32 - 1.0);
-            let normal_bytes = normalmap_from_height(n, step, &heights);
-            TileBuildResult { coord, height_bytes, normal_bytes }
+            let mut padded = generate_padded_height_field(n, size, origin, &source);
+            if let Some(tile_edits) = &tile_edits {
+                tile_edits.apply_padded_heights(n, &mut padded);
+            }
+            for &(i, d) in &apron_deltas {
+                padded[i] += d;
+            }
+            let heights = crop_apron(n, &padded, 1);
+            let holes = tile_edits.as_ref().and_then(|e| e.hole_mask(n)).map(Arc::from);
+            let splat_bytes = tile_edits.map_or_else(|| vec![0; n * n * 4], |e| e.splat_bytes(n));
+            let normal_bytes = crop_apron(n, &normalmap_from_height(n + 2, step, &padded), 4);
+            let rgba = RgbaTextures::new(n, normal_bytes, splat_bytes, normal_mipmaps, compress);
+            let curvature = crop_apron(n, &curvature_from_height(n + 2, step, &padded), 1);
+            // directions see the apron, so they agree across tile borders
+            let flow = compute_flow.then(|| {
+                let directions = d8_flow_directions(n + 2, &padded);
+                let accumulation = flow_accumulation(n + 2, &padded, &directions);
+                TileFlow {
+                    directions: crop_apron(n, &directions, 1).into(),
+                    accumulation: crop_apron(n, &accumulation, 1).into(),
+                }
+            });
+            let mesh = cpu_mesh.then(|| displaced_grid_mesh(n, size, &padded, height_scale));
+            let min_height = heights.iter().copied().fold(f32::INFINITY, f32::min);
+            let max_height = heights.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            let height_encoding = HeightEncoding::new(height_format, unorm16, min_height, max_height);
+            let height_bytes = height_encoding.encode(&heights);
+            let biomes = BiomeSampler::new(&biomes, seed);
+            let metadata = TileMetadata::compute(coord, &heights, n, size, height_scale, sea_level, &biomes, seed);
+            let build_seconds = started.elapsed().as_secs_f32();
+            TileBuildResult {
+                coord,
+                height_bytes,
+                height_encoding,
+                rgba,
+                heights: heights.into(),
+                curvature: curvature.into(),
+                flow,
+                holes,
+                mesh,
+                min_height,
+                max_height,
+                metadata,
+                build_seconds,
+            }
         });
 
-        let e = commands.spawn(TileBuildTask { coord, origin, task }).id();
+        let e = state.take_entity(&mut commands, entities);
+        commands.entity(e).insert(TileBuildTask { coord, origin, task });
         state.pending.insert(coord, e);
         state.last_touched.insert(coord, now);
     }
 
     // Mark out-of-range for GC after grace (avoid borrow conflict by two-phase)
-    let cutoff = now - cfg.despawn_grace_seconds;
     let mut to_unmark: Vec<IVec2> = Vec::new();
-    for c in state.tiles.keys() {
-        if !desired.contains(c) && state.last_touched.get(c).copied().unwrap_or(0.0) < cutoff {
-            to_unmark.push(*c);
+    fading.retain(|c| {
+        if is_desired(&state, *c) || !(state.tiles.contains_key(c) || state.pending.contains_key(c)) {
+            return false;
         }
-    }
+        let expired = state.last_touched.get(c).copied().unwrap_or(0.0) < cutoff;
+        if expired { to_unmark.push(*c); }
+        !expired
+    });
     for c in to_unmark {
         state.last_touched.remove(&c);
     }
@@ -184,66 +1104,260 @@ pub fn queue_and_spawn_tasks_system(
 pub fn collect_finished_tasks_system(
     time: Res<Time>,
     mut commands: Commands,
-    mut images: ResMut<Assets<Image>>,
-    mut materials: ResMut<Assets<TerrainMaterial>>,  // <- material type
-    shared: Res<SharedMeshes>,
+    (mut images, mut texture_pool): (Option<ResMut<Assets<Image>>>, ResMut<TileTexturePool>),
+    mut materials: Option<ResMut<Assets<TerrainMaterial>>>,  // <- material type
+    mut meshes: Option<ResMut<Assets<Mesh>>>,
+    shared: Option<Res<SharedMeshes>>,
     mut state: ResMut<TerrainState>,
     cfg: Res<TerrainConfig>,
+    shading: Res<TerrainShadingSettings>,
+    water: Res<WaterSettings>,
+    climate: Res<AppliedClimate>,
+    offset: Res<WorldOffset>,
+    mut diagnostics: ResMut<TerrainDiagnostics>,
+    mut heightfield: ResMut<TerrainHeightfield>,
+    mut spawned: EventWriter<TileSpawned>,
     mut q_tasks: Query<(Entity, &mut TileBuildTask)>,
 ) {
     let now = time.elapsed_secs();
 
+    // finalizing uploads textures and spawns the tile, so many tasks landing
+    // together are spread over frames like dispatch is
+    let mut finalized = 0;
     for (e, mut t) in q_tasks.iter_mut() {
-        if let Some(result) = bevy::tasks::futures::check_ready(&mut t.task) {
-            let size_u = cfg.tile_resolution as u32;
-            let height_img = Image::new(
-                Extent3d { width: size_u, height: size_u, depth_or_array_layers: 1 },
-                TextureDimension::D2,
-                result.height_bytes,
-                TextureFormat::R32Float,
-                RenderAssetUsages::RENDER_WORLD,
-            );
-            let normal_img = Image::new(
-                Extent3d { width: size_u, height: size_u, depth_or_array_layers: 1 },
-                TextureDimension::D2,
-                result.normal_bytes,
-                TextureFormat::Rgba8Unorm,
-                RenderAssetUsages::RENDER_WORLD,
-            );
-            let height_h = images.add(height_img);
-            let normal_h = images.add(normal_img);
-
-            // per-tile params (linear color)
-            let c = color_for_coord(result.coord).to_linear();
-            let tile_color = Vec4::new(c.red, c.green, c.blue, c.alpha);
-            let params = TileParams {
-                tile_size: cfg.tile_size,
-                height_scale: 1.0,
-                texels_per_side: cfg.tile_resolution as u32,
-                _pad: 0,
-                tile_color,
+        if finalized >= cfg.max_spawns_per_frame { break; }
+        if let Some(mut result) = bevy::tasks::futures::check_ready(&mut t.task) {
+            let coord = result.coord;
+            if state.undesired.contains(&coord) && !state.is_pinned(coord) {
+                // left every loader while building: don't spawn it only for GC to drop it
+                state.pending.remove(&coord);
+                state.last_touched.remove(&coord);
+                state.undesired.remove(&coord);
+                state.dropped.push(coord);
+                state.release_entity(&mut commands, e, cfg.max_pooled_tiles);
+                continue;
+            }
+            finalized += 1;
+
+            // headless apps (no render plugins) keep only the CPU side of the tile
+            let mat = match (images.as_deref_mut(), materials.as_deref_mut()) {
+                (Some(images), Some(materials)) => {
+                    let textures = upload_tile_textures(&mut result, images, &mut texture_pool, &cfg);
+                    materials.add(tile_material(result.coord, textures, &cfg, &shading, &water, &climate))
+                }
+                _ => Handle::default(),
             };
 
-            // 🟣 build the *new* material with samplers + textures
-            let mat = materials.add(TerrainMaterial {
-                params, 
-                height_tex: height_h, 
-                normal_tex: normal_h
+            let local_origin = offset.to_local(t.origin);
+            let metadata = Arc::new(result.metadata.clone());
+            state.pending.remove(&result.coord);
+            state.tiles.insert(result.coord, LoadedTile {
+                entity: e,
+                material: mat.clone(),
+                min_height: result.min_height,
+                max_height: result.max_height,
+                build_seconds: result.build_seconds,
+                cpu_heights: true,
+                texture_bytes_saved: result.rgba.bytes_saved,
+                metadata: Some(metadata.clone()),
+            });
+            diagnostics.record_build(result.coord, result.build_seconds);
+
+            heightfield.tile_size = cfg.tile_size;
+            heightfield.resolution = cfg.tile_resolution;
+            heightfield.height_scale = shading.height_scale;
+            heightfield.world_offset = offset.0;
+            heightfield.insert(result.coord, HeightTile {
+                heights: result.heights,
+                curvature: result.curvature,
+                flow: result.flow,
+                holes: result.holes.clone(),
+                min_height: result.min_height,
+                max_height: result.max_height,
             });
+            state.last_touched.insert(result.coord, now);
 
             // spawn (unchanged, except the component type)
-            commands.entity(e)
-                .remove::<TileBuildTask>()
-                .insert((
-                    Tile { coord: result.coord },
-                    Mesh3d(shared.flat.clone()),
-                    bevy::pbr::MeshMaterial3d(mat),
-                    Transform::from_translation(Vec3::new(t.origin.x, 0.0, t.origin.y)),
-                    GlobalTransform::default(),
-                    Visibility::Visible,
-                    InheritedVisibility::default(),
-                    Name::new(format!("Tile {:?}", result.coord)),
-                ));
+            let mut tile = commands.entity(e);
+            // placed at collect time so a rebase while building is accounted for
+            tile.remove::<TileBuildTask>().insert(tile_components(result.coord, local_origin, Some(metadata.clone())));
+            let mesh = match (result.mesh.take(), meshes.as_deref_mut()) {
+                (Some(mesh), Some(meshes)) => Some(meshes.add(mesh)),
+                _ => shared.as_deref().map(|shared| shared.flat.clone()),
+            };
+            if let Some(mesh) = mesh {
+                tile.insert((Mesh3d(mesh), bevy::pbr::MeshMaterial3d(mat)));
+            }
+            spawned.write(TileSpawned { coord: result.coord, entity: e, metadata: Some(metadata) });
+        }
+    }
+}
+
+/// What every loaded tile entity has, wherever it was built.
+pub(crate) fn tile_components(coord: IVec2, local_origin: Vec2, metadata: Option<Arc<TileMetadata>>) -> impl Bundle {
+    (
+        Tile { coord, metadata },
+        Transform::from_translation(Vec3::new(local_origin.x, 0.0, local_origin.y)),
+        GlobalTransform::default(),
+        Visibility::Visible,
+        InheritedVisibility::default(),
+        Name::new(format!("Tile {coord:?}")),
+    )
+}
+
+/// Uploads a finished tile's textures, reusing pooled images where it can.
+fn upload_tile_textures(
+    result: &mut TileBuildResult,
+    images: &mut Assets<Image>,
+    pool: &mut TileTexturePool,
+    cfg: &TerrainConfig,
+) -> TileTextures {
+    pool.set_resolution(cfg.tile_resolution);
+    let size_u = cfg.tile_resolution as u32;
+    let curvature = result.curvature.iter().flat_map(|c| c.to_le_bytes()).collect();
+    let rgba = &mut result.rgba;
+    TileTextures {
+        height: pool.upload(images, result.height_encoding.format, size_u, 1, std::mem::take(&mut result.height_bytes)),
+        normal: pool.upload(
+            images,
+            rgba.format,
+            rgba.size,
+            rgba.normal_mip_levels,
+            std::mem::take(&mut rgba.normal_bytes),
+        ),
+        curvature: pool.upload(images, TextureFormat::R32Float, size_u, 1, curvature),
+        splat: pool.upload(images, rgba.format, rgba.size, 1, std::mem::take(&mut rgba.splat_bytes)),
+        height_encoding: result.height_encoding,
+        normal_mips: rgba.normal_mip_levels > 1,
+        holes: result.holes.as_ref().map(|mask| pool.upload(images, TextureFormat::R8Unorm, size_u, 1, mask.to_vec())),
+    }
+}
+
+/// A tile's data textures, see `TerrainMaterial`.
+pub(crate) struct TileTextures {
+    pub height: Handle<Image>,
+    pub normal: Handle<Image>,
+    pub curvature: Handle<Image>,
+    pub splat: Handle<Image>,
+    pub height_encoding: HeightEncoding,
+    /// `normal` has mips, see `TerrainConfig::normal_mipmaps`.
+    pub normal_mips: bool,
+    /// Hole mask, only for tiles with holes.
+    pub holes: Option<Handle<Image>>,
+}
+
+pub(crate) fn tile_material(
+    coord: IVec2,
+    textures: TileTextures,
+    cfg: &TerrainConfig,
+    shading: &TerrainShadingSettings,
+    water: &WaterSettings,
+    climate: &ClimateState,
+) -> TerrainMaterial {
+    let params = TileParams {
+        height_offset: textures.height_encoding.offset,
+        height_range: textures.height_encoding.range,
+        ..shading.tile_params(coord, cfg)
+    };
+
+    // 🟣 build the *new* material with samplers + textures
+    TerrainMaterial {
+        params, 
+        height_tex: textures.height, 
+        normal_tex: textures.normal,
+        curvature_tex: textures.curvature,
+        splat_override_tex: textures.splat,
+        splat: shading.splat_params(climate, water.sea_level),
+        overlay: shading.overlay_params(),
+        detail: shading.detail_params(),
+        detail_tex: shading.detail.texture.clone(),
+        detail_height_tex: shading.pom.height_texture.clone(),
+        pom: shading.pom_params(),
+        // filled in by `sync_grid_overlay_system` and `sync_tile_decals_system`
+        // once the tile has spawned
+        grid: default(),
+        decals: default(),
+        decal_tex_0: None,
+        decal_tex_1: None,
+        decal_tex_2: None,
+        decal_tex_3: None,
+        hole_tex: textures.holes,
+        flat_shading: shading.style == TerrainShading::Flat,
+        cpu_displaced: cfg.render_mode == TerrainRenderMode::CpuMesh,
+        normal_mips: textures.normal_mips,
+    }
+}
+
+/// Distance at which `falloff` leaves the contrast threshold
+/// `FogFalloff::from_visibility` uses, i.e. the fog is as good as opaque.
+fn fog_opaque_distance(falloff: &FogFalloff) -> Option<f32> {
+    let k = FogFalloff::koschmieder(1.0, FogFalloff::REVISED_KOSCHMIEDER_CONTRAST_THRESHOLD);
+    match *falloff {
+        FogFalloff::Linear { end, .. } => Some(end),
+        FogFalloff::Exponential { density } => (density > 0.0).then(|| k / density),
+        FogFalloff::ExponentialSquared { density } => (density > 0.0).then(|| k.sqrt() / density),
+        // the thinnest channel is the last to become opaque
+        FogFalloff::Atmospheric { extinction, .. } => {
+            (extinction.min_element() > 0.0).then(|| k / extinction.min_element())
+        }
+    }
+}
+
+/// Hide tiles beyond `TerrainConfig::max_view_distance` (or the fog). A
+/// hidden tile shows again only once half a tile inside the distance, so
+/// tiles on the boundary don't flicker.
+pub fn cull_distant_tiles_system(
+    cfg: Res<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    mut diagnostics: ResMut<TerrainDiagnostics>,
+    q_cameras: Query<(&GlobalTransform, Option<&DistanceFog>), With<Camera3d>>,
+    mut q_tiles: Query<(&Tile, &mut Visibility)>,
+) {
+    // just past opacity, so nothing visibly pops
+    let fog = q_cameras
+        .iter()
+        .filter_map(|(_, fog)| fog_opaque_distance(&fog?.falloff))
+        .fold(None, |far: Option<f32>, d| Some(far.map_or(d, |far| far.max(d))))
+        .map(|d| d * 1.05);
+    let cameras: Vec<Vec2> = q_cameras.iter().map(|(t, _)| t.translation().xz()).collect();
+    let Some(distance) = cfg.max_view_distance.or(fog).filter(|_| !cameras.is_empty()) else {
+        for (_, mut vis) in &mut q_tiles {
+            vis.set_if_neq(Visibility::Visible);
+        }
+        diagnostics.tiles_view_culled = 0;
+        return;
+    };
+
+    let band = cfg.tile_size * 0.5;
+    let mut culled = 0;
+    for (tile, mut vis) in &mut q_tiles {
+        let center = offset.tile_origin(tile.coord, cfg.tile_size) + 0.5 * cfg.tile_size;
+        let nearest = cameras.iter().map(|c| c.distance_squared(center)).fold(f32::INFINITY, f32::min).sqrt();
+        let limit = if *vis == Visibility::Hidden { distance - band } else { distance };
+        let hide = nearest > limit;
+        vis.set_if_neq(if hide { Visibility::Hidden } else { Visibility::Visible });
+        culled += hide as usize;
+    }
+    diagnostics.tiles_view_culled = culled;
+}
+
+/// Tiles render a flat grid displaced in the vertex shader, so the mesh
+/// bounds Bevy computes are flat too. Replace them with the tile's height
+/// range so tall or deep tiles aren't culled while still on screen.
+pub fn sync_tile_bounds_system(
+    heightfield: Res<TerrainHeightfield>,
+    state: Res<TerrainState>,
+    mut q_tiles: Query<(&Tile, &mut Aabb)>,
+) {
+    for (tile, mut aabb) in q_tiles.iter_mut() {
+        // tiles without CPU heights use their (conservative) recorded range
+        let bounds = heightfield.tile_aabb(tile.coord).or_else(|| {
+            let loaded = state.tiles.get(&tile.coord)?;
+            let (a, b) = (loaded.min_height * heightfield.height_scale, loaded.max_height * heightfield.height_scale);
+            Some(Aabb::from_min_max(Vec3::new(0.0, a.min(b), 0.0), Vec3::new(heightfield.tile_size, a.max(b), heightfield.tile_size)))
+        });
+        if let Some(bounds) = bounds {
+            aabb.set_if_neq(bounds);
         }
     }
 }
@@ -251,21 +1365,226 @@ pub fn collect_finished_tasks_system(
 pub fn garbage_collect_tiles_system(
     mut commands: Commands,
     mut state: ResMut<TerrainState>,
+    cfg: Res<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    mut heightfield: ResMut<TerrainHeightfield>,
+    mut despawned: EventWriter<TileDespawned>,
+    q_loaders: Query<&Transform, With<TileLoader>>,
     q_tiles: Query<(Entity, &Tile)>,
+    q_attachments: Query<(Entity, &TileAttachment)>,
 ) {
+    let rebuild = std::mem::take(&mut state.rebuild);
+    let mut rebuilt: Vec<(IVec2, Entity)> = Vec::new();
     let mut to_despawn: Vec<(IVec2, Entity)> = Vec::new();
     for (e, tile) in &q_tiles {
-        if !state.last_touched.contains_key(&tile.coord) {
+        if rebuild.contains(&tile.coord) {
+            rebuilt.push((tile.coord, e));
+        } else if !state.last_touched.contains_key(&tile.coord) && !state.is_pinned(tile.coord) {
             to_despawn.push((tile.coord, e));
         }
     }
+    for c in rebuild {
+        if let Some(e) = state.pending.remove(&c) {
+            state.release_entity(&mut commands, e, cfg.max_pooled_tiles);
+            state.undesired.remove(&c);
+            state.dropped.push(c);
+        }
+    }
+
+    // Spread large drops (teleports) over several frames, farthest first;
+    // the rest stay loaded and are kept if a loader comes back for them
+    let cap = cfg.max_despawns_per_frame.max(1);
+    if to_despawn.len() > cap {
+        let centers: Vec<IVec2> = q_loaders
+            .iter()
+            .map(|t| world_to_coord(t.translation, cfg.tile_size, &offset))
+            .collect();
+        to_despawn.sort_by_key(|(c, _)| {
+            let distance = centers.iter().map(|cc| (*cc - *c).abs().element_sum()).min().unwrap_or(0);
+            std::cmp::Reverse(distance)
+        });
+        to_despawn.truncate(cap);
+    }
+    to_despawn.extend(rebuilt);
+    let max_pooled = cfg.max_pooled_tiles;
+    despawn_tiles(&mut commands, &mut state, &mut heightfield, &mut despawned, &q_attachments, to_despawn, max_pooled);
+}
+
+/// Drops every tile and in-flight task when the generation parameters change
+/// (seed, noise, tile size or resolution), when `compute_flow` or
+/// `render_mode` is toggled, when `height_scale` changes under
+/// `TerrainRenderMode::CpuMesh`, or on `RegenerateTerrain`; the
+/// streamer then rebuilds the desired set from scratch. Pins and tile
+/// requests are kept.
+pub fn regenerate_on_config_change_system(
+    mut commands: Commands,
+    cfg: Res<TerrainConfig>,
+    mut state: ResMut<TerrainState>,
+    mut heightfield: ResMut<TerrainHeightfield>,
+    mut despawned: EventWriter<TileDespawned>,
+    q_tiles: Query<(Entity, &Tile)>,
+    q_attachments: Query<(Entity, &TileAttachment)>,
+    shading: Res<TerrainShadingSettings>,
+    mut forced: EventReader<RegenerateTerrain>,
+    mut last_hash: Local<Option<(u64, bool, TerrainRenderMode, HeightFormat, bool, bool, Option<u32>)>>,
+) {
+    let forced = forced.read().count() > 0;
+    // flow data, meshes and texture formats only exist on tiles built with
+    // them, so toggling rebuilds too; baked meshes also carry the height scale
+    let baked_scale = (cfg.render_mode == TerrainRenderMode::CpuMesh).then(|| shading.height_scale.to_bits());
+    let hash = (
+        cfg.generation_hash(),
+        cfg.compute_flow,
+        cfg.render_mode,
+        cfg.height_format,
+        cfg.normal_mipmaps,
+        cfg.compress_textures,
+        baked_scale,
+    );
+    let hash_changed = last_hash.replace(hash).is_some_and(|h| h != hash);
+    if !hash_changed && !forced { return; }
+
+    info!("terrain: regenerating {} tiles", state.tiles.len());
+    let pending: Vec<(IVec2, Entity)> = state.pending.drain().collect();
+    for (c, e) in pending {
+        state.release_entity(&mut commands, e, cfg.max_pooled_tiles);
+        state.dropped.push(c);
+    }
+    state.undesired.clear();
+    let to_despawn = q_tiles.iter().map(|(e, t)| (t.coord, e)).collect();
+    let max_pooled = cfg.max_pooled_tiles;
+    despawn_tiles(&mut commands, &mut state, &mut heightfield, &mut despawned, &q_attachments, to_despawn, max_pooled);
+    state.last_touched.clear();
+}
+
+fn despawn_tiles(
+    commands: &mut Commands,
+    state: &mut TerrainState,
+    heightfield: &mut TerrainHeightfield,
+    despawned: &mut EventWriter<TileDespawned>,
+    q_attachments: &Query<(Entity, &TileAttachment)>,
+    to_despawn: Vec<(IVec2, Entity)>,
+    max_pooled: usize,
+) {
+    if to_despawn.is_empty() { return; }
+
+    let gone: HashSet<IVec2> = to_despawn.iter().map(|(c, _)| *c).collect();
+    for (e, attachment) in q_attachments {
+        if gone.contains(&attachment.coord) {
+            commands.entity(e).despawn();
+        }
+    }
     for (c, e) in to_despawn {
         state.tiles.remove(&c);
-        commands.entity(e).despawn();
+        state.dropped.push(c);
+        heightfield.remove(c);
+        let ev = TileDespawned { coord: c, entity: e };
+        commands.trigger(ev);
+        despawned.write(ev);
+        state.release_entity(commands, e, max_pooled);
     }
 }
 
 pub fn log_registration(world: &mut World) {
     let has_assets = world.contains_resource::<Assets<TerrainMaterial>>();
     info!("TerrainMaterial registered? {}", if has_assets { "YES" } else { "NO" });
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::rng::TileRng;
+
+    const TILE: f32 = 10.0;
+
+    fn tile_center(c: IVec2) -> Vec2 {
+        (c.as_vec2() + 0.5) * TILE
+    }
+
+    /// Loader state the walk mutates: center, radius, filter kind, heading.
+    #[derive(Clone, Copy)]
+    struct Walker {
+        center: IVec2,
+        radius: i32,
+        kind: u64,
+        angle: f32,
+    }
+
+    impl Walker {
+        fn random(rng: &mut TileRng) -> Self {
+            Self {
+                center: IVec2::new(rng.range(-12.0, 12.0).floor() as i32, rng.range(-12.0, 12.0).floor() as i32),
+                radius: rng.range(0.0, 5.0) as i32,
+                kind: rng.next_u64() % 3,
+                angle: rng.range(0.0, std::f32::consts::TAU),
+            }
+        }
+
+        fn view(&self, entity: Entity) -> LoaderView {
+            let center = self.center;
+            let square = IRect::from_center_half_size(center, IVec2::splat(self.radius));
+            let pos = tile_center(center);
+            let forward = Vec2::from_angle(self.angle);
+            let filter = match self.kind {
+                0 => LoaderFilter::All,
+                1 => LoaderFilter::Rear { center, pos, forward, cull: RearCull::default() },
+                _ => {
+                    let corner = |turn: f32| pos + Vec2::from_angle(self.angle + turn) * 4.0 * TILE;
+                    LoaderFilter::Footprint { hull: convex_hull(vec![pos, corner(-0.5), corner(0.5)]), margin: TILE }
+                }
+            };
+            LoaderView { entity, square, filter }
+        }
+    }
+
+    /// What the loaders want, from scratch.
+    fn full_recompute(views: &[LoaderView]) -> HashSet<IVec2> {
+        let mut wanted = HashSet::new();
+        for v in views {
+            for_each_in_difference(v.square, IRect::EMPTY, |c| {
+                if views.iter().any(|w| w.keeps(c, tile_center(c), TILE)) {
+                    wanted.insert(c);
+                }
+            });
+        }
+        wanted
+    }
+
+    #[test]
+    fn incremental_coverage_matches_full_recompute() {
+        for seed in 0..20 {
+            let mut rng = TileRng::new(seed, IVec2::ZERO, 0x434F_5645); // "COVE"
+            let mut walkers: Vec<Option<Walker>> = (0..3).map(|_| Some(Walker::random(&mut rng))).collect();
+            let mut coverage = LoaderCoverage::default();
+            let mut before: HashSet<IVec2> = HashSet::new();
+            for step in 0..300 {
+                for slot in walkers.iter_mut() {
+                    let roll = rng.next_f32();
+                    match slot {
+                        None if roll < 0.2 => *slot = Some(Walker::random(&mut rng)),
+                        None => {}
+                        Some(_) if roll < 0.05 => *slot = None,
+                        Some(w) if roll < 0.35 => {
+                            w.center += IVec2::new(rng.range(-1.0, 2.0).floor() as i32, rng.range(-1.0, 2.0).floor() as i32);
+                        }
+                        Some(w) if roll < 0.5 => w.angle += rng.range(-0.6, 0.6),
+                        Some(w) if roll < 0.55 => *w = Walker::random(&mut rng),
+                        Some(_) => {}
+                    }
+                }
+                let views: Vec<LoaderView> = walkers
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, w)| w.map(|w| w.view(Entity::from_raw(i as u32))))
+                    .collect();
+                coverage.update(&views, tile_center, TILE);
+
+                assert_eq!(coverage.wanted, full_recompute(&views), "seed {seed}, step {step}");
+                let entered: HashSet<IVec2> = coverage.entered.iter().copied().collect();
+                let left: HashSet<IVec2> = coverage.left.iter().copied().collect();
+                assert_eq!(entered, &coverage.wanted - &before, "seed {seed}, step {step}");
+                assert_eq!(left, &before - &coverage.wanted, "seed {seed}, step {step}");
+                before = coverage.wanted.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Reuse of tile data textures.
+//!
+//! When a tile unloads, its height, normal, curvature, splat and hole images go
+//! to `TileTexturePool` instead of being dropped. The next tile built with
+//! the same format and size overwrites one's `data` in place of
+//! `images.add`, which saves the asset slot, handle and event churn per
+//! tile. A pooled image is only handed out once nothing else holds it (the
+//! tile's `TerrainMaterial`, a water material bound to its heights), so a
+//! texture still on screen is never overwritten.
+//!
+//! Bevy re-creates the GPU texture of a modified image on upload, so this
+//! doesn't save the GPU allocation itself.
+//!
+//! The pool holds at most `TerrainConfig::texture_pool_bytes` of image
+//! data, dropping the oldest first. A tile uploads at two sizes when its
+//! normal and splat textures are compressed (`tile_resolution` and the
+//! padded size), so entries of every size are kept side by side; the whole
+//! pool is dropped when `tile_resolution` changes.
+
+use bevy::pbr::MeshMaterial3d;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use std::collections::VecDeque;
+
+use super::material::TerrainMaterial;
+use super::systems::{TerrainConfig, TileDespawned};
+
+#[derive(Resource, Default)]
+pub struct TileTexturePool {
+    /// Oldest first.
+    retired: VecDeque<PooledImage>,
+    bytes: usize,
+    /// `TerrainConfig::tile_resolution` the pooled images were built for.
+    resolution: usize,
+}
+
+struct PooledImage {
+    handle: Handle<Image>,
+    format: TextureFormat,
+    size: u32,
+    mip_levels: u32,
+    bytes: usize,
+}
+
+impl TileTexturePool {
+    /// Image data held by the pool, in bytes.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.retired.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.retired.is_empty()
+    }
+
+    fn retire(&mut self, images: &Assets<Image>, handle: &Handle<Image>, budget: usize) {
+        // GPU-built tiles' images only live in the render world
+        let Some(image) = images.get(handle) else { return };
+        let Some(data) = image.data.as_ref() else { return };
+        let size = image.texture_descriptor.size;
+        if size.width != size.height { return; }
+        let bytes = data.len();
+        self.retired.push_back(PooledImage {
+            handle: handle.clone(),
+            format: image.texture_descriptor.format,
+            size: size.width,
+            mip_levels: image.texture_descriptor.mip_level_count,
+            bytes,
+        });
+        self.bytes += bytes;
+        self.trim(budget);
+    }
+
+    /// Tiles are now built at `resolution`; a change leaves no pooled size
+    /// that will be asked for again.
+    pub(crate) fn set_resolution(&mut self, resolution: usize) {
+        if resolution != self.resolution {
+            self.retired.clear();
+            self.bytes = 0;
+            self.resolution = resolution;
+        }
+    }
+
+    fn trim(&mut self, budget: usize) {
+        while self.bytes > budget {
+            let Some(old) = self.retired.pop_front() else { break };
+            self.bytes -= old.bytes;
+        }
+    }
+
+    /// A `size²` image of `format` with `data` (`mip_levels` levels, level
+    /// after level): a pooled one nobody else holds anymore, or a new one.
+    /// Mipmapped images sample trilinearly.
+    pub(crate) fn upload(
+        &mut self,
+        images: &mut Assets<Image>,
+        format: TextureFormat,
+        size: u32,
+        mip_levels: u32,
+        data: Vec<u8>,
+    ) -> Handle<Image> {
+        self.retired.retain(|p| {
+            let keep = images.contains(&p.handle);
+            if !keep {
+                self.bytes -= p.bytes;
+            }
+            keep
+        });
+        // the pool's own handle is the only strong one once the material is gone
+        let free = self.retired.iter().position(|p| {
+            p.size == size
+                && p.format == format
+                && p.mip_levels == mip_levels
+                && matches!(&p.handle, Handle::Strong(arc) if std::sync::Arc::strong_count(arc) == 1)
+        });
+        if let Some(pooled) = free.and_then(|i| self.retired.remove(i)) {
+            self.bytes -= pooled.bytes;
+            if let Some(image) = images.get_mut(&pooled.handle) {
+                image.data = Some(data);
+                return pooled.handle;
+            }
+        }
+        // `new_uninit`: `Image::new` expects exactly one level of data
+        let mut image = Image::new_uninit(
+            Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+            TextureDimension::D2,
+            format,
+            // kept in the main world too so brush edits can patch texels in place
+            RenderAssetUsages::default(),
+        );
+        image.data = Some(data);
+        if mip_levels > 1 {
+            image.texture_descriptor.mip_level_count = mip_levels;
+            image.sampler = ImageSampler::linear();
+        }
+        images.add(image)
+    }
+}
+
+/// Hand an unloading tile's textures to the pool. Runs before the tile
+/// entity is stripped, while it still has its material.
+pub fn retire_tile_textures(
+    trigger: Trigger<TileDespawned>,
+    cfg: Res<TerrainConfig>,
+    images: Res<Assets<Image>>,
+    materials: Res<Assets<TerrainMaterial>>,
+    mut pool: ResMut<TileTexturePool>,
+    q_materials: Query<&MeshMaterial3d<TerrainMaterial>>,
+) {
+    let Ok(material) = q_materials.get(trigger.event().entity) else { return };
+    let Some(material) = materials.get(&material.0) else { return };
+    pool.set_resolution(cfg.tile_resolution);
+    // built before a resolution change, nothing will reuse its sizes
+    if material.params.texels_per_side as usize != cfg.tile_resolution { return; }
+    let data = [&material.height_tex, &material.normal_tex, &material.curvature_tex, &material.splat_override_tex];
+    for handle in data.into_iter().chain(material.hole_tex.as_ref()) {
+        pool.retire(&images, handle, cfg.texture_pool_bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uploads_of_another_size_keep_pooled_images() {
+        let mut images = Assets::<Image>::default();
+        let mut pool = TileTexturePool::default();
+        pool.set_resolution(4);
+        let small = pool.upload(&mut images, TextureFormat::R8Unorm, 4, 1, vec![0; 16]);
+        let large = pool.upload(&mut images, TextureFormat::R8Unorm, 8, 1, vec![0; 64]);
+        pool.retire(&images, &small, usize::MAX);
+        pool.retire(&images, &large, usize::MAX);
+        let (small_id, large_id) = (small.id(), large.id());
+        drop((small, large));
+
+        // one tile's worth of uploads at both sizes reuses both images
+        let large = pool.upload(&mut images, TextureFormat::R8Unorm, 8, 1, vec![1; 64]);
+        let small = pool.upload(&mut images, TextureFormat::R8Unorm, 4, 1, vec![1; 16]);
+        assert_eq!((small.id(), large.id()), (small_id, large_id));
+        assert!(pool.is_empty());
+
+        pool.retire(&images, &small, usize::MAX);
+        pool.set_resolution(8);
+        assert!(pool.is_empty());
+        assert_eq!(pool.bytes(), 0);
+    }
+}
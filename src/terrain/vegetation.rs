@@ -0,0 +1,329 @@
+//! Per-tile grass scattering.
+//!
+//! When a tile spawns, a task scatters blades over its CPU height field and
+//! merges them into one mesh, spawned as a child of the tile (one draw per
+//! tile, despawned together with it). Density follows the `"grass"` entry of
+//! the biome multipliers; editing `GrassSettings` or `BiomeSettings` regrows
+//! all loaded tiles.
+//!
+//! Blades sway in the `Wind` through `GrassMaterial`, a `StandardMaterial`
+//! extension with its own vertex shader. The sway weight is each vertex's
+//! height within the source mesh (`UV_1.x` of the batch), so bases stay put.
+
+use bevy::pbr::{ExtendedMaterial, MaterialExtension, MaterialPlugin};
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use std::sync::Arc;
+
+use super::biome::{BiomeSampler, BiomeSettings};
+use super::heightfield::{grid_normal, sample_bilinear, TerrainHeightfield};
+use super::origin::WorldOffset;
+use super::rng::TileRng;
+use super::systems::{collect_finished_tasks_system, TerrainConfig, TerrainState, TileSpawned};
+use super::wind::{Wind, WindParams};
+
+pub struct VegetationPlugin;
+impl Plugin for VegetationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(MaterialPlugin::<GrassMaterial>::default())
+            .init_resource::<GrassSettings>()
+            .init_resource::<BiomeSettings>()
+            .add_systems(
+                Update,
+                (
+                    spawn_grass_tasks_system,
+                    collect_grass_tasks_system,
+                    grass_view_distance_system,
+                ).chain().after(collect_finished_tasks_system),
+            )
+            .add_systems(Update, update_grass_wind_system);
+    }
+}
+
+#[derive(Resource, Clone)]
+pub struct GrassSettings {
+    pub density_per_m2: f32,
+    /// Slope (degrees) at which density reaches zero; it fades linearly from flat ground.
+    pub max_slope: f32,
+    /// Uniform blade scale range.
+    pub scale_range: (f32, f32),
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<GrassMaterial>,
+    /// Batches whose tile center is farther than this from every camera are hidden.
+    pub view_distance: f32,
+    /// Multiplier on `Wind::strength` for the blades.
+    pub wind_amplitude: f32,
+}
+
+/// `StandardMaterial` with wind sway in the vertex (and prepass) shader.
+pub type GrassMaterial = ExtendedMaterial<StandardMaterial, GrassWind>;
+
+#[derive(Asset, TypePath, AsBindGroup, Clone, Default)]
+pub struct GrassWind {
+    /// Written every frame by `update_grass_wind_system`.
+    #[uniform(100)]
+    pub wind: WindParams,
+}
+
+impl MaterialExtension for GrassWind {
+    fn vertex_shader() -> ShaderRef { "shaders/grass_wind.wgsl".into() }
+    // shadows and the depth prepass sway along with the blades
+    fn prepass_vertex_shader() -> ShaderRef { "shaders/grass_wind_prepass.wgsl".into() }
+}
+
+impl FromWorld for GrassSettings {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(grass_blade_mesh());
+        let material = world.resource_mut::<Assets<GrassMaterial>>().add(GrassMaterial {
+            base: StandardMaterial {
+                base_color: Color::srgb(0.28, 0.5, 0.18),
+                perceptual_roughness: 0.8,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            },
+            extension: GrassWind::default(),
+        });
+        Self {
+            density_per_m2: 4.0,
+            max_slope: 35.0,
+            scale_range: (0.7, 1.3),
+            mesh,
+            material,
+            view_distance: 150.0,
+            wind_amplitude: 1.0,
+        }
+    }
+}
+
+/// Single tapered blade, ~0.6 units tall, base at the origin.
+pub fn grass_blade_mesh() -> Mesh {
+    let (w, h) = (0.04, 0.6);
+    let positions = vec![
+        [-w, 0.0, 0.0], [w, 0.0, 0.0],
+        [-w * 0.6, h * 0.5, 0.0], [w * 0.6, h * 0.5, 0.0],
+        [0.0, h, 0.0],
+    ];
+    let normals = vec![[0.0, 0.0, 1.0]; 5];
+    let uvs = vec![[0.0, 0.0], [1.0, 0.0], [0.0, 0.5], [1.0, 0.5], [0.5, 1.0]];
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_indices(Indices::U32(vec![0, 1, 2, 1, 3, 2, 2, 3, 4]))
+}
+
+/// Marks the merged vegetation mesh entity of a tile.
+#[derive(Component)]
+pub struct GrassBatch {
+    pub coord: IVec2,
+}
+
+#[derive(Component)]
+pub struct GrassBuildTask {
+    pub task: Task<Option<Mesh>>,
+}
+
+/// CPU copy of a source mesh that can be stamped many times into one batch.
+#[derive(Clone)]
+pub struct MeshTemplate {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<[f32; 2]>,
+    /// Sway weight per vertex: height above the lowest vertex, 0..1.
+    pub sway: Vec<f32>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshTemplate {
+    pub fn from_mesh(mesh: &Mesh) -> Option<Self> {
+        let positions: Vec<Vec3> = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)?
+            .as_float3()?
+            .iter()
+            .map(|p| Vec3::from_array(*p))
+            .collect();
+        let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(n)) => n.iter().map(|n| Vec3::from_array(*n)).collect(),
+            _ => vec![Vec3::Y; positions.len()],
+        };
+        let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            Some(VertexAttributeValues::Float32x2(uv)) => uv.clone(),
+            _ => vec![[0.0, 0.0]; positions.len()],
+        };
+        let indices = match mesh.indices() {
+            Some(i) => i.iter().map(|i| i as u32).collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+        let (lo, hi) = positions.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), p| (lo.min(p.y), hi.max(p.y)));
+        let sway = positions.iter().map(|p| ((p.y - lo) / (hi - lo).max(1e-6)).clamp(0.0, 1.0)).collect();
+        Some(Self { positions, normals, uvs, sway, indices })
+    }
+}
+
+/// Accumulates transformed copies of templates into a single mesh.
+#[derive(Default)]
+pub struct MeshBatch {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    sway: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl MeshBatch {
+    pub fn push(&mut self, template: &MeshTemplate, xf: &Transform) {
+        let base = self.positions.len() as u32;
+        self.positions.extend(template.positions.iter().map(|p| xf.transform_point(*p).to_array()));
+        self.normals.extend(template.normals.iter().map(|n| (xf.rotation * *n).to_array()));
+        self.uvs.extend_from_slice(&template.uvs);
+        self.sway.extend(template.sway.iter().map(|w| [*w, 0.0]));
+        self.indices.extend(template.indices.iter().map(|i| base + i));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn into_mesh(self) -> Mesh {
+        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_1, self.sway)
+            .with_inserted_indices(Indices::U32(self.indices))
+    }
+}
+
+pub fn spawn_grass_tasks_system(
+    mut commands: Commands,
+    mut spawned: EventReader<TileSpawned>,
+    settings: Res<GrassSettings>,
+    biomes: Res<BiomeSettings>,
+    cfg: Res<TerrainConfig>,
+    state: Res<TerrainState>,
+    heightfield: Res<TerrainHeightfield>,
+    meshes: Res<Assets<Mesh>>,
+    q_batches: Query<Entity, With<GrassBatch>>,
+    // tiles that spawned before the blade mesh finished loading
+    mut waiting: Local<Vec<TileSpawned>>,
+) {
+    waiting.extend(spawned.read().cloned());
+
+    let edited = |changed: bool, added: bool| changed && !added;
+    if edited(settings.is_changed(), settings.is_added()) || edited(biomes.is_changed(), biomes.is_added()) {
+        for e in &q_batches {
+            commands.entity(e).despawn();
+        }
+        waiting.clear();
+        waiting.extend(state.tiles.iter().map(|(c, t)| TileSpawned { coord: *c, entity: t.entity, metadata: t.metadata.clone() }));
+    }
+    if waiting.is_empty() { return; }
+    let Some(template) = meshes.get(&settings.mesh).and_then(MeshTemplate::from_mesh) else { return };
+    let template = Arc::new(template);
+
+    let pool = AsyncComputeTaskPool::get();
+    for ev in waiting.drain(..) {
+        let Some(tile) = heightfield.tile(ev.coord) else { continue };
+        let heights = tile.heights.clone();
+        let template = template.clone();
+        let (n, size, scale) = (heightfield.resolution, heightfield.tile_size, heightfield.height_scale);
+        let rng = TileRng::new(cfg.seed, ev.coord, 0x6752_4153); // "gRAS"
+        let s = settings.clone();
+        let (biomes, seed, origin) = (biomes.clone(), cfg.seed, ev.coord.as_vec2() * size);
+
+        let task = pool.spawn(async move {
+            let biomes = BiomeSampler::new(&biomes, seed);
+            scatter_grass(&template, &heights, n, size, scale, origin, &biomes, rng, &s)
+        });
+        commands.spawn((
+            Name::new(format!("Grass {:?}", ev.coord)),
+            GrassBatch { coord: ev.coord },
+            GrassBuildTask { task },
+            Transform::IDENTITY,
+            Visibility::Hidden,
+            ChildOf(ev.entity),
+        ));
+    }
+}
+
+fn scatter_grass(
+    template: &MeshTemplate,
+    heights: &[f32],
+    n: usize,
+    tile_size: f32,
+    height_scale: f32,
+    origin: Vec2,
+    biomes: &BiomeSampler,
+    mut rng: TileRng,
+    s: &GrassSettings,
+) -> Option<Mesh> {
+    let step = tile_size / (n as f32 - 1.0);
+    let count = (s.density_per_m2 * tile_size * tile_size) as usize;
+    let max_slope = s.max_slope.to_radians().max(1e-3);
+    let mut batch = MeshBatch::default();
+    for _ in 0..count {
+        let p = Vec2::new(rng.next_f32(), rng.next_f32()) * tile_size;
+        let (yaw, scale, keep) = (rng.range(0.0, std::f32::consts::TAU), rng.range(s.scale_range.0, s.scale_range.1), rng.next_f32());
+
+        let local = p / step;
+        let normal = grid_normal(heights, n, step, height_scale, local);
+        let slope = normal.y.clamp(-1.0, 1.0).acos();
+        if keep > (1.0 - slope / max_slope) * biomes.density_at(origin + p, "grass") { continue; }
+
+        let y = sample_bilinear(heights, n, local) * height_scale;
+        let xf = Transform::from_xyz(p.x, y, p.y)
+            .with_rotation(Quat::from_rotation_y(yaw))
+            .with_scale(Vec3::splat(scale));
+        batch.push(template, &xf);
+    }
+    (!batch.is_empty()).then(|| batch.into_mesh())
+}
+
+pub fn collect_grass_tasks_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    settings: Res<GrassSettings>,
+    mut q_tasks: Query<(Entity, &mut GrassBuildTask)>,
+) {
+    for (e, mut t) in q_tasks.iter_mut() {
+        let Some(result) = bevy::tasks::futures::check_ready(&mut t.task) else { continue };
+        let mut ec = commands.entity(e);
+        ec.remove::<GrassBuildTask>();
+        if let Some(mesh) = result {
+            ec.insert((Mesh3d(meshes.add(mesh)), MeshMaterial3d(settings.material.clone())));
+        }
+    }
+}
+
+pub fn grass_view_distance_system(
+    settings: Res<GrassSettings>,
+    cfg: Res<TerrainConfig>,
+    offset: Res<WorldOffset>,
+    q_cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut q_batches: Query<(&GrassBatch, &mut Visibility), Without<GrassBuildTask>>,
+) {
+    let cameras: Vec<Vec2> = q_cameras.iter().map(|t| t.translation().xz()).collect();
+    // a batch is visible if any part of its tile is within view distance
+    let reach = settings.view_distance + cfg.tile_size * std::f32::consts::FRAC_1_SQRT_2;
+    for (batch, mut vis) in q_batches.iter_mut() {
+        let center = offset.tile_origin(batch.coord, cfg.tile_size) + 0.5 * cfg.tile_size;
+        let near = cameras.iter().any(|c| c.distance_squared(center) <= reach * reach);
+        vis.set_if_neq(if near { Visibility::Inherited } else { Visibility::Hidden });
+    }
+}
+
+pub fn update_grass_wind_system(
+    time: Res<Time>,
+    wind: Res<Wind>,
+    settings: Res<GrassSettings>,
+    mut materials: ResMut<Assets<GrassMaterial>>,
+) {
+    if let Some(mat) = materials.get_mut(&settings.material) {
+        mat.extension.wind = wind.params(time.elapsed_secs_f64(), settings.wind_amplitude);
+    }
+}
@@ -0,0 +1,339 @@
+//! Sea-level water surface.
+//!
+//! `WaterSettings` is owned by the terrain module (`TerrainPlugin` inits it)
+//! so splatting and tile metadata can read `sea_level` without depending on
+//! the water renderer. `WaterPlugin` adds the visible surface: one quad per
+//! tile whose lowest point is below sea level, spawned as a child of the tile
+//! so it despawns with it. Tiles entirely above the water get nothing; a
+//! brush edit or a GPU tile's height readback re-checks its tile.
+//! Each quad gets its own `WaterMaterial` (summed Gerstner waves, see
+//! `shaders/water.wgsl`) bound to its tile's height texture, so the shader
+//! knows the water depth per pixel for shallow tinting and shoreline foam.
+//! It follows the tile's height encoding when an edit re-encodes it.
+
+use bevy::asset::Asset;
+use bevy::pbr::{Material, MaterialPlugin};
+use bevy::prelude::*;
+use bevy::reflect::TypePath;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef, ShaderType};
+use bevy::render::RenderPlugin;
+use std::collections::HashSet;
+use std::f32::consts::TAU;
+
+use super::edit::TileHeightsEdited;
+use super::gpu_generation::TileHeightsReady;
+use super::heightfield::TerrainHeightfield;
+use super::material::{TerrainMaterial, TileParams};
+use super::systems::{collect_finished_tasks_system, TerrainConfig, TerrainState, TileSpawned};
+
+pub struct WaterPlugin;
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WaterSettings>();
+        // nothing to draw in headless apps; `sea_level` still works
+        if !app.is_plugin_added::<RenderPlugin>() {
+            return;
+        }
+        app
+            // the wave displacement only exists in our vertex shader
+            .add_plugins(MaterialPlugin::<WaterMaterial> {
+                prepass_enabled: false,
+                shadows_enabled: false,
+                ..default()
+            })
+            .init_resource::<WaterAssets>()
+            // sent by the edit and GPU generation plugins, which may be left out
+            .add_event::<TileHeightsEdited>()
+            .add_event::<TileHeightsReady>()
+            .add_systems(
+                Update,
+                (
+                    spawn_water_tiles_system,
+                    apply_water_settings_system.run_if(resource_changed::<WaterSettings>),
+                    sync_water_height_encoding_system,
+                )
+                    .chain()
+                    .after(collect_finished_tasks_system),
+            );
+    }
+}
+
+/// Number of summed waves; matches `WAVE_COUNT` in `water.wgsl`.
+pub const WAVE_COUNT: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GerstnerWave {
+    pub direction: Vec2,
+    /// Crest height above the rest level; 0 disables the wave.
+    pub amplitude: f32,
+    pub wavelength: f32,
+    /// Phase speed along `direction`, units per second.
+    pub speed: f32,
+}
+
+#[derive(Resource, Clone)]
+pub struct WaterSettings {
+    /// World height of the water surface.
+    pub sea_level: f32,
+    /// Color of shallow water; blends to `deep_color` with depth.
+    pub color: Color,
+    pub deep_color: Color,
+    /// How quickly `deep_color` takes over, per unit of water depth.
+    pub absorption: f32,
+    /// Water depth below which the shoreline foam band appears.
+    pub foam_width: f32,
+    /// Alpha scales foam coverage.
+    pub foam_color: Color,
+    /// World-space frequency of the noise breaking up the foam band.
+    pub foam_noise_scale: f32,
+    pub roughness: f32,
+    pub waves: [GerstnerWave; WAVE_COUNT],
+    /// 0 = sine waves, 1 = sharpest crests without looping.
+    pub steepness: f32,
+}
+impl Default for WaterSettings {
+    fn default() -> Self {
+        let wave = |x: f32, z: f32, amplitude, wavelength, speed| GerstnerWave {
+            direction: Vec2::new(x, z),
+            amplitude,
+            wavelength,
+            speed,
+        };
+        Self {
+            sea_level: 0.0,
+            color: Color::srgba(0.15, 0.45, 0.5, 0.6),
+            deep_color: Color::srgba(0.02, 0.1, 0.2, 0.95),
+            absorption: 0.15,
+            foam_width: 0.6,
+            foam_color: Color::srgba(0.9, 0.95, 1.0, 0.9),
+            foam_noise_scale: 0.8,
+            roughness: 0.08,
+            waves: [
+                wave(1.0, 0.2, 0.35, 24.0, 3.0),
+                wave(0.7, 0.7, 0.2, 13.0, 2.2),
+                wave(-0.3, 1.0, 0.1, 7.0, 1.6),
+                wave(0.2, -1.0, 0.05, 3.5, 1.1),
+            ],
+            steepness: 0.6,
+        }
+    }
+}
+
+impl WaterSettings {
+    /// Gerstner offset of the surface point whose rest position is `rest` (world xz).
+    /// Mirrors `gerstner()` in `water.wgsl`.
+    pub fn displacement_at(&self, rest: Vec2, time: f32) -> Vec3 {
+        let mut offset = Vec3::ZERO;
+        for w in &self.waves {
+            if w.amplitude <= 0.0 || w.wavelength <= 0.0 { continue; }
+            let dir = w.direction.normalize_or(Vec2::X);
+            let k = TAU / w.wavelength;
+            let (s, c) = (k * (dir.dot(rest) - w.speed * time)).sin_cos();
+            let q = self.steepness / (k * w.amplitude * WAVE_COUNT as f32);
+            offset += Vec3::new(dir.x * q * w.amplitude * c, w.amplitude * s, dir.y * q * w.amplitude * c);
+        }
+        offset
+    }
+
+    /// World height of the animated surface at `pos` (world xz). `time` must be
+    /// the shader's clock, i.e. `Time::elapsed_secs_wrapped()`.
+    pub fn water_height_at(&self, pos: Vec2, time: f32) -> f32 {
+        // Gerstner waves move points sideways; find the rest position that
+        // lands on `pos` with a few fixed-point steps.
+        let mut rest = pos;
+        for _ in 0..4 {
+            rest = pos - self.displacement_at(rest, time).xz();
+        }
+        self.sea_level + self.displacement_at(rest, time).y
+    }
+
+    fn params(&self) -> WaterParams {
+        let mut waves = [Vec4::ZERO; WAVE_COUNT];
+        let mut speeds = [0.0; WAVE_COUNT];
+        for (i, w) in self.waves.iter().enumerate() {
+            let dir = w.direction.normalize_or(Vec2::X);
+            waves[i] = Vec4::new(dir.x, dir.y, w.amplitude, w.wavelength);
+            speeds[i] = w.speed;
+        }
+        WaterParams {
+            color: self.color.to_linear().to_vec4(),
+            deep_color: self.deep_color.to_linear().to_vec4(),
+            waves,
+            speeds: Vec4::from_array(speeds),
+            foam_color: self.foam_color.to_linear().to_vec4(),
+            steepness: self.steepness,
+            roughness: self.roughness,
+            absorption: self.absorption,
+            foam_width: self.foam_width,
+            foam_noise_scale: self.foam_noise_scale,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ShaderType, Default)]
+pub struct WaterParams {
+    pub color: Vec4,
+    pub deep_color: Vec4,
+    /// xy = direction, z = amplitude, w = wavelength
+    pub waves: [Vec4; WAVE_COUNT],
+    pub speeds: Vec4,
+    pub foam_color: Vec4,
+    pub steepness: f32,
+    pub roughness: f32,
+    pub absorption: f32,
+    pub foam_width: f32,
+    pub foam_noise_scale: f32,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+pub struct WaterMaterial {
+    #[uniform(0)]
+    pub params: WaterParams,
+
+    /// The tile's terrain params and heightmap, for water depth.
+    #[uniform(1)]
+    pub tile: TileParams,
+    #[texture(2, sample_type = "float", filterable = false)]
+    pub height_tex: Handle<Image>,
+}
+
+impl Material for WaterMaterial {
+    fn vertex_shader() -> ShaderRef { "shaders/water.wgsl".into() }
+    fn fragment_shader() -> ShaderRef { "shaders/water.wgsl".into() }
+    fn alpha_mode(&self) -> AlphaMode { AlphaMode::Blend }
+}
+
+/// Shared grid mesh (unit size, scaled per tile) for water tiles.
+#[derive(Resource)]
+pub struct WaterAssets {
+    pub mesh: Handle<Mesh>,
+}
+
+impl FromWorld for WaterAssets {
+    fn from_world(world: &mut World) -> Self {
+        // corner-anchored so the quad lines up with the tile's local origin;
+        // subdivided so the vertex shader has something to displace
+        let mesh = Plane3d::new(Vec3::Y, Vec2::splat(0.5)).mesh().subdivisions(31).build()
+            .translated_by(Vec3::new(0.5, 0.0, 0.5));
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(mesh);
+        Self { mesh }
+    }
+}
+
+/// Water quad of one tile (child of the tile entity).
+#[derive(Component)]
+pub struct WaterTile {
+    pub coord: IVec2,
+}
+
+/// Whether any part of a tile with this (unscaled) height range is under water.
+pub fn tile_has_water(min_height: f32, height_scale: f32, sea_level: f32) -> bool {
+    min_height * height_scale < sea_level
+}
+
+pub fn spawn_water_tiles_system(
+    mut commands: Commands,
+    mut spawned: EventReader<TileSpawned>,
+    mut edited: EventReader<TileHeightsEdited>,
+    mut ready: EventReader<TileHeightsReady>,
+    settings: Res<WaterSettings>,
+    assets: Res<WaterAssets>,
+    cfg: Res<TerrainConfig>,
+    state: Res<TerrainState>,
+    heightfield: Res<TerrainHeightfield>,
+    terrain_materials: Res<Assets<TerrainMaterial>>,
+    mut materials: ResMut<Assets<WaterMaterial>>,
+    q_water: Query<(Entity, &WaterTile)>,
+    mut last_level: Local<Option<(f32, f32)>>,
+) {
+    let mut tiles: Vec<(IVec2, Entity)> = spawned.read().map(|ev| (ev.coord, ev.entity)).collect();
+    // heights changed in place: the tile may have gone under or come out of the water
+    let mut refresh: HashSet<IVec2> = edited.read().map(|ev| ev.coord).chain(ready.read().map(|ev| ev.coord)).collect();
+
+    // sea level (or terrain height scale) moved: re-evaluate every loaded tile
+    let level = (settings.sea_level, heightfield.height_scale);
+    if last_level.is_some_and(|l| l != level) {
+        for (e, _) in &q_water {
+            commands.entity(e).despawn();
+        }
+        tiles = state.tiles.iter().map(|(c, t)| (*c, t.entity)).collect();
+    } else if !refresh.is_empty() {
+        for (e, water) in &q_water {
+            if !refresh.remove(&water.coord) { continue; }
+            let under = state.tiles.get(&water.coord).is_some_and(|t| {
+                tile_has_water(t.min_height, heightfield.height_scale, settings.sea_level)
+            });
+            if !under { commands.entity(e).despawn(); }
+        }
+        // the rest have no quad yet; one that also spawned this frame is listed once
+        tiles.extend(refresh.into_iter().filter_map(|c| state.tiles.get(&c).map(|t| (c, t.entity))));
+        tiles.sort_unstable_by_key(|(c, _)| (c.y, c.x));
+        tiles.dedup_by_key(|(c, _)| *c);
+    }
+    *last_level = Some(level);
+
+    for (coord, entity) in tiles {
+        let Some(tile) = state.tiles.get(&coord) else { continue };
+        if !tile_has_water(tile.min_height, heightfield.height_scale, settings.sea_level) { continue; }
+        let Some(terrain) = terrain_materials.get(&tile.material) else { continue };
+        let material = materials.add(WaterMaterial {
+            params: settings.params(),
+            tile: terrain.params,
+            height_tex: terrain.height_tex.clone(),
+        });
+        commands.spawn((
+            Name::new(format!("Water {:?}", coord)),
+            WaterTile { coord },
+            Mesh3d(assets.mesh.clone()),
+            MeshMaterial3d(material),
+            Transform::from_xyz(0.0, settings.sea_level, 0.0)
+                .with_scale(Vec3::new(cfg.tile_size, 1.0, cfg.tile_size)),
+            ChildOf(entity),
+        ));
+    }
+}
+
+pub fn apply_water_settings_system(
+    settings: Res<WaterSettings>,
+    mut materials: ResMut<Assets<WaterMaterial>>,
+    q_water: Query<&MeshMaterial3d<WaterMaterial>, With<WaterTile>>,
+) {
+    let params = settings.params();
+    for handle in q_water.iter() {
+        if let Some(mat) = materials.get_mut(&handle.0) {
+            mat.params = params;
+        }
+    }
+}
+
+/// Copy a tile's height encoding (`TileParams::height_offset`/`height_range`)
+/// to its water when the terrain material changed, e.g. after an edit
+/// re-encoded an `R16` height texture.
+pub fn sync_water_height_encoding_system(
+    mut events: EventReader<AssetEvent<TerrainMaterial>>,
+    terrain_materials: Res<Assets<TerrainMaterial>>,
+    mut materials: ResMut<Assets<WaterMaterial>>,
+    q_tiles: Query<&MeshMaterial3d<TerrainMaterial>>,
+    q_water: Query<(&ChildOf, &MeshMaterial3d<WaterMaterial>), With<WaterTile>>,
+) {
+    let modified: Vec<AssetId<TerrainMaterial>> = events
+        .read()
+        .filter_map(|ev| match ev {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if modified.is_empty() { return; }
+    for (parent, water) in &q_water {
+        let Ok(terrain) = q_tiles.get(parent.parent()) else { continue };
+        if !modified.contains(&terrain.id()) { continue; }
+        let Some(terrain) = terrain_materials.get(&terrain.0) else { continue };
+        let (offset, range) = (terrain.params.height_offset, terrain.params.height_range);
+        let Some(current) = materials.get(&water.0) else { continue };
+        if (current.tile.height_offset, current.tile.height_range) == (offset, range) { continue; }
+        if let Some(mat) = materials.get_mut(&water.0) {
+            mat.tile.height_offset = offset;
+            mat.tile.height_range = range;
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Wind for vegetation sway.
+//!
+//! `Wind` is owned by `TerrainPlugin`. Every frame the vegetation and
+//! impostor materials get a copy as `WindParams`, with the current gust
+//! factor from a seeded 1D noise so strength varies over time. The shaders
+//! add a travelling phase along the wind direction so neighbouring plants
+//! don't sway in lockstep, and weight the offset so bases stay planted.
+
+use bevy::prelude::*;
+use bevy::render::render_resource::ShaderType;
+
+use super::rng::TileRng;
+
+#[derive(Resource, Clone, Debug)]
+pub struct Wind {
+    /// Blowing toward, on the XZ plane; normalized when uploaded.
+    pub direction: Vec2,
+    /// Sway at the top of a plant, world units, before per-layer amplitude.
+    pub strength: f32,
+    /// Sway oscillations (and gust changes) per second.
+    pub gust_frequency: f32,
+    /// How much gusts vary the strength, 0 = steady wind.
+    pub gustiness: f32,
+    pub seed: u32,
+}
+impl Default for Wind {
+    fn default() -> Self {
+        Self { direction: Vec2::X, strength: 0.15, gust_frequency: 0.5, gustiness: 0.5, seed: 0 }
+    }
+}
+
+impl Wind {
+    /// Strength multiplier at time `t`, in `1 ± gustiness`.
+    pub fn gust_at(&self, t: f32) -> f32 {
+        // smoothed value noise over whole steps of the gust frequency
+        let x = t * self.gust_frequency.max(0.0) * 0.25;
+        let k = x.floor();
+        let value = |k: f32| TileRng::new(self.seed, IVec2::new(k as i32, 0), 0x4755_5354).next_f32() * 2.0 - 1.0; // "GUST"
+        let f = x - k;
+        let n = value(k).lerp(value(k + 1.0), f * f * (3.0 - 2.0 * f));
+        (1.0 + self.gustiness * n).max(0.0)
+    }
+
+    /// Uniform for a material whose plants sway `amplitude` times the wind.
+    /// `t` is the elapsed time in seconds.
+    pub fn params(&self, t: f64, amplitude: f32) -> WindParams {
+        WindParams {
+            direction: self.direction.normalize_or(Vec2::X),
+            strength: self.strength * self.gust_at(t as f32),
+            // wrapped here so the shader never sees a large time value
+            phase: (t * self.gust_frequency as f64).fract() as f32 * std::f32::consts::TAU,
+            amplitude,
+            phase_seed: (self.seed % 1024) as f32,
+        }
+    }
+}
+
+/// Per-material copy of the wind, see `Wind::params`.
+#[derive(Clone, Copy, ShaderType, Default, Debug)]
+pub struct WindParams {
+    pub direction: Vec2,
+    /// Gusted strength.
+    pub strength: f32,
+    /// Sway cycle position, radians.
+    pub phase: f32,
+    pub amplitude: f32,
+    pub phase_seed: f32,
+}
@@ -0,0 +1,50 @@
+//! Headless app helpers shared by the integration tests.
+#![cfg(feature = "terrain")]
+#![allow(dead_code)]
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use thrive::prelude::*;
+
+/// Small tiles, so a test streams a few in well under a second.
+pub fn test_config() -> TerrainConfig {
+    TerrainConfig {
+        tile_size: 16.0,
+        tile_resolution: 17,
+        despawn_grace_seconds: 0.0,
+        ..default()
+    }
+}
+
+/// `MinimalPlugins` and the terrain streaming layer with `test_config`.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).insert_resource(test_config()).add_plugins(TerrainPlugin);
+    app
+}
+
+/// Run updates until `done` holds; false if it didn't within a few seconds.
+pub fn update_until(app: &mut App, mut done: impl FnMut(&mut World) -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        app.update();
+        if done(app.world_mut()) { return true; }
+        std::thread::sleep(Duration::from_millis(2));
+    }
+    false
+}
+
+/// Coords of the loaded tiles, sorted.
+pub fn loaded_tiles(world: &World) -> Vec<IVec2> {
+    let mut coords: Vec<IVec2> = world.resource::<TerrainState>().tiles.keys().copied().collect();
+    coords.sort_by_key(|c| (c.y, c.x));
+    coords
+}
+
+/// The `(2r+1)²` coords around `center`, sorted like `loaded_tiles`.
+pub fn square(center: IVec2, r: i32) -> Vec<IVec2> {
+    let mut coords: Vec<IVec2> = (-r..=r).flat_map(|z| (-r..=r).map(move |x| center + IVec2::new(x, z))).collect();
+    coords.sort_by_key(|c| (c.y, c.x));
+    coords
+}
@@ -0,0 +1,24 @@
+//! `ThrivePlugins` runs without a renderer: tiles stream on `MinimalPlugins`.
+#![cfg(feature = "terrain")]
+
+mod common;
+
+use bevy::prelude::*;
+use common::{loaded_tiles, square, test_config, update_until};
+use thrive::prelude::*;
+
+#[test]
+fn thrive_plugins_stream_headless() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ThrivePlugins::with_terrain_config(test_config())));
+    app.world_mut().spawn((Transform::from_xyz(8.0, 0.0, 8.0), TileLoader { radius_tiles: 1, ..default() }));
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(update_until(&mut app, |w| loaded_tiles(w) == square(IVec2::ZERO, 1)));
+
+    let world = app.world();
+    let heightfield = world.resource::<TerrainHeightfield>();
+    assert!(heightfield.height_at(Vec2::new(8.0, 8.0)).is_some());
+    assert!(world.contains_resource::<WaterSettings>());
+}
@@ -0,0 +1,33 @@
+//! With `inspector`, editors find the terrain types in the type registry
+//! and can edit them through reflection.
+#![cfg(all(feature = "inspector", feature = "terrain"))]
+
+mod common;
+
+use bevy::ecs::reflect::{ReflectComponent, ReflectResource};
+use bevy::prelude::*;
+use bevy::reflect::GetPath;
+use common::headless_app;
+use thrive::prelude::*;
+
+#[test]
+fn terrain_types_are_registered() {
+    let app = headless_app();
+    let registry = app.world().resource::<AppTypeRegistry>().read();
+    assert!(registry.get_type_data::<ReflectResource>(std::any::TypeId::of::<TerrainConfig>()).is_some());
+    assert!(registry.get_type_data::<ReflectComponent>(std::any::TypeId::of::<TileLoader>()).is_some());
+    for name in ["TileParams", "LoadMode", "RearCull"] {
+        assert!(registry.get_with_short_type_path(name).is_some(), "{name} is not registered");
+    }
+}
+
+#[test]
+fn config_edits_through_reflection() {
+    let mut app = headless_app();
+    let registry = app.world().resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let reflect = registry.get_type_data::<ReflectResource>(std::any::TypeId::of::<TerrainConfig>()).unwrap();
+    let mut cfg = reflect.reflect_mut(app.world_mut()).unwrap();
+    *cfg.path_mut::<u32>("seed").unwrap() = 99;
+    assert_eq!(app.world().resource::<TerrainConfig>().seed, 99);
+}
@@ -0,0 +1,45 @@
+//! A stationary `TileLoader` still reacts to radius changes and to being
+//! despawned.
+#![cfg(feature = "terrain")]
+
+mod common;
+
+use bevy::prelude::*;
+use common::{headless_app, loaded_tiles, square, update_until};
+use thrive::prelude::*;
+
+fn loaded_around_origin(radius_tiles: i32) -> (App, Entity) {
+    let mut app = headless_app();
+    let loader = app.world_mut().spawn((Transform::from_xyz(8.0, 0.0, 8.0), TileLoader { radius_tiles, ..default() })).id();
+    assert!(update_until(&mut app, |w| loaded_tiles(w) == square(IVec2::ZERO, radius_tiles)));
+    (app, loader)
+}
+
+#[test]
+fn growing_radius_queues_the_new_ring() {
+    let (mut app, loader) = loaded_around_origin(1);
+    app.world_mut().get_mut::<TileLoader>(loader).unwrap().radius_tiles = 2;
+    app.update();
+
+    let ring: Vec<IVec2> = square(IVec2::ZERO, 2).into_iter().filter(|c| c.abs().max_element() == 2).collect();
+    let state = app.world().resource::<TerrainState>();
+    let started = ring.iter().filter(|c| state.pending.contains_key(c) || state.tiles.contains_key(c)).count();
+    let budget = app.world().resource::<TerrainConfig>().max_spawns_per_frame;
+    assert_eq!(started, budget.min(ring.len()));
+    assert!(update_until(&mut app, |w| loaded_tiles(w) == square(IVec2::ZERO, 2)));
+}
+
+#[test]
+fn shrinking_radius_unloads_the_outer_ring() {
+    let (mut app, loader) = loaded_around_origin(2);
+    app.world_mut().get_mut::<TileLoader>(loader).unwrap().radius_tiles = 1;
+    assert!(update_until(&mut app, |w| loaded_tiles(w) == square(IVec2::ZERO, 1)));
+}
+
+#[test]
+fn despawned_loader_unloads_its_tiles() {
+    let (mut app, loader) = loaded_around_origin(1);
+    app.world_mut().despawn(loader);
+    assert!(update_until(&mut app, |w| loaded_tiles(w).is_empty()));
+    assert!(app.world().resource::<TerrainState>().pending.is_empty());
+}
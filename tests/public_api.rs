@@ -0,0 +1,26 @@
+//! The binary and the examples only use the library's public surface; this
+//! stops compiling if a module or an item they rely on goes private again.
+//! (`cargo test` also builds the examples themselves.)
+#![cfg(all(feature = "camera", feature = "terrain"))]
+
+use bevy::prelude::*;
+use thrive::prelude::*;
+
+/// What `main.rs` and the examples add, by module path and through the prelude.
+#[allow(dead_code)]
+fn examples_setup(app: &mut App, commands: &mut Commands) {
+    app.add_plugins((AppPlugin::default().title("check"), ThrivePlugins::default()))
+        .add_plugins((thrive::terrain::TerrainPlugin, thrive::terrain::water::WaterPlugin))
+        .add_plugins((thrive::camera::FreeFlightCameraPlugin, thrive::camera::TerrainCameraPlugin));
+    let _: Entity = spawn_terrain_camera(commands, TerrainCameraSettings::default());
+    let _ = spawn_default_world;
+    let _: TerrainConfig = thrive::terrain::systems::TerrainConfig::default();
+}
+
+#[test]
+fn library_exposes_the_modules() {
+    let cfg = TerrainConfig::default();
+    assert!(cfg.tile_size > 0.0);
+    let _ = TileLoader { radius_tiles: 1, ..default() };
+    let _ = FreeFlightCamera::default();
+}
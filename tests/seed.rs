@@ -0,0 +1,43 @@
+//! Heights depend on the seed alone, and changing it rebuilds loaded tiles.
+#![cfg(feature = "terrain")]
+
+mod common;
+
+use bevy::prelude::*;
+use common::{headless_app, square, update_until};
+use thrive::prelude::*;
+
+type Heights = Vec<Vec<f32>>;
+
+/// Heights of the tiles around the origin, once all of them are loaded.
+fn loaded_heights(world: &World) -> Option<Heights> {
+    let heightfield = world.resource::<TerrainHeightfield>();
+    square(IVec2::ZERO, 1).into_iter().map(|c| heightfield.tile(c).map(|t| t.heights.to_vec())).collect()
+}
+
+/// Update until the loaded heights satisfy `done`, and return them.
+fn heights_when(app: &mut App, done: impl Fn(&Heights) -> bool) -> Heights {
+    assert!(update_until(app, |w| loaded_heights(w).is_some_and(|h| done(&h))));
+    loaded_heights(app.world()).unwrap()
+}
+
+fn app_with_loader() -> App {
+    let mut app = headless_app();
+    app.world_mut().spawn((Transform::from_xyz(8.0, 0.0, 8.0), TileLoader { radius_tiles: 1, ..default() }));
+    app
+}
+
+#[test]
+fn same_seed_same_heights() {
+    let mut app = app_with_loader();
+    let first = heights_when(&mut app, |_| true);
+
+    let mut other = app_with_loader();
+    heights_when(&mut other, |h| *h == first);
+
+    // the streamer rebuilds every tile when the seed changes
+    app.world_mut().resource_mut::<TerrainConfig>().seed ^= 0xBEEF;
+    heights_when(&mut app, |h| h.iter().zip(&first).all(|(a, b)| a != b));
+    app.world_mut().resource_mut::<TerrainConfig>().seed ^= 0xBEEF;
+    heights_when(&mut app, |h| *h == first);
+}
@@ -0,0 +1,96 @@
+//! A snapshot survives a serde round trip into a fresh app, and loads
+//! whatever fields it understands.
+#![cfg(feature = "terrain")]
+
+mod common;
+
+use bevy::prelude::*;
+use common::{headless_app, square, update_until};
+use thrive::prelude::*;
+
+type Heights = Vec<Vec<f32>>;
+
+/// Heights of the tiles around the origin, once all of them are loaded.
+fn loaded_heights(world: &World) -> Option<Heights> {
+    let heightfield = world.resource::<TerrainHeightfield>();
+    square(IVec2::ZERO, 1).into_iter().map(|c| heightfield.tile(c).map(|t| t.heights.to_vec())).collect()
+}
+
+fn pins(world: &World) -> Vec<IVec2> {
+    let mut pins: Vec<IVec2> = world.resource::<TerrainState>().pinned().copied().collect();
+    pins.sort_by_key(|c| (c.y, c.x));
+    pins
+}
+
+fn apply(app: &mut App, snapshot: TerrainSnapshot) {
+    snapshot.apply(&mut app.world_mut().commands());
+    app.world_mut().flush();
+}
+
+#[test]
+fn snapshot_round_trips_into_a_fresh_app() {
+    let mut app = headless_app();
+    app.world_mut().resource_mut::<TerrainConfig>().seed = 1234;
+    app.world_mut().spawn((Transform::from_xyz(8.0, 0.0, 8.0), TileLoader { radius_tiles: 1, ..default() }));
+    app.update();
+    {
+        let world = app.world_mut();
+        world.resource_mut::<TerrainEdits>().add_height(IVec2::ZERO, 40, 3.0);
+        let mut state = world.resource_mut::<TerrainState>();
+        state.pin(IVec2::new(5, -2));
+        state.set_tile_flags(IVec2::new(1, 1), 0b101);
+        world.send_event(RegenerateTerrain);
+    }
+    assert!(update_until(&mut app, |w| loaded_heights(w).is_some()));
+    let heights = loaded_heights(app.world()).unwrap();
+
+    let world = app.world_mut();
+    let positions: Vec<Vec3> = world.query_filtered::<&Transform, With<TileLoader>>().iter(world).map(|t| t.translation).collect();
+    let snapshot = TerrainSnapshot::capture(world.resource(), world.resource(), world.resource())
+        .with_loaders(world.resource(), positions);
+    let json = serde_json::to_string(&snapshot).unwrap();
+
+    let mut restored = headless_app();
+    restored.world_mut().spawn((Transform::from_xyz(300.0, 0.0, -300.0), TileLoader { radius_tiles: 1, ..default() }));
+    restored.update();
+    apply(&mut restored, serde_json::from_str(&json).unwrap());
+    assert!(update_until(&mut restored, |w| loaded_heights(w).is_some_and(|h| h == heights)));
+
+    let world = restored.world();
+    assert_eq!(world.resource::<TerrainConfig>().seed, 1234);
+    assert_eq!(pins(world), vec![IVec2::new(5, -2)]);
+    let state = world.resource::<TerrainState>();
+    assert_eq!(state.tile_flags(IVec2::new(1, 1)), 0b101);
+    assert_eq!(state.flagged_tiles().count(), 1);
+    assert!(world.resource::<TerrainEdits>().current_tile(IVec2::ZERO).is_some());
+}
+
+#[test]
+fn unknown_fields_are_ignored() {
+    let json = r#"{
+        "version": 1,
+        "pinned": [[3, 4]],
+        "weather": "rain",
+        "config": { "seed": 7, "rivers": { "count": 2 } }
+    }"#;
+    let snapshot: TerrainSnapshot = serde_json::from_str(json).unwrap();
+    assert_eq!(snapshot.pinned, vec![[3, 4]]);
+    assert_eq!(snapshot.config.seed, 7);
+    assert_eq!(snapshot.config.tile_size, TerrainConfig::default().tile_size);
+    assert!(snapshot.loaders.is_empty());
+}
+
+#[test]
+fn newer_versions_restore_what_is_understood() {
+    let mut app = headless_app();
+    app.update();
+    let snapshot = TerrainSnapshot { version: u32::MAX, pinned: vec![[2, 2]], tile_flags: vec![([0, 1], 9)], ..default() };
+    let seed = snapshot.config.seed;
+    apply(&mut app, serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap());
+    app.update();
+
+    let world = app.world();
+    assert_eq!(world.resource::<TerrainConfig>().seed, seed);
+    assert_eq!(pins(world), vec![IVec2::new(2, 2)]);
+    assert_eq!(world.resource::<TerrainState>().tile_flags(IVec2::new(0, 1)), 9);
+}
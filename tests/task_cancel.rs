@@ -0,0 +1,108 @@
+//! A loader that jumps away doesn't leave the task slots busy behind it, and
+//! finished tiles are spawned within the per-frame budget.
+#![cfg(feature = "terrain")]
+
+mod common;
+
+use bevy::prelude::*;
+use common::{headless_app, loaded_tiles, square, update_until};
+use thrive::prelude::*;
+use thrive::terrain::systems::collect_finished_tasks_system;
+
+#[derive(Resource, Default)]
+struct Spawned(Vec<IVec2>);
+
+fn record_spawns(mut spawned: EventReader<TileSpawned>, mut log: ResMut<Spawned>) {
+    log.0.extend(spawned.read().map(|ev| ev.coord));
+}
+
+fn jump(app: &mut App, loader: Entity, coord: IVec2) {
+    let tile_size = app.world().resource::<TerrainConfig>().tile_size;
+    app.world_mut().get_mut::<Transform>(loader).unwrap().translation =
+        (coord.as_vec2() * tile_size + 0.5 * tile_size).extend(0.0).xzy();
+}
+
+#[test]
+fn jumping_loader_cancels_tasks_behind_it() {
+    let mut app = headless_app();
+    {
+        let mut cfg = app.world_mut().resource_mut::<TerrainConfig>();
+        cfg.max_in_flight_tasks = 4;
+        cfg.max_spawns_per_frame = 4;
+    }
+    app.init_resource::<Spawned>().add_systems(Update, record_spawns.after(collect_finished_tasks_system));
+    let loader = app.world_mut().spawn((Transform::default(), TileLoader { radius_tiles: 2, ..default() })).id();
+
+    // fill the slots with tiles around the origin, then jump before they finish
+    app.update();
+    assert!(!app.world().resource::<TerrainState>().pending.is_empty());
+    let far = IVec2::new(-60, 90);
+    jump(&mut app, loader, far);
+    app.world_mut().resource_mut::<Spawned>().0.clear();
+
+    let wanted = square(far, 2);
+    let mut behind = Vec::new();
+    assert!(update_until(&mut app, |w| {
+        behind.extend(w.resource::<TerrainState>().pending.keys().filter(|c| !wanted.contains(c)).copied());
+        loaded_tiles(w) == wanted
+    }));
+    // one frame to notice the jump, then only tiles around the loader
+    assert!(behind.is_empty(), "tasks kept behind the loader: {behind:?}");
+    let spawned = &app.world().resource::<Spawned>().0;
+    assert!(spawned.iter().all(|c| wanted.contains(c)), "spawned behind the loader: {spawned:?}");
+}
+
+#[test]
+fn tasks_finishing_in_the_grace_period_are_not_spawned() {
+    let mut app = headless_app();
+    {
+        let mut cfg = app.world_mut().resource_mut::<TerrainConfig>();
+        cfg.max_in_flight_tasks = 8;
+        cfg.max_spawns_per_frame = 8;
+        // nothing is cancelled, the tasks behind the loader run to the end
+        cfg.despawn_grace_seconds = 60.0;
+    }
+    app.init_resource::<Spawned>().add_systems(Update, record_spawns.after(collect_finished_tasks_system));
+    let loader = app.world_mut().spawn((Transform::default(), TileLoader { radius_tiles: 2, ..default() })).id();
+
+    app.update();
+    let behind: Vec<IVec2> = app.world().resource::<TerrainState>().pending.keys().copied().collect();
+    assert!(!behind.is_empty());
+    let far = IVec2::new(40, -70);
+    jump(&mut app, loader, far);
+    app.world_mut().resource_mut::<Spawned>().0.clear();
+
+    let wanted = square(far, 2);
+    assert!(update_until(&mut app, |w| {
+        let state = w.resource::<TerrainState>();
+        wanted.iter().all(|c| state.tiles.contains_key(c)) && behind.iter().all(|c| !state.pending.contains_key(c))
+    }));
+    let spawned = &app.world().resource::<Spawned>().0;
+    assert!(spawned.iter().all(|c| wanted.contains(c)), "spawned behind the loader: {spawned:?}");
+    let state = app.world().resource::<TerrainState>();
+    assert!(behind.iter().all(|c| !state.tiles.contains_key(c)));
+}
+
+#[test]
+fn finished_tiles_spawn_within_the_frame_budget() {
+    let mut app = headless_app();
+    {
+        let mut cfg = app.world_mut().resource_mut::<TerrainConfig>();
+        cfg.max_in_flight_tasks = 16;
+        cfg.max_spawns_per_frame = 16;
+    }
+    app.init_resource::<Spawned>().add_systems(Update, record_spawns.after(collect_finished_tasks_system));
+    app.world_mut().spawn((Transform::default(), TileLoader { radius_tiles: 2, ..default() }));
+
+    // start a batch of tasks, then let all of them finish before the next frame
+    app.update();
+    app.world_mut().resource_mut::<TerrainConfig>().max_spawns_per_frame = 2;
+    app.world_mut().resource_mut::<Spawned>().0.clear();
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    let mut per_frame = Vec::new();
+    assert!(update_until(&mut app, |w| {
+        per_frame.push(std::mem::take(&mut w.resource_mut::<Spawned>().0).len());
+        loaded_tiles(w) == square(IVec2::ZERO, 2)
+    }));
+    assert!(per_frame.iter().all(|n| *n <= 2), "spawned per frame: {per_frame:?}");
+}
@@ -0,0 +1,60 @@
+//! Tile children and `TileAttachment`s go away with their tile.
+#![cfg(feature = "terrain")]
+
+mod common;
+
+use bevy::prelude::*;
+use common::{headless_app, loaded_tiles, square, update_until};
+use thrive::prelude::*;
+
+/// Stands in for vegetation or props parented to the tile.
+#[derive(Component)]
+struct Prop(IVec2);
+
+/// Stands in for an entity kept in a side map rather than parented.
+#[derive(Component)]
+struct SideEntity;
+
+fn decorate_tiles(mut commands: Commands, mut spawned: EventReader<TileSpawned>) {
+    for ev in spawned.read() {
+        commands.entity(ev.entity).with_child(Prop(ev.coord));
+        commands.spawn((SideEntity, TileAttachment { coord: ev.coord }));
+    }
+}
+
+#[test]
+fn moving_away_leaves_no_orphans() {
+    for max_pooled_tiles in [0, 64] {
+        let mut app = headless_app();
+        app.world_mut().resource_mut::<TerrainConfig>().max_pooled_tiles = max_pooled_tiles;
+        app.add_systems(Update, decorate_tiles);
+        let loader = app.world_mut().spawn((Transform::default(), TileLoader { radius_tiles: 1, ..default() })).id();
+        assert!(update_until(&mut app, |w| loaded_tiles(w) == square(IVec2::ZERO, 1)));
+
+        let far = IVec2::new(100, -40);
+        let tile_size = app.world().resource::<TerrainConfig>().tile_size;
+        app.world_mut().get_mut::<Transform>(loader).unwrap().translation = (far.as_vec2() * tile_size + 0.5 * tile_size).extend(0.0).xzy();
+        assert!(update_until(&mut app, |w| loaded_tiles(w) == square(far, 1)));
+        app.update();
+
+        let world = app.world_mut();
+        let loaded = loaded_tiles(world);
+        let mut props = world.query::<(&Prop, &ChildOf)>();
+        let mut parents = Vec::new();
+        for (prop, child_of) in props.iter(world) {
+            let tile = world.get::<Tile>(child_of.parent()).expect("prop parent is a tile");
+            assert_eq!(tile.coord, prop.0);
+            parents.push(prop.0);
+        }
+        parents.sort_by_key(|c| (c.y, c.x));
+        assert_eq!(parents, loaded, "max_pooled_tiles = {max_pooled_tiles}");
+
+        let mut attached: Vec<IVec2> = world
+            .query_filtered::<&TileAttachment, With<SideEntity>>()
+            .iter(world)
+            .map(|a| a.coord)
+            .collect();
+        attached.sort_by_key(|c| (c.y, c.x));
+        assert_eq!(attached, loaded, "max_pooled_tiles = {max_pooled_tiles}");
+    }
+}